@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+pub struct CreateTenantCommand {
+    /// User creating the tenant.
+    pub actor_id: String,
+    pub name: String,
+    pub slug: String,
+    /// Defaults to the free plan.
+    pub plan: Option<String>,
+}
+
+/// Changes the fields that are set and leaves the others as they are.
+pub struct UpdateTenantCommand {
+    /// User making the change.
+    pub actor_id: String,
+    pub tenant_id: String,
+    pub name: Option<String>,
+    pub plan: Option<String>,
+    /// When the new `plan` lapses. Without a `plan`, moves the end of the
+    /// current one.
+    pub plan_expires_at: Option<DateTime<Utc>>,
+    pub status: Option<String>,
+    pub setting: Option<Value>,
+}
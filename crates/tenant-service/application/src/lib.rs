@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod queries;
+pub mod revocation_service;
+pub mod tenant_service;
+
+#[cfg(test)]
+mod test_support;
@@ -0,0 +1,9 @@
+pub struct GetTenantQuery {
+    pub tenant_id: String,
+}
+
+pub struct ListTenantsQuery {
+    pub status: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
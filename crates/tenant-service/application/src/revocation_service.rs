@@ -0,0 +1,73 @@
+use auth::RevocationList;
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::repository::RevokedSessionRepository;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Mirrors the sessions the user service revoked into the
+/// [`RevocationList`] tokens are checked against, so a signed-out or
+/// revoked session cannot keep managing tenants until its tokens expire.
+pub struct RevocationSyncService {
+    revoked_session_repo: Arc<dyn RevokedSessionRepository>,
+    revocations: Arc<RevocationList>,
+    /// How long the longest-lived token the user service binds to a
+    /// session lives, and so how long a revocation must be remembered.
+    token_ttl: Duration,
+}
+
+impl RevocationSyncService {
+    pub fn new(
+        revoked_session_repo: Arc<dyn RevokedSessionRepository>,
+        revocations: Arc<RevocationList>,
+        token_ttl: Duration,
+    ) -> Self {
+        Self {
+            revoked_session_repo,
+            revocations,
+            token_ttl,
+        }
+    }
+
+    /// Loads the sessions revoked within a token lifetime. Run
+    /// periodically.
+    pub async fn sync(&self) -> Result<(), AppError> {
+        let token_ttl = chrono::Duration::from_std(self.token_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let revoked = self
+            .revoked_session_repo
+            .list_revoked_since(Utc::now() - token_ttl)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.revocations.extend(
+            revoked
+                .into_iter()
+                .map(|(session_id, revoked_at)| (session_id, revoked_at + token_ttl)),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::Stores;
+    use chrono::{Duration, Utc};
+
+    #[tokio::test]
+    async fn test_sessions_revoked_within_a_token_lifetime_are_refused() {
+        let stores = Stores::default();
+        let now = Utc::now();
+        stores
+            .revoked_sessions
+            .revoke("recent", now - Duration::minutes(5));
+        stores
+            .revoked_sessions
+            .revoke("stale", now - Duration::hours(2));
+
+        stores.revocation_service().sync().await.unwrap();
+
+        assert!(stores.revocations.is_revoked("recent"));
+        assert!(!stores.revocations.is_revoked("stale"));
+        assert!(!stores.revocations.is_revoked("active"));
+    }
+}
@@ -0,0 +1,303 @@
+use crate::commands::{CreateTenantCommand, UpdateTenantCommand};
+use crate::queries::{GetTenantQuery, ListTenantsQuery};
+use base::model::value_objects::{CreatedBy, UpdatedBy};
+use base::web::error::AppError;
+use domain::Tenant;
+use domain::repository::{TenantFilter, TenantRepository};
+use domain::value_objects::{Plan, TenantId, TenantSlug, TenantStatus};
+use std::sync::Arc;
+use tracing::info;
+
+/// Tenants a listing returns when the caller does not say.
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+/// Most tenants a single listing returns.
+pub const MAX_PAGE_SIZE: u32 = 200;
+
+pub struct TenantApplicationService {
+    tenant_repo: Arc<dyn TenantRepository>,
+}
+
+impl TenantApplicationService {
+    pub fn new(tenant_repo: Arc<dyn TenantRepository>) -> Self {
+        Self { tenant_repo }
+    }
+
+    pub async fn create(&self, cmd: CreateTenantCommand) -> Result<Tenant, AppError> {
+        let slug = TenantSlug::new(&cmd.slug)?;
+        let plan = cmd
+            .plan
+            .as_deref()
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let tenant = Tenant::new(&cmd.name, slug, plan, Some(CreatedBy::new(cmd.actor_id)))?;
+        let created = self
+            .tenant_repo
+            .create(tenant.clone())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if !created {
+            return Err(AppError::BadRequest("Slug is already taken".into()));
+        }
+        info!(tenant_id = %tenant.id, slug = %tenant.slug, "Tenant created");
+        Ok(tenant)
+    }
+
+    pub async fn get(&self, query: GetTenantQuery) -> Result<Tenant, AppError> {
+        let tenant_id = parse_tenant_id(&query.tenant_id)?;
+        self.find(&tenant_id).await
+    }
+
+    pub async fn update(&self, cmd: UpdateTenantCommand) -> Result<Tenant, AppError> {
+        let tenant_id = parse_tenant_id(&cmd.tenant_id)?;
+        let mut tenant = self.find(&tenant_id).await?;
+        if let Some(name) = &cmd.name {
+            tenant.rename(name)?;
+        }
+        match (&cmd.plan, cmd.plan_expires_at) {
+            (Some(plan), expires_at) => tenant.change_plan(plan.parse::<Plan>()?, expires_at)?,
+            // An expiry alone moves the end of the current plan.
+            (None, Some(expires_at)) => tenant.change_plan(tenant.plan, Some(expires_at))?,
+            (None, None) => {}
+        }
+        if let Some(status) = &cmd.status {
+            tenant.change_status(status.parse::<TenantStatus>()?)?;
+        }
+        if let Some(setting) = cmd.setting {
+            tenant.replace_setting(setting)?;
+        }
+        tenant.touch(Some(UpdatedBy::new(cmd.actor_id)));
+        let updated = self
+            .tenant_repo
+            .update(&tenant)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if !updated {
+            return Err(tenant_not_found());
+        }
+        info!(tenant_id = %tenant.id, status = %tenant.status, plan = %tenant.plan, "Tenant updated");
+        Ok(tenant)
+    }
+
+    pub async fn list(&self, query: ListTenantsQuery) -> Result<Vec<Tenant>, AppError> {
+        let filter = TenantFilter {
+            status: query
+                .status
+                .as_deref()
+                .map(str::parse::<TenantStatus>)
+                .transpose()?,
+            limit: query
+                .limit
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE),
+            offset: query.offset.unwrap_or(0),
+        };
+        self.tenant_repo
+            .list(&filter)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    async fn find(&self, tenant_id: &TenantId) -> Result<Tenant, AppError> {
+        self.tenant_repo
+            .find_by_id(tenant_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(tenant_not_found)
+    }
+}
+
+fn parse_tenant_id(tenant_id: &str) -> Result<TenantId, AppError> {
+    TenantId::from_string(tenant_id).map_err(|_| AppError::BadRequest("Invalid tenant id".into()))
+}
+
+fn tenant_not_found() -> AppError {
+    AppError::NotFound("Tenant not found".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ACTOR_ID, Stores};
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    fn create_command(slug: &str) -> CreateTenantCommand {
+        CreateTenantCommand {
+            actor_id: ACTOR_ID.to_string(),
+            name: "Acme Corp".to_string(),
+            slug: slug.to_string(),
+            plan: None,
+        }
+    }
+
+    fn update_command(tenant: &Tenant) -> UpdateTenantCommand {
+        UpdateTenantCommand {
+            actor_id: ACTOR_ID.to_string(),
+            tenant_id: tenant.id.to_string(),
+            name: None,
+            plan: None,
+            plan_expires_at: None,
+            status: None,
+            setting: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_stores_an_active_free_tenant() {
+        let stores = Stores::default();
+        let service = stores.tenant_service();
+        let tenant = service.create(create_command("Acme")).await.unwrap();
+        assert_eq!(tenant.slug.as_str(), "acme");
+        assert_eq!(tenant.plan, Plan::Free);
+        assert!(tenant.is_active());
+
+        let stored = stores.tenants.get(&tenant.id).unwrap();
+        assert_eq!(stored, tenant);
+        let created_by = stored.audit.unwrap().created_by.unwrap();
+        assert_eq!(created_by.as_str(), ACTOR_ID);
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_taken_slug_and_unknown_plan() {
+        let stores = Stores::default();
+        let service = stores.tenant_service();
+        service.create(create_command("acme")).await.unwrap();
+        let taken = service.create(create_command("ACME")).await;
+        assert!(matches!(taken, Err(AppError::BadRequest(_))));
+
+        let mut command = create_command("globex");
+        command.plan = Some("premium".to_string());
+        let unknown = service.create(command).await;
+        assert!(matches!(unknown, Err(AppError::BadRequest(_))));
+        assert_eq!(stores.tenants.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_finds_tenant_by_id() {
+        let stores = Stores::default();
+        let service = stores.tenant_service();
+        let tenant = service.create(create_command("acme")).await.unwrap();
+        let found = service
+            .get(GetTenantQuery {
+                tenant_id: tenant.id.to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(found, tenant);
+
+        let missing = service
+            .get(GetTenantQuery {
+                tenant_id: TenantId::new().to_string(),
+            })
+            .await;
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+        let malformed = service
+            .get(GetTenantQuery {
+                tenant_id: "acme".to_string(),
+            })
+            .await;
+        assert!(matches!(malformed, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_changes_only_the_given_fields() {
+        let stores = Stores::default();
+        let service = stores.tenant_service();
+        let tenant = service.create(create_command("acme")).await.unwrap();
+        let expires_at = Utc::now() + Duration::days(30);
+
+        let mut command = update_command(&tenant);
+        command.plan = Some("professional".to_string());
+        command.plan_expires_at = Some(expires_at);
+        command.setting = Some(json!({"require_email_verification": true}));
+        let updated = service.update(command).await.unwrap();
+
+        assert_eq!(updated.name, "Acme Corp");
+        assert_eq!(updated.plan, Plan::Professional);
+        assert_eq!(updated.plan_expires_at, Some(expires_at));
+        assert_eq!(updated.setting["require_email_verification"], true);
+        let audit = stores.tenants.get(&tenant.id).unwrap().audit.unwrap();
+        assert_eq!(audit.updated_by.unwrap().as_str(), ACTOR_ID);
+        assert!(audit.updated_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_moves_the_expiry_of_the_current_plan() {
+        let stores = Stores::default();
+        let service = stores.tenant_service();
+        let tenant = service.create(create_command("acme")).await.unwrap();
+        let mut upgrade = update_command(&tenant);
+        upgrade.plan = Some("professional".to_string());
+        upgrade.plan_expires_at = Some(Utc::now() + Duration::days(30));
+        service.update(upgrade).await.unwrap();
+
+        let extended_to = Utc::now() + Duration::days(60);
+        let mut extend = update_command(&tenant);
+        extend.plan_expires_at = Some(extended_to);
+        let extended = service.update(extend).await.unwrap();
+        assert_eq!(extended.plan, Plan::Professional);
+        assert_eq!(extended.plan_expires_at, Some(extended_to));
+
+        let mut past = update_command(&tenant);
+        past.plan_expires_at = Some(Utc::now() - Duration::days(1));
+        let result = service.update(past).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        let stored = stores.tenants.get(&tenant.id).unwrap();
+        assert_eq!(stored.plan_expires_at, Some(extended_to));
+    }
+
+    #[tokio::test]
+    async fn test_update_refuses_to_reactivate_cancelled_tenant() {
+        let stores = Stores::default();
+        let service = stores.tenant_service();
+        let tenant = service.create(create_command("acme")).await.unwrap();
+        let mut cancel = update_command(&tenant);
+        cancel.status = Some("cancelled".to_string());
+        service.update(cancel).await.unwrap();
+
+        let mut reactivate = update_command(&tenant);
+        reactivate.status = Some("active".to_string());
+        reactivate.name = Some("Acme Again".to_string());
+        let result = service.update(reactivate).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        let stored = stores.tenants.get(&tenant.id).unwrap();
+        assert_eq!(stored.status, TenantStatus::Cancelled);
+        assert_eq!(stored.name, "Acme Corp");
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_by_status_and_pages() {
+        let stores = Stores::default();
+        let service = stores.tenant_service();
+        for slug in ["acme", "globex", "initech"] {
+            service.create(create_command(slug)).await.unwrap();
+        }
+        let globex = stores.tenants.find_slug("globex").unwrap();
+        let mut suspend = update_command(&globex);
+        suspend.status = Some("suspended".to_string());
+        service.update(suspend).await.unwrap();
+
+        let active = service
+            .list(ListTenantsQuery {
+                status: Some("active".to_string()),
+                limit: None,
+                offset: None,
+            })
+            .await
+            .unwrap();
+        let slugs: Vec<_> = active.iter().map(|t| t.slug.as_str()).collect();
+        assert_eq!(slugs, ["acme", "initech"]);
+
+        let page = service
+            .list(ListTenantsQuery {
+                status: None,
+                limit: Some(1),
+                offset: Some(1),
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].slug.as_str(), "globex");
+    }
+}
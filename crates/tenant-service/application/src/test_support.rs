@@ -0,0 +1,137 @@
+use crate::revocation_service::RevocationSyncService;
+use crate::tenant_service::TenantApplicationService;
+use anyhow::Result;
+use async_trait::async_trait;
+use auth::RevocationList;
+use chrono::{DateTime, Utc};
+use domain::Tenant;
+use domain::repository::{RevokedSessionRepository, TenantFilter, TenantRepository};
+use domain::value_objects::{TenantId, TenantSlug};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const ACTOR_ID: &str = "0191f0a0-0000-7000-8000-000000000001";
+
+/// The stores behind the services under test.
+#[derive(Default)]
+pub struct Stores {
+    pub tenants: Arc<InMemoryTenantRepository>,
+    pub revoked_sessions: Arc<InMemoryRevokedSessionRepository>,
+    pub revocations: Arc<RevocationList>,
+}
+
+impl Stores {
+    pub fn tenant_service(&self) -> TenantApplicationService {
+        TenantApplicationService::new(self.tenants.clone())
+    }
+
+    /// Remembers revocations for an hour.
+    pub fn revocation_service(&self) -> RevocationSyncService {
+        RevocationSyncService::new(
+            self.revoked_sessions.clone(),
+            Arc::clone(&self.revocations),
+            Duration::from_secs(60 * 60),
+        )
+    }
+}
+
+/// Keeps tenants in creation order, as the Postgres repository lists them.
+#[derive(Default)]
+pub struct InMemoryTenantRepository {
+    tenants: Mutex<Vec<Tenant>>,
+}
+
+impl InMemoryTenantRepository {
+    pub fn get(&self, id: &TenantId) -> Option<Tenant> {
+        self.tenants
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.id == *id)
+            .cloned()
+    }
+
+    pub fn find_slug(&self, slug: &str) -> Option<Tenant> {
+        self.tenants
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|t| t.slug.as_str() == slug)
+            .cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tenants.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl TenantRepository for InMemoryTenantRepository {
+    async fn create(&self, tenant: Tenant) -> Result<bool> {
+        let mut tenants = self.tenants.lock().unwrap();
+        if tenants.iter().any(|t| t.slug == tenant.slug) {
+            return Ok(false);
+        }
+        tenants.push(tenant);
+        Ok(true)
+    }
+    async fn find_by_id(&self, id: &TenantId) -> Result<Option<Tenant>> {
+        Ok(self.get(id))
+    }
+    async fn find_by_slug(&self, slug: &TenantSlug) -> Result<Option<Tenant>> {
+        Ok(self.find_slug(slug.as_str()))
+    }
+    async fn update(&self, tenant: &Tenant) -> Result<bool> {
+        let mut tenants = self.tenants.lock().unwrap();
+        match tenants.iter_mut().find(|t| t.id == tenant.id) {
+            Some(stored) => {
+                *stored = tenant.clone();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+    async fn list(&self, filter: &TenantFilter) -> Result<Vec<Tenant>> {
+        Ok(self
+            .tenants
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| filter.status.is_none_or(|status| t.status == status))
+            .skip(filter.offset as usize)
+            .take(filter.limit as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryRevokedSessionRepository {
+    sessions: Mutex<Vec<(String, DateTime<Utc>)>>,
+}
+
+impl InMemoryRevokedSessionRepository {
+    pub fn revoke(&self, session_id: &str, at: DateTime<Utc>) {
+        self.sessions
+            .lock()
+            .unwrap()
+            .push((session_id.to_string(), at));
+    }
+}
+
+#[async_trait]
+impl RevokedSessionRepository for InMemoryRevokedSessionRepository {
+    async fn list_revoked_since(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<(String, DateTime<Utc>)>> {
+        Ok(self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, at)| *at > cutoff)
+            .cloned()
+            .collect())
+    }
+}
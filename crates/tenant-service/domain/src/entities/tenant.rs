@@ -0,0 +1,207 @@
+use crate::value_objects::{Plan, TenantId, TenantSlug, TenantStatus};
+use base::model::Audit;
+use base::model::value_objects::{CreatedAt, CreatedBy, UpdatedAt, UpdatedBy};
+use base::web::error::AppError;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use user_domain::value_objects::TenantSettings;
+
+/// An organisation using the platform, which users, API keys and every
+/// other tenant-scoped record belong to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tenant {
+    pub id: TenantId,
+    pub name: String,
+    pub slug: TenantSlug,
+    pub plan: Plan,
+    /// `None` for plans that do not lapse.
+    pub plan_expires_at: Option<DateTime<Utc>>,
+    /// Options the services read from `tbl_tenants.setting`, always a JSON
+    /// object.
+    pub setting: Value,
+    pub status: TenantStatus,
+    pub audit: Option<Audit>,
+}
+
+impl Tenant {
+    pub fn new(
+        name: &str,
+        slug: TenantSlug,
+        plan: Plan,
+        created_by: Option<CreatedBy>,
+    ) -> Result<Self, AppError> {
+        let mut audit = Audit::with_created_at(CreatedAt::now());
+        audit.created_by = created_by;
+        Ok(Self {
+            id: TenantId::new(),
+            name: checked_name(name)?,
+            slug,
+            plan,
+            plan_expires_at: None,
+            setting: Value::Object(Default::default()),
+            status: TenantStatus::default(),
+            audit: Some(audit),
+        })
+    }
+
+    pub fn rename(&mut self, name: &str) -> Result<(), AppError> {
+        self.name = checked_name(name)?;
+        Ok(())
+    }
+
+    /// Moves the tenant to `plan` until `expires_at`, or for good when it is
+    /// `None`.
+    pub fn change_plan(
+        &mut self,
+        plan: Plan,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        if expires_at.is_some_and(|at| at <= Utc::now()) {
+            return Err(AppError::BadRequest(
+                "Plan expiry must be in the future".into(),
+            ));
+        }
+        self.plan = plan;
+        self.plan_expires_at = expires_at;
+        Ok(())
+    }
+
+    pub fn change_status(&mut self, status: TenantStatus) -> Result<(), AppError> {
+        if !self.status.can_become(status) {
+            return Err(AppError::BadRequest(format!(
+                "A {} tenant cannot become {}",
+                self.status, status
+            )));
+        }
+        self.status = status;
+        Ok(())
+    }
+
+    /// Fails when `setting` is not an object, or a key the user service
+    /// reads has a value it could not parse, which would fail every login
+    /// to the tenant.
+    pub fn replace_setting(&mut self, setting: Value) -> Result<(), AppError> {
+        if !setting.is_object() {
+            return Err(AppError::BadRequest(
+                "Tenant setting must be a JSON object".into(),
+            ));
+        }
+        TenantSettings::from_json(setting.clone())
+            .map_err(|e| AppError::BadRequest(format!("Invalid tenant setting: {}", e)))?;
+        self.setting = setting;
+        Ok(())
+    }
+
+    /// Records who changed the tenant, and when.
+    pub fn touch(&mut self, updated_by: Option<UpdatedBy>) {
+        let audit = self
+            .audit
+            .get_or_insert_with(|| Audit::with_created_at(CreatedAt::now()));
+        audit.updated_at = Some(UpdatedAt::now());
+        audit.updated_by = updated_by;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == TenantStatus::Active
+    }
+}
+
+fn checked_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 255 {
+        return Err(AppError::BadRequest(
+            "Tenant name must be 1 to 255 characters".into(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn tenant() -> Tenant {
+        Tenant::new(
+            " Acme Corp ",
+            TenantSlug::new("acme").unwrap(),
+            Plan::Free,
+            Some(CreatedBy::new("0191f0a0-0000-7000-8000-000000000001")),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new_tenant_is_active_with_empty_setting() {
+        let tenant = tenant();
+        assert_eq!(tenant.name, "Acme Corp");
+        assert!(tenant.is_active());
+        assert_eq!(tenant.setting, json!({}));
+        assert!(tenant.audit.unwrap().created_by.is_some());
+    }
+
+    #[test]
+    fn test_name_must_not_be_blank() {
+        let mut tenant = tenant();
+        assert!(matches!(tenant.rename("  "), Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            tenant.rename(&"a".repeat(256)),
+            Err(AppError::BadRequest(_))
+        ));
+        assert_eq!(tenant.name, "Acme Corp");
+    }
+
+    #[test]
+    fn test_plan_expiry_must_be_in_the_future() {
+        let mut tenant = tenant();
+        let past = Utc::now() - Duration::days(1);
+        assert!(tenant.change_plan(Plan::Starter, Some(past)).is_err());
+        assert_eq!(tenant.plan, Plan::Free);
+
+        let future = Utc::now() + Duration::days(30);
+        tenant.change_plan(Plan::Starter, Some(future)).unwrap();
+        assert_eq!(tenant.plan, Plan::Starter);
+        assert_eq!(tenant.plan_expires_at, Some(future));
+    }
+
+    #[test]
+    fn test_cancelled_tenant_cannot_be_reactivated() {
+        let mut tenant = tenant();
+        tenant.change_status(TenantStatus::Cancelled).unwrap();
+        assert!(matches!(
+            tenant.change_status(TenantStatus::Active),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_setting_must_be_an_object() {
+        let mut tenant = tenant();
+        assert!(tenant.replace_setting(json!(["mfa"])).is_err());
+        tenant
+            .replace_setting(json!({"require_email_verification": true}))
+            .unwrap();
+        assert_eq!(tenant.setting["require_email_verification"], true);
+    }
+
+    #[test]
+    fn test_setting_keys_must_have_the_types_the_user_service_reads() {
+        let mut tenant = tenant();
+        assert!(matches!(
+            tenant.replace_setting(json!({"mfa_required_roles": "admin"})),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            tenant.replace_setting(json!({"require_email_verification": "yes"})),
+            Err(AppError::BadRequest(_))
+        ));
+        assert_eq!(tenant.setting, json!({}));
+
+        // Keys the user service does not know are left to other readers.
+        tenant
+            .replace_setting(json!({"mfa_required_roles": ["admin"], "theme": "dark"}))
+            .unwrap();
+        assert_eq!(tenant.setting["theme"], "dark");
+    }
+}
@@ -0,0 +1,6 @@
+pub mod entities;
+pub mod repository;
+pub mod value_objects;
+
+pub use entities::tenant::Tenant;
+pub use repository::*;
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    Tenant,
+    value_objects::{TenantId, TenantSlug, TenantStatus},
+};
+
+/// Which tenants a listing returns, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct TenantFilter {
+    /// Only tenants in this status, when set.
+    pub status: Option<TenantStatus>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+#[async_trait]
+pub trait TenantRepository: Send + Sync {
+    /// Stores a new tenant. Returns `false`, storing nothing, when its slug
+    /// is already taken.
+    async fn create(&self, tenant: Tenant) -> Result<bool>;
+    async fn find_by_id(&self, id: &TenantId) -> Result<Option<Tenant>>;
+    async fn find_by_slug(&self, slug: &TenantSlug) -> Result<Option<Tenant>>;
+    /// Saves the changes made to a tenant. Returns `false` when it no longer
+    /// exists.
+    async fn update(&self, tenant: &Tenant) -> Result<bool>;
+    async fn list(&self, filter: &TenantFilter) -> Result<Vec<Tenant>>;
+}
+
+/// Login sessions the user service has revoked. Their tokens are still
+/// signed and unexpired, so this service has to refuse them too.
+#[async_trait]
+pub trait RevokedSessionRepository: Send + Sync {
+    /// Ids of the sessions revoked after `cutoff`, with when they were.
+    async fn list_revoked_since(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<(String, DateTime<Utc>)>>;
+}
@@ -0,0 +1,9 @@
+pub mod plan;
+pub mod tenant_id;
+pub mod tenant_slug;
+pub mod tenant_status;
+
+pub use plan::Plan;
+pub use tenant_id::TenantId;
+pub use tenant_slug::TenantSlug;
+pub use tenant_status::TenantStatus;
@@ -0,0 +1,66 @@
+use base::web::error::AppError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Subscription plan of a tenant, as allowed by `tenants_plan_check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Plan {
+    #[default]
+    Free,
+    Starter,
+    Professional,
+    Enterprise,
+}
+
+impl Plan {
+    /// Name of the plan as stored in `tbl_tenants.plan`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Plan::Free => "free",
+            Plan::Starter => "starter",
+            Plan::Professional => "professional",
+            Plan::Enterprise => "enterprise",
+        }
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Plan {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "free" => Ok(Plan::Free),
+            "starter" => Ok(Plan::Starter),
+            "professional" => Ok(Plan::Professional),
+            "enterprise" => Ok(Plan::Enterprise),
+            _ => Err(AppError::BadRequest(format!("Unknown plan {}", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_round_trips_through_its_name() {
+        for plan in [
+            Plan::Free,
+            Plan::Starter,
+            Plan::Professional,
+            Plan::Enterprise,
+        ] {
+            assert_eq!(plan.as_str().parse::<Plan>().unwrap(), plan);
+        }
+        assert!(matches!(
+            "premium".parse::<Plan>(),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantId(Uuid);
+
+impl TenantId {
+    /// Create a new TenantId with a time-ordered UUIDv7
+    pub fn new() -> Self {
+        let ts = uuid::Timestamp::now(uuid::NoContext);
+        Self(Uuid::new_v7(ts))
+    }
+
+    /// Parse a TenantId from a string representation
+    pub fn from_string(s: &str) -> Result<Self, uuid::Error> {
+        Ok(Self(Uuid::parse_str(s)?))
+    }
+
+    /// Get the inner UUID reference
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+
+    /// Get string representation (convenience method)
+    pub fn as_str(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for TenantId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_string(s)
+    }
+}
+
+impl From<Uuid> for TenantId {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+impl From<TenantId> for Uuid {
+    fn from(tenant_id: TenantId) -> Self {
+        tenant_id.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_ids_are_time_ordered() {
+        let first = TenantId::new();
+        let second = TenantId::new();
+        assert_ne!(first, second);
+        assert_eq!(first.as_uuid().get_version_num(), 7);
+    }
+
+    #[test]
+    fn test_from_string_invalid() {
+        assert!(TenantId::from_string("not-a-uuid").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use base::web::error::AppError;
+use std::fmt;
+
+/// Longest slug `tbl_tenants.slug` holds.
+const MAX_SLUG_LEN: usize = 100;
+const MIN_SLUG_LEN: usize = 3;
+
+/// URL-safe handle of a tenant, unique across tenants: lowercase letters,
+/// digits and single hyphens between them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TenantSlug(String);
+
+impl TenantSlug {
+    pub fn new(slug: &str) -> Result<Self, AppError> {
+        let slug = slug.trim().to_ascii_lowercase();
+        if slug.len() < MIN_SLUG_LEN || slug.len() > MAX_SLUG_LEN {
+            return Err(AppError::BadRequest(format!(
+                "Slug must be {} to {} characters",
+                MIN_SLUG_LEN, MAX_SLUG_LEN
+            )));
+        }
+        let well_formed = slug
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()));
+        if !well_formed {
+            return Err(AppError::BadRequest(
+                "Slug may only hold lowercase letters, digits and single hyphens between them"
+                    .into(),
+            ));
+        }
+        Ok(Self(slug))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TenantSlug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slug_is_trimmed_and_lowercased() {
+        let slug = TenantSlug::new("  Acme-Corp2 ").unwrap();
+        assert_eq!(slug.as_str(), "acme-corp2");
+    }
+
+    #[test]
+    fn test_slug_length_is_bounded() {
+        assert!(matches!(
+            TenantSlug::new("ab"),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(TenantSlug::new(&"a".repeat(100)).is_ok());
+        assert!(matches!(
+            TenantSlug::new(&"a".repeat(101)),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_slug_rejects_malformed_hyphens_and_characters() {
+        for slug in ["-acme", "acme-", "ac--me", "ac me", "acme_corp", "acmé"] {
+            assert!(
+                matches!(TenantSlug::new(slug), Err(AppError::BadRequest(_))),
+                "{} was accepted",
+                slug
+            );
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use base::web::error::AppError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Lifecycle state of a tenant, as allowed by `tenants_status_check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TenantStatus {
+    #[default]
+    Active,
+    /// Temporarily shut out, for instance over an unpaid invoice.
+    Suspended,
+    /// Closed for good.
+    Cancelled,
+}
+
+impl TenantStatus {
+    /// Name of the status as stored in `tbl_tenants.status`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TenantStatus::Active => "active",
+            TenantStatus::Suspended => "suspended",
+            TenantStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether a tenant in this status may move to `next`. A cancelled
+    /// tenant stays cancelled.
+    pub fn can_become(&self, next: TenantStatus) -> bool {
+        *self == next || *self != TenantStatus::Cancelled
+    }
+}
+
+impl fmt::Display for TenantStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TenantStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(TenantStatus::Active),
+            "suspended" => Ok(TenantStatus::Suspended),
+            "cancelled" => Ok(TenantStatus::Cancelled),
+            _ => Err(AppError::BadRequest(format!("Unknown tenant status {}", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_round_trips_through_its_name() {
+        for status in [
+            TenantStatus::Active,
+            TenantStatus::Suspended,
+            TenantStatus::Cancelled,
+        ] {
+            assert_eq!(status.as_str().parse::<TenantStatus>().unwrap(), status);
+        }
+        assert!(matches!(
+            "deleted".parse::<TenantStatus>(),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_cancelled_is_final() {
+        assert!(TenantStatus::Active.can_become(TenantStatus::Suspended));
+        assert!(TenantStatus::Suspended.can_become(TenantStatus::Active));
+        assert!(TenantStatus::Suspended.can_become(TenantStatus::Cancelled));
+        assert!(!TenantStatus::Cancelled.can_become(TenantStatus::Active));
+        assert!(TenantStatus::Cancelled.can_become(TenantStatus::Cancelled));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod model;
+pub mod pg_revoked_session_repository;
+pub mod pg_tenant_repository;
+
+pub use model::*;
+pub use pg_revoked_session_repository::*;
+pub use pg_tenant_repository::*;
@@ -0,0 +1,100 @@
+use base::model::Audit;
+use base::model::value_objects::{CreatedAt, CreatedBy, UpdatedAt, UpdatedBy};
+use chrono::{DateTime, Utc};
+use domain::Tenant;
+use domain::value_objects::TenantSlug;
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, Debug, Clone)]
+pub struct TenantModel {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub plan: String,
+    pub plan_expires_at: Option<DateTime<Utc>>,
+    pub setting: Value,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Option<Uuid>,
+}
+
+impl From<Tenant> for TenantModel {
+    fn from(tenant: Tenant) -> Self {
+        let audit = tenant
+            .audit
+            .unwrap_or_else(|| Audit::with_created_at(CreatedAt::now()));
+        let audit_id = |id: &str| Uuid::parse_str(id).ok();
+        Self {
+            id: tenant.id.into(),
+            name: tenant.name,
+            slug: tenant.slug.as_str().to_string(),
+            plan: tenant.plan.to_string(),
+            plan_expires_at: tenant.plan_expires_at,
+            setting: tenant.setting,
+            status: tenant.status.to_string(),
+            created_at: audit.created_at.value(),
+            created_by: audit.created_by.and_then(|by| audit_id(by.as_str())),
+            updated_at: audit
+                .updated_at
+                .map_or(audit.created_at.value(), |at| at.value()),
+            updated_by: audit.updated_by.and_then(|by| audit_id(by.as_str())),
+        }
+    }
+}
+
+impl From<TenantModel> for Tenant {
+    fn from(model: TenantModel) -> Self {
+        Tenant {
+            id: model.id.into(),
+            name: model.name,
+            slug: TenantSlug::new(&model.slug).expect("Invalid tenant slug"),
+            plan: model.plan.parse().expect("Invalid plan"),
+            plan_expires_at: model.plan_expires_at,
+            setting: model.setting,
+            status: model.status.parse().expect("Invalid tenant status"),
+            audit: Some(Audit {
+                created_at: CreatedAt::from_datetime(model.created_at),
+                updated_at: Some(UpdatedAt::from_datetime(model.updated_at)),
+                created_by: model.created_by.map(|by| CreatedBy::new(by.to_string())),
+                updated_by: model.updated_by.map(|by| UpdatedBy::new(by.to_string())),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::value_objects::{Plan, TenantStatus};
+    use serde_json::json;
+
+    #[test]
+    fn test_tenant_model_round_trip() {
+        let created_by = "0191f0a0-0000-7000-8000-000000000001";
+        let mut tenant = Tenant::new(
+            "Acme Corp",
+            TenantSlug::new("acme").unwrap(),
+            Plan::Starter,
+            Some(CreatedBy::new(created_by)),
+        )
+        .unwrap();
+        tenant.change_status(TenantStatus::Suspended).unwrap();
+        tenant.replace_setting(json!({"mfa": true})).unwrap();
+
+        let model = TenantModel::from(tenant.clone());
+        assert_eq!(model.plan, "starter");
+        assert_eq!(model.status, "suspended");
+        assert_eq!(model.created_by.unwrap().to_string(), created_by);
+
+        let restored = Tenant::from(model);
+        assert_eq!(restored.id, tenant.id);
+        assert_eq!(restored.slug, tenant.slug);
+        assert_eq!(restored.plan, Plan::Starter);
+        assert_eq!(restored.status, TenantStatus::Suspended);
+        assert_eq!(restored.setting, json!({"mfa": true}));
+    }
+}
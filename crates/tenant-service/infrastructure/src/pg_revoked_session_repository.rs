@@ -0,0 +1,37 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::repository::RevokedSessionRepository;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Reads the user service's `tbl_sessions`, which it alone writes.
+pub struct PgRevokedSessionRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgRevokedSessionRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RevokedSessionRepository for PgRevokedSessionRepository {
+    async fn list_revoked_since(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let rows = sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+            "SELECT id, revoked_at FROM tbl_sessions WHERE revoked_at > $1",
+        )
+        .bind(cutoff)
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, revoked_at)| (id.to_string(), revoked_at))
+            .collect())
+    }
+}
@@ -0,0 +1,97 @@
+use crate::TenantModel;
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::Tenant;
+use domain::repository::{TenantFilter, TenantRepository};
+use domain::value_objects::{TenantId, TenantSlug};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+const COLUMNS: &str = "id, name, slug, plan, plan_expires_at, setting, status, created_at, \
+     created_by, updated_at, updated_by";
+
+pub struct PgTenantRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgTenantRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TenantRepository for PgTenantRepository {
+    async fn create(&self, tenant: Tenant) -> Result<bool> {
+        let model = TenantModel::from(tenant);
+        let result = sqlx::query(&format!(
+            "INSERT INTO tbl_tenants ({COLUMNS}) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+             ON CONFLICT (slug) DO NOTHING"
+        ))
+        .bind(model.id)
+        .bind(model.name)
+        .bind(model.slug)
+        .bind(model.plan)
+        .bind(model.plan_expires_at)
+        .bind(model.setting)
+        .bind(model.status)
+        .bind(model.created_at)
+        .bind(model.created_by)
+        .bind(model.updated_at)
+        .bind(model.updated_by)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+    async fn find_by_id(&self, id: &TenantId) -> Result<Option<Tenant>> {
+        let row = sqlx::query_as::<_, TenantModel>(&format!(
+            "SELECT {COLUMNS} FROM tbl_tenants WHERE id = $1 AND deleted_at IS NULL"
+        ))
+        .bind(id.as_uuid())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(Tenant::from))
+    }
+    async fn find_by_slug(&self, slug: &TenantSlug) -> Result<Option<Tenant>> {
+        let row = sqlx::query_as::<_, TenantModel>(&format!(
+            "SELECT {COLUMNS} FROM tbl_tenants WHERE slug = $1 AND deleted_at IS NULL"
+        ))
+        .bind(slug.as_str())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(Tenant::from))
+    }
+    async fn update(&self, tenant: &Tenant) -> Result<bool> {
+        let model = TenantModel::from(tenant.clone());
+        // `updated_at` is set by the table's trigger.
+        let result = sqlx::query(
+            "UPDATE tbl_tenants SET name = $2, plan = $3, plan_expires_at = $4, setting = $5, \
+             status = $6, updated_by = $7 \
+             WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(model.id)
+        .bind(model.name)
+        .bind(model.plan)
+        .bind(model.plan_expires_at)
+        .bind(model.setting)
+        .bind(model.status)
+        .bind(model.updated_by)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+    async fn list(&self, filter: &TenantFilter) -> Result<Vec<Tenant>> {
+        let rows = sqlx::query_as::<_, TenantModel>(&format!(
+            "SELECT {COLUMNS} FROM tbl_tenants \
+             WHERE deleted_at IS NULL AND ($1::varchar IS NULL OR status = $1) \
+             ORDER BY created_at, id LIMIT $2 OFFSET $3"
+        ))
+        .bind(filter.status.map(|status| status.as_str()))
+        .bind(i64::from(filter.limit))
+        .bind(i64::from(filter.offset))
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Tenant::from).collect())
+    }
+}
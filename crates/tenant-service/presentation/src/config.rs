@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use auth::{JwtAlgorithm, JwtConfig};
+use serde::Deserialize;
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use tracing::info;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Env {
+    pub database_url: String,
+
+    #[serde(default = "default_max_connection")]
+    pub max_connection: u32,
+
+    #[serde(default = "default_min_connection")]
+    pub min_connection: u32,
+
+    #[serde(default = "default_tenant_service_addr")]
+    pub tenant_service_addr: String,
+
+    /// One of `HS256`, `RS256` or `EdDSA`, as the user service signs with.
+    #[serde(default = "default_jwt_algorithm")]
+    pub jwt_algorithm: String,
+
+    /// Shared secret used when `jwt_algorithm` is `HS256`.
+    pub jwt_secret: Option<String>,
+
+    /// PEM file of the user service's public key, for `RS256` or `EdDSA`
+    /// when its keys are not fetched from `jwks_url`.
+    pub jwt_public_key_path: Option<String>,
+
+    #[serde(default = "default_jwt_issuer")]
+    pub jwt_issuer: String,
+
+    #[serde(default = "default_jwt_audience")]
+    pub jwt_audience: String,
+
+    /// JWKS endpoint of the user service. When set, the keys tokens are
+    /// verified with are fetched from it and follow its rotations, and the
+    /// configured key is not used.
+    pub jwks_url: Option<String>,
+
+    /// How long fetched keys are used before they are fetched again.
+    #[serde(default = "default_jwks_cache_secs")]
+    pub jwks_cache_secs: u64,
+
+    #[serde(default = "default_jwks_http_timeout_secs")]
+    pub jwks_http_timeout_secs: u64,
+
+    /// Lifetime of the longest-lived token the user service binds to a
+    /// session, access or impersonation. A revoked session is refused for
+    /// this long after its revocation.
+    #[serde(default = "default_session_token_ttl_secs")]
+    pub session_token_ttl_secs: u64,
+
+    /// How often sessions revoked through the user service are picked up.
+    #[serde(default = "default_session_revocation_sync_secs")]
+    pub session_revocation_sync_secs: u64,
+}
+
+fn default_max_connection() -> u32 {
+    20
+}
+
+fn default_min_connection() -> u32 {
+    2
+}
+
+fn default_tenant_service_addr() -> String {
+    "0.0.0.0:3001".to_string()
+}
+
+fn default_jwt_algorithm() -> String {
+    "HS256".to_string()
+}
+
+fn default_jwt_issuer() -> String {
+    "user-service".to_string()
+}
+
+fn default_jwt_audience() -> String {
+    "ddd-rust-application".to_string()
+}
+
+fn default_jwks_cache_secs() -> u64 {
+    5 * 60
+}
+
+fn default_jwks_http_timeout_secs() -> u64 {
+    5
+}
+
+fn default_session_token_ttl_secs() -> u64 {
+    15 * 60
+}
+
+fn default_session_revocation_sync_secs() -> u64 {
+    10
+}
+
+impl Env {
+    pub fn from_env() -> Result<Self, config::ConfigError> {
+        dotenvy::dotenv().ok();
+        info!("Loading configuration from environment variables");
+        let config = config::Config::builder()
+            .add_source(config::Environment::default())
+            .build()?;
+
+        config.try_deserialize()
+    }
+
+    /// Settings for verifying the user service's access tokens. This
+    /// service issues none, so it holds no private key.
+    pub fn jwt_config(&self) -> Result<JwtConfig> {
+        let algorithm: JwtAlgorithm = self.jwt_algorithm.parse()?;
+        let public_key_pem = self
+            .jwt_public_key_path
+            .as_ref()
+            .map(|p| {
+                std::fs::read_to_string(p).with_context(|| format!("Failed to read key {}", p))
+            })
+            .transpose()?;
+        Ok(JwtConfig {
+            algorithm,
+            secret: self.jwt_secret.clone(),
+            private_key_pem: None,
+            public_key_pem,
+            issuer: self.jwt_issuer.clone(),
+            audience: self.jwt_audience.clone(),
+            // Only matters for issuing.
+            access_token_ttl: Duration::ZERO,
+        })
+    }
+}
+
+pub async fn init_connection(db_url: &str, max_conn: u32, min_conn: u32) -> Result<PgPool> {
+    let pool = PgPoolOptions::new()
+        .max_connections(max_conn)
+        .min_connections(min_conn)
+        .acquire_timeout(Duration::from_secs(1))
+        .connect(db_url)
+        .await?;
+    Ok(pool)
+}
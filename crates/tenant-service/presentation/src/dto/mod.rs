@@ -0,0 +1,3 @@
+pub mod tenant;
+
+pub use tenant::*;
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use domain::Tenant;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Deserialize)]
+pub struct CreateTenantRequest {
+    pub name: String,
+    pub slug: String,
+    /// Defaults to `free`.
+    pub plan: Option<String>,
+}
+
+/// Fields left out are not changed. The slug never changes.
+#[derive(Deserialize)]
+pub struct UpdateTenantRequest {
+    pub name: Option<String>,
+    pub plan: Option<String>,
+    /// When the new `plan` lapses; omit for a plan that does not. Sent
+    /// without a `plan`, moves the end of the current one.
+    pub plan_expires_at: Option<DateTime<Utc>>,
+    pub status: Option<String>,
+    /// Replaces the whole setting object.
+    pub setting: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct ListTenantsParams {
+    pub status: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct TenantResponse {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub plan: String,
+    pub plan_expires_at: Option<DateTime<Utc>>,
+    pub setting: Value,
+    pub status: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Tenant> for TenantResponse {
+    fn from(tenant: Tenant) -> Self {
+        let audit = tenant.audit.as_ref();
+        Self {
+            id: tenant.id.to_string(),
+            slug: tenant.slug.as_str().to_string(),
+            plan: tenant.plan.to_string(),
+            plan_expires_at: tenant.plan_expires_at,
+            status: tenant.status.to_string(),
+            created_at: audit.map(|audit| audit.created_at.value()),
+            updated_at: audit.and_then(|audit| audit.updated_at.map(|at| at.value())),
+            name: tenant.name,
+            setting: tenant.setting,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use auth::Principal;
+use axum::{extract::FromRequestParts, http::request::Parts};
+use base::web::error::AppError;
+
+use crate::handlers::AppState;
+
+/// Role of the platform operators the user service issues tokens for.
+/// Tenants are managed across tenant boundaries, so only they may.
+pub const PLATFORM_ADMIN_ROLE: &str = "supper_admin";
+
+/// A signed-in platform operator. Rejects anonymous requests with 401 and
+/// everyone else with 403.
+pub struct PlatformAdmin(pub Principal);
+
+impl FromRequestParts<Arc<AppState>> for PlatformAdmin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let principal = Principal::from_request_parts(parts, state).await?;
+        if principal.role != PLATFORM_ADMIN_ROLE {
+            return Err(AppError::Forbidden(format!(
+                "Requires the {} role",
+                PLATFORM_ADMIN_ROLE
+            )));
+        }
+        Ok(Self(principal))
+    }
+}
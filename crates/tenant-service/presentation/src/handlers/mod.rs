@@ -0,0 +1,32 @@
+pub mod tenant;
+
+use std::sync::Arc;
+
+use ::auth::{AuthState, JwksCache, JwtService, KeyRefresher, RevocationList};
+use application::tenant_service::TenantApplicationService;
+
+pub use tenant::*;
+
+pub struct AppState {
+    pub tenant_service: Arc<TenantApplicationService>,
+    pub jwt: Arc<JwtService>,
+    /// `None` when tokens are verified with the configured key rather than
+    /// the user service's published keys.
+    pub jwks: Option<Arc<JwksCache>>,
+    /// Sessions the user service revoked, kept in sync from its table.
+    pub revocations: Arc<RevocationList>,
+}
+
+impl AuthState for AppState {
+    fn jwt_service(&self) -> &JwtService {
+        &self.jwt
+    }
+
+    fn key_refresher(&self) -> Option<&dyn KeyRefresher> {
+        self.jwks.as_deref().map(|jwks| jwks as &dyn KeyRefresher)
+    }
+
+    fn revocation_list(&self) -> Option<&RevocationList> {
+        Some(&self.revocations)
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use application::commands::{CreateTenantCommand, UpdateTenantCommand};
+use application::queries::{GetTenantQuery, ListTenantsQuery};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{CreateTenantRequest, ListTenantsParams, TenantResponse, UpdateTenantRequest};
+use crate::extractors::PlatformAdmin;
+use crate::handlers::AppState;
+
+pub async fn create_tenant_handler(
+    PlatformAdmin(principal): PlatformAdmin,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<CreateTenantRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = CreateTenantCommand {
+        actor_id: principal.audit_id().to_string(),
+        name: request.name,
+        slug: request.slug,
+        plan: request.plan,
+    };
+    let tenant = app_state.tenant_service.create(command).await?;
+    Ok(ApiResponse::created(TenantResponse::from(tenant)))
+}
+
+pub async fn get_tenant_handler(
+    _: PlatformAdmin,
+    State(app_state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let tenant = app_state
+        .tenant_service
+        .get(GetTenantQuery { tenant_id })
+        .await?;
+    Ok(ApiResponse::ok(TenantResponse::from(tenant)))
+}
+
+pub async fn update_tenant_handler(
+    PlatformAdmin(principal): PlatformAdmin,
+    State(app_state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<UpdateTenantRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = UpdateTenantCommand {
+        actor_id: principal.audit_id().to_string(),
+        tenant_id,
+        name: request.name,
+        plan: request.plan,
+        plan_expires_at: request.plan_expires_at,
+        status: request.status,
+        setting: request.setting,
+    };
+    let tenant = app_state.tenant_service.update(command).await?;
+    Ok(ApiResponse::ok(TenantResponse::from(tenant)))
+}
+
+pub async fn list_tenants_handler(
+    _: PlatformAdmin,
+    State(app_state): State<Arc<AppState>>,
+    Query(params): Query<ListTenantsParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = ListTenantsQuery {
+        status: params.status,
+        limit: params.limit,
+        offset: params.offset,
+    };
+    let tenants = app_state.tenant_service.list(query).await?;
+    Ok(ApiResponse::ok(
+        tenants
+            .into_iter()
+            .map(TenantResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
@@ -0,0 +1,132 @@
+mod config;
+mod dto;
+mod extractors;
+mod handlers;
+
+use application::revocation_service::RevocationSyncService;
+use application::tenant_service::TenantApplicationService;
+use auth::{HttpJwksSource, JwksCache, JwtService, RevocationList};
+use axum::{Router, routing::get};
+use config::Env;
+use handlers::AppState;
+use handlers::{
+    create_tenant_handler, get_tenant_handler, list_tenants_handler, update_tenant_handler,
+};
+use infrastructure::{PgRevokedSessionRepository, PgTenantRepository};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+    let cfg = Env::from_env()
+        .map_err(|e| {
+            panic!("Failed to load configuration: {}", e);
+        })
+        .unwrap();
+    info!("Load configuration successfully.");
+
+    let jwt_config = cfg.jwt_config()?;
+    let (jwt, jwks) = match &cfg.jwks_url {
+        Some(url) => {
+            let jwt = Arc::new(JwtService::without_keys(jwt_config));
+            let source =
+                HttpJwksSource::new(url.clone(), Duration::from_secs(cfg.jwks_http_timeout_secs))?;
+            let jwks = Arc::new(JwksCache::new(
+                Arc::clone(&jwt),
+                Arc::new(source),
+                Duration::from_secs(cfg.jwks_cache_secs),
+            ));
+            // Not fatal: the keys are fetched again when a token needs them.
+            if let Err(e) = jwks.refresh_if_stale().await {
+                warn!("Failed to fetch the user service's keys: {}", e);
+            }
+            spawn_jwks_refresh(Arc::clone(&jwks), Duration::from_secs(cfg.jwks_cache_secs));
+            info!("Verifying tokens with the keys published at {}", url);
+            (jwt, Some(jwks))
+        }
+        None => (Arc::new(JwtService::new(jwt_config)?), None),
+    };
+
+    let pool =
+        config::init_connection(&cfg.database_url, cfg.max_connection, cfg.min_connection).await?;
+    info!("Connected to the database");
+
+    let conn = Arc::new(pool);
+    let tenant_repo = Arc::new(PgTenantRepository::new(Arc::clone(&conn)));
+    let tenant_service = Arc::new(TenantApplicationService::new(tenant_repo));
+    let revocations = Arc::new(RevocationList::new());
+    let revocation_service = Arc::new(RevocationSyncService::new(
+        Arc::new(PgRevokedSessionRepository::new(Arc::clone(&conn))),
+        Arc::clone(&revocations),
+        Duration::from_secs(cfg.session_token_ttl_secs),
+    ));
+    revocation_service
+        .sync()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load revoked sessions: {}", e))?;
+    spawn_revocation_sync(
+        revocation_service,
+        Duration::from_secs(cfg.session_revocation_sync_secs),
+    );
+
+    let share_state = Arc::new(AppState {
+        tenant_service,
+        jwt,
+        jwks,
+        revocations,
+    });
+    let app = Router::new()
+        .route(
+            "/tenants",
+            get(list_tenants_handler).post(create_tenant_handler),
+        )
+        .route(
+            "/tenants/{id}",
+            get(get_tenant_handler).patch(update_tenant_handler),
+        )
+        .with_state(share_state);
+
+    let listener = tokio::net::TcpListener::bind(&cfg.tenant_service_addr).await?;
+    info!(
+        "Tenant service is running on http://{}",
+        listener.local_addr()?
+    );
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// Fetches the user service's keys again once they get stale, picking up
+/// its rotations before a token signed with a new key arrives.
+fn spawn_jwks_refresh(jwks: Arc<JwksCache>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick is immediate, and startup has just fetched.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = jwks.refresh_if_stale().await {
+                warn!("Refreshing the user service's keys failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Picks up sessions revoked through the user service.
+fn spawn_revocation_sync(service: Arc<RevocationSyncService>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick is immediate, and startup has just synced.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = service.sync().await {
+                warn!("Revoked session sync failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,222 @@
+use auth::{AccessRequest, Attributes, Decision, PolicySet, Principal};
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::User;
+use domain::repository::TenantSettingsRepository;
+use domain::value_objects::TenantId;
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+pub const ACTION_USERS_READ: &str = "users:read";
+pub const ACTION_USERS_UNLOCK: &str = "users:unlock";
+pub const ACTION_USERS_IMPERSONATE: &str = "users:impersonate";
+pub const ACTION_USERS_REVOKE_SESSIONS: &str = "users:revoke_sessions";
+
+/// Policies used when the deployment does not configure its own.
+const DEFAULT_POLICIES: &str = include_str!("default_access_policies.json");
+
+/// Checks callers against the service-wide access policies and those of
+/// their tenant before a command touches anything.
+pub struct AccessControl {
+    policies: PolicySet,
+    tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+}
+
+impl AccessControl {
+    pub fn new(
+        policies: PolicySet,
+        tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+    ) -> Self {
+        Self {
+            policies,
+            tenant_settings_repo,
+        }
+    }
+
+    pub fn default_policies() -> PolicySet {
+        PolicySet::from_json(DEFAULT_POLICIES).expect("Invalid default access policies")
+    }
+
+    /// Decides whether `actor` may perform `action` on `resource`, logging
+    /// the decision and its reason for the audit trail.
+    pub async fn authorize(
+        &self,
+        actor: &Principal,
+        action: &str,
+        resource: Attributes,
+    ) -> Result<Decision, AppError> {
+        let tenant_policies = self.tenant_policies(&actor.tenant_id).await?;
+        let mut context = Attributes::new();
+        context.insert("now".into(), Utc::now().timestamp().into());
+        let request = AccessRequest {
+            subject: actor.attributes(),
+            action: action.to_string(),
+            resource,
+            context,
+        };
+        let decision = self.policies.evaluate_with(&tenant_policies, &request);
+        if decision.allowed {
+            info!(
+                subject = %actor.user_id,
+                tenant_id = %actor.tenant_id,
+                action,
+                policy = decision.policy_id.as_deref().unwrap_or_default(),
+                "Access granted: {}",
+                decision.reason
+            );
+            Ok(decision)
+        } else {
+            warn!(
+                subject = %actor.user_id,
+                tenant_id = %actor.tenant_id,
+                action,
+                policy = decision.policy_id.as_deref().unwrap_or_default(),
+                "Access denied: {}",
+                decision.reason
+            );
+            Err(AppError::Forbidden(format!("Not allowed to {}", action)))
+        }
+    }
+
+    async fn tenant_policies(&self, tenant_id: &str) -> Result<PolicySet, AppError> {
+        let tenant_id = TenantId::from_string(tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let settings = self
+            .tenant_settings_repo
+            .find_settings(&tenant_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .unwrap_or_default();
+        if settings.access_policies.is_empty() {
+            return Ok(PolicySet::default());
+        }
+        // A tenant whose policies cannot be read gets no access at all
+        // rather than access its policies may have meant to refuse.
+        PolicySet::from_value(Value::Array(settings.access_policies)).map_err(|e| {
+            warn!(tenant_id = %tenant_id, "Rejecting tenant access policies: {}", e);
+            AppError::from(e)
+        })
+    }
+}
+
+/// A user as the resource of an access request.
+pub fn user_attributes(user: &User) -> Attributes {
+    let mut attributes = Attributes::new();
+    attributes.insert("id".into(), user.id.as_str().into());
+    attributes.insert("tenant_id".into(), user.tenant_id.as_str().into());
+    attributes.insert("role".into(), user.role.as_str().into());
+    attributes.insert("email_verified".into(), user.email_verified.into());
+    attributes.insert("locked".into(), user.is_locked(Utc::now()).into());
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Stores, TENANT_ID, principal, tenant_id};
+    use auth::Credential;
+    use domain::value_objects::{Role, TenantSettings};
+    use serde_json::json;
+
+    async fn allowed(
+        access: &AccessControl,
+        actor: &Principal,
+        action: &str,
+        target: &User,
+    ) -> bool {
+        access
+            .authorize(actor, action, user_attributes(target))
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_default_policies_follow_roles() {
+        let stores = Stores::default();
+        let access = stores.access_control();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        let manager = stores.seed_user_with_role("manager", Role::Manager);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let bob = stores.seed_user_with_role("bob", Role::User);
+
+        assert!(allowed(&access, &principal(&admin), ACTION_USERS_UNLOCK, &manager).await);
+        assert!(allowed(&access, &principal(&manager), ACTION_USERS_UNLOCK, &alice).await);
+        assert!(!allowed(&access, &principal(&manager), ACTION_USERS_UNLOCK, &admin).await);
+        assert!(allowed(&access, &principal(&alice), ACTION_USERS_READ, &alice).await);
+        assert!(!allowed(&access, &principal(&alice), ACTION_USERS_READ, &bob).await);
+        assert!(!allowed(&access, &principal(&alice), ACTION_USERS_UNLOCK, &alice).await);
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_are_bounded_by_scopes() {
+        let stores = Stores::default();
+        let access = stores.access_control();
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let key = Principal {
+            user_id: "0190b4a2-7c1e-7000-8000-0000000000aa".to_string(),
+            tenant_id: TENANT_ID.to_string(),
+            role: auth::API_KEY_ROLE.to_string(),
+            credential: Credential::ApiKey {
+                scopes: vec!["users:read".to_string()],
+            },
+        };
+        assert!(allowed(&access, &key, ACTION_USERS_READ, &alice).await);
+        assert!(!allowed(&access, &key, ACTION_USERS_UNLOCK, &alice).await);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_policies_apply_on_top() {
+        let stores = Stores::default();
+        let access = stores.access_control();
+        let manager = stores.seed_user_with_role("manager", Role::Manager);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        stores.tenant_settings.set(
+            tenant_id(),
+            TenantSettings {
+                access_policies: vec![json!({
+                    "id": "managers-read-only",
+                    "effect": "deny",
+                    "actions": ["users:unlock"],
+                    "condition": { "eq": [{ "attr": "subject.role" }, "manager"] }
+                })],
+                ..TenantSettings::default()
+            },
+        );
+        assert!(!allowed(&access, &principal(&manager), ACTION_USERS_UNLOCK, &alice).await);
+        let decision = access
+            .authorize(
+                &principal(&manager),
+                ACTION_USERS_READ,
+                user_attributes(&alice),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            decision.policy_id.as_deref(),
+            Some("managers-manage-non-admins")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_tenant_policies_deny_everything() {
+        let stores = Stores::default();
+        let access = stores.access_control();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        stores.tenant_settings.set(
+            tenant_id(),
+            TenantSettings {
+                access_policies: vec![json!({ "id": "broken" })],
+                ..TenantSettings::default()
+            },
+        );
+        let result = access
+            .authorize(
+                &principal(&admin),
+                ACTION_USERS_READ,
+                user_attributes(&admin),
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::InternalServerError(_))));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::commands::{CreateApiKeyCommand, RevokeApiKeyCommand};
+use crate::queries::ListApiKeysQuery;
+use async_trait::async_trait;
+use auth::{
+    API_KEY_ROLE, ApiKeyVerifier, Credential, Principal, api_key_prefix, generate_api_key,
+    hash_token,
+};
+use base::secret::SecretString;
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::ApiKey;
+use domain::repository::ApiKeyRepository;
+use domain::value_objects::{TenantId, UserId};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Tunables for API keys.
+#[derive(Debug, Clone)]
+pub struct ApiKeySettings {
+    /// How stale a key's last use may get before a request records it again.
+    /// Saves a write on every request of a busy integration.
+    pub usage_record_interval: Duration,
+}
+
+impl Default for ApiKeySettings {
+    fn default() -> Self {
+        Self {
+            usage_record_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A newly minted key. The key itself is only ever shown this once.
+#[derive(Debug, Clone)]
+pub struct CreatedApiKey {
+    pub api_key: ApiKey,
+    pub key: SecretString,
+}
+
+pub struct ApiKeyApplicationService {
+    api_key_repo: Arc<dyn ApiKeyRepository>,
+    settings: ApiKeySettings,
+}
+
+impl ApiKeyApplicationService {
+    pub fn new(api_key_repo: Arc<dyn ApiKeyRepository>, settings: ApiKeySettings) -> Self {
+        Self {
+            api_key_repo,
+            settings,
+        }
+    }
+
+    pub async fn create(&self, cmd: CreateApiKeyCommand) -> Result<CreatedApiKey, AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let created_by = UserId::from_string(&cmd.created_by)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        let generated = generate_api_key();
+        let api_key = ApiKey::new(
+            tenant_id,
+            cmd.name,
+            generated.prefix,
+            hash_token(&generated.key),
+            cmd.scopes,
+            created_by,
+            cmd.expires_at,
+        )?;
+        self.api_key_repo
+            .create(api_key.clone())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        info!(
+            api_key_id = %api_key.id,
+            tenant_id = %api_key.tenant_id,
+            created_by = %api_key.created_by,
+            "API key created"
+        );
+        Ok(CreatedApiKey {
+            api_key,
+            key: generated.key.into(),
+        })
+    }
+
+    pub async fn list(&self, query: ListApiKeysQuery) -> Result<Vec<ApiKey>, AppError> {
+        let tenant_id = TenantId::from_string(&query.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        self.api_key_repo
+            .list(&tenant_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    pub async fn revoke(&self, cmd: RevokeApiKeyCommand) -> Result<(), AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let api_key_id = Uuid::parse_str(&cmd.api_key_id)
+            .map_err(|_| AppError::BadRequest("Invalid API key id".into()))?;
+        let revoked = self
+            .api_key_repo
+            .revoke(&tenant_id, api_key_id, Utc::now())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if !revoked {
+            return Err(AppError::NotFound("API key not found".into()));
+        }
+        info!(api_key_id = %api_key_id, "API key revoked");
+        Ok(())
+    }
+
+    /// Resolves a presented key into the principal it acts as.
+    pub async fn authenticate(&self, key: &str) -> Result<Principal, AppError> {
+        let prefix = api_key_prefix(key).ok_or_else(invalid_api_key)?;
+        let api_key = self
+            .api_key_repo
+            .find_by_prefix(prefix)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|k| k.key_hash == hash_token(key))
+            .ok_or_else(invalid_api_key)?;
+        let now = Utc::now();
+        if !api_key.is_active(now) {
+            return Err(invalid_api_key());
+        }
+
+        let interval = chrono::Duration::from_std(self.settings.usage_record_interval)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if api_key.last_used_at.is_none_or(|at| at + interval <= now) {
+            // Usage tracking is informational; it must not fail the request.
+            if let Err(e) = self.api_key_repo.record_use(api_key.id, now).await {
+                warn!(api_key_id = %api_key.id, "Failed to record API key use: {}", e);
+            }
+        }
+
+        Ok(Principal {
+            user_id: api_key.id.to_string(),
+            tenant_id: api_key.tenant_id.as_str(),
+            role: API_KEY_ROLE.to_string(),
+            credential: Credential::ApiKey {
+                scopes: api_key.scopes,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl ApiKeyVerifier for ApiKeyApplicationService {
+    async fn verify_api_key(&self, key: &str) -> Result<Principal, AppError> {
+        self.authenticate(key).await
+    }
+}
+
+fn invalid_api_key() -> AppError {
+    AppError::Unauthorized("Invalid API key".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Stores, TENANT_ID};
+    use domain::entities::api_key::{SCOPE_USERS_READ, SCOPE_USERS_WRITE};
+
+    fn create_command(scopes: &[&str]) -> CreateApiKeyCommand {
+        CreateApiKeyCommand {
+            tenant_id: TENANT_ID.to_string(),
+            created_by: UserId::new().as_str(),
+            name: "CRM sync".to_string(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            expires_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_key_authenticates_with_its_scopes() {
+        let stores = Stores::default();
+        let service = stores.api_key_service();
+        let created = service
+            .create(create_command(&[SCOPE_USERS_READ]))
+            .await
+            .unwrap();
+        assert!(
+            created
+                .key
+                .expose_secret()
+                .contains(&created.api_key.prefix)
+        );
+        assert_ne!(created.api_key.key_hash, *created.key.expose_secret());
+
+        let principal = service
+            .authenticate(created.key.expose_secret())
+            .await
+            .unwrap();
+        assert_eq!(principal.tenant_id, TENANT_ID);
+        assert_eq!(principal.role, API_KEY_ROLE);
+        assert!(principal.has_scope(SCOPE_USERS_READ));
+        assert!(!principal.has_scope(SCOPE_USERS_WRITE));
+        assert!(
+            stores
+                .api_keys
+                .get(created.api_key.id)
+                .unwrap()
+                .last_used_at
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wrong_secret_is_rejected() {
+        let stores = Stores::default();
+        let service = stores.api_key_service();
+        let created = service
+            .create(create_command(&[SCOPE_USERS_READ]))
+            .await
+            .unwrap();
+        let forged = format!("ak_{}_not-the-secret", created.api_key.prefix);
+        for key in [forged.as_str(), "ak_garbage", ""] {
+            let result = service.authenticate(key).await;
+            assert!(matches!(result, Err(AppError::Unauthorized(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revoked_key_is_rejected() {
+        let stores = Stores::default();
+        let service = stores.api_key_service();
+        let created = service
+            .create(create_command(&[SCOPE_USERS_WRITE]))
+            .await
+            .unwrap();
+        service
+            .revoke(RevokeApiKeyCommand {
+                tenant_id: TENANT_ID.to_string(),
+                api_key_id: created.api_key.id.to_string(),
+            })
+            .await
+            .unwrap();
+        let result = service.authenticate(created.key.expose_secret()).await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+
+        let listed = service
+            .list(ListApiKeysQuery {
+                tenant_id: TENANT_ID.to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(listed[0].revoked_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_key_of_another_tenant_cannot_be_revoked() {
+        let stores = Stores::default();
+        let service = stores.api_key_service();
+        let created = service
+            .create(create_command(&[SCOPE_USERS_READ]))
+            .await
+            .unwrap();
+        let result = service
+            .revoke(RevokeApiKeyCommand {
+                tenant_id: "0190b4a2-7c1e-7000-8000-000000000000".to_string(),
+                api_key_id: created.api_key.id.to_string(),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert!(
+            service
+                .authenticate(created.key.expose_secret())
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,1232 @@
+use crate::commands::{
+    ChangeExpiredPasswordCommand, FinishPasskeyLoginCommand, LoginCommand, RefreshTokenCommand,
+    StartPasskeyLoginCommand, VerifyMfaCommand,
+};
+use crate::passkey_service::{PasskeyCeremony, take_challenge};
+use crate::password_service::PasswordApplicationService;
+use crate::session_service::{SessionApplicationService, invalid_refresh_token};
+use auth::webauthn::{RelyingParty, RequestOptions};
+use auth::{JwtService, generate_opaque_token, hash_token};
+use base::secret::SecretString;
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::policies::LockoutPolicy;
+use domain::repository::{
+    MfaRepository, TenantSettingsRepository, UserRepositories, WebAuthnRepository,
+};
+use domain::value_objects::{EmailAddress, Password, TenantId, TenantSettings, UserId};
+use domain::{ClientInfo, RecoveryCode, TotpEnrollment, User, WebAuthnCeremony, WebAuthnChallenge};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::warn;
+use uuid::Uuid;
+
+pub const TOKEN_TYPE_BEARER: &str = "Bearer";
+/// Challenge purpose of a login waiting for its second factor.
+pub const MFA_CHALLENGE_PURPOSE: &str = "mfa";
+/// Challenge purpose of a login waiting for a mandatory MFA enrollment.
+pub const MFA_ENROLLMENT_PURPOSE: &str = "mfa_enrollment";
+/// Challenge purpose of a login waiting for an expired password to be
+/// changed.
+pub const PASSWORD_CHANGE_PURPOSE: &str = "password_change";
+
+/// Tokens handed back to a client after a successful authentication.
+#[derive(Debug, Clone)]
+pub struct AuthTokens {
+    pub access_token: SecretString,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: SecretString,
+}
+
+/// A short-lived token standing in for a login that still needs a second step.
+#[derive(Debug, Clone)]
+pub struct MfaChallenge {
+    pub mfa_token: SecretString,
+    pub expires_in: i64,
+}
+
+/// Result of a correct email and password.
+#[derive(Debug, Clone)]
+pub enum LoginOutcome {
+    Authenticated(AuthTokens),
+    /// The user has MFA enabled and must present a code to `verify_mfa`.
+    MfaRequired(MfaChallenge),
+    /// The tenant requires MFA for the user's role but none is enrolled yet.
+    /// The challenge token allows enrolling, after which `verify_mfa` completes
+    /// the login.
+    MfaEnrollmentRequired(MfaChallenge),
+    /// The password is older than the tenant allows. The challenge token
+    /// allows `change_expired_password`, which then carries on with the
+    /// login.
+    PasswordChangeRequired(MfaChallenge),
+}
+
+/// Tunables for the authentication flows.
+#[derive(Debug, Clone)]
+pub struct AuthSettings {
+    pub lockout: LockoutPolicy,
+    /// How long a login may wait between the password and the MFA step.
+    pub mfa_challenge_ttl: Duration,
+    /// Relying party that passkey logins are checked against.
+    pub relying_party: RelyingParty,
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            lockout: LockoutPolicy::default(),
+            mfa_challenge_ttl: Duration::from_secs(5 * 60),
+            relying_party: RelyingParty::default(),
+        }
+    }
+}
+
+pub struct AuthApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    sessions: Arc<SessionApplicationService<R>>,
+    passwords: Arc<PasswordApplicationService<R>>,
+    tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+    mfa_repo: Arc<dyn MfaRepository>,
+    webauthn_repo: Arc<dyn WebAuthnRepository>,
+    jwt: Arc<JwtService>,
+    settings: AuthSettings,
+    /// Verified in place of a password when the email is unknown.
+    dummy_password: OnceCell<Password>,
+}
+
+impl<R: UserRepositories> AuthApplicationService<R> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_repo: Arc<R>,
+        sessions: Arc<SessionApplicationService<R>>,
+        passwords: Arc<PasswordApplicationService<R>>,
+        tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+        mfa_repo: Arc<dyn MfaRepository>,
+        webauthn_repo: Arc<dyn WebAuthnRepository>,
+        jwt: Arc<JwtService>,
+        settings: AuthSettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            sessions,
+            passwords,
+            tenant_settings_repo,
+            mfa_repo,
+            webauthn_repo,
+            jwt,
+            settings,
+            dummy_password: OnceCell::new(),
+        }
+    }
+
+    pub async fn login(&self, cmd: LoginCommand) -> Result<LoginOutcome, AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let email = EmailAddress::new(cmd.email)?;
+        let user = self
+            .user_repo
+            .find_by_email(&tenant_id, &email)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        let Some(user) = user else {
+            // Spend the same time as a real verification so unknown emails
+            // cannot be told apart from wrong passwords.
+            let dummy = self.dummy_password().await?;
+            self.passwords
+                .hashing_pool()
+                .verify(dummy, &cmd.password)
+                .await?;
+            return Err(invalid_credentials());
+        };
+        if user.is_locked(Utc::now()) {
+            return Err(account_locked());
+        }
+        if !self
+            .passwords
+            .hashing_pool()
+            .verify(&user.password_hash, &cmd.password)
+            .await?
+        {
+            return Err(self
+                .register_failed_login(&user, invalid_credentials())
+                .await);
+        }
+        self.passwords.upgrade_hash(&user, &cmd.password).await;
+        let tenant_settings = self.tenant_settings(&user.tenant_id).await?;
+        if !user.email_verified && tenant_settings.require_email_verification {
+            return Err(AppError::Forbidden("Email address is not verified".into()));
+        }
+        let expired = user.password_set_at().is_some_and(|set_at| {
+            tenant_settings
+                .password_policy
+                .is_expired(set_at, Utc::now())
+        });
+        if expired {
+            let challenge = self.mfa_challenge(&user, PASSWORD_CHANGE_PURPOSE)?;
+            return Ok(LoginOutcome::PasswordChangeRequired(challenge));
+        }
+
+        self.continue_login(&user, &tenant_settings, cmd.client)
+            .await
+    }
+
+    /// Replaces an expired password, then carries on with the login that
+    /// asked for it.
+    pub async fn change_expired_password(
+        &self,
+        cmd: ChangeExpiredPasswordCommand,
+    ) -> Result<LoginOutcome, AppError> {
+        let claims = self.jwt.verify_challenge_token(
+            cmd.password_change_token.expose_secret(),
+            PASSWORD_CHANGE_PURPOSE,
+        )?;
+        let user_id =
+            UserId::from_string(&claims.sub).map_err(|_| invalid_password_change_token())?;
+        let user = self
+            .user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(invalid_password_change_token)?;
+        if user.is_locked(Utc::now()) {
+            return Err(account_locked());
+        }
+        self.passwords
+            .replace_password(&user, &cmd.new_password)
+            .await?;
+
+        let tenant_settings = self.tenant_settings(&user.tenant_id).await?;
+        self.continue_login(&user, &tenant_settings, cmd.client)
+            .await
+    }
+
+    /// Completes a login with a TOTP code or a recovery code. Wrong codes count
+    /// towards the lockout just like wrong passwords.
+    pub async fn verify_mfa(&self, cmd: VerifyMfaCommand) -> Result<AuthTokens, AppError> {
+        let claims = self
+            .jwt
+            .verify_challenge_token(cmd.mfa_token.expose_secret(), MFA_CHALLENGE_PURPOSE)
+            .or_else(|_| {
+                self.jwt
+                    .verify_challenge_token(cmd.mfa_token.expose_secret(), MFA_ENROLLMENT_PURPOSE)
+            })?;
+        let user_id = UserId::from_string(&claims.sub).map_err(|_| invalid_mfa_token())?;
+        let user = self
+            .user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(invalid_mfa_token)?;
+        if user.is_locked(Utc::now()) {
+            return Err(account_locked());
+        }
+        let enrollment = self
+            .confirmed_totp(&user.id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("MFA is not enabled".into()))?;
+
+        let accepted = match (cmd.code, cmd.recovery_code) {
+            (Some(code), _) => match enrollment.secret.verify(&code, Utc::now()) {
+                Some(step) => self
+                    .mfa_repo
+                    .record_totp_step(&user.id, step)
+                    .await
+                    .map_err(|e| AppError::InternalServerError(e.to_string()))?,
+                None => false,
+            },
+            (None, Some(recovery_code)) => {
+                let code_hash = hash_token(&RecoveryCode::normalize(&recovery_code));
+                self.mfa_repo
+                    .use_recovery_code(&user.id, &code_hash)
+                    .await
+                    .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            }
+            (None, None) => {
+                return Err(AppError::BadRequest(
+                    "A code or recovery code is required".into(),
+                ));
+            }
+        };
+        if !accepted {
+            return Err(self.register_failed_login(&user, invalid_mfa_code()).await);
+        }
+
+        self.sign_in(&user, cmd.client, true).await
+    }
+
+    /// Starts a passkey login. Without an email, or for an unknown one, any
+    /// discoverable passkey of the tenant may answer, so the response does not
+    /// reveal whether an account exists.
+    pub async fn start_passkey_login(
+        &self,
+        cmd: StartPasskeyLoginCommand,
+    ) -> Result<PasskeyCeremony<RequestOptions>, AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let user = match cmd.email {
+            Some(email) => self
+                .user_repo
+                .find_by_email(&tenant_id, &EmailAddress::new(email)?)
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?,
+            None => None,
+        };
+        let allow = match &user {
+            Some(user) => self
+                .webauthn_repo
+                .list_credentials(&user.id)
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?
+                .into_iter()
+                .map(|c| c.credential_id)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let rp = &self.settings.relying_party;
+        let ttl = chrono::Duration::from_std(rp.timeout)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let challenge = WebAuthnChallenge::issue(
+            tenant_id,
+            user.map(|u| u.id),
+            WebAuthnCeremony::Authentication,
+            generate_opaque_token(),
+            ttl,
+        );
+        let options = rp.request_options(&challenge.challenge, &allow);
+        let ceremony_id = challenge.id.to_string();
+        self.webauthn_repo
+            .create_challenge(challenge)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(PasskeyCeremony {
+            ceremony_id,
+            options,
+        })
+    }
+
+    /// Completes a passkey login. A passkey proves possession of a device and,
+    /// usually, a local PIN or biometric, so no further MFA step is asked for.
+    pub async fn finish_passkey_login(
+        &self,
+        cmd: FinishPasskeyLoginCommand,
+    ) -> Result<AuthTokens, AppError> {
+        let challenge = take_challenge(
+            self.webauthn_repo.as_ref(),
+            &cmd.ceremony_id,
+            WebAuthnCeremony::Authentication,
+        )
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Invalid or expired passkey ceremony".into()))?;
+        let credential_id = cmd
+            .credential
+            .credential_id()
+            .map_err(|_| passkey_rejected())?;
+        let stored = self
+            .webauthn_repo
+            .find_credential(&challenge.tenant_id, &credential_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(passkey_rejected)?;
+        if challenge.user_id.is_some_and(|id| id != stored.user_id) {
+            return Err(passkey_rejected());
+        }
+        let user_handle = cmd
+            .credential
+            .user_handle()
+            .map_err(|_| passkey_rejected())?;
+        if user_handle.is_some_and(|h| h != stored.user_id.as_uuid().as_bytes()) {
+            return Err(passkey_rejected());
+        }
+
+        let sign_count = self
+            .settings
+            .relying_party
+            .verify_authentication(
+                &cmd.credential,
+                &challenge.challenge,
+                &stored.public_key,
+                stored.sign_count,
+            )
+            .map_err(|e| {
+                warn!(passkey_id = %stored.id, "Passkey assertion rejected: {}", e);
+                passkey_rejected()
+            })?;
+        self.webauthn_repo
+            .record_credential_use(stored.id, sign_count, Utc::now())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        let user = self
+            .user_repo
+            .find_by_user_id(&stored.user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(passkey_rejected)?;
+        if user.is_locked(Utc::now()) {
+            return Err(account_locked());
+        }
+        if !user.email_verified
+            && self
+                .tenant_settings(&user.tenant_id)
+                .await?
+                .require_email_verification
+        {
+            return Err(AppError::Forbidden("Email address is not verified".into()));
+        }
+
+        self.sign_in(&user, cmd.client, true).await
+    }
+
+    /// Signs in a user whose identity was established by their tenant's
+    /// identity provider. The provider owns the credentials and any second
+    /// factor, so only the lockout is checked here.
+    pub async fn login_federated(
+        &self,
+        user: &User,
+        client: ClientInfo,
+    ) -> Result<AuthTokens, AppError> {
+        if user.is_locked(Utc::now()) {
+            return Err(account_locked());
+        }
+        self.sign_in(user, client, false).await
+    }
+
+    /// Rotates a refresh token. Presenting a token that was already rotated
+    /// is treated as theft and ends its session.
+    pub async fn refresh(&self, cmd: RefreshTokenCommand) -> Result<AuthTokens, AppError> {
+        let stored = self
+            .sessions
+            .rotate_refresh_token(cmd.refresh_token.expose_secret())
+            .await?;
+        let user = self
+            .user_repo
+            .find_by_user_id(&stored.user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(invalid_refresh_token)?;
+        self.issue_tokens(&user, stored.family_id).await
+    }
+
+    /// Finishes a login whose password was accepted: asks for the second
+    /// factor when there is or must be one, and signs in otherwise.
+    async fn continue_login(
+        &self,
+        user: &User,
+        tenant_settings: &TenantSettings,
+        client: ClientInfo,
+    ) -> Result<LoginOutcome, AppError> {
+        if self.confirmed_totp(&user.id).await?.is_some() {
+            let challenge = self.mfa_challenge(user, MFA_CHALLENGE_PURPOSE)?;
+            return Ok(LoginOutcome::MfaRequired(challenge));
+        }
+        if tenant_settings.requires_mfa(user.role) {
+            let challenge = self.mfa_challenge(user, MFA_ENROLLMENT_PURPOSE)?;
+            return Ok(LoginOutcome::MfaEnrollmentRequired(challenge));
+        }
+
+        self.sign_in(user, client, false)
+            .await
+            .map(LoginOutcome::Authenticated)
+    }
+
+    /// Hashed with the configured parameters, so verifying it costs as much
+    /// as verifying a real password.
+    async fn dummy_password(&self) -> Result<&Password, AppError> {
+        self.dummy_password
+            .get_or_try_init(|| async {
+                self.passwords
+                    .hashing_pool()
+                    .hash(&"dummy-password-for-timing".into(), Vec::new())
+                    .await
+            })
+            .await
+    }
+
+    /// Starts a session for a completed login and issues its tokens. Earlier
+    /// failed attempts are only forgotten here: clearing them on a correct
+    /// password that still awaits its second factor would let wrong codes be
+    /// guessed without ever reaching the lockout.
+    async fn sign_in(
+        &self,
+        user: &User,
+        client: ClientInfo,
+        mfa_verified: bool,
+    ) -> Result<AuthTokens, AppError> {
+        if user.failed_login_attempts > 0 || user.locked_until.is_some() {
+            self.user_repo
+                .clear_lockout(&user.id, None)
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        }
+        let session = self.sessions.start(user, client, mfa_verified).await?;
+        self.issue_tokens(user, session.id).await
+    }
+
+    async fn issue_tokens(&self, user: &User, session_id: Uuid) -> Result<AuthTokens, AppError> {
+        let issued = self.jwt.issue_session_access_token(
+            &user.id.as_str(),
+            &user.tenant_id.as_str(),
+            user.role.as_str(),
+            &session_id.to_string(),
+        )?;
+        let refresh_token = self.sessions.issue_refresh_token(user, session_id).await?;
+
+        Ok(AuthTokens {
+            access_token: issued.token.into(),
+            token_type: TOKEN_TYPE_BEARER.to_string(),
+            expires_in: issued.expires_in,
+            refresh_token: refresh_token.into(),
+        })
+    }
+
+    async fn confirmed_totp(&self, user_id: &UserId) -> Result<Option<TotpEnrollment>, AppError> {
+        Ok(self
+            .mfa_repo
+            .find_totp(user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(TotpEnrollment::is_confirmed))
+    }
+
+    fn mfa_challenge(&self, user: &User, purpose: &str) -> Result<MfaChallenge, AppError> {
+        let issued = self.jwt.issue_challenge_token(
+            &user.id.as_str(),
+            &user.tenant_id.as_str(),
+            purpose,
+            self.settings.mfa_challenge_ttl,
+        )?;
+        Ok(MfaChallenge {
+            mfa_token: issued.token.into(),
+            expires_in: issued.expires_in,
+        })
+    }
+
+    async fn tenant_settings(&self, tenant_id: &TenantId) -> Result<TenantSettings, AppError> {
+        Ok(self
+            .tenant_settings_repo
+            .find_settings(tenant_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .unwrap_or_default())
+    }
+
+    /// Counts a failed credential check and locks the account once the lockout
+    /// policy says so. Returns the error to hand back to the caller, which is
+    /// `rejection` unless the account just got locked.
+    async fn register_failed_login(&self, user: &User, rejection: AppError) -> AppError {
+        let attempts = match self.user_repo.record_failed_login(&user.id).await {
+            Ok(attempts) => attempts,
+            Err(e) => return AppError::InternalServerError(e.to_string()),
+        };
+        let Some(duration) = self.settings.lockout.lock_duration_for(attempts) else {
+            return rejection;
+        };
+        if let Err(e) = self
+            .user_repo
+            .lock_until(&user.id, Utc::now() + duration)
+            .await
+        {
+            return AppError::InternalServerError(e.to_string());
+        }
+        warn!(
+            user_id = %user.id,
+            attempts,
+            "Account locked after repeated failed logins"
+        );
+        account_locked()
+    }
+}
+
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid email or password".into())
+}
+
+fn account_locked() -> AppError {
+    AppError::Locked("Account is temporarily locked, try again later".into())
+}
+
+fn invalid_mfa_token() -> AppError {
+    AppError::Unauthorized("Invalid MFA token".into())
+}
+
+fn invalid_password_change_token() -> AppError {
+    AppError::Unauthorized("Invalid password change token".into())
+}
+
+fn invalid_mfa_code() -> AppError {
+    AppError::Unauthorized("Invalid authentication code".into())
+}
+
+fn passkey_rejected() -> AppError {
+    AppError::Unauthorized("Passkey authentication failed".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{FinishPasskeyRegistrationCommand, StartPasskeyRegistrationCommand};
+    use crate::passkey_service::PasskeyApplicationService;
+    use crate::test_support::{
+        InMemoryUserRepository, Stores, TENANT_ID, current_totp_code, jwt_service, tenant_id,
+        wrong_totp_code,
+    };
+    use auth::webauthn::testing::SoftwareAuthenticator;
+    use base::model::value_objects::UpdatedBy;
+    use domain::RefreshToken;
+    use domain::repository::RefreshTokenRepository;
+    use domain::value_objects::{PasswordHashing, PasswordPolicy};
+
+    /// Locks accounts after the second failed attempt.
+    fn lockout_after_two_failures() -> AuthSettings {
+        AuthSettings {
+            lockout: LockoutPolicy {
+                max_failed_attempts: 2,
+                ..LockoutPolicy::default()
+            },
+            ..AuthSettings::default()
+        }
+    }
+
+    fn authenticated(outcome: Result<LoginOutcome, AppError>) -> AuthTokens {
+        match outcome.unwrap() {
+            LoginOutcome::Authenticated(tokens) => tokens,
+            other => panic!("Expected tokens, got {:?}", other),
+        }
+    }
+
+    fn login_command(password: &str) -> LoginCommand {
+        LoginCommand {
+            tenant_id: TENANT_ID.to_string(),
+            email: "alice@example.com".to_string(),
+            password: password.to_string().into(),
+            client: ClientInfo::new(Some("Firefox".into()), Some("203.0.113.7".into())),
+        }
+    }
+
+    fn refresh_command(token: &str) -> RefreshTokenCommand {
+        RefreshTokenCommand {
+            refresh_token: token.to_string().into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_issues_access_and_refresh_tokens() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+
+        let tokens = authenticated(service.login(login_command("correct-horse")).await);
+        let claims = jwt_service()
+            .verify_access_token(tokens.access_token.expose_secret())
+            .unwrap();
+        assert_eq!(claims.sub, user.id.as_str());
+        assert_eq!(claims.tid, TENANT_ID);
+        assert_eq!(stores.refresh_tokens.len(), 1);
+
+        // The login is recorded as a session the tokens belong to.
+        let sessions = stores.sessions.sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(claims.sid, Some(sessions[0].id.to_string()));
+        assert_eq!(sessions[0].user_agent.as_deref(), Some("Firefox"));
+        assert_eq!(sessions[0].ip_address.as_deref(), Some("203.0.113.7"));
+        assert!(!sessions[0].mfa_verified);
+        let refresh_token = stores
+            .refresh_tokens
+            .get(&hash_token(tokens.refresh_token.expose_secret()))
+            .unwrap();
+        assert_eq!(refresh_token.family_id, sessions[0].id);
+        assert!(stores.users.get(&user.id).unwrap().last_login_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_login_rehashes_weaker_password_hash() {
+        let stores = Stores::default();
+        let mut user = stores.seed_alice();
+        let light = PasswordHashing::new(argon2::Algorithm::Argon2id, 8 * 1024, 1, 1).unwrap();
+        user.password_hash =
+            Password::from_plain("correct-horse", &PasswordPolicy::default(), &light).unwrap();
+        stores.users.insert(user.clone());
+        let service = stores.auth_service(AuthSettings::default());
+
+        authenticated(service.login(login_command("correct-horse")).await);
+        let stored = stores.users.get(&user.id).unwrap();
+        assert_ne!(stored.password_hash, user.password_hash);
+        assert!(
+            !stored
+                .password_hash
+                .needs_rehash(&PasswordHashing::default())
+        );
+        assert!(
+            stored
+                .password_hash
+                .verify("correct-horse", &PasswordHashing::default())
+                .unwrap()
+        );
+        // A rehash is not a password change.
+        assert_eq!(stored.password_changed_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_login_upgrades_legacy_password_hash() {
+        let stores = Stores::default();
+        let mut user = stores.seed_alice();
+        user.password_hash = Password::from_hash(bcrypt::hash("correct-horse", 4).unwrap());
+        stores.users.insert(user.clone());
+        let service = stores.auth_service(AuthSettings::default());
+
+        let result = service.login(login_command("wrong-horse")).await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+        authenticated(service.login(login_command("correct-horse")).await);
+        let stored = stores.users.get(&user.id).unwrap();
+        assert!(stored.password_hash.as_str().starts_with("$argon2id$"));
+        assert!(
+            stored
+                .password_hash
+                .verify("correct-horse", &PasswordHashing::default())
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_login_with_malformed_password_hash_fails_without_panicking() {
+        let stores = Stores::default();
+        let mut user = stores.seed_alice();
+        user.password_hash = Password::from_hash("$2b$12$truncated".into());
+        stores.users.insert(user);
+        let service = stores.auth_service(AuthSettings::default());
+
+        let result = service.login(login_command("correct-horse")).await;
+        assert!(matches!(result, Err(AppError::InternalServerError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login_rejects_wrong_password() {
+        let stores = Stores::default();
+        stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+
+        let result = service.login(login_command("wrong-horse")).await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_passwords_and_tokens_stay_out_of_logs_and_errors() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+
+        let command = login_command("correct-horse");
+        assert!(!format!("{:?}", command).contains("correct-horse"));
+        assert!(!format!("{:?}", user).contains(user.password_hash.as_str()));
+
+        let error = service
+            .login(login_command("wrong-horse"))
+            .await
+            .unwrap_err();
+        assert!(!format!("{} {:?}", error, error).contains("wrong-horse"));
+
+        let outcome = service.login(command).await.unwrap();
+        let logged = format!("{:?}", outcome);
+        let tokens = authenticated(Ok(outcome));
+        assert!(!logged.contains(tokens.access_token.expose_secret()));
+        assert!(!logged.contains(tokens.refresh_token.expose_secret()));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_lock_account() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(lockout_after_two_failures());
+
+        let first = service.login(login_command("wrong-horse")).await;
+        assert!(matches!(first, Err(AppError::Unauthorized(_))));
+        let second = service.login(login_command("wrong-horse")).await;
+        assert!(matches!(second, Err(AppError::Locked(_))));
+
+        // Even the right password is refused while the lock holds.
+        let locked = service.login(login_command("correct-horse")).await;
+        assert!(matches!(locked, Err(AppError::Locked(_))));
+        assert_eq!(stores.users.get(&user.id).unwrap().failed_login_attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_resets_failed_attempts() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+
+        let mut edited = user.clone();
+        edited.audit.as_mut().unwrap().updated_by = Some(UpdatedBy::new("admin"));
+        stores.users.insert(edited);
+
+        let _ = service.login(login_command("wrong-horse")).await;
+        assert_eq!(stores.users.get(&user.id).unwrap().failed_login_attempts, 1);
+        service.login(login_command("correct-horse")).await.unwrap();
+        let stored = stores.users.get(&user.id).unwrap();
+        assert_eq!(stored.failed_login_attempts, 0);
+        // Clearing the lockout on its own does not erase who last edited.
+        let updated_by = stored.audit.unwrap().updated_by.unwrap();
+        assert_eq!(updated_by.as_str(), "admin");
+    }
+
+    #[tokio::test]
+    async fn test_tenant_can_require_verified_email() {
+        let stores = Stores::default();
+        let mut user = stores.seed_alice();
+        stores.tenant_settings.set(
+            tenant_id(),
+            TenantSettings {
+                require_email_verification: true,
+                ..TenantSettings::default()
+            },
+        );
+        let service = stores.auth_service(AuthSettings::default());
+
+        let blocked = service.login(login_command("correct-horse")).await;
+        assert!(matches!(blocked, Err(AppError::Forbidden(_))));
+
+        user.email_verified = true;
+        stores.users.insert(user);
+        assert!(service.login(login_command("correct-horse")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_expired_password_must_be_changed_before_tokens() {
+        let stores = Stores::default();
+        let mut user = stores.seed_alice();
+        user.password_changed_at = Some(Utc::now() - chrono::Duration::days(91));
+        stores.users.insert(user.clone());
+        stores.tenant_settings.set(
+            tenant_id(),
+            TenantSettings::from_json(serde_json::json!({
+                "password_policy": { "max_age_days": 90 }
+            }))
+            .unwrap(),
+        );
+        let service = stores.auth_service(AuthSettings::default());
+
+        let challenge = match service.login(login_command("correct-horse")).await.unwrap() {
+            LoginOutcome::PasswordChangeRequired(challenge) => challenge,
+            other => panic!("Expected a password change, got {:?}", other),
+        };
+        let change = |new_password: &str| ChangeExpiredPasswordCommand {
+            password_change_token: challenge.mfa_token.clone(),
+            new_password: new_password.to_string().into(),
+            client: ClientInfo::default(),
+        };
+        // Keeping the expired password is not a change.
+        let kept = service
+            .change_expired_password(change("correct-horse"))
+            .await;
+        assert!(matches!(kept, Err(AppError::Validation(_))));
+
+        authenticated(
+            service
+                .change_expired_password(change("battery-staple"))
+                .await,
+        );
+        assert!(
+            stores
+                .users
+                .get(&user.id)
+                .unwrap()
+                .password_hash
+                .verify("battery-staple", &PasswordHashing::default())
+                .unwrap()
+        );
+        authenticated(service.login(login_command("battery-staple")).await);
+
+        // The token only works for a password change.
+        let result = service
+            .verify_mfa(VerifyMfaCommand {
+                mfa_token: challenge.mfa_token.clone(),
+                code: Some("000000".into()),
+                recovery_code: None,
+                client: ClientInfo::default(),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotates_token() {
+        let stores = Stores::default();
+        stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+        let tokens = authenticated(service.login(login_command("correct-horse")).await);
+
+        let rotated = service
+            .refresh(refresh_command(tokens.refresh_token.expose_secret()))
+            .await
+            .unwrap();
+        assert_ne!(rotated.refresh_token, tokens.refresh_token);
+
+        let first = stores
+            .refresh_tokens
+            .get(&hash_token(tokens.refresh_token.expose_secret()))
+            .unwrap();
+        let second = stores
+            .refresh_tokens
+            .get(&hash_token(rotated.refresh_token.expose_secret()))
+            .unwrap();
+        assert!(first.rotated_at.is_some());
+        assert_eq!(first.family_id, second.family_id);
+        let claims = jwt_service()
+            .verify_access_token(rotated.access_token.expose_secret())
+            .unwrap();
+        assert_eq!(claims.sid, Some(first.family_id.to_string()));
+        assert_eq!(stores.sessions.sessions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reused_token_revokes_family() {
+        let stores = Stores::default();
+        stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+        let tokens = authenticated(service.login(login_command("correct-horse")).await);
+        let rotated = service
+            .refresh(refresh_command(tokens.refresh_token.expose_secret()))
+            .await
+            .unwrap();
+
+        let reuse = service
+            .refresh(refresh_command(tokens.refresh_token.expose_secret()))
+            .await;
+        assert!(matches!(reuse, Err(AppError::Unauthorized(_))));
+
+        // The legitimate successor is revoked together with the family.
+        let successor = service
+            .refresh(refresh_command(rotated.refresh_token.expose_secret()))
+            .await;
+        assert!(matches!(successor, Err(AppError::Unauthorized(_))));
+        let stored = stores
+            .refresh_tokens
+            .get(&hash_token(rotated.refresh_token.expose_secret()))
+            .unwrap();
+        assert!(stored.revoked_at.is_some());
+        assert!(stores.sessions.sessions()[0].revoked_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_expired_tokens_are_rejected() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+
+        let unknown = service.refresh(refresh_command("unknown")).await;
+        assert!(matches!(unknown, Err(AppError::Unauthorized(_))));
+
+        let expired = RefreshToken::issue(
+            user.tenant_id,
+            user.id,
+            None,
+            hash_token("expired"),
+            chrono::Duration::seconds(-1),
+        );
+        stores.refresh_tokens.create(expired).await.unwrap();
+        let result = service.refresh(refresh_command("expired")).await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    async fn enable_totp(stores: &Stores, user: &User, recovery_code: &str) -> TotpEnrollment {
+        let mut enrollment = TotpEnrollment::new(user.tenant_id, user.id);
+        enrollment.confirmed_at = Some(Utc::now());
+        stores.mfa.save_totp(enrollment.clone()).await.unwrap();
+        let code = RecoveryCode::new(user.id, hash_token(&RecoveryCode::normalize(recovery_code)));
+        stores
+            .mfa
+            .replace_recovery_codes(&user.id, vec![code])
+            .await
+            .unwrap();
+        enrollment
+    }
+
+    async fn mfa_token(service: &AuthApplicationService<InMemoryUserRepository>) -> String {
+        match service.login(login_command("correct-horse")).await.unwrap() {
+            LoginOutcome::MfaRequired(challenge) => challenge.mfa_token.into_exposed(),
+            other => panic!("Expected an MFA challenge, got {:?}", other),
+        }
+    }
+
+    fn verify_command(
+        mfa_token: &str,
+        code: Option<&str>,
+        recovery: Option<&str>,
+    ) -> VerifyMfaCommand {
+        VerifyMfaCommand {
+            mfa_token: mfa_token.to_string().into(),
+            code: code.map(str::to_string),
+            recovery_code: recovery.map(str::to_string),
+            client: ClientInfo::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_with_mfa_requires_code() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(lockout_after_two_failures());
+        let enrollment = enable_totp(&stores, &user, "aaaaa-bbbbb").await;
+        let token = mfa_token(&service).await;
+
+        let code = current_totp_code(&enrollment.secret);
+        let tokens = service
+            .verify_mfa(verify_command(&token, Some(&code), None))
+            .await
+            .unwrap();
+        let claims = jwt_service()
+            .verify_access_token(tokens.access_token.expose_secret())
+            .unwrap();
+        assert_eq!(claims.sub, user.id.as_str());
+
+        // The same code cannot be replayed within its window.
+        let replay = service
+            .verify_mfa(verify_command(&token, Some(&code), None))
+            .await;
+        assert!(matches!(replay, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_recovery_code_works_once() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(lockout_after_two_failures());
+        enable_totp(&stores, &user, "aaaaa-bbbbb").await;
+        let token = mfa_token(&service).await;
+
+        service
+            .verify_mfa(verify_command(&token, None, Some("AAAAA BBBBB")))
+            .await
+            .unwrap();
+        let reuse = service
+            .verify_mfa(verify_command(&token, None, Some("aaaaa-bbbbb")))
+            .await;
+        assert!(matches!(reuse, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_mfa_codes_lock_account() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(lockout_after_two_failures());
+        let enrollment = enable_totp(&stores, &user, "aaaaa-bbbbb").await;
+        let token = mfa_token(&service).await;
+        let wrong = wrong_totp_code(&enrollment.secret);
+
+        let first = service
+            .verify_mfa(verify_command(&token, Some(&wrong), None))
+            .await;
+        assert!(matches!(first, Err(AppError::Unauthorized(_))));
+        let second = service
+            .verify_mfa(verify_command(&token, Some(&wrong), None))
+            .await;
+        assert!(matches!(second, Err(AppError::Locked(_))));
+        assert!(stores.users.get(&user.id).unwrap().is_locked(Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn test_correct_password_does_not_reset_wrong_mfa_codes() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(lockout_after_two_failures());
+        let enrollment = enable_totp(&stores, &user, "aaaaa-bbbbb").await;
+        let wrong = wrong_totp_code(&enrollment.secret);
+
+        let mut results = Vec::new();
+        for _ in 0..2 {
+            let token = mfa_token(&service).await;
+            results.push(
+                service
+                    .verify_mfa(verify_command(&token, Some(&wrong), None))
+                    .await,
+            );
+        }
+        assert!(matches!(results[0], Err(AppError::Unauthorized(_))));
+        assert!(matches!(results[1], Err(AppError::Locked(_))));
+        assert!(stores.users.get(&user.id).unwrap().is_locked(Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn test_tenant_can_require_mfa_enrollment_for_role() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(lockout_after_two_failures());
+        stores.tenant_settings.set(
+            tenant_id(),
+            TenantSettings {
+                mfa_required_roles: vec!["user".to_string()],
+                ..TenantSettings::default()
+            },
+        );
+
+        let challenge = match service.login(login_command("correct-horse")).await.unwrap() {
+            LoginOutcome::MfaEnrollmentRequired(challenge) => challenge,
+            other => panic!("Expected an enrollment challenge, got {:?}", other),
+        };
+        let not_enrolled = service
+            .verify_mfa(verify_command(
+                challenge.mfa_token.expose_secret(),
+                Some("123456"),
+                None,
+            ))
+            .await;
+        assert!(matches!(not_enrolled, Err(AppError::BadRequest(_))));
+
+        let enrollment = enable_totp(&stores, &user, "aaaaa-bbbbb").await;
+        let code = current_totp_code(&enrollment.secret);
+        service
+            .verify_mfa(verify_command(
+                challenge.mfa_token.expose_secret(),
+                Some(&code),
+                None,
+            ))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_access_token_is_not_an_mfa_token() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(lockout_after_two_failures());
+        let enrollment = enable_totp(&stores, &user, "aaaaa-bbbbb").await;
+        let access = jwt_service()
+            .issue_access_token(&user.id.as_str(), TENANT_ID, "user")
+            .unwrap();
+        let code = current_totp_code(&enrollment.secret);
+        let result = service
+            .verify_mfa(verify_command(&access.token, Some(&code), None))
+            .await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    /// Registers a fresh software authenticator for `user`.
+    async fn register_passkey(
+        passkeys: &PasskeyApplicationService<InMemoryUserRepository>,
+        user: &User,
+    ) -> SoftwareAuthenticator {
+        let mut authenticator = SoftwareAuthenticator::new(&RelyingParty::default().origin);
+        let ceremony = passkeys
+            .start_registration(StartPasskeyRegistrationCommand {
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+        passkeys
+            .finish_registration(FinishPasskeyRegistrationCommand {
+                user_id: user.id.as_str(),
+                ceremony_id: ceremony.ceremony_id,
+                name: None,
+                credential: authenticator.register(&ceremony.options),
+            })
+            .await
+            .unwrap();
+        authenticator
+    }
+
+    async fn passkey_login(
+        service: &AuthApplicationService<InMemoryUserRepository>,
+        authenticator: &mut SoftwareAuthenticator,
+        email: Option<&str>,
+    ) -> Result<AuthTokens, AppError> {
+        let ceremony = service
+            .start_passkey_login(StartPasskeyLoginCommand {
+                tenant_id: TENANT_ID.to_string(),
+                email: email.map(str::to_string),
+            })
+            .await
+            .unwrap();
+        service
+            .finish_passkey_login(FinishPasskeyLoginCommand {
+                ceremony_id: ceremony.ceremony_id,
+                credential: authenticator.authenticate(&ceremony.options),
+                client: ClientInfo::default(),
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_passkey_registration_and_login_end_to_end() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+        let mut authenticator = register_passkey(&stores.passkey_service(), &user).await;
+
+        let tokens = passkey_login(&service, &mut authenticator, Some("alice@example.com"))
+            .await
+            .unwrap();
+        let claims = jwt_service()
+            .verify_access_token(tokens.access_token.expose_secret())
+            .unwrap();
+        assert_eq!(claims.sub, user.id.as_str());
+        let stored = &stores.webauthn.credentials()[0];
+        assert_eq!(stored.sign_count, 1);
+        assert!(stored.last_used_at.is_some());
+
+        // Usernameless login with a discoverable credential.
+        passkey_login(&service, &mut authenticator, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cloned_passkey_is_rejected() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+        let mut authenticator = register_passkey(&stores.passkey_service(), &user).await;
+        passkey_login(&service, &mut authenticator, None)
+            .await
+            .unwrap();
+        passkey_login(&service, &mut authenticator, None)
+            .await
+            .unwrap();
+
+        authenticator.set_sign_count(0);
+        let clone = passkey_login(&service, &mut authenticator, None).await;
+        assert!(matches!(clone, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_passkey_of_another_user_is_rejected() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.auth_service(AuthSettings::default());
+        let mut authenticator = register_passkey(&stores.passkey_service(), &user).await;
+
+        let ceremony = service
+            .start_passkey_login(StartPasskeyLoginCommand {
+                tenant_id: TENANT_ID.to_string(),
+                email: Some("alice@example.com".to_string()),
+            })
+            .await
+            .unwrap();
+        let mut challenge = stores
+            .webauthn
+            .take_challenge(Uuid::parse_str(&ceremony.ceremony_id).unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        // Pretend the ceremony was started for someone else.
+        challenge.id = Uuid::now_v7();
+        challenge.user_id = Some(UserId::new());
+        challenge.consumed_at = None;
+        stores
+            .webauthn
+            .create_challenge(challenge.clone())
+            .await
+            .unwrap();
+        let result = service
+            .finish_passkey_login(FinishPasskeyLoginCommand {
+                ceremony_id: challenge.id.to_string(),
+                credential: authenticator.authenticate(&ceremony.options),
+                client: ClientInfo::default(),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+}
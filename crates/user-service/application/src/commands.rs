@@ -1,5 +1,194 @@
+use auth::Principal;
+use auth::webauthn::{AuthenticationCredential, RegistrationCredential};
+use base::secret::SecretString;
+use chrono::{DateTime, Utc};
+use domain::ClientInfo;
+
 pub struct AddUserCommand {
+    pub tenant_id: String,
     pub username: String,
-    pub password: String,
+    pub password: SecretString,
     pub email: String,
 }
+
+#[derive(Debug)]
+pub struct LoginCommand {
+    pub tenant_id: String,
+    pub email: String,
+    pub password: SecretString,
+    pub client: ClientInfo,
+}
+
+pub struct RefreshTokenCommand {
+    pub refresh_token: SecretString,
+}
+
+pub struct UnlockUserCommand {
+    pub actor: Principal,
+    pub user_id: String,
+}
+
+pub struct StartImpersonationCommand {
+    pub actor: Principal,
+    pub user_id: String,
+    /// Why support needs to act as the user, kept in the audit trail.
+    pub reason: String,
+}
+
+pub struct EndImpersonationCommand {
+    pub actor: Principal,
+}
+
+pub struct RevokeSessionCommand {
+    pub actor: Principal,
+    pub user_id: String,
+    pub session_id: String,
+}
+
+pub struct RevokeAllSessionsCommand {
+    pub actor: Principal,
+    pub user_id: String,
+}
+
+pub struct VerifyEmailCommand {
+    pub token: SecretString,
+}
+
+pub struct ResendVerificationEmailCommand {
+    pub tenant_id: String,
+    pub email: String,
+}
+
+pub struct ForgotPasswordCommand {
+    pub tenant_id: String,
+    pub email: String,
+}
+
+pub struct ResetPasswordCommand {
+    pub token: SecretString,
+    pub new_password: SecretString,
+}
+
+pub struct ChangePasswordCommand {
+    pub user_id: String,
+    pub current_password: SecretString,
+    pub new_password: SecretString,
+}
+
+/// Sets a new password for a login that was held back because the old one
+/// expired.
+pub struct ChangeExpiredPasswordCommand {
+    pub password_change_token: SecretString,
+    pub new_password: SecretString,
+    pub client: ClientInfo,
+}
+
+pub struct VerifyMfaCommand {
+    pub mfa_token: SecretString,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+    pub client: ClientInfo,
+}
+
+pub struct EnrollTotpCommand {
+    pub user_id: String,
+}
+
+pub struct ConfirmTotpCommand {
+    pub user_id: String,
+    pub code: String,
+}
+
+pub struct StartPasskeyRegistrationCommand {
+    pub user_id: String,
+}
+
+pub struct FinishPasskeyRegistrationCommand {
+    pub user_id: String,
+    pub ceremony_id: String,
+    pub name: Option<String>,
+    pub credential: RegistrationCredential,
+}
+
+pub struct DeletePasskeyCommand {
+    pub user_id: String,
+    pub passkey_id: String,
+}
+
+pub struct StartPasskeyLoginCommand {
+    pub tenant_id: String,
+    /// Narrows the login to this user's passkeys; without it any discoverable
+    /// passkey of the tenant may answer.
+    pub email: Option<String>,
+}
+
+pub struct FinishPasskeyLoginCommand {
+    pub ceremony_id: String,
+    pub credential: AuthenticationCredential,
+    pub client: ClientInfo,
+}
+
+pub struct RegisterOAuthClientCommand {
+    pub tenant_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    /// Issue a client secret. Leave off for browser and mobile apps, which
+    /// cannot keep one.
+    pub confidential: bool,
+}
+
+/// Parameters of an OpenID Connect authorization request, as received.
+#[derive(Debug, Clone, Default)]
+pub struct AuthorizeCommand {
+    pub response_type: Option<String>,
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeAuthorizationCodeCommand {
+    pub grant_type: Option<String>,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<SecretString>,
+    pub code_verifier: Option<String>,
+}
+
+pub struct ProvisionFederatedUserCommand {
+    pub tenant_id: String,
+    pub email: String,
+    /// Username suggested by the identity provider, if any.
+    pub preferred_username: Option<String>,
+}
+
+pub struct StartFederatedLoginCommand {
+    pub tenant_id: String,
+    /// Id of the identity provider in the tenant's settings.
+    pub provider: String,
+}
+
+pub struct FinishFederatedLoginCommand {
+    pub state: String,
+    pub code: String,
+    pub client: ClientInfo,
+}
+
+pub struct CreateApiKeyCommand {
+    pub tenant_id: String,
+    /// Admin minting the key.
+    pub created_by: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub struct RevokeApiKeyCommand {
+    pub tenant_id: String,
+    pub api_key_id: String,
+}
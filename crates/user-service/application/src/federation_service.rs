@@ -0,0 +1,434 @@
+use crate::auth_service::{AuthApplicationService, AuthTokens};
+use crate::commands::{
+    FinishFederatedLoginCommand, ProvisionFederatedUserCommand, StartFederatedLoginCommand,
+};
+use crate::oidc_service::with_query;
+use crate::user_service::UserApplicationService;
+use auth::federation::{ExternalIdTokenClaims, JwkSet, verify_external_id_token};
+use auth::oidc::{PKCE_METHOD_S256, pkce_s256_challenge};
+use auth::{AuthError, generate_opaque_token, hash_token};
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::repository::{FederationRepository, TenantSettingsRepository, UserRepositories};
+use domain::services::IdentityProviderClient;
+use domain::value_objects::{EmailAddress, IdentityProviderSettings, TenantId, TenantSettings};
+use domain::{FederatedLoginState, User, UserIdentity};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Tunables for signing in through a tenant's identity provider.
+#[derive(Debug, Clone)]
+pub struct FederationSettings {
+    /// Front-end page the provider sends the user back to. It posts the
+    /// `state` and `code` it receives to the callback endpoint.
+    pub redirect_uri: String,
+    /// How long the user may spend at the provider.
+    pub login_state_ttl: Duration,
+}
+
+impl Default for FederationSettings {
+    fn default() -> Self {
+        Self {
+            redirect_uri: "http://localhost:3000/login/callback".to_string(),
+            login_state_ttl: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Signs users in with their tenant's own OpenID Connect provider, acting as
+/// a relying party. Users are matched by the provider's subject, then by
+/// verified email, and created on first login when the tenant allows it.
+pub struct FederationApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+    federation_repo: Arc<dyn FederationRepository>,
+    idp_client: Arc<dyn IdentityProviderClient>,
+    user_service: Arc<UserApplicationService<R>>,
+    auth_service: Arc<AuthApplicationService<R>>,
+    settings: FederationSettings,
+}
+
+impl<R: UserRepositories> FederationApplicationService<R> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_repo: Arc<R>,
+        tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+        federation_repo: Arc<dyn FederationRepository>,
+        idp_client: Arc<dyn IdentityProviderClient>,
+        user_service: Arc<UserApplicationService<R>>,
+        auth_service: Arc<AuthApplicationService<R>>,
+        settings: FederationSettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            tenant_settings_repo,
+            federation_repo,
+            idp_client,
+            user_service,
+            auth_service,
+            settings,
+        }
+    }
+
+    /// Starts a login at the provider and returns the URL to send the
+    /// browser to.
+    pub async fn start(&self, cmd: StartFederatedLoginCommand) -> Result<String, AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let tenant_settings = self.tenant_settings(&tenant_id).await?;
+        let provider = tenant_settings
+            .identity_provider(&cmd.provider)
+            .ok_or_else(unknown_provider)?;
+
+        let state = generate_opaque_token();
+        let nonce = generate_opaque_token();
+        let code_verifier = generate_opaque_token();
+        let code_challenge = pkce_s256_challenge(&code_verifier);
+        let ttl = chrono::Duration::from_std(self.settings.login_state_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let login_state = FederatedLoginState::issue(
+            tenant_id,
+            provider.id.clone(),
+            hash_token(&state),
+            nonce.clone(),
+            code_verifier,
+            ttl,
+        );
+        self.federation_repo
+            .create_login_state(login_state)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        Ok(with_query(
+            &provider.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &provider.client_id),
+                ("redirect_uri", &self.settings.redirect_uri),
+                ("scope", &provider.scopes.join(" ")),
+                ("state", &state),
+                ("nonce", &nonce),
+                ("code_challenge", &code_challenge),
+                ("code_challenge_method", PKCE_METHOD_S256),
+            ],
+        ))
+    }
+
+    /// Completes a login with the code the provider sent back, and signs the
+    /// matching user in.
+    pub async fn finish(&self, cmd: FinishFederatedLoginCommand) -> Result<AuthTokens, AppError> {
+        let login_state = self
+            .federation_repo
+            .take_login_state(&hash_token(&cmd.state))
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|s| s.expires_at > Utc::now())
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired login state".into()))?;
+        let tenant_settings = self.tenant_settings(&login_state.tenant_id).await?;
+        let provider = tenant_settings
+            .identity_provider(&login_state.provider_id)
+            .ok_or_else(unknown_provider)?;
+
+        let id_token = self
+            .idp_client
+            .exchange_code(
+                provider,
+                &cmd.code,
+                &self.settings.redirect_uri,
+                &login_state.code_verifier,
+            )
+            .await
+            .map_err(|e| {
+                warn!(provider = %provider.id, "Code exchange failed: {}", e);
+                federated_login_failed()
+            })?;
+        let claims = self
+            .verify_id_token(provider, &id_token, &login_state.nonce)
+            .await?;
+        let user = self
+            .resolve_user(&login_state.tenant_id, provider, &claims)
+            .await?;
+        self.auth_service.login_federated(&user, cmd.client).await
+    }
+
+    /// Checks the ID token against the provider's keys, fetching them again
+    /// once if the token names a key we have not seen.
+    async fn verify_id_token(
+        &self,
+        provider: &IdentityProviderSettings,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<ExternalIdTokenClaims, AppError> {
+        let verify = |jwks: &JwkSet| {
+            verify_external_id_token(id_token, jwks, &provider.issuer, &provider.client_id, nonce)
+        };
+        let claims = match verify(&self.jwks(provider, false).await?) {
+            Err(AuthError::UnknownKey(_)) => verify(&self.jwks(provider, true).await?),
+            result => result,
+        };
+        claims.map_err(|e| {
+            warn!(provider = %provider.id, "ID token rejected: {}", e);
+            federated_login_failed()
+        })
+    }
+
+    async fn jwks(
+        &self,
+        provider: &IdentityProviderSettings,
+        refresh: bool,
+    ) -> Result<JwkSet, AppError> {
+        let jwks = self
+            .idp_client
+            .fetch_jwks(&provider.jwks_uri, refresh)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        serde_json::from_value(jwks).map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    /// Finds the user behind the provider's identity, linking or creating an
+    /// account on first login.
+    async fn resolve_user(
+        &self,
+        tenant_id: &TenantId,
+        provider: &IdentityProviderSettings,
+        claims: &ExternalIdTokenClaims,
+    ) -> Result<User, AppError> {
+        let identity = self
+            .federation_repo
+            .find_identity(tenant_id, &provider.id, &claims.sub)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if let Some(identity) = identity {
+            self.federation_repo
+                .record_identity_login(identity.id, Utc::now())
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            return self
+                .user_repo
+                .find_by_user_id(&identity.user_id)
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?
+                .ok_or_else(federated_login_failed);
+        }
+
+        let email = claims.verified_email().ok_or_else(|| {
+            AppError::Forbidden("The identity provider did not confirm an email address".into())
+        })?;
+        let email = EmailAddress::new(email.to_string())?;
+        let existing = self
+            .user_repo
+            .find_by_email(tenant_id, &email)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let user = match existing {
+            Some(user) if user.email_verified => user,
+            // Anyone can sign up with an address they do not own. Linking
+            // such an account would hand it to whoever created it.
+            Some(_) => {
+                return Err(AppError::Forbidden(
+                    "An account with this email exists but its address is not verified".into(),
+                ));
+            }
+            None if provider.jit_provisioning => {
+                self.user_service
+                    .provision_federated_user(ProvisionFederatedUserCommand {
+                        tenant_id: tenant_id.as_str(),
+                        email: email.as_str().to_string(),
+                        preferred_username: claims.preferred_username.clone(),
+                    })
+                    .await?
+            }
+            None => {
+                return Err(AppError::Forbidden(
+                    "No account exists for this identity".into(),
+                ));
+            }
+        };
+
+        self.federation_repo
+            .create_identity(UserIdentity::new(
+                *tenant_id,
+                user.id,
+                provider.id.clone(),
+                claims.sub.clone(),
+                Some(email.as_str().to_string()),
+            ))
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        info!(user_id = %user.id, provider = %provider.id, "Linked external identity");
+        Ok(user)
+    }
+
+    async fn tenant_settings(&self, tenant_id: &TenantId) -> Result<TenantSettings, AppError> {
+        Ok(self
+            .tenant_settings_repo
+            .find_settings(tenant_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .unwrap_or_default())
+    }
+}
+
+fn unknown_provider() -> AppError {
+    AppError::BadRequest("Unknown identity provider".into())
+}
+
+fn federated_login_failed() -> AppError {
+    AppError::Unauthorized("Sign-in with the identity provider failed".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        IdpUser, InMemoryUserRepository, MockIdentityProvider, Stores, TENANT_ID, jwt_service,
+        query_param, seed_user,
+    };
+    use domain::ClientInfo;
+
+    fn idp_user(email: &str, email_verified: bool) -> IdpUser {
+        IdpUser {
+            subject: "idp-user-1".to_string(),
+            email: email.to_string(),
+            email_verified,
+        }
+    }
+
+    fn start_command(provider: &str) -> StartFederatedLoginCommand {
+        StartFederatedLoginCommand {
+            tenant_id: TENANT_ID.to_string(),
+            provider: provider.to_string(),
+        }
+    }
+
+    /// Runs a login through the mock provider and returns the callback's
+    /// command.
+    async fn sign_in_at_idp(
+        service: &FederationApplicationService<InMemoryUserRepository>,
+        idp: &MockIdentityProvider,
+        user: IdpUser,
+    ) -> FinishFederatedLoginCommand {
+        let authorization_url = service.start(start_command("corp")).await.unwrap();
+        assert!(authorization_url.starts_with("https://idp.example.com/authorize?"));
+        let code = idp.authorize(&authorization_url, user);
+        FinishFederatedLoginCommand {
+            state: query_param(&authorization_url, "state").unwrap(),
+            code,
+            client: ClientInfo::default(),
+        }
+    }
+
+    fn signed_in_user(tokens: &AuthTokens) -> String {
+        jwt_service()
+            .verify_access_token(tokens.access_token.expose_secret())
+            .unwrap()
+            .sub
+    }
+
+    #[tokio::test]
+    async fn test_first_login_provisions_user() {
+        let stores = Stores::default();
+        stores.trust_identity_provider(MockIdentityProvider::settings("corp"));
+        let idp = Arc::new(MockIdentityProvider::new());
+        let service = stores.federation_service(idp.clone());
+        let cmd = sign_in_at_idp(&service, &idp, idp_user("carol@corp.example.com", true)).await;
+        let tokens = service.finish(cmd).await.unwrap();
+
+        let identities = stores.federation.identities();
+        assert_eq!(identities.len(), 1);
+        assert_eq!(identities[0].subject, "idp-user-1");
+        assert_eq!(signed_in_user(&tokens), identities[0].user_id.as_str());
+        let user = stores.users.get(&identities[0].user_id).unwrap();
+        assert_eq!(user.username.as_str(), "carol");
+        assert!(user.email_verified);
+
+        // The second login goes through the stored link.
+        let cmd = sign_in_at_idp(&service, &idp, idp_user("carol@corp.example.com", true)).await;
+        let tokens = service.finish(cmd).await.unwrap();
+        assert_eq!(signed_in_user(&tokens), user.id.as_str());
+        assert_eq!(stores.federation.identities().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_login_links_existing_verified_user() {
+        let stores = Stores::default();
+        stores.trust_identity_provider(MockIdentityProvider::settings("corp"));
+        let idp = Arc::new(MockIdentityProvider::new());
+        let service = stores.federation_service(idp.clone());
+        let mut alice = seed_user(&stores.users, "alice", "alice@example.com", "correct-horse");
+        alice.email_verified = true;
+        stores.users.insert(alice.clone());
+
+        let cmd = sign_in_at_idp(&service, &idp, idp_user("alice@example.com", true)).await;
+        let tokens = service.finish(cmd).await.unwrap();
+        assert_eq!(signed_in_user(&tokens), alice.id.as_str());
+        assert_eq!(stores.federation.identities()[0].user_id, alice.id);
+    }
+
+    #[tokio::test]
+    async fn test_unverified_local_account_is_not_linked() {
+        let stores = Stores::default();
+        stores.trust_identity_provider(MockIdentityProvider::settings("corp"));
+        let idp = Arc::new(MockIdentityProvider::new());
+        let service = stores.federation_service(idp.clone());
+        seed_user(&stores.users, "alice", "alice@example.com", "correct-horse");
+
+        let cmd = sign_in_at_idp(&service, &idp, idp_user("alice@example.com", true)).await;
+        let result = service.finish(cmd).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+        assert!(stores.federation.identities().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unverified_provider_email_is_rejected() {
+        let stores = Stores::default();
+        stores.trust_identity_provider(MockIdentityProvider::settings("corp"));
+        let idp = Arc::new(MockIdentityProvider::new());
+        let service = stores.federation_service(idp.clone());
+        let cmd = sign_in_at_idp(&service, &idp, idp_user("carol@corp.example.com", false)).await;
+        let result = service.finish(cmd).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    async fn test_provisioning_can_be_disabled() {
+        let stores = Stores::default();
+        stores.trust_identity_provider(IdentityProviderSettings {
+            jit_provisioning: false,
+            ..MockIdentityProvider::settings("corp")
+        });
+        let idp = Arc::new(MockIdentityProvider::new());
+        let service = stores.federation_service(idp.clone());
+        let cmd = sign_in_at_idp(&service, &idp, idp_user("carol@corp.example.com", true)).await;
+        let result = service.finish(cmd).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login_state_is_single_use() {
+        let stores = Stores::default();
+        stores.trust_identity_provider(MockIdentityProvider::settings("corp"));
+        let idp = Arc::new(MockIdentityProvider::new());
+        let service = stores.federation_service(idp.clone());
+        let cmd = sign_in_at_idp(&service, &idp, idp_user("carol@corp.example.com", true)).await;
+        let replay = FinishFederatedLoginCommand {
+            state: cmd.state.clone(),
+            code: cmd.code.clone(),
+            client: ClientInfo::default(),
+        };
+        service.finish(cmd).await.unwrap();
+        let result = service.finish(replay).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_provider_is_rejected() {
+        let stores = Stores::default();
+        stores.trust_identity_provider(MockIdentityProvider::settings("corp"));
+        let idp = Arc::new(MockIdentityProvider::new());
+        let service = stores.federation_service(idp.clone());
+        let result = service.start(start_command("other")).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,228 @@
+use base::secret::SecretString;
+use base::web::error::AppError;
+use domain::value_objects::{Password, PasswordHashing, PasswordViolation};
+use metrics::{counter, gauge, histogram};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+/// Callers waiting for a hashing slot.
+pub const QUEUE_DEPTH_METRIC: &str = "password_hashing_queue_depth";
+/// Seconds a hash or verification took on the blocking pool, labelled by
+/// `operation`.
+pub const DURATION_METRIC: &str = "password_hashing_duration_seconds";
+/// Callers refused after waiting out the queue timeout.
+pub const REJECTED_METRIC: &str = "password_hashing_rejected_total";
+
+/// Tunables for the hashing pool.
+#[derive(Debug, Clone)]
+pub struct HashingPoolSettings {
+    /// Hashes and verifications running at once.
+    pub max_concurrency: usize,
+    /// How long a caller waits for a slot before being refused with a 503.
+    pub queue_timeout: Duration,
+}
+
+impl Default for HashingPoolSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrency: std::thread::available_parallelism().map_or(4, |n| n.get()),
+            queue_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Runs password hashing and verification on the blocking thread pool,
+/// a bounded number at a time, so bursts of signups and logins do not
+/// stall the async runtime's workers. Callers beyond the bound queue, and
+/// are refused once they have waited longer than the queue timeout.
+///
+/// Passwords reach the blocking pool as [`SecretString`]s, so every copy
+/// of them is wiped once the work is done.
+pub struct HashingPool {
+    hashing: Arc<PasswordHashing>,
+    permits: Arc<Semaphore>,
+    queue_timeout: Duration,
+    waiting: AtomicUsize,
+}
+
+impl HashingPool {
+    pub fn new(hashing: PasswordHashing, settings: HashingPoolSettings) -> Self {
+        Self {
+            hashing: Arc::new(hashing),
+            permits: Arc::new(Semaphore::new(settings.max_concurrency.max(1))),
+            queue_timeout: settings.queue_timeout,
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    /// Parameters new password hashes are computed with.
+    pub fn hashing(&self) -> &PasswordHashing {
+        &self.hashing
+    }
+
+    /// Callers currently waiting for a slot.
+    pub fn queue_depth(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed)
+    }
+
+    /// Hashes `password`, or refuses it without hashing when its checks
+    /// found `violations`.
+    pub async fn hash(
+        &self,
+        password: &SecretString,
+        violations: Vec<PasswordViolation>,
+    ) -> Result<Password, AppError> {
+        if !violations.is_empty() {
+            return Err(PasswordViolation::into_error(violations));
+        }
+        let password = password.clone();
+        self.run("hash", move |hashing| {
+            Password::from_checked(password.expose_secret(), Vec::new(), hashing)
+        })
+        .await
+    }
+
+    /// Whether `password` matches `hash`.
+    pub async fn verify(&self, hash: &Password, password: &SecretString) -> Result<bool, AppError> {
+        let (hash, password) = (hash.clone(), password.clone());
+        self.run("verify", move |hashing| {
+            hash.verify(password.expose_secret(), hashing)
+        })
+        .await
+    }
+
+    async fn run<T: Send + 'static>(
+        &self,
+        operation: &'static str,
+        work: impl FnOnce(&PasswordHashing) -> Result<T, AppError> + Send + 'static,
+    ) -> Result<T, AppError> {
+        let queued = Queued::enter(&self.waiting);
+        let permit = tokio::time::timeout(
+            self.queue_timeout,
+            Arc::clone(&self.permits).acquire_owned(),
+        )
+        .await;
+        drop(queued);
+        let permit = match permit {
+            Ok(permit) => permit.map_err(|e| AppError::InternalServerError(e.to_string()))?,
+            Err(_) => {
+                counter!(REJECTED_METRIC).increment(1);
+                warn!("Password hashing queue is full, refusing a {}", operation);
+                return Err(AppError::ServiceUnavailable(
+                    "Too many password operations in progress, retry later".into(),
+                ));
+            }
+        };
+
+        let hashing = Arc::clone(&self.hashing);
+        let started = Instant::now();
+        let result = tokio::task::spawn_blocking(move || {
+            // Held until the work is done, even when the caller gave up.
+            let _permit = permit;
+            work(&hashing)
+        })
+        .await
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        histogram!(DURATION_METRIC, "operation" => operation)
+            .record(started.elapsed().as_secs_f64());
+        result
+    }
+}
+
+/// Counts a caller as waiting until dropped, including when the caller's
+/// future is cancelled while queued.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl<'a> Queued<'a> {
+    fn enter(waiting: &'a AtomicUsize) -> Self {
+        let depth = waiting.fetch_add(1, Ordering::Relaxed) + 1;
+        gauge!(QUEUE_DEPTH_METRIC).set(depth as f64);
+        Self(waiting)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let depth = self.0.fetch_sub(1, Ordering::Relaxed) - 1;
+        gauge!(QUEUE_DEPTH_METRIC).set(depth as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::value_objects::PasswordPolicy;
+
+    fn pool(max_concurrency: usize, queue_timeout: Duration) -> Arc<HashingPool> {
+        Arc::new(HashingPool::new(
+            PasswordHashing::default(),
+            HashingPoolSettings {
+                max_concurrency,
+                queue_timeout,
+            },
+        ))
+    }
+
+    /// Keeps the pool's only slot busy for `busy`.
+    fn occupy(pool: &Arc<HashingPool>, busy: Duration) -> tokio::task::JoinHandle<()> {
+        let pool = Arc::clone(pool);
+        tokio::spawn(async move {
+            pool.run("test", move |_| {
+                std::thread::sleep(busy);
+                Ok(())
+            })
+            .await
+            .unwrap();
+        })
+    }
+
+    #[tokio::test]
+    async fn test_hashes_and_verifies_off_the_runtime() {
+        let pool = pool(2, Duration::from_secs(5));
+        let hash = pool
+            .hash(&"correct-horse".into(), Vec::new())
+            .await
+            .unwrap();
+        assert!(pool.verify(&hash, &"correct-horse".into()).await.unwrap());
+        assert!(!pool.verify(&hash, &"wrong-horse".into()).await.unwrap());
+
+        let violations = PasswordPolicy::default().check("short", &[]);
+        let refused = pool.hash(&"short".into(), violations).await;
+        assert!(matches!(refused, Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_saturated_pool_refuses_after_queue_timeout() {
+        let pool = pool(1, Duration::from_millis(20));
+        let busy = occupy(&pool, Duration::from_millis(300));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let refused = pool.hash(&"correct-horse".into(), Vec::new()).await;
+        assert!(matches!(refused, Err(AppError::ServiceUnavailable(_))));
+        assert_eq!(pool.queue_depth(), 0);
+
+        busy.await.unwrap();
+        assert!(pool.hash(&"correct-horse".into(), Vec::new()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_callers_leave_the_queue() {
+        let pool = pool(1, Duration::from_secs(5));
+        let busy = occupy(&pool, Duration::from_millis(300));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let waiting = {
+            let pool = Arc::clone(&pool);
+            tokio::spawn(async move { pool.hash(&"correct-horse".into(), Vec::new()).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(pool.queue_depth(), 1);
+        waiting.abort();
+        let _ = waiting.await;
+        assert_eq!(pool.queue_depth(), 0);
+        busy.await.unwrap();
+    }
+}
@@ -0,0 +1,378 @@
+use crate::access_control::{ACTION_USERS_IMPERSONATE, AccessControl, user_attributes};
+use crate::commands::{EndImpersonationCommand, StartImpersonationCommand};
+use crate::session_service::SessionApplicationService;
+use auth::{Credential, JwtService};
+use base::secret::SecretString;
+use base::web::error::AppError;
+use domain::entities::audit_event::{AUDIT_IMPERSONATION_ENDED, AUDIT_IMPERSONATION_STARTED};
+use domain::repository::{AuditEventRepository, UserRepositories};
+use domain::value_objects::{TenantId, UserId};
+use domain::{AuditEvent, User};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+use uuid::Uuid;
+
+/// Tunables for impersonation.
+#[derive(Debug, Clone)]
+pub struct ImpersonationSettings {
+    /// Lifetime of an impersonation token. It cannot be refreshed, so this
+    /// bounds how long an impersonation lasts unless it is ended first.
+    pub token_ttl: Duration,
+}
+
+impl Default for ImpersonationSettings {
+    fn default() -> Self {
+        Self {
+            token_ttl: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// An access token that lets an admin act as a user.
+#[derive(Debug, Clone)]
+pub struct ImpersonationToken {
+    pub access_token: SecretString,
+    pub token_type: String,
+    pub expires_in: i64,
+    /// The session the token acts in. It ties the start and end events of
+    /// the impersonation together.
+    pub session_id: String,
+}
+
+pub struct ImpersonationApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    sessions: Arc<SessionApplicationService<R>>,
+    audit_repo: Arc<dyn AuditEventRepository>,
+    access_control: Arc<AccessControl>,
+    jwt: Arc<JwtService>,
+    settings: ImpersonationSettings,
+}
+
+impl<R: UserRepositories> ImpersonationApplicationService<R> {
+    pub fn new(
+        user_repo: Arc<R>,
+        sessions: Arc<SessionApplicationService<R>>,
+        audit_repo: Arc<dyn AuditEventRepository>,
+        access_control: Arc<AccessControl>,
+        jwt: Arc<JwtService>,
+        settings: ImpersonationSettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            sessions,
+            audit_repo,
+            access_control,
+            jwt,
+            settings,
+        }
+    }
+
+    /// Issues a token that acts as the user while naming the admin behind
+    /// it. Which users an admin may act as is up to the access policies.
+    ///
+    /// The token belongs to a session of the user, so it is refused once
+    /// the impersonation ends or the user's sessions are revoked.
+    pub async fn start(
+        &self,
+        cmd: StartImpersonationCommand,
+    ) -> Result<ImpersonationToken, AppError> {
+        cmd.actor.forbid_impersonation()?;
+        let reason = cmd.reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::BadRequest(
+                "A reason is required to impersonate a user".into(),
+            ));
+        }
+        let user = self
+            .find_in_tenant(&cmd.actor.tenant_id, &cmd.user_id)
+            .await?;
+        if user.id.as_str() == cmd.actor.user_id {
+            return Err(AppError::BadRequest("Cannot impersonate yourself".into()));
+        }
+        self.access_control
+            .authorize(&cmd.actor, ACTION_USERS_IMPERSONATE, user_attributes(&user))
+            .await?;
+        let actor_id = parse_user_id(&cmd.actor.user_id)?;
+
+        let session_id = self
+            .sessions
+            .start_impersonation(&user)
+            .await?
+            .id
+            .to_string();
+        let issued = self.jwt.issue_impersonation_token(
+            &user.id.as_str(),
+            &user.tenant_id.as_str(),
+            user.role.as_str(),
+            &cmd.actor.user_id,
+            &session_id,
+            self.settings.token_ttl,
+        )?;
+        self.record(AuditEvent::new(
+            user.tenant_id,
+            AUDIT_IMPERSONATION_STARTED,
+            actor_id,
+            Some(user.id),
+            json!({
+                "session_id": session_id,
+                "reason": reason,
+                "expires_at": issued.claims.exp,
+            }),
+        ))
+        .await?;
+        info!(
+            actor_id = %cmd.actor.user_id,
+            user_id = %user.id,
+            session_id = %session_id,
+            "Impersonation started"
+        );
+        Ok(ImpersonationToken {
+            access_token: issued.token.into(),
+            token_type: "Bearer".to_string(),
+            expires_in: issued.expires_in,
+            session_id,
+        })
+    }
+
+    /// Ends the impersonation session, so its token is refused from now
+    /// on, and records that the admin is done.
+    pub async fn end(&self, cmd: EndImpersonationCommand) -> Result<(), AppError> {
+        let Credential::Impersonation {
+            actor_id,
+            session_id,
+        } = &cmd.actor.credential
+        else {
+            return Err(AppError::BadRequest("Not impersonating a user".into()));
+        };
+        let tenant_id = TenantId::from_string(&cmd.actor.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let user_id = parse_user_id(&cmd.actor.user_id)?;
+        let session = Uuid::parse_str(session_id)
+            .map_err(|_| AppError::BadRequest("Invalid session id".into()))?;
+        self.sessions.end(session).await?;
+        self.record(AuditEvent::new(
+            tenant_id,
+            AUDIT_IMPERSONATION_ENDED,
+            parse_user_id(actor_id)?,
+            Some(user_id),
+            json!({ "session_id": session_id }),
+        ))
+        .await?;
+        info!(
+            actor_id = %actor_id,
+            user_id = %cmd.actor.user_id,
+            session_id = %session_id,
+            "Impersonation ended"
+        );
+        Ok(())
+    }
+
+    async fn record(&self, event: AuditEvent) -> Result<(), AppError> {
+        self.audit_repo
+            .record(event)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    async fn find_in_tenant(&self, tenant_id: &str, user_id: &str) -> Result<User, AppError> {
+        let tenant_id = TenantId::from_string(tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        self.user_repo
+            .find_by_user_id(&parse_user_id(user_id)?)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|u| u.tenant_id == tenant_id)
+            .ok_or_else(|| AppError::NotFound("User not found".into()))
+    }
+}
+
+fn parse_user_id(user_id: &str) -> Result<UserId, AppError> {
+    UserId::from_string(user_id).map_err(|_| AppError::BadRequest("Invalid user id".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::RevokeAllSessionsCommand;
+    use crate::test_support::{Stores, jwt_service, principal};
+    use auth::Principal;
+    use domain::value_objects::Role;
+
+    /// Whether a request presenting the token would get through, which
+    /// takes a valid token whose session was not revoked.
+    fn is_accepted(stores: &Stores, token: &ImpersonationToken) -> bool {
+        jwt_service()
+            .verify_access_token(token.access_token.expose_secret())
+            .is_ok_and(|claims| {
+                claims
+                    .sid
+                    .is_some_and(|sid| !stores.revocations.is_revoked(&sid))
+            })
+    }
+
+    fn start_command(actor: Principal, user: &User) -> StartImpersonationCommand {
+        StartImpersonationCommand {
+            actor,
+            user_id: user.id.as_str(),
+            reason: "Ticket #4711".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_admin_acts_as_user() {
+        let stores = Stores::default();
+        let service = stores.impersonation_service();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+
+        let token = service
+            .start(start_command(principal(&admin), &alice))
+            .await
+            .unwrap();
+        assert_eq!(token.expires_in, 15 * 60);
+        assert!(is_accepted(&stores, &token));
+
+        let claims = jwt_service()
+            .verify_access_token(token.access_token.expose_secret())
+            .unwrap();
+        let impersonated = Principal::from(claims);
+        assert_eq!(impersonated.user_id, alice.id.as_str());
+        assert_eq!(impersonated.role, "user");
+        assert_eq!(
+            impersonated.impersonator(),
+            Some(admin.id.as_str().as_str())
+        );
+
+        let events = stores.audit.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, AUDIT_IMPERSONATION_STARTED);
+        assert_eq!(events[0].actor_id, admin.id);
+        assert_eq!(events[0].subject_id, Some(alice.id));
+        assert_eq!(events[0].details["reason"], "Ticket #4711");
+        assert_eq!(events[0].details["session_id"], token.session_id);
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_is_limited_by_policy() {
+        let stores = Stores::default();
+        let service = stores.impersonation_service();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        let other_admin = stores.seed_user_with_role("other_admin", Role::Admin);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let bob = stores.seed_user_with_role("bob", Role::User);
+
+        let result = service
+            .start(start_command(principal(&admin), &other_admin))
+            .await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+        let result = service.start(start_command(principal(&alice), &bob)).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+        let result = service
+            .start(StartImpersonationCommand {
+                reason: " ".to_string(),
+                ..start_command(principal(&admin), &alice)
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert!(stores.audit.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_end_is_recorded_and_nesting_is_refused() {
+        let stores = Stores::default();
+        let service = stores.impersonation_service();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let bob = stores.seed_user_with_role("bob", Role::User);
+        let token = service
+            .start(start_command(principal(&admin), &alice))
+            .await
+            .unwrap();
+        let impersonated = Principal::from(
+            jwt_service()
+                .verify_access_token(token.access_token.expose_secret())
+                .unwrap(),
+        );
+
+        let nested = service
+            .start(start_command(impersonated.clone(), &bob))
+            .await;
+        assert!(matches!(nested, Err(AppError::Forbidden(_))));
+
+        service
+            .end(EndImpersonationCommand {
+                actor: impersonated,
+            })
+            .await
+            .unwrap();
+        let events = stores.audit.events();
+        assert_eq!(events[1].action, AUDIT_IMPERSONATION_ENDED);
+        assert_eq!(events[1].actor_id, admin.id);
+        assert_eq!(events[1].subject_id, Some(alice.id));
+        assert_eq!(events[1].details["session_id"], token.session_id);
+        assert!(!is_accepted(&stores, &token));
+
+        let not_impersonating = service
+            .end(EndImpersonationCommand {
+                actor: principal(&admin),
+            })
+            .await;
+        assert!(matches!(not_impersonating, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_revoking_the_users_sessions_ends_impersonations() {
+        let stores = Stores::default();
+        let service = stores.impersonation_service();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let token = service
+            .start(start_command(principal(&admin), &alice))
+            .await
+            .unwrap();
+
+        let revoked = stores
+            .session_service()
+            .revoke_all(RevokeAllSessionsCommand {
+                actor: principal(&admin),
+                user_id: alice.id.as_str(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(revoked, 1);
+        assert!(!is_accepted(&stores, &token));
+    }
+
+    #[tokio::test]
+    async fn test_ended_impersonation_is_refused_until_its_token_expires() {
+        let stores = Stores {
+            impersonation: ImpersonationSettings {
+                token_ttl: Duration::from_secs(60 * 60),
+            },
+            ..Stores::default()
+        };
+        let service = stores.impersonation_service();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let token = service
+            .start(start_command(principal(&admin), &alice))
+            .await
+            .unwrap();
+        let claims = jwt_service()
+            .verify_access_token(token.access_token.expose_secret())
+            .unwrap();
+
+        service
+            .end(EndImpersonationCommand {
+                actor: Principal::from(claims.clone()),
+            })
+            .await
+            .unwrap();
+        assert!(!is_accepted(&stores, &token));
+        // The token outlives access tokens, and so must its revocation.
+        let refused_until = stores.revocations.revoked_until(&token.session_id).unwrap();
+        assert!(refused_until.timestamp() >= claims.exp);
+    }
+}
@@ -1,3 +1,19 @@
+pub mod access_control;
+pub mod api_key_service;
+pub mod auth_service;
 pub mod commands;
+pub mod federation_service;
+pub mod hashing_pool;
+pub mod impersonation_service;
+pub mod mfa_service;
+pub mod oidc_service;
+pub mod passkey_service;
+pub mod password_screen;
+pub mod password_service;
 pub mod queries;
+pub mod session_service;
+pub mod signing_key_service;
 pub mod user_service;
+
+#[cfg(test)]
+mod test_support;
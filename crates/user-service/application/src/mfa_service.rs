@@ -0,0 +1,219 @@
+use crate::commands::{ConfirmTotpCommand, EnrollTotpCommand};
+use auth::hash_token;
+use base::secret::SecretString;
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::repository::{MfaRepository, UserRepositories};
+use domain::value_objects::UserId;
+use domain::{RECOVERY_CODE_COUNT, RecoveryCode, TotpEnrollment, User};
+use std::sync::Arc;
+use tracing::info;
+
+/// Tunables for MFA enrollment.
+#[derive(Debug, Clone)]
+pub struct MfaSettings {
+    /// Name authenticator apps show next to the account.
+    pub totp_issuer: String,
+}
+
+impl Default for MfaSettings {
+    fn default() -> Self {
+        Self {
+            totp_issuer: "ddd-rust-application".to_string(),
+        }
+    }
+}
+
+/// What a user needs to add the account to an authenticator app.
+#[derive(Debug, Clone)]
+pub struct TotpSetup {
+    /// Base32 secret for manual entry.
+    pub secret: SecretString,
+    /// `otpauth://` URI, usually rendered as a QR code.
+    pub otpauth_uri: SecretString,
+}
+
+pub struct MfaApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    mfa_repo: Arc<dyn MfaRepository>,
+    settings: MfaSettings,
+}
+
+impl<R: UserRepositories> MfaApplicationService<R> {
+    pub fn new(user_repo: Arc<R>, mfa_repo: Arc<dyn MfaRepository>, settings: MfaSettings) -> Self {
+        Self {
+            user_repo,
+            mfa_repo,
+            settings,
+        }
+    }
+
+    /// Starts a TOTP enrollment. Calling it again before confirming replaces
+    /// the secret, so a user who lost the first QR code can simply retry.
+    pub async fn enroll_totp(&self, cmd: EnrollTotpCommand) -> Result<TotpSetup, AppError> {
+        let user = self.find_user(&cmd.user_id).await?;
+        let existing = self
+            .mfa_repo
+            .find_totp(&user.id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if existing.is_some_and(|e| e.is_confirmed()) {
+            return Err(AppError::BadRequest("MFA is already enabled".into()));
+        }
+
+        let enrollment = TotpEnrollment::new(user.tenant_id, user.id);
+        let setup = TotpSetup {
+            secret: enrollment.secret.to_base32().into(),
+            otpauth_uri: enrollment
+                .secret
+                .provisioning_uri(&self.settings.totp_issuer, user.email_address.as_str())
+                .into(),
+        };
+        self.mfa_repo
+            .save_totp(enrollment)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(setup)
+    }
+
+    /// Turns MFA on once the user proves their app produces valid codes, and
+    /// returns the recovery codes. They are only ever shown this once.
+    pub async fn confirm_totp(&self, cmd: ConfirmTotpCommand) -> Result<Vec<String>, AppError> {
+        let user = self.find_user(&cmd.user_id).await?;
+        let enrollment = self
+            .mfa_repo
+            .find_totp(&user.id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(|| AppError::BadRequest("No MFA enrollment in progress".into()))?;
+        if enrollment.is_confirmed() {
+            return Err(AppError::BadRequest("MFA is already enabled".into()));
+        }
+        if enrollment.secret.verify(&cmd.code, Utc::now()).is_none() {
+            return Err(AppError::BadRequest("Invalid authentication code".into()));
+        }
+
+        self.mfa_repo
+            .confirm_totp(&user.id, Utc::now())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let plain_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| RecoveryCode::generate_plain())
+            .collect();
+        let codes = plain_codes
+            .iter()
+            .map(|code| RecoveryCode::new(user.id, hash_token(&RecoveryCode::normalize(code))))
+            .collect();
+        self.mfa_repo
+            .replace_recovery_codes(&user.id, codes)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        info!(user_id = %user.id, "TOTP MFA enabled");
+        Ok(plain_codes)
+    }
+
+    async fn find_user(&self, user_id: &str) -> Result<User, AppError> {
+        let user_id = UserId::from_string(user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        self.user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Stores, current_totp_code, wrong_totp_code};
+    use domain::value_objects::TotpSecret;
+
+    #[tokio::test]
+    async fn test_enroll_returns_secret_and_uri() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.mfa_service();
+        let setup = service
+            .enroll_totp(EnrollTotpCommand {
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+        assert!(
+            setup
+                .otpauth_uri
+                .expose_secret()
+                .starts_with("otpauth://totp/")
+        );
+        assert!(
+            setup
+                .otpauth_uri
+                .expose_secret()
+                .contains(setup.secret.expose_secret())
+        );
+        let stored = stores.mfa.totp(&user.id).unwrap();
+        assert!(!stored.is_confirmed());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_enables_mfa_and_stores_hashed_recovery_codes() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.mfa_service();
+        let setup = service
+            .enroll_totp(EnrollTotpCommand {
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+
+        let secret = TotpSecret::from_base32(setup.secret.expose_secret()).unwrap();
+        let wrong = service
+            .confirm_totp(ConfirmTotpCommand {
+                user_id: user.id.as_str(),
+                code: wrong_totp_code(&secret),
+            })
+            .await;
+        assert!(matches!(wrong, Err(AppError::BadRequest(_))));
+
+        let codes = service
+            .confirm_totp(ConfirmTotpCommand {
+                user_id: user.id.as_str(),
+                code: current_totp_code(&secret),
+            })
+            .await
+            .unwrap();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert!(stores.mfa.totp(&user.id).unwrap().is_confirmed());
+        let stored = stores.mfa.recovery_codes(&user.id);
+        assert_eq!(stored.len(), RECOVERY_CODE_COUNT);
+        assert!(stored.iter().all(|c| !codes.contains(&c.code_hash)));
+        assert!(
+            stored
+                .iter()
+                .any(|c| c.code_hash == hash_token(&RecoveryCode::normalize(&codes[0])))
+        );
+
+        let again = service
+            .enroll_totp(EnrollTotpCommand {
+                user_id: user.id.as_str(),
+            })
+            .await;
+        assert!(matches!(again, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_without_enrollment_fails() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.mfa_service();
+        let result = service
+            .confirm_totp(ConfirmTotpCommand {
+                user_id: user.id.as_str(),
+                code: "123456".to_string(),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+}
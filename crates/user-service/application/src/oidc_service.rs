@@ -0,0 +1,693 @@
+use crate::commands::{
+    AuthorizeCommand, ExchangeAuthorizationCodeCommand, RegisterOAuthClientCommand,
+};
+use crate::queries::GetUserInfoQuery;
+use auth::oidc::{
+    IdTokenClaims, PKCE_METHOD_S256, SCOPE_EMAIL, SCOPE_OPENID, SCOPE_PROFILE, SUPPORTED_SCOPES,
+    StandardClaims, UserInfo, is_valid_code_verifier, verify_pkce_s256,
+};
+use auth::{JwtService, generate_opaque_token, hash_token};
+use base::secret::SecretString;
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::repository::{OAuthRepository, UserRepositories};
+use domain::value_objects::{TenantId, UserId};
+use domain::{AuthorizationCode, OAuthClient, User};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+/// Tunables for the OpenID Connect provider.
+#[derive(Debug, Clone)]
+pub struct OidcSettings {
+    /// Public base URL of the provider. It is the `iss` of every ID token
+    /// and the prefix of every endpoint in the discovery document.
+    pub issuer: String,
+    /// Front-end page that signs the user in and then completes the
+    /// authorization request. It receives the request's parameters.
+    pub login_url: String,
+    pub authorization_code_ttl: Duration,
+    pub id_token_ttl: Duration,
+}
+
+impl Default for OidcSettings {
+    fn default() -> Self {
+        Self {
+            issuer: "http://localhost:3000".to_string(),
+            login_url: "http://localhost:3000/login".to_string(),
+            authorization_code_ttl: Duration::from_secs(60),
+            id_token_ttl: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// A newly registered client. The secret is only ever shown this once.
+#[derive(Debug, Clone)]
+pub struct RegisteredClient {
+    pub client: OAuthClient,
+    pub client_secret: Option<SecretString>,
+}
+
+/// Response of the token endpoint.
+#[derive(Debug, Clone)]
+pub struct OidcTokens {
+    pub access_token: SecretString,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub id_token: SecretString,
+    pub scope: String,
+}
+
+/// Error codes of RFC 6749 §4.1.2.1 and §5.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OAuthErrorCode {
+    InvalidRequest,
+    InvalidClient,
+    InvalidGrant,
+    UnsupportedGrantType,
+    UnsupportedResponseType,
+    InvalidScope,
+    AccessDenied,
+    ServerError,
+}
+
+impl OAuthErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OAuthErrorCode::InvalidRequest => "invalid_request",
+            OAuthErrorCode::InvalidClient => "invalid_client",
+            OAuthErrorCode::InvalidGrant => "invalid_grant",
+            OAuthErrorCode::UnsupportedGrantType => "unsupported_grant_type",
+            OAuthErrorCode::UnsupportedResponseType => "unsupported_response_type",
+            OAuthErrorCode::InvalidScope => "invalid_scope",
+            OAuthErrorCode::AccessDenied => "access_denied",
+            OAuthErrorCode::ServerError => "server_error",
+        }
+    }
+}
+
+impl fmt::Display for OAuthErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An error reported to a relying party in the shape OAuth 2.0 prescribes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthError {
+    pub code: OAuthErrorCode,
+    pub description: String,
+}
+
+impl OAuthError {
+    pub fn new(code: OAuthErrorCode, description: impl Into<String>) -> Self {
+        Self {
+            code,
+            description: description.into(),
+        }
+    }
+
+    fn server_error(err: impl fmt::Display) -> Self {
+        Self::new(OAuthErrorCode::ServerError, err.to_string())
+    }
+}
+
+pub struct OidcApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    oauth_repo: Arc<dyn OAuthRepository>,
+    jwt: Arc<JwtService>,
+    settings: OidcSettings,
+}
+
+impl<R: UserRepositories> OidcApplicationService<R> {
+    pub fn new(
+        user_repo: Arc<R>,
+        oauth_repo: Arc<dyn OAuthRepository>,
+        jwt: Arc<JwtService>,
+        settings: OidcSettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            oauth_repo,
+            jwt,
+            settings,
+        }
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.settings.issuer
+    }
+
+    pub async fn register_client(
+        &self,
+        cmd: RegisterOAuthClientCommand,
+    ) -> Result<RegisteredClient, AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let client_secret = cmd.confidential.then(generate_opaque_token);
+        let client = OAuthClient::new(
+            tenant_id,
+            generate_opaque_token(),
+            client_secret.as_deref().map(hash_token),
+            cmd.name,
+            cmd.redirect_uris,
+        )?;
+        self.oauth_repo
+            .create_client(client.clone())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        info!(tenant_id = %tenant_id, client_id = %client.client_id, "OAuth client registered");
+        Ok(RegisteredClient {
+            client,
+            client_secret: client_secret.map(Into::into),
+        })
+    }
+
+    /// Handles an authorization request and returns where to send the
+    /// browser next: the login page while `user_id` is unknown, otherwise
+    /// the client's redirect URI with a code or an error.
+    ///
+    /// Requests naming an unknown client or redirect URI fail outright, as
+    /// redirecting them would make us an open redirector.
+    pub async fn authorize(
+        &self,
+        cmd: AuthorizeCommand,
+        user_id: Option<String>,
+    ) -> Result<String, AppError> {
+        let client = self
+            .find_client(cmd.client_id.as_deref().unwrap_or_default())
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Unknown client".into()))?;
+        let redirect_uri = cmd
+            .redirect_uri
+            .as_deref()
+            .filter(|uri| client.allows_redirect_uri(uri))
+            .ok_or_else(|| AppError::BadRequest("Redirect URI is not registered".into()))?;
+        let state = cmd.state.as_deref();
+        let reject = |code: OAuthErrorCode, description: &str| {
+            error_redirect(redirect_uri, &OAuthError::new(code, description), state)
+        };
+
+        if cmd.response_type.as_deref() != Some("code") {
+            return Ok(reject(
+                OAuthErrorCode::UnsupportedResponseType,
+                "Only the code response type is supported",
+            ));
+        }
+        let Some(scope) = granted_scope(cmd.scope.as_deref().unwrap_or_default()) else {
+            return Ok(reject(
+                OAuthErrorCode::InvalidScope,
+                "The openid scope is required",
+            ));
+        };
+        let Some(code_challenge) = cmd
+            .code_challenge
+            .as_deref()
+            .filter(|c| is_valid_code_verifier(c))
+            .filter(|_| cmd.code_challenge_method.as_deref() == Some(PKCE_METHOD_S256))
+        else {
+            return Ok(reject(
+                OAuthErrorCode::InvalidRequest,
+                "PKCE with the S256 method is required",
+            ));
+        };
+
+        let Some(user_id) = user_id else {
+            return Ok(login_redirect(&self.settings.login_url, &cmd));
+        };
+        let user = self.find_user(&user_id).await?;
+        if user.tenant_id != client.tenant_id {
+            return Ok(reject(
+                OAuthErrorCode::AccessDenied,
+                "The user cannot sign in to this client",
+            ));
+        }
+
+        let code = generate_opaque_token();
+        let ttl = chrono::Duration::from_std(self.settings.authorization_code_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let authorization = AuthorizationCode::issue(
+            &client,
+            user.id,
+            hash_token(&code),
+            redirect_uri.to_string(),
+            scope,
+            cmd.nonce.clone(),
+            code_challenge.to_string(),
+            ttl,
+        );
+        self.oauth_repo
+            .create_authorization_code(authorization)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        info!(user_id = %user.id, client_id = %client.client_id, "Authorization code issued");
+
+        let mut params = vec![("code", code.as_str())];
+        params.extend(state.map(|s| ("state", s)));
+        Ok(with_query(redirect_uri, &params))
+    }
+
+    /// Redeems an authorization code at the token endpoint.
+    pub async fn exchange_code(
+        &self,
+        cmd: ExchangeAuthorizationCodeCommand,
+    ) -> Result<OidcTokens, OAuthError> {
+        if cmd.grant_type.as_deref() != Some("authorization_code") {
+            return Err(OAuthError::new(
+                OAuthErrorCode::UnsupportedGrantType,
+                "Only the authorization_code grant is supported",
+            ));
+        }
+        let client = self.authenticate_client(&cmd).await?;
+        let (Some(code), Some(code_verifier)) = (cmd.code.as_deref(), cmd.code_verifier.as_deref())
+        else {
+            return Err(OAuthError::new(
+                OAuthErrorCode::InvalidRequest,
+                "code and code_verifier are required",
+            ));
+        };
+
+        let invalid_grant = || {
+            OAuthError::new(
+                OAuthErrorCode::InvalidGrant,
+                "Invalid or expired authorization code",
+            )
+        };
+        let authorization = self
+            .oauth_repo
+            .take_authorization_code(&hash_token(code))
+            .await
+            .map_err(OAuthError::server_error)?
+            .filter(|a| a.expires_at > Utc::now())
+            .ok_or_else(invalid_grant)?;
+        if authorization.client_id != client.client_id
+            || cmd.redirect_uri.as_deref() != Some(authorization.redirect_uri.as_str())
+            || !verify_pkce_s256(code_verifier, &authorization.code_challenge)
+        {
+            return Err(invalid_grant());
+        }
+
+        let user = self
+            .user_repo
+            .find_by_user_id(&authorization.user_id)
+            .await
+            .map_err(OAuthError::server_error)?
+            .ok_or_else(invalid_grant)?;
+        let access = self
+            .jwt
+            .issue_oidc_access_token(
+                &user.id.as_str(),
+                &user.tenant_id.as_str(),
+                &client.client_id,
+                &authorization.scope,
+            )
+            .map_err(OAuthError::server_error)?;
+        let now = Utc::now().timestamp();
+        let id_token = self
+            .jwt
+            .issue_id_token(&IdTokenClaims {
+                iss: self.settings.issuer.clone(),
+                sub: user.id.as_str(),
+                aud: client.client_id.clone(),
+                iat: now,
+                exp: now + self.settings.id_token_ttl.as_secs() as i64,
+                auth_time: authorization.created_at.timestamp(),
+                nonce: authorization.nonce,
+                tid: user.tenant_id.as_str(),
+                profile: standard_claims(&user, &authorization.scope),
+            })
+            .map_err(OAuthError::server_error)?;
+        info!(user_id = %user.id, client_id = %client.client_id, "OIDC tokens issued");
+        Ok(OidcTokens {
+            access_token: access.token.into(),
+            token_type: "Bearer".to_string(),
+            expires_in: access.expires_in,
+            id_token: id_token.into(),
+            scope: authorization.scope,
+        })
+    }
+
+    pub async fn userinfo(&self, query: GetUserInfoQuery) -> Result<UserInfo, AppError> {
+        let claims = self.jwt.verify_oidc_access_token(&query.access_token)?;
+        let user = self.find_user(&claims.sub).await.map_err(|e| match e {
+            AppError::NotFound(_) => AppError::Unauthorized("Unknown user".into()),
+            other => other,
+        })?;
+        Ok(UserInfo {
+            sub: user.id.as_str(),
+            profile: standard_claims(&user, &claims.scope),
+        })
+    }
+
+    /// Identifies the client at the token endpoint. Confidential clients
+    /// must present their secret; public clients are held to PKCE alone.
+    async fn authenticate_client(
+        &self,
+        cmd: &ExchangeAuthorizationCodeCommand,
+    ) -> Result<OAuthClient, OAuthError> {
+        let invalid_client = || {
+            OAuthError::new(
+                OAuthErrorCode::InvalidClient,
+                "Client authentication failed",
+            )
+        };
+        let client = self
+            .find_client(cmd.client_id.as_deref().unwrap_or_default())
+            .await
+            .map_err(OAuthError::server_error)?
+            .ok_or_else(invalid_client)?;
+        if let Some(secret_hash) = &client.client_secret_hash {
+            let presented = cmd
+                .client_secret
+                .as_ref()
+                .map(|secret| hash_token(secret.expose_secret()));
+            if presented.as_ref() != Some(secret_hash) {
+                return Err(invalid_client());
+            }
+        }
+        Ok(client)
+    }
+
+    async fn find_client(&self, client_id: &str) -> Result<Option<OAuthClient>, AppError> {
+        if client_id.is_empty() {
+            return Ok(None);
+        }
+        self.oauth_repo
+            .find_client(client_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    async fn find_user(&self, user_id: &str) -> Result<User, AppError> {
+        let user_id = UserId::from_string(user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        self.user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))
+    }
+}
+
+/// The supported scopes out of a requested scope string, or `None` when
+/// `openid` is missing. Unknown scopes are dropped, as RFC 6749 allows.
+fn granted_scope(requested: &str) -> Option<String> {
+    let requested: Vec<&str> = requested.split_whitespace().collect();
+    if !requested.contains(&SCOPE_OPENID) {
+        return None;
+    }
+    let granted: Vec<&str> = SUPPORTED_SCOPES
+        .into_iter()
+        .filter(|s| requested.contains(s))
+        .collect();
+    Some(granted.join(" "))
+}
+
+/// The claims about `user` that `scope` releases.
+fn standard_claims(user: &User, scope: &str) -> StandardClaims {
+    let granted = |wanted: &str| scope.split_whitespace().any(|s| s == wanted);
+    let mut claims = StandardClaims::default();
+    if granted(SCOPE_EMAIL) {
+        claims.email = Some(user.email_address.as_str().to_string());
+        claims.email_verified = Some(user.email_verified);
+    }
+    if granted(SCOPE_PROFILE) {
+        claims.name = Some(user.username.as_str().to_string());
+    }
+    claims
+}
+
+fn error_redirect(redirect_uri: &str, error: &OAuthError, state: Option<&str>) -> String {
+    let mut params = vec![
+        ("error", error.code.as_str()),
+        ("error_description", error.description.as_str()),
+    ];
+    params.extend(state.map(|s| ("state", s)));
+    with_query(redirect_uri, &params)
+}
+
+/// Sends the browser to the login page with the original request, so the
+/// front-end can resubmit it once the user is signed in.
+fn login_redirect(login_url: &str, cmd: &AuthorizeCommand) -> String {
+    let params: Vec<(&str, &str)> = [
+        ("response_type", &cmd.response_type),
+        ("client_id", &cmd.client_id),
+        ("redirect_uri", &cmd.redirect_uri),
+        ("scope", &cmd.scope),
+        ("state", &cmd.state),
+        ("nonce", &cmd.nonce),
+        ("code_challenge", &cmd.code_challenge),
+        ("code_challenge_method", &cmd.code_challenge_method),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.as_deref().map(|v| (name, v)))
+    .collect();
+    with_query(login_url, &params)
+}
+
+/// Appends URL encoded query parameters to `uri`.
+pub(crate) fn with_query(uri: &str, params: &[(&str, &str)]) -> String {
+    let query: Vec<String> = params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, urlencoding::encode(value)))
+        .collect();
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", uri, separator, query.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        InMemoryUserRepository, Stores, jwt_service, query_param, seed_user,
+    };
+    use auth::oidc::pkce_s256_challenge;
+
+    const REDIRECT_URI: &str = "https://app.example.com/callback";
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+    async fn register(
+        service: &OidcApplicationService<InMemoryUserRepository>,
+        user: &User,
+        confidential: bool,
+    ) -> RegisteredClient {
+        service
+            .register_client(RegisterOAuthClientCommand {
+                tenant_id: user.tenant_id.as_str(),
+                name: "Dashboard".to_string(),
+                redirect_uris: vec![REDIRECT_URI.to_string()],
+                confidential,
+            })
+            .await
+            .unwrap()
+    }
+
+    fn authorize_command(client_id: &str) -> AuthorizeCommand {
+        AuthorizeCommand {
+            response_type: Some("code".to_string()),
+            client_id: Some(client_id.to_string()),
+            redirect_uri: Some(REDIRECT_URI.to_string()),
+            scope: Some("openid email profile offline_access".to_string()),
+            state: Some("xyz".to_string()),
+            nonce: Some("n-0S6_WzA2Mj".to_string()),
+            code_challenge: Some(pkce_s256_challenge(VERIFIER)),
+            code_challenge_method: Some("S256".to_string()),
+        }
+    }
+
+    fn exchange_command(client_id: &str, code: &str) -> ExchangeAuthorizationCodeCommand {
+        ExchangeAuthorizationCodeCommand {
+            grant_type: Some("authorization_code".to_string()),
+            code: Some(code.to_string()),
+            redirect_uri: Some(REDIRECT_URI.to_string()),
+            client_id: Some(client_id.to_string()),
+            client_secret: None,
+            code_verifier: Some(VERIFIER.to_string()),
+        }
+    }
+
+    async fn authorize_code(
+        service: &OidcApplicationService<InMemoryUserRepository>,
+        user: &User,
+        client_id: &str,
+    ) -> String {
+        let redirect = service
+            .authorize(authorize_command(client_id), Some(user.id.as_str()))
+            .await
+            .unwrap();
+        assert!(redirect.starts_with(REDIRECT_URI));
+        assert_eq!(query_param(&redirect, "state").as_deref(), Some("xyz"));
+        query_param(&redirect, "code").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_authorization_code_flow_end_to_end() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.oidc_service();
+        let client = register(&service, &user, false).await;
+        let client_id = client.client.client_id.as_str();
+
+        let login = service
+            .authorize(authorize_command(client_id), None)
+            .await
+            .unwrap();
+        assert!(login.starts_with("http://localhost:3000/login?"));
+        assert_eq!(query_param(&login, "client_id").as_deref(), Some(client_id));
+
+        let code = authorize_code(&service, &user, client_id).await;
+        let tokens = service
+            .exchange_code(exchange_command(client_id, &code))
+            .await
+            .unwrap();
+        assert_eq!(tokens.scope, "openid profile email");
+
+        let claims = jwt_service()
+            .verify_id_token(tokens.id_token.expose_secret(), service.issuer(), client_id)
+            .unwrap();
+        assert_eq!(claims.sub, user.id.as_str());
+        assert_eq!(claims.nonce.as_deref(), Some("n-0S6_WzA2Mj"));
+        assert_eq!(claims.profile.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(claims.profile.email_verified, Some(false));
+        assert_eq!(claims.profile.name.as_deref(), Some("alice"));
+
+        let userinfo = service
+            .userinfo(GetUserInfoQuery {
+                access_token: tokens.access_token.into_exposed(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(userinfo.sub, user.id.as_str());
+        assert_eq!(userinfo.profile, claims.profile);
+    }
+
+    #[tokio::test]
+    async fn test_scope_limits_released_claims() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.oidc_service();
+        let client = register(&service, &user, false).await;
+        let client_id = client.client.client_id.as_str();
+        let mut command = authorize_command(client_id);
+        command.scope = Some("openid".to_string());
+        let redirect = service
+            .authorize(command, Some(user.id.as_str()))
+            .await
+            .unwrap();
+        let code = query_param(&redirect, "code").unwrap();
+        let tokens = service
+            .exchange_code(exchange_command(client_id, &code))
+            .await
+            .unwrap();
+        let claims = jwt_service()
+            .verify_id_token(tokens.id_token.expose_secret(), service.issuer(), client_id)
+            .unwrap();
+        assert_eq!(claims.profile, StandardClaims::default());
+    }
+
+    #[tokio::test]
+    async fn test_code_is_single_use_and_bound_to_verifier() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.oidc_service();
+        let client = register(&service, &user, false).await;
+        let client_id = client.client.client_id.as_str();
+
+        let code = authorize_code(&service, &user, client_id).await;
+        let mut wrong_verifier = exchange_command(client_id, &code);
+        wrong_verifier.code_verifier = Some("a".repeat(43));
+        let result = service.exchange_code(wrong_verifier).await;
+        assert_eq!(result.unwrap_err().code, OAuthErrorCode::InvalidGrant);
+
+        // The failed attempt burnt the code.
+        let replay = service
+            .exchange_code(exchange_command(client_id, &code))
+            .await;
+        assert_eq!(replay.unwrap_err().code, OAuthErrorCode::InvalidGrant);
+    }
+
+    #[tokio::test]
+    async fn test_confidential_client_must_authenticate() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.oidc_service();
+        let client = register(&service, &user, true).await;
+        let client_id = client.client.client_id.as_str();
+
+        let code = authorize_code(&service, &user, client_id).await;
+        let anonymous = service
+            .exchange_code(exchange_command(client_id, &code))
+            .await;
+        assert_eq!(anonymous.unwrap_err().code, OAuthErrorCode::InvalidClient);
+
+        let mut command = exchange_command(client_id, &code);
+        command.client_secret = client.client_secret.clone();
+        assert!(service.exchange_code(command).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_redirect_uri_is_not_followed() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.oidc_service();
+        let client = register(&service, &user, false).await;
+        let mut command = authorize_command(&client.client.client_id);
+        command.redirect_uri = Some("https://evil.example.com/callback".to_string());
+        let result = service.authorize(command, None).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+        let result = service.authorize(authorize_command("unknown"), None).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_requests_are_redirected_with_an_error() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.oidc_service();
+        let client = register(&service, &user, false).await;
+        let client_id = client.client.client_id.as_str();
+
+        let mut plain_pkce = authorize_command(client_id);
+        plain_pkce.code_challenge_method = Some("plain".to_string());
+        let mut no_openid = authorize_command(client_id);
+        no_openid.scope = Some("email".to_string());
+        let mut token_flow = authorize_command(client_id);
+        token_flow.response_type = Some("token".to_string());
+
+        for (command, error) in [
+            (plain_pkce, "invalid_request"),
+            (no_openid, "invalid_scope"),
+            (token_flow, "unsupported_response_type"),
+        ] {
+            let redirect = service.authorize(command, None).await.unwrap();
+            assert!(redirect.starts_with(REDIRECT_URI));
+            assert_eq!(query_param(&redirect, "error").as_deref(), Some(error));
+            assert_eq!(query_param(&redirect, "state").as_deref(), Some("xyz"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_users_of_other_tenants_are_denied() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.oidc_service();
+        let client = register(&service, &user, false).await;
+        let mut stranger = seed_user(&stores.users, "bob", "bob@example.com", "correct-horse");
+        stranger.tenant_id = "0190f5c1-0000-7000-8000-000000000000".parse().unwrap();
+        stores.users.insert(stranger.clone());
+
+        let redirect = service
+            .authorize(
+                authorize_command(&client.client.client_id),
+                Some(stranger.id.as_str()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            query_param(&redirect, "error").as_deref(),
+            Some("access_denied")
+        );
+    }
+}
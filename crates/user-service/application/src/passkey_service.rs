@@ -0,0 +1,326 @@
+use crate::commands::{
+    DeletePasskeyCommand, FinishPasskeyRegistrationCommand, StartPasskeyRegistrationCommand,
+};
+use crate::queries::ListPasskeysQuery;
+use auth::generate_opaque_token;
+use auth::webauthn::{CreationOptions, RelyingParty};
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::repository::{UserRepositories, WebAuthnRepository};
+use domain::value_objects::UserId;
+use domain::{User, WebAuthnCeremony, WebAuthnChallenge, WebAuthnCredential};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+const MAX_PASSKEY_NAME_LEN: usize = 100;
+
+/// Tunables for passkey registration.
+#[derive(Debug, Clone, Default)]
+pub struct PasskeySettings {
+    pub relying_party: RelyingParty,
+}
+
+/// Options for the browser together with the id the answer must be posted
+/// back with.
+#[derive(Debug, Clone)]
+pub struct PasskeyCeremony<T> {
+    pub ceremony_id: String,
+    pub options: T,
+}
+
+pub struct PasskeyApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    webauthn_repo: Arc<dyn WebAuthnRepository>,
+    settings: PasskeySettings,
+}
+
+impl<R: UserRepositories> PasskeyApplicationService<R> {
+    pub fn new(
+        user_repo: Arc<R>,
+        webauthn_repo: Arc<dyn WebAuthnRepository>,
+        settings: PasskeySettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            webauthn_repo,
+            settings,
+        }
+    }
+
+    pub async fn start_registration(
+        &self,
+        cmd: StartPasskeyRegistrationCommand,
+    ) -> Result<PasskeyCeremony<CreationOptions>, AppError> {
+        let user = self.find_user(&cmd.user_id).await?;
+        let existing: Vec<Vec<u8>> = self
+            .list_for(&user.id)
+            .await?
+            .into_iter()
+            .map(|c| c.credential_id)
+            .collect();
+
+        let rp = &self.settings.relying_party;
+        let ttl = chrono::Duration::from_std(rp.timeout)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let challenge = WebAuthnChallenge::issue(
+            user.tenant_id,
+            Some(user.id),
+            WebAuthnCeremony::Registration,
+            generate_opaque_token(),
+            ttl,
+        );
+        let options = rp.creation_options(
+            &challenge.challenge,
+            user.id.as_uuid().as_bytes(),
+            user.email_address.as_str(),
+            &existing,
+        );
+        let ceremony_id = challenge.id.to_string();
+        self.webauthn_repo
+            .create_challenge(challenge)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(PasskeyCeremony {
+            ceremony_id,
+            options,
+        })
+    }
+
+    pub async fn finish_registration(
+        &self,
+        cmd: FinishPasskeyRegistrationCommand,
+    ) -> Result<WebAuthnCredential, AppError> {
+        let user = self.find_user(&cmd.user_id).await?;
+        let name = cmd
+            .name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        if name
+            .as_ref()
+            .is_some_and(|n| n.chars().count() > MAX_PASSKEY_NAME_LEN)
+        {
+            return Err(AppError::BadRequest("Passkey name is too long".into()));
+        }
+        let challenge = take_challenge(
+            self.webauthn_repo.as_ref(),
+            &cmd.ceremony_id,
+            WebAuthnCeremony::Registration,
+        )
+        .await?
+        .filter(|c| c.user_id == Some(user.id))
+        .ok_or_else(invalid_ceremony)?;
+
+        let verified = self
+            .settings
+            .relying_party
+            .verify_registration(&cmd.credential, &challenge.challenge)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        let taken = self
+            .webauthn_repo
+            .find_credential(&user.tenant_id, &verified.credential_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if taken.is_some() {
+            return Err(AppError::BadRequest("Passkey is already registered".into()));
+        }
+
+        let credential = WebAuthnCredential::new(
+            user.tenant_id,
+            user.id,
+            verified.credential_id,
+            verified.public_key,
+            verified.sign_count,
+            name,
+        );
+        self.webauthn_repo
+            .create_credential(credential.clone())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        info!(user_id = %user.id, passkey_id = %credential.id, "Passkey registered");
+        Ok(credential)
+    }
+
+    pub async fn list(
+        &self,
+        query: ListPasskeysQuery,
+    ) -> Result<Vec<WebAuthnCredential>, AppError> {
+        let user = self.find_user(&query.user_id).await?;
+        self.list_for(&user.id).await
+    }
+
+    pub async fn delete(&self, cmd: DeletePasskeyCommand) -> Result<(), AppError> {
+        let user_id = UserId::from_string(&cmd.user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        let passkey_id = Uuid::parse_str(&cmd.passkey_id)
+            .map_err(|_| AppError::BadRequest("Invalid passkey id".into()))?;
+        let deleted = self
+            .webauthn_repo
+            .delete_credential(&user_id, passkey_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if !deleted {
+            return Err(AppError::NotFound("Passkey not found".into()));
+        }
+        Ok(())
+    }
+
+    async fn list_for(&self, user_id: &UserId) -> Result<Vec<WebAuthnCredential>, AppError> {
+        self.webauthn_repo
+            .list_credentials(user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    async fn find_user(&self, user_id: &str) -> Result<User, AppError> {
+        let user_id = UserId::from_string(user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        self.user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))
+    }
+}
+
+/// Consumes the challenge of a ceremony. `None` when it is unknown, expired,
+/// already answered or belongs to another kind of ceremony.
+pub(crate) async fn take_challenge(
+    repo: &dyn WebAuthnRepository,
+    ceremony_id: &str,
+    ceremony: WebAuthnCeremony,
+) -> Result<Option<WebAuthnChallenge>, AppError> {
+    let Ok(id) = Uuid::parse_str(ceremony_id) else {
+        return Ok(None);
+    };
+    Ok(repo
+        .take_challenge(id)
+        .await
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?
+        .filter(|c| c.ceremony == ceremony && c.expires_at > Utc::now()))
+}
+
+fn invalid_ceremony() -> AppError {
+    AppError::BadRequest("Invalid or expired passkey ceremony".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{InMemoryUserRepository, Stores};
+    use auth::webauthn::testing::SoftwareAuthenticator;
+
+    fn authenticator() -> SoftwareAuthenticator {
+        SoftwareAuthenticator::new(&RelyingParty::default().origin)
+    }
+
+    async fn register(
+        service: &PasskeyApplicationService<InMemoryUserRepository>,
+        user: &User,
+        authenticator: &mut SoftwareAuthenticator,
+    ) -> Result<WebAuthnCredential, AppError> {
+        let ceremony = service
+            .start_registration(StartPasskeyRegistrationCommand {
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+        service
+            .finish_registration(FinishPasskeyRegistrationCommand {
+                user_id: user.id.as_str(),
+                ceremony_id: ceremony.ceremony_id,
+                name: Some("Laptop".to_string()),
+                credential: authenticator.register(&ceremony.options),
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_registration_stores_credential() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.passkey_service();
+        let mut authenticator = authenticator();
+        let credential = register(&service, &user, &mut authenticator).await.unwrap();
+        assert_eq!(credential.credential_id, authenticator.credential_id());
+        assert_eq!(credential.name.as_deref(), Some("Laptop"));
+
+        let listed = service
+            .list(ListPasskeysQuery {
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![credential]);
+    }
+
+    #[tokio::test]
+    async fn test_same_authenticator_cannot_register_twice() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.passkey_service();
+        let mut authenticator = authenticator();
+        register(&service, &user, &mut authenticator).await.unwrap();
+
+        let ceremony = service
+            .start_registration(StartPasskeyRegistrationCommand {
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(ceremony.options.exclude_credentials.len(), 1);
+        let again = register(&service, &user, &mut authenticator).await;
+        assert!(matches!(again, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_ceremony_can_only_be_answered_once() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.passkey_service();
+        let mut authenticator = authenticator();
+        let ceremony = service
+            .start_registration(StartPasskeyRegistrationCommand {
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+        let response = authenticator.register(&ceremony.options);
+        let finish = |credential| FinishPasskeyRegistrationCommand {
+            user_id: user.id.as_str(),
+            ceremony_id: ceremony.ceremony_id.clone(),
+            name: None,
+            credential,
+        };
+        service
+            .finish_registration(finish(response.clone()))
+            .await
+            .unwrap();
+        let replay = service.finish_registration(finish(response)).await;
+        assert!(matches!(replay, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_removes_only_own_passkey() {
+        let stores = Stores::default();
+        let user = stores.seed_alice();
+        let service = stores.passkey_service();
+        let credential = register(&service, &user, &mut authenticator())
+            .await
+            .unwrap();
+
+        let other = service.delete(DeletePasskeyCommand {
+            user_id: UserId::new().as_str(),
+            passkey_id: credential.id.to_string(),
+        });
+        assert!(matches!(other.await, Err(AppError::NotFound(_))));
+        service
+            .delete(DeletePasskeyCommand {
+                user_id: user.id.as_str(),
+                passkey_id: credential.id.to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(stores.webauthn.credentials().is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use crate::hashing_pool::HashingPool;
+use base::secret::SecretString;
+use base::web::error::AppError;
+use chrono::{DateTime, Utc};
+use domain::User;
+use domain::repository::{PasswordHistoryRepository, TenantSettingsRepository};
+use domain::services::BreachedPasswords;
+use domain::value_objects::{Password, PasswordViolation, TenantId, TenantSettings};
+use std::sync::Arc;
+
+/// Checks new passwords against the tenant's password policy and the
+/// breached password corpus, unless the tenant opted out of the latter or
+/// the service runs without a corpus. Keeps the password history the
+/// policy refuses reuse from.
+pub struct PasswordScreen {
+    breached: Option<Arc<dyn BreachedPasswords>>,
+    tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+    history_repo: Arc<dyn PasswordHistoryRepository>,
+    pool: Arc<HashingPool>,
+}
+
+impl PasswordScreen {
+    pub fn new(
+        breached: Option<Arc<dyn BreachedPasswords>>,
+        tenant_settings_repo: Arc<dyn TenantSettingsRepository>,
+        history_repo: Arc<dyn PasswordHistoryRepository>,
+        pool: Arc<HashingPool>,
+    ) -> Self {
+        Self {
+            breached,
+            tenant_settings_repo,
+            history_repo,
+            pool,
+        }
+    }
+
+    /// Where accepted passwords are hashed.
+    pub fn pool(&self) -> &HashingPool {
+        &self.pool
+    }
+
+    /// Hashes `password` as the new password of a user of `tenant_id`
+    /// known by `username` and `email`, or refuses it with every rule it
+    /// breaks.
+    pub async fn new_password(
+        &self,
+        tenant_id: &TenantId,
+        password: &SecretString,
+        username: &str,
+        email: &str,
+    ) -> Result<Password, AppError> {
+        let settings = self.tenant_settings(tenant_id).await?;
+        let violations = self
+            .violations(
+                &settings,
+                tenant_id,
+                password.expose_secret(),
+                username,
+                email,
+            )
+            .await?;
+        self.pool.hash(password, violations).await
+    }
+
+    /// Hashes `password` to replace the current password of `user`. On top
+    /// of the checks of [`Self::new_password`], the current password and
+    /// the ones in the user's history are refused.
+    pub async fn replacement_password(
+        &self,
+        user: &User,
+        password: &SecretString,
+    ) -> Result<Password, AppError> {
+        let settings = self.tenant_settings(&user.tenant_id).await?;
+        let mut violations = self
+            .violations(
+                &settings,
+                &user.tenant_id,
+                password.expose_secret(),
+                user.username.as_str(),
+                user.email_address.as_str(),
+            )
+            .await?;
+        if self
+            .recently_used(user, password, settings.password_policy.history_size)
+            .await?
+        {
+            violations.push(PasswordViolation::Reused);
+        }
+        self.pool.hash(password, violations).await
+    }
+
+    /// Adds the password `user` gave up at `replaced_at` to their history,
+    /// when the tenant keeps one.
+    pub async fn remember_replaced(
+        &self,
+        user: &User,
+        replaced_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let keep = self
+            .tenant_settings(&user.tenant_id)
+            .await?
+            .password_policy
+            .history_size;
+        if keep == 0 {
+            return Ok(());
+        }
+        self.history_repo
+            .add(
+                &user.tenant_id,
+                &user.id,
+                &user.password_hash,
+                replaced_at,
+                keep,
+            )
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    async fn violations(
+        &self,
+        settings: &TenantSettings,
+        tenant_id: &TenantId,
+        password: &str,
+        username: &str,
+        email: &str,
+    ) -> Result<Vec<PasswordViolation>, AppError> {
+        let policy = &settings.password_policy;
+        let tenant_name = if policy.ban_account_details {
+            self.tenant_settings_repo
+                .find_name(tenant_id)
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?
+        } else {
+            None
+        };
+        let mut account_details = Vec::new();
+        if policy.ban_account_details {
+            let mailbox = email.split('@').next().unwrap_or_default();
+            account_details.extend([username, mailbox]);
+            account_details.extend(tenant_name.as_deref());
+        }
+
+        let mut violations = policy.check(password, &account_details);
+        let screened = self
+            .breached
+            .as_ref()
+            .filter(|_| !settings.allow_breached_passwords);
+        if screened.is_some_and(|breached| breached.contains(password)) {
+            violations.push(PasswordViolation::Breached);
+        }
+        Ok(violations)
+    }
+
+    /// Whether `password` is the user's current one or among the last
+    /// `history_size` they replaced.
+    async fn recently_used(
+        &self,
+        user: &User,
+        password: &SecretString,
+        history_size: usize,
+    ) -> Result<bool, AppError> {
+        if self.pool.verify(&user.password_hash, password).await? {
+            return Ok(true);
+        }
+        if history_size == 0 {
+            return Ok(false);
+        }
+        let previous = self
+            .history_repo
+            .list_recent(&user.id, history_size)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        for hash in &previous {
+            if self.pool.verify(hash, password).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn tenant_settings(&self, tenant_id: &TenantId) -> Result<TenantSettings, AppError> {
+        Ok(self
+            .tenant_settings_repo
+            .find_settings(tenant_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        InMemoryPasswordHistoryRepository, InMemoryTenantSettingsRepository,
+        InMemoryUserRepository, KnownBreachedPasswords, hashing_pool, seed_user, tenant_id,
+    };
+    use serde_json::json;
+
+    fn reasons(result: Result<Password, AppError>) -> Vec<String> {
+        match result {
+            Err(AppError::Validation(errors)) => {
+                errors.into_iter().filter_map(|e| e.reason).collect()
+            }
+            Err(e) => panic!("expected a validation error, got {}", e),
+            Ok(_) => Vec::new(),
+        }
+    }
+
+    async fn check(screen: &PasswordScreen, password: &str) -> Result<Password, AppError> {
+        screen
+            .new_password(&tenant_id(), &password.into(), "alice", "alice@example.com")
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_tenant_can_opt_out_of_screening() {
+        let tenant_settings = Arc::new(InMemoryTenantSettingsRepository::default());
+        let screen = PasswordScreen::new(
+            Some(Arc::new(KnownBreachedPasswords::new(["password123"]))),
+            tenant_settings.clone(),
+            Arc::new(InMemoryPasswordHistoryRepository::default()),
+            hashing_pool(),
+        );
+        assert_eq!(
+            reasons(check(&screen, "password123").await),
+            vec!["password_breached"]
+        );
+        assert!(check(&screen, "correct-horse").await.is_ok());
+
+        tenant_settings.set(
+            tenant_id(),
+            TenantSettings {
+                allow_breached_passwords: true,
+                ..TenantSettings::default()
+            },
+        );
+        assert!(check(&screen, "password123").await.is_ok());
+
+        let unscreened = PasswordScreen::new(
+            None,
+            tenant_settings,
+            Arc::new(InMemoryPasswordHistoryRepository::default()),
+            hashing_pool(),
+        );
+        assert!(check(&unscreened, "password123").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reports_every_rule_of_the_tenant_policy() {
+        let tenant_settings = Arc::new(InMemoryTenantSettingsRepository::default());
+        tenant_settings.set(
+            tenant_id(),
+            TenantSettings::from_json(json!({
+                "password_policy": {
+                    "min_length": 12,
+                    "require_uppercase": true,
+                    "require_symbol": true
+                }
+            }))
+            .unwrap(),
+        );
+        tenant_settings.set_name(tenant_id(), "Acme");
+        let screen = PasswordScreen::new(
+            Some(Arc::new(KnownBreachedPasswords::new(["acme2024"]))),
+            tenant_settings,
+            Arc::new(InMemoryPasswordHistoryRepository::default()),
+            hashing_pool(),
+        );
+
+        let result = screen
+            .new_password(
+                &tenant_id(),
+                &"acme2024".into(),
+                "alice",
+                "alice@example.com",
+            )
+            .await;
+        assert_eq!(
+            reasons(result),
+            vec![
+                "password_too_short",
+                "password_missing_uppercase",
+                "password_missing_symbol",
+                "password_contains_banned_word",
+                "password_breached",
+            ]
+        );
+
+        let result = screen
+            .new_password(
+                &tenant_id(),
+                &"Alice-Wonderland".into(),
+                "alice",
+                "alice@example.com",
+            )
+            .await;
+        assert_eq!(reasons(result), vec!["password_contains_banned_word"]);
+        let result = screen
+            .new_password(
+                &tenant_id(),
+                &"Tr0ub4dor&3-horse".into(),
+                "alice",
+                "alice@example.com",
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_recent_passwords_cannot_be_reused() {
+        let tenant_settings = Arc::new(InMemoryTenantSettingsRepository::default());
+        tenant_settings.set(
+            tenant_id(),
+            TenantSettings::from_json(json!({ "password_policy": { "history_size": 2 } })).unwrap(),
+        );
+        let screen = PasswordScreen::new(
+            None,
+            tenant_settings,
+            Arc::new(InMemoryPasswordHistoryRepository::default()),
+            hashing_pool(),
+        );
+        let users = InMemoryUserRepository::default();
+        let mut user = seed_user(&users, "alice", "alice@example.com", "first-horse");
+
+        // Each change puts the replaced password in the history.
+        for next in ["second-horse", "third-horse", "fourth-horse"] {
+            let hash = screen
+                .replacement_password(&user, &next.into())
+                .await
+                .unwrap();
+            screen.remember_replaced(&user, Utc::now()).await.unwrap();
+            user.password_hash = hash;
+        }
+
+        for reused in ["fourth-horse", "third-horse", "second-horse"] {
+            let result = screen.replacement_password(&user, &reused.into()).await;
+            assert_eq!(reasons(result), vec!["password_reused"]);
+        }
+        // Only the last two replaced passwords are kept.
+        assert!(
+            screen
+                .replacement_password(&user, &"first-horse".into())
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use crate::commands::{ChangePasswordCommand, ForgotPasswordCommand, ResetPasswordCommand};
+use crate::hashing_pool::HashingPool;
+use crate::password_screen::PasswordScreen;
+use crate::session_service::SessionApplicationService;
+use auth::{generate_opaque_token, hash_token};
+use base::secret::SecretString;
+use base::web::error::AppError;
+use chrono::Utc;
+use domain::repository::{UserRepositories, UserTokenRepository};
+use domain::services::{EmailMessage, Mailer};
+use domain::{
+    TokenPurpose, User, UserToken,
+    value_objects::{EmailAddress, Password, TenantId, UserId},
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Tunables for the password recovery flow.
+#[derive(Debug, Clone)]
+pub struct PasswordSettings {
+    pub reset_token_ttl: Duration,
+    /// Minimum time between two reset emails for the same user.
+    pub reset_request_interval: Duration,
+    /// Page that redeems a reset token passed as `?token=`.
+    pub password_reset_url: String,
+}
+
+impl Default for PasswordSettings {
+    fn default() -> Self {
+        Self {
+            reset_token_ttl: Duration::from_secs(60 * 60),
+            reset_request_interval: Duration::from_secs(60),
+            password_reset_url: "http://localhost:3000/reset-password".to_string(),
+        }
+    }
+}
+
+pub struct PasswordApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    user_token_repo: Arc<dyn UserTokenRepository>,
+    sessions: Arc<SessionApplicationService<R>>,
+    mailer: Arc<dyn Mailer>,
+    password_screen: Arc<PasswordScreen>,
+    settings: PasswordSettings,
+}
+
+impl<R: UserRepositories> PasswordApplicationService<R> {
+    pub fn new(
+        user_repo: Arc<R>,
+        user_token_repo: Arc<dyn UserTokenRepository>,
+        sessions: Arc<SessionApplicationService<R>>,
+        mailer: Arc<dyn Mailer>,
+        password_screen: Arc<PasswordScreen>,
+        settings: PasswordSettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            user_token_repo,
+            sessions,
+            mailer,
+            password_screen,
+            settings,
+        }
+    }
+
+    /// Where password hashes are computed and verified.
+    pub fn hashing_pool(&self) -> &HashingPool {
+        self.password_screen.pool()
+    }
+
+    /// Mails a reset link when the account exists. The outcome is the same
+    /// whether or not it does, so the endpoint cannot be used to probe emails.
+    pub async fn forgot_password(&self, cmd: ForgotPasswordCommand) -> Result<(), AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let email = EmailAddress::new(cmd.email)?;
+        let user = self
+            .user_repo
+            .find_by_email(&tenant_id, &email)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let Some(user) = user else {
+            return Ok(());
+        };
+
+        let latest = self
+            .user_token_repo
+            .find_latest(&user.id, TokenPurpose::PasswordReset)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if let Some(latest) = latest {
+            let interval = chrono::Duration::from_std(self.settings.reset_request_interval)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            if latest.created_at + interval > Utc::now() {
+                info!(user_id = %user.id, "Password reset requested too soon, ignoring");
+                return Ok(());
+            }
+        }
+        self.send_reset_email(&user).await
+    }
+
+    /// Sets a new password from a reset token and signs the user out everywhere.
+    pub async fn reset_password(&self, cmd: ResetPasswordCommand) -> Result<(), AppError> {
+        let token = self
+            .user_token_repo
+            .find_by_hash(
+                TokenPurpose::PasswordReset,
+                &hash_token(cmd.token.expose_secret()),
+            )
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|t| t.is_usable(Utc::now()))
+            .ok_or_else(invalid_reset_token)?;
+        let user = self
+            .user_repo
+            .find_by_user_id(&token.user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(invalid_reset_token)?;
+        // Validate the new password before consuming the token so a
+        // rejected choice does not burn it.
+        let password_hash = self
+            .password_screen
+            .replacement_password(&user, &cmd.new_password)
+            .await?;
+        let consumed = self
+            .user_token_repo
+            .consume(token.id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if !consumed {
+            return Err(invalid_reset_token());
+        }
+
+        self.store_password(&user, &password_hash).await?;
+        // Proving control of the mailbox is enough to lift a lockout.
+        self.user_repo
+            .clear_lockout(&token.user_id, None)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.user_token_repo
+            .consume_all(&token.user_id, TokenPurpose::PasswordReset)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let revoked = self.sessions.end_all(&token.user_id).await?;
+        info!(user_id = %token.user_id, revoked, "Password reset, sessions revoked");
+        Ok(())
+    }
+
+    /// Changes the password of a signed-in user who knows their current one.
+    pub async fn change_password(&self, cmd: ChangePasswordCommand) -> Result<(), AppError> {
+        let user_id = UserId::from_string(&cmd.user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        let user = self
+            .user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+        if !self
+            .hashing_pool()
+            .verify(&user.password_hash, &cmd.current_password)
+            .await?
+        {
+            return Err(AppError::BadRequest("Current password is incorrect".into()));
+        }
+        self.replace_password(&user, &cmd.new_password).await?;
+        info!(user_id = %user.id, "Password changed");
+        Ok(())
+    }
+
+    /// Replaces the password of `user` with `new_password` once it passes
+    /// the screen, including the password history.
+    pub async fn replace_password(
+        &self,
+        user: &User,
+        new_password: &SecretString,
+    ) -> Result<(), AppError> {
+        let password_hash = self
+            .password_screen
+            .replacement_password(user, new_password)
+            .await?;
+        self.store_password(user, &password_hash).await
+    }
+
+    /// Recomputes the hash of `password`, just verified against the stored
+    /// hash of `user`, when that hash is weaker than the configured
+    /// parameters. A failure only costs the upgrade, not the login.
+    pub async fn upgrade_hash(&self, user: &User, password: &SecretString) {
+        let pool = self.hashing_pool();
+        if !user.password_hash.needs_rehash(pool.hashing()) {
+            return;
+        }
+        let rehashed = match pool.hash(password, Vec::new()).await {
+            Ok(rehashed) => rehashed,
+            Err(e) => {
+                warn!(user_id = %user.id, "Failed to rehash password: {}", e);
+                return;
+            }
+        };
+        match self.user_repo.rehash_password(&user.id, &rehashed).await {
+            Ok(()) => info!(user_id = %user.id, "Password rehashed with {}", pool.hashing()),
+            Err(e) => warn!(user_id = %user.id, "Failed to store rehashed password: {}", e),
+        }
+    }
+
+    /// Saves a screened password and moves the one it replaces to the
+    /// history.
+    async fn store_password(&self, user: &User, password_hash: &Password) -> Result<(), AppError> {
+        let now = Utc::now();
+        self.user_repo
+            .update_password(&user.id, password_hash, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.password_screen.remember_replaced(user, now).await
+    }
+
+    async fn send_reset_email(&self, user: &User) -> Result<(), AppError> {
+        self.user_token_repo
+            .consume_all(&user.id, TokenPurpose::PasswordReset)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        let token = generate_opaque_token();
+        let ttl = chrono::Duration::from_std(self.settings.reset_token_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let record = UserToken::issue(
+            user.tenant_id,
+            user.id,
+            TokenPurpose::PasswordReset,
+            hash_token(&token),
+            ttl,
+        );
+        self.user_token_repo
+            .create(record)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        let message = EmailMessage {
+            to: user.email_address.clone(),
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "Hi {},\n\nReset your password by opening {}?token={}\n\nIf you did not ask for this, you can ignore this email.\n",
+                user.username.as_str(),
+                self.settings.password_reset_url,
+                token
+            ),
+        };
+        if let Err(e) = self.mailer.send(message).await {
+            warn!(user_id = %user.id, "Failed to send password reset email: {}", e);
+        }
+        Ok(())
+    }
+}
+
+fn invalid_reset_token() -> AppError {
+    AppError::BadRequest("Invalid or expired password reset token".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Stores, TENANT_ID, seed_user};
+    use domain::repository::{RefreshTokenRepository, SessionRepository};
+    use domain::value_objects::{PasswordHashing, PasswordPolicy, Peppers};
+    use domain::{ClientInfo, RefreshToken, Session};
+
+    fn forgot_command(email: &str) -> ForgotPasswordCommand {
+        ForgotPasswordCommand {
+            tenant_id: TENANT_ID.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    fn reset_command(token: &str, new_password: &str) -> ResetPasswordCommand {
+        ResetPasswordCommand {
+            token: token.to_string().into(),
+            new_password: new_password.to_string().into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_hash_repeppers_with_the_current_pepper() {
+        let first = Peppers::default().with(1, vec![1; 32]).unwrap();
+        let rotated = first.clone().with(2, vec![2; 32]).unwrap();
+        let hashing = PasswordHashing::default().with_peppers(rotated);
+        let stores = Stores {
+            hashing: hashing.clone(),
+            ..Stores::default()
+        };
+        let mut user = stores.seed_alice();
+        let old_hashing = PasswordHashing::default().with_peppers(first);
+        user.password_hash =
+            Password::from_plain("correct-horse", &PasswordPolicy::default(), &old_hashing)
+                .unwrap();
+        stores.users.insert(user.clone());
+        let service = stores.password_service();
+
+        assert!(
+            user.password_hash
+                .verify("correct-horse", &hashing)
+                .unwrap()
+        );
+        service.upgrade_hash(&user, &"correct-horse".into()).await;
+        let stored = stores.users.get(&user.id).unwrap();
+        assert_ne!(stored.password_hash, user.password_hash);
+        assert!(!stored.password_hash.needs_rehash(&hashing));
+        assert!(
+            stored
+                .password_hash
+                .verify("correct-horse", &hashing)
+                .unwrap()
+        );
+        assert!(
+            stored
+                .password_hash
+                .verify("correct-horse", &old_hashing)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forgot_password_is_silent_for_unknown_email() {
+        let stores = Stores::default();
+        let service = stores.password_service();
+        service
+            .forgot_password(forgot_command("nobody@example.com"))
+            .await
+            .unwrap();
+        assert!(stores.mailer.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_sets_new_hash_and_revokes_sessions() {
+        let stores = Stores::default();
+        let service = stores.password_service();
+        let user = seed_user(&stores.users, "alice", "alice@example.com", "old-password");
+        let session = Session::start(
+            user.tenant_id,
+            user.id,
+            ClientInfo::default(),
+            false,
+            Utc::now(),
+        );
+        stores.sessions.create(session.clone()).await.unwrap();
+        let refresh_token = RefreshToken::issue(
+            user.tenant_id,
+            user.id,
+            Some(session.id),
+            hash_token("session"),
+            chrono::Duration::days(1),
+        );
+        stores.refresh_tokens.create(refresh_token).await.unwrap();
+
+        service
+            .forgot_password(forgot_command("alice@example.com"))
+            .await
+            .unwrap();
+        let token = stores.mailer.last_token().unwrap();
+        service
+            .reset_password(reset_command(&token, "new-password"))
+            .await
+            .unwrap();
+
+        let stored = stores.users.get(&user.id).unwrap();
+        assert!(
+            stored
+                .password_hash
+                .verify("new-password", &PasswordHashing::default())
+                .unwrap()
+        );
+        assert!(
+            !stored
+                .password_hash
+                .verify("old-password", &PasswordHashing::default())
+                .unwrap()
+        );
+        assert!(stored.password_changed_at.is_some());
+        let refresh_token = stores.refresh_tokens.get(&hash_token("session")).unwrap();
+        assert!(refresh_token.revoked_at.is_some());
+        assert!(stores.sessions.sessions()[0].revoked_at.is_some());
+
+        let reuse = service
+            .reset_password(reset_command(&token, "another-password"))
+            .await;
+        assert!(matches!(reuse, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_weak_password_does_not_consume_token() {
+        let stores = Stores::default();
+        let service = stores.password_service();
+        seed_user(&stores.users, "alice", "alice@example.com", "old-password");
+        service
+            .forgot_password(forgot_command("alice@example.com"))
+            .await
+            .unwrap();
+        let token = stores.mailer.last_token().unwrap();
+
+        let weak = service.reset_password(reset_command(&token, "short")).await;
+        assert!(matches!(weak, Err(AppError::Validation(_))));
+        let breached = service
+            .reset_password(reset_command(&token, "password123"))
+            .await;
+        assert!(matches!(breached, Err(AppError::Validation(_))));
+        service
+            .reset_password(reset_command(&token, "long-enough"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_change_password_checks_current_and_refuses_reuse() {
+        let stores = Stores::default();
+        let service = stores.password_service();
+        let user = seed_user(&stores.users, "alice", "alice@example.com", "old-password");
+        let change = |current: &str, new: &str| ChangePasswordCommand {
+            user_id: user.id.as_str(),
+            current_password: current.to_string().into(),
+            new_password: new.to_string().into(),
+        };
+
+        let wrong = service
+            .change_password(change("wrong-password", "new-password"))
+            .await;
+        assert!(matches!(wrong, Err(AppError::BadRequest(_))));
+        let same = service
+            .change_password(change("old-password", "old-password"))
+            .await;
+        assert!(matches!(same, Err(AppError::Validation(_))));
+
+        service
+            .change_password(change("old-password", "new-password"))
+            .await
+            .unwrap();
+        let stored = stores.users.get(&user.id).unwrap();
+        assert!(
+            stored
+                .password_hash
+                .verify("new-password", &PasswordHashing::default())
+                .unwrap()
+        );
+        assert!(stored.password_changed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_expired_reset_token_is_rejected() {
+        let stores = Stores::default();
+        let service = stores.password_service();
+        let user = seed_user(&stores.users, "alice", "alice@example.com", "old-password");
+        let expired = UserToken::issue(
+            user.tenant_id,
+            user.id,
+            TokenPurpose::PasswordReset,
+            hash_token("expired"),
+            chrono::Duration::seconds(-1),
+        );
+        stores.user_tokens.create(expired).await.unwrap();
+
+        let result = service
+            .reset_password(reset_command("expired", "new-password"))
+            .await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_requests_are_throttled_silently() {
+        let stores = Stores::default();
+        let service = stores.password_service();
+        seed_user(&stores.users, "alice", "alice@example.com", "old-password");
+        for _ in 0..3 {
+            service
+                .forgot_password(forgot_command("alice@example.com"))
+                .await
+                .unwrap();
+        }
+        assert_eq!(stores.mailer.sent().len(), 1);
+    }
+}
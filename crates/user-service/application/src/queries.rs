@@ -1,3 +1,23 @@
+use auth::Principal;
+
 pub struct GetUserByIdQuery {
+    pub actor: Principal,
     pub user_id: String,
 }
+
+pub struct ListSessionsQuery {
+    pub actor: Principal,
+    pub user_id: String,
+}
+
+pub struct ListPasskeysQuery {
+    pub user_id: String,
+}
+
+pub struct GetUserInfoQuery {
+    pub access_token: String,
+}
+
+pub struct ListApiKeysQuery {
+    pub tenant_id: String,
+}
@@ -0,0 +1,484 @@
+use crate::access_control::{
+    ACTION_USERS_READ, ACTION_USERS_REVOKE_SESSIONS, AccessControl, user_attributes,
+};
+use crate::commands::{RevokeAllSessionsCommand, RevokeSessionCommand};
+use crate::queries::ListSessionsQuery;
+use auth::{RevocationList, generate_opaque_token, hash_token};
+use base::web::error::AppError;
+use chrono::{DateTime, Utc};
+use domain::repository::{RefreshTokenRepository, SessionRepository, UserRepositories};
+use domain::value_objects::{TenantId, UserId};
+use domain::{ClientInfo, RefreshToken, Session, User};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Tunables for login sessions.
+#[derive(Debug, Clone)]
+pub struct SessionSettings {
+    /// Lifetime of a refresh token, which is also how long a session lasts
+    /// without being used.
+    pub refresh_token_ttl: Duration,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            refresh_token_ttl: Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// Keeps track of signed-in devices and the refresh tokens issued to them,
+/// and lets users and admins end them.
+///
+/// Revoked sessions go on the [`RevocationList`] so their access tokens are
+/// refused before they expire.
+pub struct SessionApplicationService<R: UserRepositories> {
+    user_repo: Arc<R>,
+    session_repo: Arc<dyn SessionRepository>,
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    access_control: Arc<AccessControl>,
+    revocations: Arc<RevocationList>,
+    /// How long the longest-lived token bound to a session lives, be it an
+    /// access token or an impersonation token, and so how long a revocation
+    /// must be remembered.
+    token_ttl: Duration,
+    settings: SessionSettings,
+}
+
+impl<R: UserRepositories> SessionApplicationService<R> {
+    pub fn new(
+        user_repo: Arc<R>,
+        session_repo: Arc<dyn SessionRepository>,
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        access_control: Arc<AccessControl>,
+        revocations: Arc<RevocationList>,
+        token_ttl: Duration,
+        settings: SessionSettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            session_repo,
+            refresh_token_repo,
+            access_control,
+            revocations,
+            token_ttl,
+            settings,
+        }
+    }
+
+    /// Active sessions of a user the caller may see, most recently used
+    /// first.
+    pub async fn list(&self, query: ListSessionsQuery) -> Result<Vec<Session>, AppError> {
+        let user = self
+            .find_in_tenant(&query.actor.tenant_id, &query.user_id)
+            .await?;
+        self.access_control
+            .authorize(&query.actor, ACTION_USERS_READ, user_attributes(&user))
+            .await?;
+        let seen_after = Utc::now() - self.idle_timeout()?;
+        self.session_repo
+            .list_active(&user.id, seen_after)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    /// Signs one device of the user out.
+    pub async fn revoke(&self, cmd: RevokeSessionCommand) -> Result<(), AppError> {
+        let user = self
+            .find_in_tenant(&cmd.actor.tenant_id, &cmd.user_id)
+            .await?;
+        self.access_control
+            .authorize(
+                &cmd.actor,
+                ACTION_USERS_REVOKE_SESSIONS,
+                user_attributes(&user),
+            )
+            .await?;
+        let session_id = Uuid::parse_str(&cmd.session_id).map_err(|_| session_not_found())?;
+        let session = self
+            .session_repo
+            .find(session_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|s| s.user_id == user.id && s.revoked_at.is_none())
+            .ok_or_else(session_not_found)?;
+        self.end(session.id).await?;
+        info!(
+            actor_id = %cmd.actor.audit_id(),
+            user_id = %user.id,
+            session_id = %session.id,
+            "Session revoked"
+        );
+        Ok(())
+    }
+
+    /// Signs every device of the user out, the caller's own included.
+    /// Returns how many sessions were ended.
+    pub async fn revoke_all(&self, cmd: RevokeAllSessionsCommand) -> Result<usize, AppError> {
+        let user = self
+            .find_in_tenant(&cmd.actor.tenant_id, &cmd.user_id)
+            .await?;
+        self.access_control
+            .authorize(
+                &cmd.actor,
+                ACTION_USERS_REVOKE_SESSIONS,
+                user_attributes(&user),
+            )
+            .await?;
+        let revoked = self.end_all(&user.id).await?;
+        info!(
+            actor_id = %cmd.actor.audit_id(),
+            user_id = %user.id,
+            revoked,
+            "All sessions revoked"
+        );
+        Ok(revoked)
+    }
+
+    /// Loads the sessions other instances revoked, so their access tokens
+    /// are refused here too. Run periodically.
+    pub async fn sync_revocations(&self) -> Result<(), AppError> {
+        let token_ttl = self.token_ttl()?;
+        let revoked = self
+            .session_repo
+            .list_revoked_since(Utc::now() - token_ttl)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.revocations.extend(
+            revoked
+                .into_iter()
+                .filter_map(|s| s.revoked_at.map(|at| (s.id.to_string(), at + token_ttl))),
+        );
+        Ok(())
+    }
+
+    /// Records a login and starts its session.
+    pub(crate) async fn start(
+        &self,
+        user: &User,
+        client: ClientInfo,
+        mfa_verified: bool,
+    ) -> Result<Session, AppError> {
+        let now = Utc::now();
+        let session = Session::start(user.tenant_id, user.id, client, mfa_verified, now);
+        self.session_repo
+            .create(session.clone())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.user_repo
+            .record_login(&user.id, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(session)
+    }
+
+    /// Starts the session an admin acts as the user in. It gets no refresh
+    /// tokens and is not a login of the user, but ends like any other
+    /// session of theirs.
+    pub(crate) async fn start_impersonation(&self, user: &User) -> Result<Session, AppError> {
+        let session = Session::start(
+            user.tenant_id,
+            user.id,
+            ClientInfo::default(),
+            false,
+            Utc::now(),
+        );
+        self.session_repo
+            .create(session.clone())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(session)
+    }
+
+    /// Stores a new refresh token for the session and returns it.
+    pub(crate) async fn issue_refresh_token(
+        &self,
+        user: &User,
+        session_id: Uuid,
+    ) -> Result<String, AppError> {
+        let refresh_token = generate_opaque_token();
+        let ttl = chrono::Duration::from_std(self.settings.refresh_token_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let record = RefreshToken::issue(
+            user.tenant_id,
+            user.id,
+            Some(session_id),
+            hash_token(&refresh_token),
+            ttl,
+        );
+        self.refresh_token_repo
+            .create(record)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(refresh_token)
+    }
+
+    /// Spends a refresh token and returns it, so a successor can be issued
+    /// into its session. Presenting a token that was already spent is
+    /// treated as theft and ends the session.
+    pub(crate) async fn rotate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<RefreshToken, AppError> {
+        let stored = self
+            .refresh_token_repo
+            .find_by_hash(&hash_token(refresh_token))
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .ok_or_else(invalid_refresh_token)?;
+
+        if stored.is_spent() {
+            self.end_on_reuse(&stored).await?;
+            return Err(invalid_refresh_token());
+        }
+        let now = Utc::now();
+        if stored.is_expired(now) {
+            return Err(invalid_refresh_token());
+        }
+        let rotated = self
+            .refresh_token_repo
+            .mark_rotated(stored.id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if !rotated {
+            // Lost a race against another refresh with the same token.
+            self.end_on_reuse(&stored).await?;
+            return Err(invalid_refresh_token());
+        }
+        self.session_repo
+            .touch(stored.family_id, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(stored)
+    }
+
+    /// Ends every session of the user and returns how many there were.
+    pub(crate) async fn end_all(&self, user_id: &UserId) -> Result<usize, AppError> {
+        let now = Utc::now();
+        let session_ids = self
+            .session_repo
+            .revoke_all_for_user(user_id, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.refresh_token_repo
+            .revoke_all_for_user(user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        for session_id in &session_ids {
+            self.remember_revocation(*session_id, now)?;
+        }
+        Ok(session_ids.len())
+    }
+
+    /// Ends a session and refuses the access tokens issued for it.
+    pub(crate) async fn end(&self, session_id: Uuid) -> Result<(), AppError> {
+        let now = Utc::now();
+        self.session_repo
+            .revoke(session_id, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.refresh_token_repo
+            .revoke_family(session_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.remember_revocation(session_id, now)
+    }
+
+    async fn end_on_reuse(&self, token: &RefreshToken) -> Result<(), AppError> {
+        warn!(
+            user_id = %token.user_id,
+            session_id = %token.family_id,
+            "Refresh token reuse detected, revoking session"
+        );
+        let now = Utc::now();
+        self.session_repo
+            .revoke(token.family_id, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.refresh_token_repo
+            .revoke_family(token.family_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        self.remember_revocation(token.family_id, now)
+    }
+
+    fn remember_revocation(&self, session_id: Uuid, at: DateTime<Utc>) -> Result<(), AppError> {
+        self.revocations
+            .revoke(&session_id.to_string(), at + self.token_ttl()?);
+        Ok(())
+    }
+
+    fn token_ttl(&self) -> Result<chrono::Duration, AppError> {
+        chrono::Duration::from_std(self.token_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    fn idle_timeout(&self) -> Result<chrono::Duration, AppError> {
+        chrono::Duration::from_std(self.settings.refresh_token_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    async fn find_in_tenant(&self, tenant_id: &str, user_id: &str) -> Result<User, AppError> {
+        let tenant_id = TenantId::from_string(tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let user_id = UserId::from_string(user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        self.user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|u| u.tenant_id == tenant_id)
+            .ok_or_else(|| AppError::NotFound("User not found".into()))
+    }
+}
+
+fn session_not_found() -> AppError {
+    AppError::NotFound("Session not found".into())
+}
+
+pub(crate) fn invalid_refresh_token() -> AppError {
+    AppError::Unauthorized("Invalid refresh token".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{InMemoryUserRepository, Stores, principal};
+    use auth::Principal;
+    use domain::value_objects::Role;
+
+    /// Signs the user in and returns the session and its refresh token.
+    async fn sign_in(
+        service: &SessionApplicationService<InMemoryUserRepository>,
+        user: &User,
+    ) -> (Session, String) {
+        let session = service
+            .start(user, ClientInfo::default(), false)
+            .await
+            .unwrap();
+        let refresh_token = service.issue_refresh_token(user, session.id).await.unwrap();
+        (session, refresh_token)
+    }
+
+    fn list_query(actor: Principal, user: &User) -> ListSessionsQuery {
+        ListSessionsQuery {
+            actor,
+            user_id: user.id.as_str(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_signs_out_one_device() {
+        let stores = Stores::default();
+        let service = stores.session_service();
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let (laptop, laptop_token) = sign_in(&service, &alice).await;
+        let (phone, _) = sign_in(&service, &alice).await;
+
+        let listed = service
+            .list(list_query(principal(&alice), &alice))
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+
+        service
+            .revoke(RevokeSessionCommand {
+                actor: principal(&alice),
+                user_id: alice.id.as_str(),
+                session_id: laptop.id.to_string(),
+            })
+            .await
+            .unwrap();
+        let listed = service
+            .list(list_query(principal(&alice), &alice))
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![phone.clone()]);
+        assert!(stores.revocations.is_revoked(&laptop.id.to_string()));
+        assert!(!stores.revocations.is_revoked(&phone.id.to_string()));
+        assert!(matches!(
+            service.rotate_refresh_token(&laptop_token).await,
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_log_out_everywhere() {
+        let stores = Stores::default();
+        let service = stores.session_service();
+        let admin = stores.seed_user_with_role("admin", Role::Admin);
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let (_, first_token) = sign_in(&service, &alice).await;
+        let (_, second_token) = sign_in(&service, &alice).await;
+        sign_in(&service, &admin).await;
+
+        let revoked = service
+            .revoke_all(RevokeAllSessionsCommand {
+                actor: principal(&admin),
+                user_id: alice.id.as_str(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(revoked, 2);
+        assert_eq!(stores.revocations.len(), 2);
+        for token in [first_token, second_token] {
+            assert!(service.rotate_refresh_token(&token).await.is_err());
+        }
+        let admin_sessions = service
+            .list(list_query(principal(&admin), &admin))
+            .await
+            .unwrap();
+        assert_eq!(admin_sessions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_of_others_are_guarded_by_policy() {
+        let stores = Stores::default();
+        let service = stores.session_service();
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let bob = stores.seed_user_with_role("bob", Role::User);
+        let (session, _) = sign_in(&service, &alice).await;
+
+        let listed = service.list(list_query(principal(&bob), &alice)).await;
+        assert!(matches!(listed, Err(AppError::Forbidden(_))));
+        let revoked = service
+            .revoke(RevokeSessionCommand {
+                actor: principal(&bob),
+                user_id: alice.id.as_str(),
+                session_id: session.id.to_string(),
+            })
+            .await;
+        assert!(matches!(revoked, Err(AppError::Forbidden(_))));
+
+        // A session id only counts for the user it belongs to.
+        let mismatched = service
+            .revoke(RevokeSessionCommand {
+                actor: principal(&bob),
+                user_id: bob.id.as_str(),
+                session_id: session.id.to_string(),
+            })
+            .await;
+        assert!(matches!(mismatched, Err(AppError::NotFound(_))));
+        assert!(stores.sessions.sessions()[0].revoked_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_revocations_of_other_instances_are_picked_up() {
+        let stores = Stores::default();
+        let service = stores.session_service();
+        let alice = stores.seed_user_with_role("alice", Role::User);
+        let (session, _) = sign_in(&service, &alice).await;
+        stores
+            .sessions
+            .revoke(session.id, Utc::now())
+            .await
+            .unwrap();
+        assert!(!stores.revocations.is_revoked(&session.id.to_string()));
+
+        service.sync_revocations().await.unwrap();
+        assert!(stores.revocations.is_revoked(&session.id.to_string()));
+    }
+}
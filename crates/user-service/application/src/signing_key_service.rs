@@ -0,0 +1,336 @@
+use async_trait::async_trait;
+use auth::{
+    AuthError, JwtAlgorithm, JwtKey, JwtService, KeyRefresher, MasterKey, generate_signing_key,
+};
+use base::web::error::AppError;
+use chrono::{DateTime, Utc};
+use domain::repository::SigningKeyRepository;
+use domain::{SigningKey, SigningKeyStatus};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Keys are reloaded for a token naming an unknown key only once they are
+/// this old, so made-up key ids cannot make us hammer the database.
+const MIN_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Tunables for signing key rotation.
+#[derive(Debug, Clone)]
+pub struct SigningKeySettings {
+    /// How long a key signs tokens before the next key takes over.
+    pub rotation_interval: Duration,
+    /// How long a retired key keeps verifying tokens. It must outlast the
+    /// longest-lived token the key signed.
+    pub retired_key_ttl: Duration,
+}
+
+impl Default for SigningKeySettings {
+    fn default() -> Self {
+        Self {
+            rotation_interval: Duration::from_secs(30 * 24 * 60 * 60),
+            retired_key_ttl: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// Keeps the token signing keys in the key store and loads them into the
+/// token service.
+pub struct SigningKeyApplicationService {
+    key_repo: Arc<dyn SigningKeyRepository>,
+    master_key: MasterKey,
+    jwt: Arc<JwtService>,
+    settings: SigningKeySettings,
+    loaded_at: Mutex<Option<Instant>>,
+}
+
+impl SigningKeyApplicationService {
+    pub fn new(
+        key_repo: Arc<dyn SigningKeyRepository>,
+        master_key: MasterKey,
+        jwt: Arc<JwtService>,
+        settings: SigningKeySettings,
+    ) -> Self {
+        Self {
+            key_repo,
+            master_key,
+            jwt,
+            settings,
+            loaded_at: Mutex::new(None),
+        }
+    }
+
+    /// Brings the key store up to date and loads its keys: creates the first
+    /// keys, rotates once the active key is due and deletes keys retired for
+    /// long enough. Run at startup and then periodically, which also picks up
+    /// rotations made by other instances.
+    pub async fn maintain(&self) -> Result<(), AppError> {
+        let now = Utc::now();
+        let rotation_interval = chrono::Duration::from_std(self.settings.rotation_interval)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let keys = self.list().await?;
+        if keys.is_empty() {
+            let active = self.generate(SigningKeyStatus::Active, now)?;
+            let next = self.generate(SigningKeyStatus::Next, now)?;
+            let kid = active.kid.clone();
+            let created = self
+                .key_repo
+                .initialize(active, next)
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            if created {
+                info!(kid = %kid, "Signing keys created");
+            }
+        } else if let Some(active) = keys
+            .iter()
+            .find(|k| k.is_due_for_rotation(rotation_interval, now))
+        {
+            self.rotate_from(&active.kid, now).await?;
+        }
+
+        let retired_key_ttl = chrono::Duration::from_std(self.settings.retired_key_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let deleted = self
+            .key_repo
+            .delete_retired_before(now - retired_key_ttl)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if deleted > 0 {
+            info!(count = deleted, "Retired signing keys deleted");
+        }
+        self.reload().await
+    }
+
+    /// Rotates the keys right away, such as when a key may have leaked.
+    /// Returns the keys after the rotation.
+    pub async fn rotate(&self) -> Result<Vec<SigningKey>, AppError> {
+        let keys = self.list().await?;
+        let active = active_key(&keys)?;
+        self.rotate_from(&active.kid, Utc::now()).await?;
+        self.reload().await?;
+        self.list().await
+    }
+
+    pub async fn list(&self) -> Result<Vec<SigningKey>, AppError> {
+        self.key_repo
+            .list()
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))
+    }
+
+    async fn rotate_from(&self, active_kid: &str, now: DateTime<Utc>) -> Result<(), AppError> {
+        let next = self.generate(SigningKeyStatus::Next, now)?;
+        let rotated = self
+            .key_repo
+            .rotate(active_kid, next, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        // Otherwise another instance rotated first, which is just as good.
+        if rotated {
+            info!(retired = %active_kid, "Signing keys rotated");
+        }
+        Ok(())
+    }
+
+    /// Loads every stored key into the token service, signing with the
+    /// active one.
+    async fn reload(&self) -> Result<(), AppError> {
+        let keys = self.list().await?;
+        let active_kid = active_key(&keys)?.kid.clone();
+        let jwt_keys = keys
+            .iter()
+            .map(|key| self.open(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.jwt.set_keys(jwt_keys, Some(&active_kid))?;
+        *self.loaded_at.lock().unwrap() = Some(Instant::now());
+        Ok(())
+    }
+
+    fn open(&self, key: &SigningKey) -> Result<JwtKey, AppError> {
+        let algorithm: JwtAlgorithm = key.algorithm.parse()?;
+        let private_key = self.master_key.open(&key.sealed_private_key, &key.kid)?;
+        let jwt_key = JwtKey::new(algorithm, Some(&private_key), key.public_key_pem.as_deref())?;
+        if jwt_key.kid() != key.kid {
+            return Err(AppError::InternalServerError(format!(
+                "Signing key {} does not match its key id",
+                key.kid
+            )));
+        }
+        Ok(jwt_key)
+    }
+
+    fn generate(
+        &self,
+        status: SigningKeyStatus,
+        now: DateTime<Utc>,
+    ) -> Result<SigningKey, AppError> {
+        let algorithm = self.jwt.algorithm();
+        let generated = generate_signing_key(algorithm)?;
+        let kid = generated.key.kid().to_string();
+        let sealed_private_key = self.master_key.seal(&generated.private_key, &kid)?;
+        Ok(SigningKey::new(
+            kid,
+            algorithm.to_string(),
+            status,
+            sealed_private_key,
+            generated.public_key_pem,
+            now,
+        ))
+    }
+}
+
+#[async_trait]
+impl KeyRefresher for SigningKeyApplicationService {
+    async fn refresh_keys(&self) -> Result<(), AuthError> {
+        let fresh = self
+            .loaded_at
+            .lock()
+            .unwrap()
+            .is_some_and(|at| at.elapsed() < MIN_RELOAD_INTERVAL);
+        if fresh {
+            return Ok(());
+        }
+        self.reload()
+            .await
+            .map_err(|e| AuthError::KeysUnavailable(e.to_string()))
+    }
+}
+
+fn active_key(keys: &[SigningKey]) -> Result<&SigningKey, AppError> {
+    keys.iter()
+        .find(|k| k.status == SigningKeyStatus::Active)
+        .ok_or_else(|| AppError::InternalServerError("There is no active signing key".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::InMemorySigningKeyRepository;
+    use auth::JwtConfig;
+
+    const MASTER_KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+    fn jwt_without_keys() -> Arc<JwtService> {
+        Arc::new(JwtService::without_keys(JwtConfig {
+            algorithm: JwtAlgorithm::EdDSA,
+            secret: None,
+            private_key_pem: None,
+            public_key_pem: None,
+            issuer: "user-service".to_string(),
+            audience: "ddd-rust-application".to_string(),
+            access_token_ttl: Duration::from_secs(900),
+        }))
+    }
+
+    fn service(
+        keys: Arc<InMemorySigningKeyRepository>,
+        settings: SigningKeySettings,
+    ) -> SigningKeyApplicationService {
+        SigningKeyApplicationService::new(
+            keys,
+            MasterKey::from_base64(MASTER_KEY).unwrap(),
+            jwt_without_keys(),
+            settings,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_first_start_creates_and_publishes_keys() {
+        let keys = Arc::new(InMemorySigningKeyRepository::default());
+        let service = service(keys.clone(), SigningKeySettings::default());
+        service.maintain().await.unwrap();
+
+        let stored = keys.keys();
+        assert_eq!(stored.len(), 2);
+        let active = active_key(&stored).unwrap();
+        assert!(stored.iter().any(|k| k.status == SigningKeyStatus::Next));
+        assert!(stored.iter().all(|k| k.algorithm == "EdDSA"));
+        assert_eq!(service.jwt.jwks().keys.len(), 2);
+        assert_eq!(service.jwt.signing_key_id(), Some(active.kid.clone()));
+
+        // Private keys never reach the store in the clear.
+        assert!(
+            stored
+                .iter()
+                .all(|k| !String::from_utf8_lossy(&k.sealed_private_key).contains("PRIVATE KEY"))
+        );
+        service.maintain().await.unwrap();
+        assert_eq!(keys.keys(), stored);
+    }
+
+    #[tokio::test]
+    async fn test_rotation_keeps_earlier_tokens_valid() {
+        let keys = Arc::new(InMemorySigningKeyRepository::default());
+        let service = service(keys.clone(), SigningKeySettings::default());
+        service.maintain().await.unwrap();
+        let before = service
+            .jwt
+            .issue_access_token("user-1", "tenant-1", "user")
+            .unwrap();
+        let next_kid = keys
+            .keys()
+            .into_iter()
+            .find(|k| k.status == SigningKeyStatus::Next)
+            .unwrap()
+            .kid;
+
+        let rotated = service.rotate().await.unwrap();
+        assert_eq!(rotated.len(), 3);
+        assert_eq!(active_key(&rotated).unwrap().kid, next_kid);
+        assert_eq!(service.jwt.signing_key_id(), Some(next_kid));
+        assert!(service.jwt.verify_access_token(&before.token).is_ok());
+        assert_eq!(service.jwt.jwks().keys.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_due_keys_rotate_and_retired_keys_expire() {
+        let keys = Arc::new(InMemorySigningKeyRepository::default());
+        let service = service(
+            keys.clone(),
+            SigningKeySettings {
+                rotation_interval: Duration::ZERO,
+                retired_key_ttl: Duration::ZERO,
+            },
+        );
+        service.maintain().await.unwrap();
+        let first = service
+            .jwt
+            .issue_access_token("user-1", "tenant-1", "user")
+            .unwrap();
+
+        service.maintain().await.unwrap();
+        assert!(service.jwt.verify_access_token(&first.token).is_ok());
+        service.maintain().await.unwrap();
+        let stored = keys.keys();
+        assert_eq!(stored.len(), 3);
+        assert_eq!(
+            stored
+                .iter()
+                .filter(|k| k.status == SigningKeyStatus::Retired)
+                .count(),
+            1
+        );
+        assert!(matches!(
+            service.jwt.verify_access_token(&first.token),
+            Err(AuthError::UnknownKey(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_keys_sealed_under_another_master_key_are_refused() {
+        let keys = Arc::new(InMemorySigningKeyRepository::default());
+        service(keys.clone(), SigningKeySettings::default())
+            .maintain()
+            .await
+            .unwrap();
+        let other = SigningKeyApplicationService::new(
+            keys,
+            MasterKey::from_base64("ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=").unwrap(),
+            jwt_without_keys(),
+            SigningKeySettings::default(),
+        );
+        assert!(matches!(
+            other.maintain().await,
+            Err(AppError::InternalServerError(_))
+        ));
+    }
+}
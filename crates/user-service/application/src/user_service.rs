@@ -1,35 +1,643 @@
-use crate::commands::AddUserCommand;
+use crate::access_control::{
+    ACTION_USERS_READ, ACTION_USERS_UNLOCK, AccessControl, user_attributes,
+};
+use crate::commands::{
+    AddUserCommand, ProvisionFederatedUserCommand, ResendVerificationEmailCommand,
+    UnlockUserCommand, VerifyEmailCommand,
+};
+use crate::password_screen::PasswordScreen;
+use crate::queries::GetUserByIdQuery;
+use auth::{generate_opaque_token, hash_token};
+use base::model::value_objects::UpdatedBy;
 use base::web::error::AppError;
-use domain::repository::UserRepositories;
+use chrono::Utc;
+use domain::repository::{UserRepositories, UserTokenRepository};
+use domain::services::{EmailMessage, Mailer};
 use domain::{
-    User,
-    value_objects::{EmailAddress, Password, UserId, Username},
+    TokenPurpose, User, UserToken,
+    value_objects::{EmailAddress, TenantId, UserId, Username},
 };
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Longest prefix kept from a suggested username, leaving room for a suffix.
+const USERNAME_BASE_LEN: usize = 14;
+/// Suffixed usernames tried before provisioning gives up.
+const USERNAME_ATTEMPTS: usize = 5;
+
+/// Tunables for the user self-service flows.
+#[derive(Debug, Clone)]
+pub struct UserSettings {
+    pub email_verification_ttl: Duration,
+    /// Minimum time between two verification emails for the same user.
+    pub verification_resend_interval: Duration,
+    /// Page that redeems a verification token passed as `?token=`.
+    pub email_verification_url: String,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            email_verification_ttl: Duration::from_secs(24 * 60 * 60),
+            verification_resend_interval: Duration::from_secs(60),
+            email_verification_url: "http://localhost:3000/verify-email".to_string(),
+        }
+    }
+}
 
 pub struct UserApplicationService<R: UserRepositories> {
     user_repo: Arc<R>,
+    user_token_repo: Arc<dyn UserTokenRepository>,
+    mailer: Arc<dyn Mailer>,
+    access_control: Arc<AccessControl>,
+    password_screen: Arc<PasswordScreen>,
+    settings: UserSettings,
 }
 
 impl<R: UserRepositories> UserApplicationService<R> {
-    pub fn new(user_repo: Arc<R>) -> Self {
-        Self { user_repo }
+    pub fn new(
+        user_repo: Arc<R>,
+        user_token_repo: Arc<dyn UserTokenRepository>,
+        mailer: Arc<dyn Mailer>,
+        access_control: Arc<AccessControl>,
+        password_screen: Arc<PasswordScreen>,
+        settings: UserSettings,
+    ) -> Self {
+        Self {
+            user_repo,
+            user_token_repo,
+            mailer,
+            access_control,
+            password_screen,
+            settings,
+        }
     }
     pub async fn create(&self, cmd: AddUserCommand) -> Result<UserId, AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
         let username = Username::new(&cmd.username)?;
         let username_exists = self.user_repo.find_by_id(&username).await;
         if username_exists.is_ok() {
             return Err(AppError::BadRequest("Username already exists".into()));
         }
-        let password_hash = Password::from_plain(&cmd.password)?;
+        let password_hash = self
+            .password_screen
+            .new_password(&tenant_id, &cmd.password, &cmd.username, &cmd.email)
+            .await?;
         let email = EmailAddress::new(cmd.email)?;
-        let user = User::new(username, password_hash, email);
+        let user = User::new(tenant_id, username, password_hash, email);
         let user_id = self
             .user_repo
-            .create(user)
+            .create(user.clone())
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        self.send_verification_email(&user).await?;
 
         Ok(user_id)
     }
+
+    /// Redeems a verification token and marks the user's email as verified.
+    pub async fn verify_email(&self, cmd: VerifyEmailCommand) -> Result<(), AppError> {
+        let token = self
+            .user_token_repo
+            .find_by_hash(
+                TokenPurpose::EmailVerification,
+                &hash_token(cmd.token.expose_secret()),
+            )
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|t| t.is_usable(Utc::now()))
+            .ok_or_else(invalid_verification_token)?;
+        let consumed = self
+            .user_token_repo
+            .consume(token.id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if !consumed {
+            return Err(invalid_verification_token());
+        }
+        self.user_repo
+            .mark_email_verified(&token.user_id, Utc::now())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Sends a fresh verification email. Unknown or already verified
+    /// addresses are accepted silently so accounts cannot be enumerated.
+    pub async fn resend_verification_email(
+        &self,
+        cmd: ResendVerificationEmailCommand,
+    ) -> Result<(), AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let email = EmailAddress::new(cmd.email)?;
+        let user = self
+            .user_repo
+            .find_by_email(&tenant_id, &email)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let Some(user) = user.filter(|u| !u.email_verified) else {
+            return Ok(());
+        };
+
+        let latest = self
+            .user_token_repo
+            .find_latest(&user.id, TokenPurpose::EmailVerification)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        if let Some(latest) = latest {
+            let interval = chrono::Duration::from_std(self.settings.verification_resend_interval)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            if latest.created_at + interval > Utc::now() {
+                return Err(AppError::TooManyRequests(
+                    "Please wait before requesting another verification email".into(),
+                ));
+            }
+        }
+        self.send_verification_email(&user).await
+    }
+
+    /// Looks up a user of the caller's tenant the caller may see.
+    pub async fn get_by_id(&self, query: GetUserByIdQuery) -> Result<User, AppError> {
+        let user = self
+            .find_in_tenant(&query.actor.tenant_id, &query.user_id)
+            .await?;
+        self.access_control
+            .authorize(&query.actor, ACTION_USERS_READ, user_attributes(&user))
+            .await?;
+        Ok(user)
+    }
+
+    /// Lifts a lockout and resets the failed login counter.
+    pub async fn unlock(&self, cmd: UnlockUserCommand) -> Result<(), AppError> {
+        let user = self
+            .find_in_tenant(&cmd.actor.tenant_id, &cmd.user_id)
+            .await?;
+        self.access_control
+            .authorize(&cmd.actor, ACTION_USERS_UNLOCK, user_attributes(&user))
+            .await?;
+        let updated_by = UpdatedBy::new(cmd.actor.audit_id());
+        self.user_repo
+            .clear_lockout(&user.id, Some(&updated_by))
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Creates the account of someone signing in through their tenant's
+    /// identity provider for the first time. The provider vouched for the
+    /// email, so it starts out verified and no verification email is sent.
+    /// The random password is never shown to anyone; the user can set one
+    /// through a password reset.
+    pub async fn provision_federated_user(
+        &self,
+        cmd: ProvisionFederatedUserCommand,
+    ) -> Result<User, AppError> {
+        let tenant_id = TenantId::from_string(&cmd.tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let email = EmailAddress::new(cmd.email)?;
+        let hint = match cmd.preferred_username.as_deref() {
+            Some(hint) => hint,
+            None => email.as_str().split('@').next().unwrap_or_default(),
+        };
+        let username = self.available_username(&tenant_id, hint).await?;
+        let password_hash = self
+            .password_screen
+            .pool()
+            .hash(&generate_opaque_token().into(), Vec::new())
+            .await?;
+        let mut user = User::new(tenant_id, username, password_hash, email);
+        self.user_repo
+            .create(user.clone())
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let now = Utc::now();
+        self.user_repo
+            .mark_email_verified(&user.id, now)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        user.email_verified = true;
+        user.email_verified_at = Some(now);
+        Ok(user)
+    }
+
+    /// Turns `hint` into a valid username, adding a random suffix while the
+    /// name is taken in the tenant.
+    async fn available_username(
+        &self,
+        tenant_id: &TenantId,
+        hint: &str,
+    ) -> Result<Username, AppError> {
+        let base: String = hint
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .take(USERNAME_BASE_LEN)
+            .collect();
+        let base = if base.len() < 3 {
+            format!("user_{}", base)
+        } else {
+            base
+        };
+        for attempt in 0..USERNAME_ATTEMPTS {
+            let candidate = if attempt == 0 {
+                base.clone()
+            } else {
+                format!("{}-{}", base, &generate_opaque_token()[..5])
+            };
+            let username = Username::new(&candidate)?;
+            let taken = self
+                .user_repo
+                .find_by_username(tenant_id, &username)
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            if taken.is_none() {
+                return Ok(username);
+            }
+        }
+        Err(AppError::InternalServerError(
+            "Could not find a free username".into(),
+        ))
+    }
+
+    /// Users of other tenants are reported as missing, like unknown ones.
+    async fn find_in_tenant(&self, tenant_id: &str, user_id: &str) -> Result<User, AppError> {
+        let tenant_id = TenantId::from_string(tenant_id)
+            .map_err(|_| AppError::BadRequest("Invalid tenant id".into()))?;
+        let user_id = UserId::from_string(user_id)
+            .map_err(|_| AppError::BadRequest("Invalid user id".into()))?;
+        self.user_repo
+            .find_by_user_id(&user_id)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?
+            .filter(|u| u.tenant_id == tenant_id)
+            .ok_or_else(|| AppError::NotFound("User not found".into()))
+    }
+
+    /// Issues a new verification token, replacing any outstanding one, and mails it.
+    async fn send_verification_email(&self, user: &User) -> Result<(), AppError> {
+        self.user_token_repo
+            .consume_all(&user.id, TokenPurpose::EmailVerification)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        let token = generate_opaque_token();
+        let ttl = chrono::Duration::from_std(self.settings.email_verification_ttl)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        let record = UserToken::issue(
+            user.tenant_id,
+            user.id,
+            TokenPurpose::EmailVerification,
+            hash_token(&token),
+            ttl,
+        );
+        self.user_token_repo
+            .create(record)
+            .await
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        let message = EmailMessage {
+            to: user.email_address.clone(),
+            subject: "Verify your email address".to_string(),
+            body: format!(
+                "Hi {},\n\nConfirm your email address by opening {}?token={}\n",
+                user.username.as_str(),
+                self.settings.email_verification_url,
+                token
+            ),
+        };
+        // The user can ask for another email, so a delivery failure must not
+        // undo the registration.
+        if let Err(e) = self.mailer.send(message).await {
+            warn!(user_id = %user.id, "Failed to send verification email: {}", e);
+        }
+        Ok(())
+    }
+}
+
+fn invalid_verification_token() -> AppError {
+    AppError::BadRequest("Invalid or expired verification token".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{Stores, TENANT_ID, principal, seed_user};
+    use auth::{Credential, Principal};
+    use chrono::Duration;
+    use domain::value_objects::Role;
+
+    fn add_user_command() -> AddUserCommand {
+        AddUserCommand {
+            tenant_id: TENANT_ID.to_string(),
+            username: "alice".to_string(),
+            password: "correct-horse".to_string().into(),
+            email: "alice@example.com".to_string(),
+        }
+    }
+
+    fn resend_command(email: &str) -> ResendVerificationEmailCommand {
+        ResendVerificationEmailCommand {
+            tenant_id: TENANT_ID.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registration_sends_verification_email() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let user_id = service.create(add_user_command()).await.unwrap();
+
+        let user = stores.users.get(&user_id).unwrap();
+        assert!(!user.email_verified);
+        assert_eq!(stores.mailer.sent().len(), 1);
+
+        let token = stores.mailer.last_token().unwrap();
+        service
+            .verify_email(VerifyEmailCommand {
+                token: token.into(),
+            })
+            .await
+            .unwrap();
+        let user = stores.users.get(&user_id).unwrap();
+        assert!(user.email_verified);
+        assert!(user.email_verified_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_registration_refuses_breached_password() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let result = service
+            .create(AddUserCommand {
+                password: "password123".to_string().into(),
+                ..add_user_command()
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(stores.mailer.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verification_token_is_single_use() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        service.create(add_user_command()).await.unwrap();
+        let token = stores.mailer.last_token().unwrap();
+
+        service
+            .verify_email(VerifyEmailCommand {
+                token: token.clone().into(),
+            })
+            .await
+            .unwrap();
+        let again = service
+            .verify_email(VerifyEmailCommand {
+                token: token.into(),
+            })
+            .await;
+        assert!(matches!(again, Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expired_verification_token_is_rejected() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let user = stores.seed_alice();
+        let expired = UserToken::issue(
+            user.tenant_id,
+            user.id,
+            TokenPurpose::EmailVerification,
+            hash_token("expired"),
+            Duration::seconds(-1),
+        );
+        stores.user_tokens.create(expired).await.unwrap();
+
+        let result = service
+            .verify_email(VerifyEmailCommand {
+                token: "expired".to_string().into(),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert!(!stores.users.get(&user.id).unwrap().email_verified);
+    }
+
+    #[tokio::test]
+    async fn test_resend_is_throttled_and_replaces_token() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings {
+            verification_resend_interval: std::time::Duration::ZERO,
+            ..UserSettings::default()
+        });
+        service.create(add_user_command()).await.unwrap();
+        let first = stores.mailer.last_token().unwrap();
+
+        service
+            .resend_verification_email(resend_command("alice@example.com"))
+            .await
+            .unwrap();
+        let second = stores.mailer.last_token().unwrap();
+        assert_ne!(first, second);
+        let stale = service
+            .verify_email(VerifyEmailCommand {
+                token: first.into(),
+            })
+            .await;
+        assert!(stale.is_err());
+
+        let throttled = stores
+            .user_service(UserSettings::default())
+            .resend_verification_email(resend_command("alice@example.com"))
+            .await;
+        assert!(matches!(throttled, Err(AppError::TooManyRequests(_))));
+    }
+
+    #[tokio::test]
+    async fn test_resend_for_unknown_email_is_silent() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        service
+            .resend_verification_email(resend_command("nobody@example.com"))
+            .await
+            .unwrap();
+        assert!(stores.mailer.sent().is_empty());
+    }
+
+    /// An administrator of the test tenant.
+    fn admin(stores: &Stores) -> Principal {
+        principal(&stores.seed_user_with_role("admin", Role::Admin))
+    }
+
+    #[tokio::test]
+    async fn test_unlock_clears_lockout() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let mut user = stores.seed_alice();
+        user.failed_login_attempts = 5;
+        user.locked_until = Some(Utc::now() + Duration::minutes(15));
+        stores.users.insert(user.clone());
+
+        service
+            .unlock(UnlockUserCommand {
+                actor: admin(&stores),
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+
+        let stored = stores.users.get(&user.id).unwrap();
+        assert_eq!(stored.failed_login_attempts, 0);
+        assert!(stored.locked_until.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unlock_is_attributed_to_the_impersonating_admin() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let admin = admin(&stores);
+        let manager = stores.seed_user_with_role("manager", Role::Manager);
+        let alice = stores.seed_alice();
+
+        service
+            .unlock(UnlockUserCommand {
+                actor: Principal {
+                    credential: Credential::Impersonation {
+                        actor_id: admin.user_id.clone(),
+                        session_id: "session-1".to_string(),
+                    },
+                    ..principal(&manager)
+                },
+                user_id: alice.id.as_str(),
+            })
+            .await
+            .unwrap();
+
+        let audit = stores.users.get(&alice.id).unwrap().audit.unwrap();
+        assert_eq!(
+            audit.updated_by.map(|by| by.as_str().to_string()),
+            Some(admin.user_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlock_unknown_user() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let result = service
+            .unlock(UnlockUserCommand {
+                actor: admin(&stores),
+                user_id: UserId::new().as_str(),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_unlock_is_subject_to_access_policies() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let mut alice = stores.seed_alice();
+        alice.locked_until = Some(Utc::now() + Duration::minutes(15));
+        stores.users.insert(alice.clone());
+        let bob = seed_user(&stores.users, "bob", "bob@example.com", "correct-horse");
+
+        let result = service
+            .unlock(UnlockUserCommand {
+                actor: principal(&bob),
+                user_id: alice.id.as_str(),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+        assert!(stores.users.get(&alice.id).unwrap().locked_until.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_users_of_other_tenants_are_not_found() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let user = stores.seed_alice();
+        let found = service
+            .get_by_id(GetUserByIdQuery {
+                actor: principal(&user),
+                user_id: user.id.as_str(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(found.id, user.id);
+
+        let other_tenant = service
+            .get_by_id(GetUserByIdQuery {
+                actor: Principal {
+                    tenant_id: "0190b4a2-7c1e-7000-8000-000000000000".to_string(),
+                    ..admin(&stores)
+                },
+                user_id: user.id.as_str(),
+            })
+            .await;
+        assert!(matches!(other_tenant, Err(AppError::NotFound(_))));
+    }
+
+    fn provision_command(
+        email: &str,
+        preferred_username: Option<&str>,
+    ) -> ProvisionFederatedUserCommand {
+        ProvisionFederatedUserCommand {
+            tenant_id: TENANT_ID.to_string(),
+            email: email.to_string(),
+            preferred_username: preferred_username.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provisioned_user_is_verified_without_email() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        let user = service
+            .provision_federated_user(provision_command("bob.smith@corp.example.com", None))
+            .await
+            .unwrap();
+        assert_eq!(user.username.as_str(), "bob_smith");
+        let stored = stores.users.get(&user.id).unwrap();
+        assert!(stored.email_verified);
+        assert!(stores.mailer.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_provisioning_avoids_taken_usernames() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        stores.seed_alice();
+        let user = service
+            .provision_federated_user(provision_command("alice@corp.example.com", Some("alice")))
+            .await
+            .unwrap();
+        assert!(user.username.as_str().starts_with("alice-"));
+        assert_eq!(user.username.as_str().len(), "alice-".len() + 5);
+    }
+
+    #[tokio::test]
+    async fn test_usernames_of_other_tenants_do_not_count_as_taken() {
+        let stores = Stores::default();
+        let service = stores.user_service(UserSettings::default());
+        stores.seed_alice();
+        let other_tenant = "0190b4a2-7c1e-7000-8000-000000000000";
+        let user = service
+            .provision_federated_user(ProvisionFederatedUserCommand {
+                tenant_id: other_tenant.to_string(),
+                ..provision_command("alice@corp.example.com", Some("alice"))
+            })
+            .await
+            .unwrap();
+        assert_eq!(user.username.as_str(), "alice");
+        assert_eq!(user.tenant_id.as_str(), other_tenant);
+    }
 }
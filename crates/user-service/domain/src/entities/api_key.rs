@@ -0,0 +1,126 @@
+use crate::value_objects::{TenantId, UserId};
+use base::web::error::AppError;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub const SCOPE_USERS_READ: &str = "users:read";
+pub const SCOPE_USERS_WRITE: &str = "users:write";
+/// Scopes an API key can be granted.
+pub const API_KEY_SCOPES: [&str; 2] = [SCOPE_USERS_READ, SCOPE_USERS_WRITE];
+
+/// A credential that lets a machine client call the API on behalf of a
+/// tenant, within the scopes it was minted with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    pub name: String,
+    /// Public part of the key, used to look it up and shown in listings.
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    /// Admin who minted the key.
+    pub created_by: UserId,
+    /// `None` for keys that never expire.
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    pub fn new(
+        tenant_id: TenantId,
+        name: String,
+        prefix: String,
+        key_hash: String,
+        mut scopes: Vec<String>,
+        created_by: UserId,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, AppError> {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 255 {
+            return Err(AppError::BadRequest(
+                "API key name must be 1 to 255 characters".into(),
+            ));
+        }
+        if scopes.is_empty() {
+            return Err(AppError::BadRequest(
+                "An API key needs at least one scope".into(),
+            ));
+        }
+        if let Some(unknown) = scopes
+            .iter()
+            .find(|s| !API_KEY_SCOPES.contains(&s.as_str()))
+        {
+            return Err(AppError::BadRequest(format!("Unknown scope {}", unknown)));
+        }
+        scopes.sort();
+        scopes.dedup();
+        let now = Utc::now();
+        if expires_at.is_some_and(|at| at <= now) {
+            return Err(AppError::BadRequest(
+                "API key expiry must be in the future".into(),
+            ));
+        }
+        Ok(Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            name,
+            prefix,
+            key_hash,
+            scopes,
+            created_by,
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: now,
+        })
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|at| at > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn api_key(scopes: &[&str], expires_at: Option<DateTime<Utc>>) -> Result<ApiKey, AppError> {
+        ApiKey::new(
+            TenantId::from(Uuid::now_v7()),
+            "CRM sync".to_string(),
+            "0123456789ab".to_string(),
+            "hash".to_string(),
+            scopes.iter().map(|s| s.to_string()).collect(),
+            UserId::new(),
+            expires_at,
+        )
+    }
+
+    #[test]
+    fn test_scopes_are_validated() {
+        assert!(matches!(api_key(&[], None), Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            api_key(&["users:delete"], None),
+            Err(AppError::BadRequest(_))
+        ));
+        let key = api_key(&["users:write", "users:read", "users:read"], None).unwrap();
+        assert_eq!(key.scopes, vec!["users:read", "users:write"]);
+    }
+
+    #[test]
+    fn test_expiry_and_revocation() {
+        assert!(matches!(
+            api_key(&["users:read"], Some(Utc::now() - Duration::minutes(1))),
+            Err(AppError::BadRequest(_))
+        ));
+        let mut key = api_key(&["users:read"], Some(Utc::now() + Duration::days(30))).unwrap();
+        assert!(key.is_active(Utc::now()));
+        assert!(!key.is_active(Utc::now() + Duration::days(31)));
+        key.revoked_at = Some(Utc::now());
+        assert!(!key.is_active(Utc::now()));
+    }
+}
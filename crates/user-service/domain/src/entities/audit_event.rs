@@ -0,0 +1,41 @@
+use crate::value_objects::{TenantId, UserId};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub const AUDIT_IMPERSONATION_STARTED: &str = "impersonation.started";
+pub const AUDIT_IMPERSONATION_ENDED: &str = "impersonation.ended";
+
+/// A security-relevant action kept for the audit trail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    /// What happened, e.g. [`AUDIT_IMPERSONATION_STARTED`].
+    pub action: String,
+    /// Who did it.
+    pub actor_id: UserId,
+    /// The user it was done to, if any.
+    pub subject_id: Option<UserId>,
+    pub details: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl AuditEvent {
+    pub fn new(
+        tenant_id: TenantId,
+        action: &str,
+        actor_id: UserId,
+        subject_id: Option<UserId>,
+        details: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            action: action.to_string(),
+            actor_id,
+            subject_id,
+            details,
+            occurred_at: Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::value_objects::{TenantId, TotpSecret, UserId};
+use chrono::{DateTime, Utc};
+use rand_core::{OsRng, RngCore};
+use uuid::Uuid;
+
+/// Number of recovery codes handed out when MFA is enabled.
+pub const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const RECOVERY_CODE_LEN: usize = 10;
+
+/// A user's TOTP authenticator. It only counts as a second factor once the
+/// user has proven the app works by confirming a code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotpEnrollment {
+    pub user_id: UserId,
+    pub tenant_id: TenantId,
+    pub secret: TotpSecret,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// Last time step a code was accepted for; older or equal steps are replays.
+    pub last_used_step: Option<u64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TotpEnrollment {
+    pub fn new(tenant_id: TenantId, user_id: UserId) -> Self {
+        Self {
+            user_id,
+            tenant_id,
+            secret: TotpSecret::generate(),
+            confirmed_at: None,
+            last_used_step: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+/// A single-use code that stands in for a TOTP code when the authenticator is
+/// lost. Only its hash is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryCode {
+    pub id: Uuid,
+    pub user_id: UserId,
+    pub code_hash: String,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RecoveryCode {
+    pub fn new(user_id: UserId, code_hash: String) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            user_id,
+            code_hash,
+            used_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// A random code formatted for humans, e.g. `k3xq7-ab2mz`.
+    pub fn generate_plain() -> String {
+        let mut bytes = [0u8; RECOVERY_CODE_LEN];
+        OsRng.fill_bytes(&mut bytes);
+        let chars: String = bytes
+            .iter()
+            .map(|b| RECOVERY_CODE_ALPHABET[(*b as usize) % RECOVERY_CODE_ALPHABET.len()] as char)
+            .collect();
+        let (head, tail) = chars.split_at(RECOVERY_CODE_LEN / 2);
+        format!("{}-{}", head, tail)
+    }
+
+    /// Canonical form that gets hashed, so users may type codes with any case,
+    /// spacing or dashes.
+    pub fn normalize(input: &str) -> String {
+        input
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_enrollment_is_unconfirmed() {
+        let enrollment = TotpEnrollment::new(TenantId::from(Uuid::now_v7()), UserId::new());
+        assert!(!enrollment.is_confirmed());
+        assert!(enrollment.last_used_step.is_none());
+    }
+
+    #[test]
+    fn test_generated_recovery_codes_are_formatted_and_distinct() {
+        let code = RecoveryCode::generate_plain();
+        assert_eq!(code.len(), RECOVERY_CODE_LEN + 1);
+        assert_eq!(code.as_bytes()[RECOVERY_CODE_LEN / 2], b'-');
+        assert_ne!(code, RecoveryCode::generate_plain());
+    }
+
+    #[test]
+    fn test_normalize_recovery_code() {
+        assert_eq!(RecoveryCode::normalize(" K3XQ7-AB2MZ "), "k3xq7ab2mz");
+        assert_eq!(
+            RecoveryCode::normalize("k3xq7ab2mz"),
+            RecoveryCode::normalize("k3xq7-ab2mz")
+        );
+    }
+}
@@ -1 +1,11 @@
+pub mod api_key;
+pub mod audit_event;
+pub mod federation;
+pub mod mfa;
+pub mod oauth;
+pub mod refresh_token;
+pub mod session;
+pub mod signing_key;
 pub mod user;
+pub mod user_token;
+pub mod webauthn;
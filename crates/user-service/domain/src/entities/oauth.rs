@@ -0,0 +1,218 @@
+use crate::value_objects::{TenantId, UserId};
+use base::web::error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+/// Most redirect URIs a client may register.
+pub const MAX_REDIRECT_URIS: usize = 10;
+
+/// An application that signs its users in through our OpenID Connect
+/// provider. Clients belong to one tenant and can only sign in its users.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthClient {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    /// Public identifier the client sends to `/authorize` and `/token`.
+    pub client_id: String,
+    /// Hash of the client secret. `None` for public clients such as SPAs
+    /// and mobile apps, which rely on PKCE alone.
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    /// Exact URIs codes may be sent to.
+    pub redirect_uris: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OAuthClient {
+    pub fn new(
+        tenant_id: TenantId,
+        client_id: String,
+        client_secret_hash: Option<String>,
+        name: String,
+        redirect_uris: Vec<String>,
+    ) -> Result<Self, AppError> {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.chars().count() > 255 {
+            return Err(AppError::BadRequest(
+                "Client name must be 1 to 255 characters".into(),
+            ));
+        }
+        if redirect_uris.is_empty() || redirect_uris.len() > MAX_REDIRECT_URIS {
+            return Err(AppError::BadRequest(format!(
+                "A client needs 1 to {} redirect URIs",
+                MAX_REDIRECT_URIS
+            )));
+        }
+        for uri in &redirect_uris {
+            validate_redirect_uri(uri)?;
+        }
+        Ok(Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            client_id,
+            client_secret_hash,
+            name,
+            redirect_uris,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Whether the client authenticates with a secret at the token endpoint.
+    pub fn is_confidential(&self) -> bool {
+        self.client_secret_hash.is_some()
+    }
+
+    /// Redirect URIs are compared verbatim; prefix or pattern matching is
+    /// what open redirect attacks feed on.
+    pub fn allows_redirect_uri(&self, redirect_uri: &str) -> bool {
+        self.redirect_uris.iter().any(|uri| uri == redirect_uri)
+    }
+}
+
+/// Accepts absolute `https` URIs, and `http` ones only on the loopback
+/// interface for local development. Fragments are not allowed.
+pub fn validate_redirect_uri(uri: &str) -> Result<(), AppError> {
+    let invalid = || AppError::BadRequest(format!("Invalid redirect URI: {}", uri));
+    if uri.len() > 2000 || uri.contains('#') || uri.chars().any(char::is_whitespace) {
+        return Err(invalid());
+    }
+    let (secure, rest) = if let Some(rest) = uri.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = uri.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return Err(invalid());
+    };
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    if authority.is_empty() || authority.contains('@') {
+        return Err(invalid());
+    }
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    if host.is_empty() || (!secure && !matches!(host, "localhost" | "127.0.0.1" | "::1")) {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// A single-use authorization code, bound to the client, redirect URI and
+/// PKCE challenge of the request that produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorizationCode {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    pub user_id: UserId,
+    pub client_id: String,
+    pub code_hash: String,
+    pub redirect_uri: String,
+    /// Space separated granted scopes.
+    pub scope: String,
+    pub nonce: Option<String>,
+    /// S256 PKCE challenge.
+    pub code_challenge: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    /// Doubles as the `auth_time` of the ID token.
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuthorizationCode {
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue(
+        client: &OAuthClient,
+        user_id: UserId,
+        code_hash: String,
+        redirect_uri: String,
+        scope: String,
+        nonce: Option<String>,
+        code_challenge: String,
+        ttl: Duration,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::now_v7(),
+            tenant_id: client.tenant_id,
+            user_id,
+            client_id: client.client_id.clone(),
+            code_hash,
+            redirect_uri,
+            scope,
+            nonce,
+            code_challenge,
+            expires_at: now + ttl,
+            consumed_at: None,
+            created_at: now,
+        }
+    }
+
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.consumed_at.is_none() && self.expires_at > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(redirect_uris: &[&str]) -> Result<OAuthClient, AppError> {
+        OAuthClient::new(
+            "550e8400-e29b-41d4-a716-446655440000".parse().unwrap(),
+            "client-1".to_string(),
+            None,
+            "Dashboard".to_string(),
+            redirect_uris.iter().map(|u| u.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_redirect_uri_rules() {
+        assert!(validate_redirect_uri("https://app.example.com/callback").is_ok());
+        assert!(validate_redirect_uri("https://app.example.com:8443/cb?x=1").is_ok());
+        assert!(validate_redirect_uri("http://localhost:8080/callback").is_ok());
+        assert!(validate_redirect_uri("http://127.0.0.1/cb").is_ok());
+        assert!(validate_redirect_uri("http://[::1]:9000/cb").is_ok());
+
+        assert!(validate_redirect_uri("http://app.example.com/callback").is_err());
+        assert!(validate_redirect_uri("https://app.example.com/cb#frag").is_err());
+        assert!(validate_redirect_uri("https://user@evil.com/cb").is_err());
+        assert!(validate_redirect_uri("javascript:alert(1)").is_err());
+        assert!(validate_redirect_uri("/relative").is_err());
+        assert!(validate_redirect_uri("https:///cb").is_err());
+    }
+
+    #[test]
+    fn test_redirect_uri_matches_exactly() {
+        let client = client(&["https://app.example.com/callback"]).unwrap();
+        assert!(client.allows_redirect_uri("https://app.example.com/callback"));
+        assert!(!client.allows_redirect_uri("https://app.example.com/callback/"));
+        assert!(!client.allows_redirect_uri("https://app.example.com/callback?next=x"));
+        assert!(!client.is_confidential());
+    }
+
+    #[test]
+    fn test_client_needs_redirect_uris() {
+        assert!(client(&[]).is_err());
+        assert!(client(&["http://evil.example.com"]).is_err());
+    }
+
+    #[test]
+    fn test_authorization_code_is_usable_once_before_expiry() {
+        let client = client(&["https://app.example.com/callback"]).unwrap();
+        let mut code = AuthorizationCode::issue(
+            &client,
+            UserId::new(),
+            "hash".to_string(),
+            "https://app.example.com/callback".to_string(),
+            "openid".to_string(),
+            None,
+            "challenge".to_string(),
+            Duration::minutes(1),
+        );
+        assert!(code.is_usable(Utc::now()));
+        assert!(!code.is_usable(Utc::now() + Duration::minutes(2)));
+        code.consumed_at = Some(Utc::now());
+        assert!(!code.is_usable(Utc::now()));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::value_objects::{TenantId, UserId};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+/// A persisted refresh token. Only the hash of the opaque token is stored.
+///
+/// Tokens issued from the same login share a `family_id`; each refresh rotates
+/// the presented token and issues a new one into the same family.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    pub user_id: UserId,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RefreshToken {
+    /// Creates a token in `family_id`, or in a new family when `None`.
+    pub fn issue(
+        tenant_id: TenantId,
+        user_id: UserId,
+        family_id: Option<Uuid>,
+        token_hash: String,
+        ttl: Duration,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            user_id,
+            family_id: family_id.unwrap_or_else(Uuid::now_v7),
+            token_hash,
+            expires_at: now + ttl,
+            rotated_at: None,
+            revoked_at: None,
+            created_at: now,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    /// A token that was already rotated or revoked must never be presented again.
+    pub fn is_spent(&self) -> bool {
+        self.rotated_at.is_some() || self.revoked_at.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(family_id: Option<Uuid>) -> RefreshToken {
+        RefreshToken::issue(
+            "550e8400-e29b-41d4-a716-446655440000".parse().unwrap(),
+            UserId::new(),
+            family_id,
+            "hash".to_string(),
+            Duration::days(30),
+        )
+    }
+
+    #[test]
+    fn test_issue_starts_new_family() {
+        let first = token(None);
+        let second = token(None);
+        assert_ne!(first.family_id, second.family_id);
+    }
+
+    #[test]
+    fn test_issue_keeps_family() {
+        let first = token(None);
+        let rotated = token(Some(first.family_id));
+        assert_eq!(first.family_id, rotated.family_id);
+        assert_ne!(first.id, rotated.id);
+    }
+
+    #[test]
+    fn test_expiry_and_spent() {
+        let mut refresh_token = token(None);
+        assert!(!refresh_token.is_expired(Utc::now()));
+        assert!(refresh_token.is_expired(Utc::now() + Duration::days(31)));
+        assert!(!refresh_token.is_spent());
+        refresh_token.rotated_at = Some(Utc::now());
+        assert!(refresh_token.is_spent());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::value_objects::{TenantId, UserId};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+/// Longest user agent kept for a session; clients can send anything.
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// What a login request tells about the device it came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn new(user_agent: Option<String>, ip_address: Option<String>) -> Self {
+        let user_agent = user_agent
+            .map(|ua| {
+                ua.trim()
+                    .chars()
+                    .take(MAX_USER_AGENT_LEN)
+                    .collect::<String>()
+            })
+            .filter(|ua| !ua.is_empty());
+        Self {
+            user_agent,
+            ip_address,
+        }
+    }
+}
+
+/// A signed-in device. Each login starts one.
+///
+/// The session id is the `family_id` of the refresh tokens issued for it,
+/// and access tokens carry it, so revoking a session ends both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    pub user_id: UserId,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Whether the login proved a second factor or used a passkey.
+    pub mfa_verified: bool,
+    pub created_at: DateTime<Utc>,
+    /// When the session last signed in or refreshed its tokens.
+    pub last_seen_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Session {
+    pub fn start(
+        tenant_id: TenantId,
+        user_id: UserId,
+        client: ClientInfo,
+        mfa_verified: bool,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            user_id,
+            user_agent: client.user_agent,
+            ip_address: client.ip_address,
+            mfa_verified,
+            created_at: now,
+            last_seen_at: now,
+            revoked_at: None,
+        }
+    }
+
+    /// A session ends when it is revoked or, since its refresh token
+    /// expires, once it went unused for `idle_timeout`.
+    pub fn is_active(&self, idle_timeout: Duration, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.last_seen_at + idle_timeout > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_ends_when_revoked_or_idle() {
+        let now = Utc::now();
+        let mut session = Session::start(
+            "550e8400-e29b-41d4-a716-446655440000".parse().unwrap(),
+            UserId::new(),
+            ClientInfo::default(),
+            false,
+            now,
+        );
+        assert!(session.is_active(Duration::days(30), now + Duration::days(29)));
+        assert!(!session.is_active(Duration::days(30), now + Duration::days(30)));
+        session.revoked_at = Some(now);
+        assert!(!session.is_active(Duration::days(30), now));
+    }
+
+    #[test]
+    fn test_client_info_is_trimmed() {
+        let client = ClientInfo::new(Some(" ".repeat(3)), Some("203.0.113.7".into()));
+        assert_eq!(client.user_agent, None);
+        let client = ClientInfo::new(Some("x".repeat(1000)), None);
+        assert_eq!(client.user_agent.unwrap().len(), MAX_USER_AGENT_LEN);
+    }
+}
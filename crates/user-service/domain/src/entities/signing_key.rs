@@ -0,0 +1,135 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// Where a signing key is in its lifecycle. Keys are published from the
+/// moment they are created until they are deleted, so verifiers know a key
+/// before it signs anything and after it stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SigningKeyStatus {
+    /// Takes over at the next rotation.
+    Next,
+    /// Signs new tokens.
+    Active,
+    /// Only verifies tokens issued before it was retired.
+    Retired,
+}
+
+impl SigningKeyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigningKeyStatus::Next => "next",
+            SigningKeyStatus::Active => "active",
+            SigningKeyStatus::Retired => "retired",
+        }
+    }
+}
+
+impl FromStr for SigningKeyStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "next" => Ok(SigningKeyStatus::Next),
+            "active" => Ok(SigningKeyStatus::Active),
+            "retired" => Ok(SigningKeyStatus::Retired),
+            other => Err(anyhow!("Unknown signing key status: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for SigningKeyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A key the service signs its tokens with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningKey {
+    /// Key id named in the header of the tokens the key signs.
+    pub kid: String,
+    pub algorithm: String,
+    pub status: SigningKeyStatus,
+    /// Private key, or the secret of a symmetric key, sealed under the
+    /// master key. Only ever stored sealed.
+    pub sealed_private_key: Vec<u8>,
+    /// `None` for symmetric keys.
+    pub public_key_pem: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+impl SigningKey {
+    pub fn new(
+        kid: String,
+        algorithm: String,
+        status: SigningKeyStatus,
+        sealed_private_key: Vec<u8>,
+        public_key_pem: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            kid,
+            algorithm,
+            status,
+            sealed_private_key,
+            public_key_pem,
+            created_at: now,
+            activated_at: (status == SigningKeyStatus::Active).then_some(now),
+            retired_at: None,
+        }
+    }
+
+    /// Whether the key has signed for `rotation_interval` and the next key
+    /// should take over.
+    pub fn is_due_for_rotation(
+        &self,
+        rotation_interval: chrono::Duration,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.status == SigningKeyStatus::Active
+            && self
+                .activated_at
+                .is_some_and(|at| at + rotation_interval <= now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [
+            SigningKeyStatus::Next,
+            SigningKeyStatus::Active,
+            SigningKeyStatus::Retired,
+        ] {
+            assert_eq!(status.as_str().parse::<SigningKeyStatus>().unwrap(), status);
+        }
+        assert!("revoked".parse::<SigningKeyStatus>().is_err());
+    }
+
+    #[test]
+    fn test_only_the_active_key_comes_due() {
+        let now = Utc::now();
+        let key = |status| {
+            SigningKey::new(
+                "kid".to_string(),
+                "EdDSA".to_string(),
+                status,
+                vec![1, 2, 3],
+                None,
+                now - Duration::days(31),
+            )
+        };
+        let active = key(SigningKeyStatus::Active);
+        assert!(active.is_due_for_rotation(Duration::days(30), now));
+        assert!(!active.is_due_for_rotation(Duration::days(32), now));
+        assert!(!key(SigningKeyStatus::Next).is_due_for_rotation(Duration::days(30), now));
+    }
+}
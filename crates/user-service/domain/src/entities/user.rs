@@ -1,25 +1,100 @@
-use crate::value_objects::{EmailAddress, Password, UserId, Username};
+use crate::value_objects::{EmailAddress, Password, Role, TenantId, UserId, Username};
 use base::model::{Audit, value_objects::CreatedAt};
+use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct User {
     pub id: UserId,
+    pub tenant_id: TenantId,
     pub username: Username,
     pub password_hash: Password,
     pub email_address: EmailAddress,
+    pub email_verified: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub role: Role,
+    pub failed_login_attempts: u32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    pub last_login_at: Option<DateTime<Utc>>,
     pub audit: Option<Audit>,
 }
 impl User {
-    pub fn new(username: Username, password_hash: Password, email_address: EmailAddress) -> Self {
+    pub fn new(
+        tenant_id: TenantId,
+        username: Username,
+        password_hash: Password,
+        email_address: EmailAddress,
+    ) -> Self {
         let created_at = CreatedAt::now();
         let id = UserId::new();
         let audit = Audit::with_created_at(created_at);
         Self {
             id,
+            tenant_id,
             username,
             password_hash,
             email_address,
+            email_verified: false,
+            email_verified_at: None,
+            role: Role::default(),
+            failed_login_attempts: 0,
+            locked_until: None,
+            password_changed_at: None,
+            last_login_at: None,
             audit: Some(audit),
         }
     }
+
+    /// Whether a lockout is still in effect at `now`.
+    pub fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|until| until > now)
+    }
+
+    /// When the current password was set: its last change, or else the
+    /// creation of the account.
+    pub fn password_set_at(&self) -> Option<DateTime<Utc>> {
+        self.password_changed_at
+            .or_else(|| self.audit.as_ref().map(|audit| audit.created_at.value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn user() -> User {
+        User::new(
+            "550e8400-e29b-41d4-a716-446655440000".parse().unwrap(),
+            Username::new("alice").unwrap(),
+            Password::from_hash("hash".to_string()),
+            EmailAddress::new("alice@example.com".to_string()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_new_user_is_not_locked() {
+        let user = user();
+        assert_eq!(user.failed_login_attempts, 0);
+        assert!(!user.is_locked(Utc::now()));
+    }
+
+    #[test]
+    fn test_lock_expires() {
+        let mut user = user();
+        let now = Utc::now();
+        user.locked_until = Some(now + Duration::minutes(5));
+        assert!(user.is_locked(now));
+        assert!(!user.is_locked(now + Duration::minutes(6)));
+    }
+
+    #[test]
+    fn test_password_set_at_falls_back_to_creation() {
+        let mut user = user();
+        let created_at = user.audit.as_ref().unwrap().created_at.value();
+        assert_eq!(user.password_set_at(), Some(created_at));
+        let changed_at = created_at + Duration::days(1);
+        user.password_changed_at = Some(changed_at);
+        assert_eq!(user.password_set_at(), Some(changed_at));
+    }
 }
@@ -0,0 +1,118 @@
+use crate::value_objects::{TenantId, UserId};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// What a one-time user token may be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenPurpose {
+    EmailVerification,
+    PasswordReset,
+}
+
+impl TokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenPurpose::EmailVerification => "email_verification",
+            TokenPurpose::PasswordReset => "password_reset",
+        }
+    }
+}
+
+impl FromStr for TokenPurpose {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "email_verification" => Ok(TokenPurpose::EmailVerification),
+            "password_reset" => Ok(TokenPurpose::PasswordReset),
+            other => Err(anyhow!("Unknown token purpose: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for TokenPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A single-use, expiring token mailed to a user. Only its hash is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserToken {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    pub user_id: UserId,
+    pub purpose: TokenPurpose,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserToken {
+    pub fn issue(
+        tenant_id: TenantId,
+        user_id: UserId,
+        purpose: TokenPurpose,
+        token_hash: String,
+        ttl: Duration,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            user_id,
+            purpose,
+            token_hash,
+            expires_at: now + ttl,
+            consumed_at: None,
+            created_at: now,
+        }
+    }
+
+    /// Whether the token can still be redeemed at `now`.
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.consumed_at.is_none() && self.expires_at > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(ttl: Duration) -> UserToken {
+        UserToken::issue(
+            "550e8400-e29b-41d4-a716-446655440000".parse().unwrap(),
+            UserId::new(),
+            TokenPurpose::EmailVerification,
+            "hash".to_string(),
+            ttl,
+        )
+    }
+
+    #[test]
+    fn test_purpose_roundtrip() {
+        let purpose: TokenPurpose = "email_verification".parse().unwrap();
+        assert_eq!(purpose, TokenPurpose::EmailVerification);
+        assert_eq!(purpose.to_string(), "email_verification");
+        let purpose: TokenPurpose = "password_reset".parse().unwrap();
+        assert_eq!(purpose, TokenPurpose::PasswordReset);
+        assert!("unknown".parse::<TokenPurpose>().is_err());
+    }
+
+    #[test]
+    fn test_fresh_token_is_usable() {
+        assert!(token(Duration::hours(1)).is_usable(Utc::now()));
+    }
+
+    #[test]
+    fn test_expired_or_consumed_token_is_not_usable() {
+        assert!(!token(Duration::seconds(-1)).is_usable(Utc::now()));
+        let mut consumed = token(Duration::hours(1));
+        consumed.consumed_at = Some(Utc::now());
+        assert!(!consumed.is_usable(Utc::now()));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::value_objects::{TenantId, UserId};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// A passkey registered by a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebAuthnCredential {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    pub user_id: UserId,
+    /// Id the authenticator knows the credential by.
+    pub credential_id: Vec<u8>,
+    /// COSE encoded public key.
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+    /// Label chosen by the user, e.g. "Work laptop".
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl WebAuthnCredential {
+    pub fn new(
+        tenant_id: TenantId,
+        user_id: UserId,
+        credential_id: Vec<u8>,
+        public_key: Vec<u8>,
+        sign_count: u32,
+        name: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            user_id,
+            credential_id,
+            public_key,
+            sign_count,
+            name,
+            created_at: Utc::now(),
+            last_used_at: None,
+        }
+    }
+}
+
+/// Which WebAuthn ceremony a challenge was issued for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WebAuthnCeremony {
+    Registration,
+    Authentication,
+}
+
+impl WebAuthnCeremony {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebAuthnCeremony::Registration => "registration",
+            WebAuthnCeremony::Authentication => "authentication",
+        }
+    }
+}
+
+impl FromStr for WebAuthnCeremony {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "registration" => Ok(WebAuthnCeremony::Registration),
+            "authentication" => Ok(WebAuthnCeremony::Authentication),
+            other => Err(anyhow!("Unknown WebAuthn ceremony: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for WebAuthnCeremony {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A challenge handed to the browser at the start of a ceremony. It can be
+/// answered once, before it expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebAuthnChallenge {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    /// The user registering a passkey, or the user who named themselves
+    /// before a passkey login. `None` for usernameless logins.
+    pub user_id: Option<UserId>,
+    pub ceremony: WebAuthnCeremony,
+    /// base64url challenge bytes.
+    pub challenge: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebAuthnChallenge {
+    pub fn issue(
+        tenant_id: TenantId,
+        user_id: Option<UserId>,
+        ceremony: WebAuthnCeremony,
+        challenge: String,
+        ttl: Duration,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::now_v7(),
+            tenant_id,
+            user_id,
+            ceremony,
+            challenge,
+            expires_at: now + ttl,
+            consumed_at: None,
+            created_at: now,
+        }
+    }
+
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.consumed_at.is_none() && self.expires_at > now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ceremony_roundtrip() {
+        for ceremony in [
+            WebAuthnCeremony::Registration,
+            WebAuthnCeremony::Authentication,
+        ] {
+            assert_eq!(
+                ceremony.as_str().parse::<WebAuthnCeremony>().unwrap(),
+                ceremony
+            );
+        }
+        assert!("login".parse::<WebAuthnCeremony>().is_err());
+    }
+
+    #[test]
+    fn test_challenge_usability() {
+        let tenant_id = TenantId::from(Uuid::now_v7());
+        let mut challenge = WebAuthnChallenge::issue(
+            tenant_id,
+            None,
+            WebAuthnCeremony::Authentication,
+            "abc".to_string(),
+            Duration::minutes(5),
+        );
+        assert!(challenge.is_usable(Utc::now()));
+        assert!(!challenge.is_usable(Utc::now() + Duration::minutes(6)));
+        challenge.consumed_at = Some(Utc::now());
+        assert!(!challenge.is_usable(Utc::now()));
+    }
+}
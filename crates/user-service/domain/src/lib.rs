@@ -1,7 +1,19 @@
 pub mod entities;
+pub mod policies;
 pub mod repository;
+pub mod services;
 pub mod value_objects;
 
+pub use entities::api_key::ApiKey;
+pub use entities::audit_event::AuditEvent;
+pub use entities::federation::{FederatedLoginState, UserIdentity};
+pub use entities::mfa::{RECOVERY_CODE_COUNT, RecoveryCode, TotpEnrollment};
+pub use entities::oauth::{AuthorizationCode, OAuthClient};
+pub use entities::refresh_token::RefreshToken;
+pub use entities::session::{ClientInfo, Session};
+pub use entities::signing_key::{SigningKey, SigningKeyStatus};
 pub use entities::user::User;
+pub use entities::user_token::{TokenPurpose, UserToken};
+pub use entities::webauthn::{WebAuthnCeremony, WebAuthnChallenge, WebAuthnCredential};
 pub use repository::*;
 pub use value_objects::username;
@@ -0,0 +1,85 @@
+use chrono::Duration;
+
+/// Decides when repeated failed logins lock an account, and for how long.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    /// Consecutive failures that trigger the first lock.
+    pub max_failed_attempts: u32,
+    /// Length of the first lock.
+    pub lock_duration: Duration,
+    /// Doubles the lock for every failure past `max_failed_attempts`.
+    pub exponential_backoff: bool,
+    /// Upper bound for a single lock when backing off exponentially.
+    pub max_lock_duration: Duration,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_failed_attempts: 5,
+            lock_duration: Duration::minutes(15),
+            exponential_backoff: false,
+            max_lock_duration: Duration::hours(24),
+        }
+    }
+}
+
+impl LockoutPolicy {
+    /// Returns how long to lock the account after `failed_attempts`
+    /// consecutive failures, or `None` when it should stay unlocked.
+    pub fn lock_duration_for(&self, failed_attempts: u32) -> Option<Duration> {
+        if self.max_failed_attempts == 0 || failed_attempts < self.max_failed_attempts {
+            return None;
+        }
+        if !self.exponential_backoff {
+            return Some(self.lock_duration);
+        }
+        let exponent = (failed_attempts - self.max_failed_attempts).min(30);
+        let duration = self
+            .lock_duration
+            .checked_mul(1 << exponent)
+            .unwrap_or(self.max_lock_duration);
+        Some(duration.min(self.max_lock_duration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_lock_below_threshold() {
+        let policy = LockoutPolicy::default();
+        assert_eq!(policy.lock_duration_for(0), None);
+        assert_eq!(policy.lock_duration_for(4), None);
+    }
+
+    #[test]
+    fn test_fixed_lock_duration() {
+        let policy = LockoutPolicy::default();
+        assert_eq!(policy.lock_duration_for(5), Some(Duration::minutes(15)));
+        assert_eq!(policy.lock_duration_for(9), Some(Duration::minutes(15)));
+    }
+
+    #[test]
+    fn test_exponential_lock_duration_is_capped() {
+        let policy = LockoutPolicy {
+            exponential_backoff: true,
+            max_lock_duration: Duration::hours(1),
+            ..LockoutPolicy::default()
+        };
+        assert_eq!(policy.lock_duration_for(5), Some(Duration::minutes(15)));
+        assert_eq!(policy.lock_duration_for(6), Some(Duration::minutes(30)));
+        assert_eq!(policy.lock_duration_for(7), Some(Duration::hours(1)));
+        assert_eq!(policy.lock_duration_for(100), Some(Duration::hours(1)));
+    }
+
+    #[test]
+    fn test_zero_attempts_disables_lockout() {
+        let policy = LockoutPolicy {
+            max_failed_attempts: 0,
+            ..LockoutPolicy::default()
+        };
+        assert_eq!(policy.lock_duration_for(50), None);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod lockout_policy;
+pub mod rate_limit;
+
+pub use lockout_policy::LockoutPolicy;
+pub use rate_limit::{RateLimit, RateLimitDecision};
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// At most `requests` requests per `period`, enforced with the generic cell
+/// rate algorithm: requests are spaced `period / requests` apart on
+/// average, and a full `requests` may arrive at once after a quiet period.
+///
+/// The whole state of a key is its theoretical arrival time (TAT), the
+/// moment the key will have drained back to an empty bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+/// Outcome of checking one request against a [`RateLimit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// TAT to store for the key. Unchanged when the request is refused.
+    pub tat: DateTime<Utc>,
+    pub limit: u32,
+    /// Requests the key may still make right away.
+    pub remaining: u32,
+    /// Time until the key is back to its full allowance.
+    pub reset_after: Duration,
+    /// Time until the next request would be allowed; zero when allowed.
+    pub retry_after: Duration,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, period: Duration) -> Self {
+        Self { requests, period }
+    }
+
+    /// Average spacing between requests.
+    fn emission_interval(&self) -> Duration {
+        self.period / self.requests.max(1) as i32
+    }
+
+    /// Checks a request arriving at `now` for a key whose stored TAT is
+    /// `tat`, `None` for a key never seen.
+    pub fn check(&self, tat: Option<DateTime<Utc>>, now: DateTime<Utc>) -> RateLimitDecision {
+        let interval = self.emission_interval();
+        let tat = tat.map_or(now, |tat| tat.max(now));
+        let new_tat = tat + interval;
+        let allow_at = new_tat - self.period;
+        if now < allow_at {
+            return RateLimitDecision {
+                allowed: false,
+                tat,
+                limit: self.requests,
+                remaining: 0,
+                reset_after: tat - now,
+                retry_after: allow_at - now,
+            };
+        }
+        RateLimitDecision {
+            allowed: true,
+            tat: new_tat,
+            limit: self.requests,
+            remaining: self.remaining(new_tat, now, interval),
+            reset_after: new_tat - now,
+            retry_after: Duration::zero(),
+        }
+    }
+
+    fn remaining(&self, tat: DateTime<Utc>, now: DateTime<Utc>, interval: Duration) -> u32 {
+        let headroom = self.period - (tat - now);
+        match interval.num_milliseconds() {
+            0 => self.requests,
+            ms => (headroom.num_milliseconds() / ms).clamp(0, self.requests as i64) as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `count` requests at `now` and returns the last decision and TAT.
+    fn burst(
+        limit: &RateLimit,
+        mut tat: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+        count: u32,
+    ) -> (RateLimitDecision, Option<DateTime<Utc>>) {
+        let mut decision = limit.check(tat, now);
+        for _ in 1..count {
+            if decision.allowed {
+                tat = Some(decision.tat);
+            }
+            decision = limit.check(tat, now);
+        }
+        if decision.allowed {
+            tat = Some(decision.tat);
+        }
+        (decision, tat)
+    }
+
+    #[test]
+    fn test_allows_a_full_burst_then_refuses() {
+        let limit = RateLimit::new(5, Duration::minutes(1));
+        let now = Utc::now();
+        let first = limit.check(None, now);
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 4);
+        assert_eq!(first.reset_after, Duration::seconds(12));
+
+        let (fifth, tat) = burst(&limit, None, now, 5);
+        assert!(fifth.allowed);
+        assert_eq!(fifth.remaining, 0);
+
+        let sixth = limit.check(tat, now);
+        assert!(!sixth.allowed);
+        assert_eq!(sixth.tat, tat.unwrap());
+        assert_eq!(sixth.retry_after, Duration::seconds(12));
+        assert_eq!(sixth.reset_after, Duration::minutes(1));
+    }
+
+    #[test]
+    fn test_allowance_comes_back_over_time() {
+        let limit = RateLimit::new(5, Duration::minutes(1));
+        let now = Utc::now();
+        let (_, tat) = burst(&limit, None, now, 5);
+
+        let later = limit.check(tat, now + Duration::seconds(12));
+        assert!(later.allowed);
+        assert_eq!(later.remaining, 0);
+
+        let idle = limit.check(tat, now + Duration::minutes(5));
+        assert!(idle.allowed);
+        assert_eq!(idle.remaining, 4);
+    }
+}
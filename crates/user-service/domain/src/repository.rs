@@ -1,13 +1,254 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use base::model::value_objects::UpdatedBy;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
 
 use crate::{
     User,
-    value_objects::{UserId, Username},
+    entities::api_key::ApiKey,
+    entities::audit_event::AuditEvent,
+    entities::federation::{FederatedLoginState, UserIdentity},
+    entities::mfa::{RecoveryCode, TotpEnrollment},
+    entities::oauth::{AuthorizationCode, OAuthClient},
+    entities::refresh_token::RefreshToken,
+    entities::session::Session,
+    entities::signing_key::SigningKey,
+    entities::user_token::{TokenPurpose, UserToken},
+    entities::webauthn::{WebAuthnChallenge, WebAuthnCredential},
+    policies::{RateLimit, RateLimitDecision},
+    value_objects::{EmailAddress, Password, TenantId, TenantSettings, UserId, Username},
 };
 
 #[async_trait]
 pub trait UserRepositories: Send + Sync {
     async fn create(&self, user: User) -> Result<UserId>;
     async fn find_by_id(&self, user_id: &Username) -> Result<User>;
+    async fn find_by_user_id(&self, user_id: &UserId) -> Result<Option<User>>;
+    async fn find_by_email(
+        &self,
+        tenant_id: &TenantId,
+        email: &EmailAddress,
+    ) -> Result<Option<User>>;
+    async fn find_by_username(
+        &self,
+        tenant_id: &TenantId,
+        username: &Username,
+    ) -> Result<Option<User>>;
+    /// Atomically increments the failed login counter and returns the new value.
+    async fn record_failed_login(&self, user_id: &UserId) -> Result<u32>;
+    async fn lock_until(&self, user_id: &UserId, until: DateTime<Utc>) -> Result<()>;
+    /// Resets the failed login counter and lifts any lock. `updated_by` is
+    /// the user who asked for it, `None` when the service did on its own, in
+    /// which case the last recorded modifier is kept.
+    async fn clear_lockout(&self, user_id: &UserId, updated_by: Option<&UpdatedBy>) -> Result<()>;
+    async fn mark_email_verified(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<()>;
+    /// Stores a new password hash and records when it changed.
+    async fn update_password(
+        &self,
+        user_id: &UserId,
+        password_hash: &Password,
+        changed_at: DateTime<Utc>,
+    ) -> Result<()>;
+    /// Stores a new hash of the same password, such as one computed with
+    /// stronger parameters. The password does not count as changed.
+    async fn rehash_password(&self, user_id: &UserId, password_hash: &Password) -> Result<()>;
+    async fn record_login(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<()>;
+}
+
+#[async_trait]
+pub trait RefreshTokenRepository: Send + Sync {
+    async fn create(&self, token: RefreshToken) -> Result<()>;
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>>;
+    /// Marks the token as rotated. Returns `false` when it was already rotated
+    /// or revoked, so two concurrent refreshes cannot both succeed.
+    async fn mark_rotated(&self, id: Uuid) -> Result<bool>;
+    /// Revokes every token in the family and returns how many were revoked.
+    async fn revoke_family(&self, family_id: Uuid) -> Result<u64>;
+    /// Revokes every outstanding token of the user.
+    async fn revoke_all_for_user(&self, user_id: &UserId) -> Result<u64>;
+}
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn create(&self, session: Session) -> Result<()>;
+    async fn find(&self, id: Uuid) -> Result<Option<Session>>;
+    /// Sessions of the user that are not revoked and were seen after
+    /// `seen_after`, most recently seen first.
+    async fn list_active(
+        &self,
+        user_id: &UserId,
+        seen_after: DateTime<Utc>,
+    ) -> Result<Vec<Session>>;
+    async fn touch(&self, id: Uuid, at: DateTime<Utc>) -> Result<()>;
+    /// Revokes the session. Returns `false` when it was already revoked.
+    async fn revoke(&self, id: Uuid, at: DateTime<Utc>) -> Result<bool>;
+    /// Revokes every session of the user and returns the ids revoked.
+    async fn revoke_all_for_user(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<Vec<Uuid>>;
+    /// Sessions revoked after `cutoff`, so every instance can refuse their
+    /// access tokens.
+    async fn list_revoked_since(&self, cutoff: DateTime<Utc>) -> Result<Vec<Session>>;
+}
+
+#[async_trait]
+pub trait PasswordHistoryRepository: Send + Sync {
+    /// Hashes of passwords the user replaced, most recent first.
+    async fn list_recent(&self, user_id: &UserId, limit: usize) -> Result<Vec<Password>>;
+    /// Remembers a password the user just replaced, keeping only the `keep`
+    /// most recent entries.
+    async fn add(
+        &self,
+        tenant_id: &TenantId,
+        user_id: &UserId,
+        password_hash: &Password,
+        replaced_at: DateTime<Utc>,
+        keep: usize,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+pub trait UserTokenRepository: Send + Sync {
+    async fn create(&self, token: UserToken) -> Result<()>;
+    async fn find_by_hash(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &str,
+    ) -> Result<Option<UserToken>>;
+    /// Marks the token as used. Returns `false` when it was already consumed,
+    /// so a token can only ever be redeemed once.
+    async fn consume(&self, id: Uuid) -> Result<bool>;
+    /// The most recently issued token for the user and purpose, if any.
+    async fn find_latest(
+        &self,
+        user_id: &UserId,
+        purpose: TokenPurpose,
+    ) -> Result<Option<UserToken>>;
+    /// Consumes every outstanding token for the user and purpose.
+    async fn consume_all(&self, user_id: &UserId, purpose: TokenPurpose) -> Result<u64>;
+}
+
+#[async_trait]
+pub trait TenantSettingsRepository: Send + Sync {
+    /// Settings of an existing tenant, or `None` when the tenant does not exist.
+    async fn find_settings(&self, tenant_id: &TenantId) -> Result<Option<TenantSettings>>;
+    /// Display name of an existing tenant.
+    async fn find_name(&self, tenant_id: &TenantId) -> Result<Option<String>>;
+}
+
+#[async_trait]
+pub trait MfaRepository: Send + Sync {
+    async fn find_totp(&self, user_id: &UserId) -> Result<Option<TotpEnrollment>>;
+    /// Stores a new, unconfirmed enrollment, replacing any earlier one.
+    async fn save_totp(&self, enrollment: TotpEnrollment) -> Result<()>;
+    async fn confirm_totp(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<()>;
+    /// Records that a code for `step` was accepted. Returns `false` when the
+    /// step, or a later one, was already used, so a code cannot be replayed.
+    async fn record_totp_step(&self, user_id: &UserId, step: u64) -> Result<bool>;
+    /// Replaces every recovery code of the user.
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &UserId,
+        codes: Vec<RecoveryCode>,
+    ) -> Result<()>;
+    /// Marks the matching unused code as used. Returns `false` when there is none.
+    async fn use_recovery_code(&self, user_id: &UserId, code_hash: &str) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait WebAuthnRepository: Send + Sync {
+    async fn create_credential(&self, credential: WebAuthnCredential) -> Result<()>;
+    async fn find_credential(
+        &self,
+        tenant_id: &TenantId,
+        credential_id: &[u8],
+    ) -> Result<Option<WebAuthnCredential>>;
+    async fn list_credentials(&self, user_id: &UserId) -> Result<Vec<WebAuthnCredential>>;
+    /// Stores the counter reported by the latest assertion.
+    async fn record_credential_use(
+        &self,
+        id: Uuid,
+        sign_count: u32,
+        used_at: DateTime<Utc>,
+    ) -> Result<()>;
+    /// Deletes a credential of the user. Returns `false` when there is none.
+    async fn delete_credential(&self, user_id: &UserId, id: Uuid) -> Result<bool>;
+    async fn create_challenge(&self, challenge: WebAuthnChallenge) -> Result<()>;
+    /// Consumes a usable challenge and returns it, so each one can only be
+    /// answered once.
+    async fn take_challenge(&self, id: Uuid) -> Result<Option<WebAuthnChallenge>>;
+}
+
+#[async_trait]
+pub trait OAuthRepository: Send + Sync {
+    async fn create_client(&self, client: OAuthClient) -> Result<()>;
+    async fn find_client(&self, client_id: &str) -> Result<Option<OAuthClient>>;
+    async fn create_authorization_code(&self, code: AuthorizationCode) -> Result<()>;
+    /// Marks the code consumed and returns it, or `None` if it is unknown,
+    /// expired or was already redeemed.
+    async fn take_authorization_code(&self, code_hash: &str) -> Result<Option<AuthorizationCode>>;
+}
+
+#[async_trait]
+pub trait FederationRepository: Send + Sync {
+    async fn create_login_state(&self, state: FederatedLoginState) -> Result<()>;
+    /// Marks the login state consumed and returns it, or `None` if it is
+    /// unknown, expired or was already used.
+    async fn take_login_state(&self, state_hash: &str) -> Result<Option<FederatedLoginState>>;
+    async fn find_identity(
+        &self,
+        tenant_id: &TenantId,
+        provider_id: &str,
+        subject: &str,
+    ) -> Result<Option<UserIdentity>>;
+    async fn create_identity(&self, identity: UserIdentity) -> Result<()>;
+    async fn record_identity_login(&self, id: Uuid, at: DateTime<Utc>) -> Result<()>;
+}
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create(&self, api_key: ApiKey) -> Result<()>;
+    async fn find_by_prefix(&self, prefix: &str) -> Result<Option<ApiKey>>;
+    /// Keys of the tenant, revoked and expired ones included.
+    async fn list(&self, tenant_id: &TenantId) -> Result<Vec<ApiKey>>;
+    /// Revokes a key of the tenant. Returns `false` when there is no such
+    /// active key.
+    async fn revoke(&self, tenant_id: &TenantId, id: Uuid, at: DateTime<Utc>) -> Result<bool>;
+    async fn record_use(&self, id: Uuid, at: DateTime<Utc>) -> Result<()>;
+}
+
+#[async_trait]
+pub trait AuditEventRepository: Send + Sync {
+    async fn record(&self, event: AuditEvent) -> Result<()>;
+}
+
+#[async_trait]
+pub trait SigningKeyRepository: Send + Sync {
+    /// Every stored key, whatever its status.
+    async fn list(&self) -> Result<Vec<SigningKey>>;
+    /// Stores the first active and next keys. Returns `false`, storing
+    /// nothing, when there already are keys.
+    async fn initialize(&self, active: SigningKey, next: SigningKey) -> Result<bool>;
+    /// Retires the active key `active_kid`, activates the next key and
+    /// stores `next` in its place. Returns `false`, changing nothing, when
+    /// `active_kid` is no longer active because another instance rotated
+    /// first.
+    async fn rotate(&self, active_kid: &str, next: SigningKey, at: DateTime<Utc>) -> Result<bool>;
+    /// Deletes the keys retired before `cutoff` and returns how many.
+    async fn delete_retired_before(&self, cutoff: DateTime<Utc>) -> Result<u64>;
+}
+
+#[async_trait]
+pub trait RateLimitRepository: Send + Sync {
+    /// Checks a request of `key` against `limit` and, when it is allowed,
+    /// stores the key's new state. Concurrent checks of a key never both
+    /// spend the same allowance.
+    async fn check(
+        &self,
+        key: &str,
+        limit: &RateLimit,
+        now: DateTime<Utc>,
+    ) -> Result<RateLimitDecision>;
+    /// Forgets the keys that drained back to their full allowance before
+    /// `now` and returns how many.
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64>;
 }
@@ -0,0 +1,5 @@
+/// Passwords known from public data breaches, such as the Have I Been
+/// Pwned corpus. Lookups stay local so no password ever leaves the service.
+pub trait BreachedPasswords: Send + Sync {
+    fn contains(&self, password: &str) -> bool;
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::value_objects::IdentityProviderSettings;
+
+/// Talks to a tenant's external OpenID Connect provider over HTTP.
+#[async_trait]
+pub trait IdentityProviderClient: Send + Sync {
+    /// Redeems an authorization code at the provider's token endpoint and
+    /// returns the ID token it hands back.
+    async fn exchange_code(
+        &self,
+        provider: &IdentityProviderSettings,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+    ) -> Result<String>;
+
+    /// Returns the provider's JSON Web Key Set. Implementations may cache
+    /// it; `refresh` asks for a fresh copy, e.g. after a key rotation.
+    async fn fetch_jwks(&self, jwks_uri: &str, refresh: bool) -> Result<serde_json::Value>;
+}
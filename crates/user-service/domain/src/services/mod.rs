@@ -0,0 +1,7 @@
+pub mod breached_passwords;
+pub mod identity_provider;
+pub mod mailer;
+
+pub use breached_passwords::BreachedPasswords;
+pub use identity_provider::IdentityProviderClient;
+pub use mailer::{EmailMessage, Mailer};
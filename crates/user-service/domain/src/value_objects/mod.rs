@@ -1,9 +1,25 @@
 pub mod email;
 pub mod password;
+pub mod password_hashing;
+pub mod password_policy;
+pub mod password_schemes;
+pub mod pepper;
+pub mod role;
+pub mod tenant_id;
+pub mod tenant_settings;
+pub mod totp_secret;
 pub mod user_id;
 pub mod username;
 
 pub use email::EmailAddress;
 pub use password::Password;
+pub use password_hashing::PasswordHashing;
+pub use password_policy::{PasswordPolicy, PasswordViolation};
+pub use password_schemes::{PasswordScheme, PasswordSchemes};
+pub use pepper::Peppers;
+pub use role::Role;
+pub use tenant_id::TenantId;
+pub use tenant_settings::{IdentityProviderSettings, TenantSettings};
+pub use totp_secret::TotpSecret;
 pub use user_id::UserId;
 pub use username::Username;
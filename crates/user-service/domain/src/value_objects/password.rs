@@ -1,43 +1,56 @@
-use argon2::password_hash::{PasswordHash, PasswordVerifier, SaltString};
-use argon2::{Argon2, PasswordHasher};
+use base::secret::SecretString;
 use base::web::error::AppError;
-use rand_core::OsRng;
 
+use crate::value_objects::{PasswordHashing, PasswordPolicy, PasswordViolation};
+
+/// A password hash. Redacted in `Debug` output, since it is enough to
+/// mount an offline guessing attack.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Password(String);
+pub struct Password(SecretString);
 
 impl Password {
-    /// Create from plain password (hash it)
-    pub fn from_plain(password: &str) -> Result<Self, AppError> {
-        if password.len() < 8 {
-            return Err(AppError::BadRequest("Password too short".into()));
-        }
+    /// Hashes a plain password that meets `policy`, or lists every rule
+    /// it breaks.
+    pub fn from_plain(
+        password: &str,
+        policy: &PasswordPolicy,
+        hashing: &PasswordHashing,
+    ) -> Result<Self, AppError> {
+        Self::from_checked(password, policy.check(password, &[]), hashing)
+    }
 
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        let hash = argon2
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|e| AppError::BadRequest(e.to_string()))?
-            .to_string();
+    /// Hashes a plain password the caller has already checked, refusing it
+    /// when the checks found `violations`.
+    pub fn from_checked(
+        password: &str,
+        violations: Vec<PasswordViolation>,
+        hashing: &PasswordHashing,
+    ) -> Result<Self, AppError> {
+        if !violations.is_empty() {
+            return Err(PasswordViolation::into_error(violations));
+        }
 
-        Ok(Self(hash))
+        Ok(Self(hashing.hash(password)?.into()))
     }
 
     pub fn from_hash(hash: String) -> Self {
-        Self(hash)
+        Self(hash.into())
     }
 
     pub fn as_str(&self) -> &str {
-        &self.0
+        self.0.expose_secret()
     }
 
-    pub fn verify(&self, password: &str) -> bool {
-        let parsed_hash = PasswordHash::new(&self.0).unwrap();
-        let argon2 = Argon2::default();
+    /// Whether `password` matches the hash, which may be Argon2 or one of
+    /// the legacy formats `hashing` knows. Fails when the hash is malformed.
+    pub fn verify(&self, password: &str, hashing: &PasswordHashing) -> Result<bool, AppError> {
+        hashing.verify(password, self.as_str())
+    }
 
-        argon2
-            .verify_password(password.as_bytes(), &parsed_hash)
-            .is_ok()
+    /// Whether the hash should be recomputed with `hashing`, being weaker
+    /// than it, peppered differently or not Argon2 at all.
+    pub fn needs_rehash(&self, hashing: &PasswordHashing) -> bool {
+        hashing.is_outdated(self.as_str())
     }
 }
 
@@ -47,21 +60,118 @@ mod tests {
 
     #[test]
     fn test_password_too_short() {
-        let result = Password::from_plain("short");
-        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        let result = Password::from_plain(
+            "short",
+            &PasswordPolicy::default(),
+            &PasswordHashing::default(),
+        );
+        assert!(matches!(result, Err(AppError::Validation(_))));
     }
 
     #[test]
     fn test_password_to_long() {
-        let result = Password::from_plain("thisisalongpassword");
+        let result = Password::from_plain(
+            "thisisalongpassword",
+            &PasswordPolicy::default(),
+            &PasswordHashing::default(),
+        );
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_every_violation_is_reported() {
+        let policy = PasswordPolicy {
+            require_digit: true,
+            ..PasswordPolicy::default()
+        };
+        let mut violations = policy.check("short", &[]);
+        violations.push(PasswordViolation::Breached);
+        let Err(AppError::Validation(errors)) =
+            Password::from_checked("short", violations, &PasswordHashing::default())
+        else {
+            panic!("expected a validation error");
+        };
+        let reasons: Vec<_> = errors.iter().filter_map(|e| e.reason.as_deref()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "password_too_short",
+                "password_missing_digit",
+                "password_breached"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_weaker_hash_needs_rehash() {
+        let light = PasswordHashing::new(argon2::Algorithm::Argon2id, 8 * 1024, 1, 1).unwrap();
+        let password =
+            Password::from_plain("securepassword", &PasswordPolicy::default(), &light).unwrap();
+        assert!(!password.needs_rehash(&light));
+        assert!(password.needs_rehash(&PasswordHashing::default()));
+        assert!(
+            password
+                .verify("securepassword", &PasswordHashing::default())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_legacy_hash_verifies_and_needs_rehash() {
+        let password = Password::from_hash(bcrypt::hash("securepassword", 4).unwrap());
+        assert!(
+            password
+                .verify("securepassword", &PasswordHashing::default())
+                .unwrap()
+        );
+        assert!(
+            !password
+                .verify("wrongpassword", &PasswordHashing::default())
+                .unwrap()
+        );
+        assert!(password.needs_rehash(&PasswordHashing::default()));
+    }
+
+    #[test]
+    fn test_malformed_hash_is_an_error() {
+        let password = Password::from_hash("not-a-hash".into());
+        assert!(matches!(
+            password.verify("securepassword", &PasswordHashing::default()),
+            Err(AppError::InternalServerError(_))
+        ));
+    }
+
+    #[test]
+    fn test_debug_output_hides_the_hash() {
+        let password = Password::from_plain(
+            "securepassword",
+            &PasswordPolicy::default(),
+            &PasswordHashing::default(),
+        )
+        .unwrap();
+        let debug = format!("{:?}", password);
+        assert!(!debug.contains(password.as_str()));
+        assert!(!debug.contains("argon2"));
+    }
+
     #[test]
     fn test_password_verify() {
         let password = "securepassword";
-        let pwd_obj = Password::from_plain(password).unwrap();
-        assert!(pwd_obj.verify(password));
-        assert!(!pwd_obj.verify("wrongpassword"));
+        let pwd_obj = Password::from_plain(
+            password,
+            &PasswordPolicy::default(),
+            &PasswordHashing::default(),
+        )
+        .unwrap();
+        assert!(
+            pwd_obj
+                .verify(password, &PasswordHashing::default())
+                .unwrap()
+        );
+        assert!(
+            !pwd_obj
+                .verify("wrongpassword", &PasswordHashing::default())
+                .unwrap()
+        );
     }
 }
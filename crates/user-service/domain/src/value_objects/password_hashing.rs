@@ -0,0 +1,312 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, SaltString};
+use argon2::{Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version};
+use base::web::error::AppError;
+use rand_core::OsRng;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::value_objects::{PasswordScheme, PasswordSchemes, Peppers};
+
+/// Memory cost [`PasswordHashing::calibrate`] stops at, 1 GiB.
+const MAX_CALIBRATION_MEMORY_KIB: u32 = 1 << 20;
+
+/// Hashes and verifies passwords. New hashes use these Argon2 parameters
+/// and the current pepper, if any. A stored hash carries its own
+/// parameters and pepper version in its PHC string, so changing these
+/// affects new hashes, and existing ones as users log in and get rehashed.
+#[derive(Debug, Clone)]
+pub struct PasswordHashing {
+    algorithm: Algorithm,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    peppers: Peppers,
+    schemes: PasswordSchemes,
+}
+
+impl Default for PasswordHashing {
+    /// The argon2 crate's defaults, the OWASP minimum for Argon2id, and no
+    /// pepper.
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::Argon2id,
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            peppers: Peppers::default(),
+            schemes: PasswordSchemes::default(),
+        }
+    }
+}
+
+impl fmt::Display for PasswordHashing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} m={} t={} p={}",
+            self.algorithm, self.memory_kib, self.iterations, self.parallelism
+        )?;
+        if let Some((version, _)) = self.peppers.current() {
+            write!(f, " pepper={}", version)?;
+        }
+        Ok(())
+    }
+}
+
+impl PasswordHashing {
+    /// Fails when Argon2 does not accept the costs, e.g. a memory cost
+    /// below 8 KiB per lane.
+    pub fn new(
+        algorithm: Algorithm,
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    ) -> Result<Self, argon2::Error> {
+        Params::new(memory_kib, iterations, parallelism, None)?;
+        Ok(Self {
+            algorithm,
+            memory_kib,
+            iterations,
+            parallelism,
+            ..Self::default()
+        })
+    }
+
+    /// Peppers new hashes with the highest version of `peppers` and
+    /// verifies hashes peppered with any of them. Schemes added with
+    /// [`with_scheme`](Self::with_scheme) are kept.
+    pub fn with_peppers(mut self, peppers: Peppers) -> Self {
+        self.schemes = self.schemes.with_peppers(peppers.clone());
+        self.peppers = peppers;
+        self
+    }
+
+    /// Also verifies hashes in the format of `scheme`.
+    pub fn with_scheme(mut self, scheme: impl PasswordScheme + 'static) -> Self {
+        self.schemes = self.schemes.with(scheme);
+        self
+    }
+
+    /// Hashes `password` with a fresh salt into a PHC string. Argon2
+    /// failing is a fault of the service, not of the password, which the
+    /// password policy has checked before.
+    pub fn hash(&self, password: &str) -> Result<String, AppError> {
+        let salt = SaltString::generate(&mut OsRng);
+        self.hash_with_salt(password, &salt)
+            .map_err(|e| AppError::InternalServerError(format!("Password hashing failed: {}", e)))
+    }
+
+    /// Whether `password` matches `hash`, which may be Argon2 or any format
+    /// of the configured schemes. Fails when the hash is malformed or
+    /// names a pepper that is not configured.
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+        self.schemes.verify(password, hash)
+    }
+
+    /// Whether `hash` should be recomputed: it is not Argon2, was computed
+    /// with another variant or a lower cost, or with another pepper than
+    /// the current one.
+    pub fn is_outdated(&self, hash: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(hash) else {
+            return true;
+        };
+        if self.is_stronger_than(&hash) {
+            return true;
+        }
+        let keyid = Params::try_from(&hash).map(|params| params.keyid().to_vec());
+        let current = self.peppers.current().map(|(version, _)| version);
+        keyid.map_or(true, |keyid| pepper_version(&keyid) != current)
+    }
+
+    /// Whether `hash` was computed with another variant or a lower cost
+    /// than these parameters.
+    fn is_stronger_than(&self, hash: &PasswordHash) -> bool {
+        if hash.algorithm != self.algorithm.ident() || hash.version != Some(Version::V0x13.into()) {
+            return true;
+        }
+        match Params::try_from(hash) {
+            Ok(params) => {
+                params.m_cost() < self.memory_kib
+                    || params.t_cost() < self.iterations
+                    || params.p_cost() < self.parallelism
+            }
+            Err(_) => true,
+        }
+    }
+
+    /// Suggests parameters whose hash takes about `target` on this machine.
+    /// Starting from `self`, the memory cost is doubled, then the iterations
+    /// raised, as long as a hash stays within `target`. Returns the
+    /// suggestion and how long it took to hash with it.
+    ///
+    /// Each step hashes once, so this takes a few times `target` overall;
+    /// run it off the async runtime.
+    pub fn calibrate(&self, target: Duration) -> (Self, Duration) {
+        let mut best = self.clone();
+        let mut took = best.time_hash();
+        if took >= target {
+            return (best, took);
+        }
+        while best.memory_kib * 2 <= MAX_CALIBRATION_MEMORY_KIB {
+            let next = Self {
+                memory_kib: best.memory_kib * 2,
+                ..best.clone()
+            };
+            let elapsed = next.time_hash();
+            if elapsed > target {
+                break;
+            }
+            (best, took) = (next, elapsed);
+        }
+        loop {
+            let next = Self {
+                iterations: best.iterations + 1,
+                ..best.clone()
+            };
+            let elapsed = next.time_hash();
+            if elapsed > target {
+                break;
+            }
+            (best, took) = (next, elapsed);
+        }
+        (best, took)
+    }
+
+    fn time_hash(&self) -> Duration {
+        let salt = SaltString::encode_b64(b"calibration-salt").expect("Salt is valid");
+        let started = Instant::now();
+        self.hash_with_salt("calibration-password", &salt)
+            .expect("Calibration hash must succeed");
+        started.elapsed()
+    }
+
+    fn hash_with_salt(
+        &self,
+        password: &str,
+        salt: &SaltString,
+    ) -> argon2::password_hash::Result<String> {
+        let mut params = ParamsBuilder::new();
+        params
+            .m_cost(self.memory_kib)
+            .t_cost(self.iterations)
+            .p_cost(self.parallelism);
+        let argon2 = match self.peppers.current() {
+            Some((version, pepper)) => {
+                let keyid = KeyId::new(&version.to_be_bytes()).expect("A u32 fits a key id");
+                let params = params.keyid(keyid).build()?;
+                Argon2::new_with_secret(pepper, self.algorithm, Version::V0x13, params)?
+            }
+            None => Argon2::new(self.algorithm, Version::V0x13, params.build()?),
+        };
+        Ok(argon2.hash_password(password.as_bytes(), salt)?.to_string())
+    }
+}
+
+/// The pepper version a hash's Argon2 `keyid` records, `None` when the
+/// hash is not peppered or the key id is not a version.
+pub(crate) fn pepper_version(keyid: &[u8]) -> Option<u32> {
+    if keyid.is_empty() {
+        return None;
+    }
+    keyid.try_into().ok().map(u32::from_be_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn light() -> PasswordHashing {
+        PasswordHashing::new(Algorithm::Argon2id, 8 * 1024, 1, 1).unwrap()
+    }
+
+    #[test]
+    fn test_rejects_costs_argon2_does_not_accept() {
+        assert!(PasswordHashing::new(Algorithm::Argon2id, 1, 2, 1).is_err());
+        assert!(PasswordHashing::new(Algorithm::Argon2id, 8 * 1024, 0, 1).is_err());
+        assert!(PasswordHashing::new(Algorithm::Argon2id, 8 * 1024, 2, 1).is_ok());
+    }
+
+    #[test]
+    fn test_argon2_failures_are_internal_errors() {
+        let broken = PasswordHashing {
+            memory_kib: 1,
+            ..light()
+        };
+        assert!(matches!(
+            broken.hash("secret"),
+            Err(AppError::InternalServerError(_))
+        ));
+    }
+
+    #[test]
+    fn test_weaker_or_other_hashes_are_outdated() {
+        let heavier = PasswordHashing::new(Algorithm::Argon2id, 16 * 1024, 1, 1).unwrap();
+        let other = PasswordHashing::new(Algorithm::Argon2i, 8 * 1024, 1, 1).unwrap();
+
+        let light_hash = light().hash("secret").unwrap();
+        assert!(!light().is_outdated(&light_hash));
+        assert!(heavier.is_outdated(&light_hash));
+        assert!(other.is_outdated(&light_hash));
+        assert!(light().is_outdated("$2b$12$legacy"));
+
+        // A hash stronger than needed is kept.
+        let heavier_hash = heavier.hash("secret").unwrap();
+        assert!(!light().is_outdated(&heavier_hash));
+    }
+
+    #[test]
+    fn test_hashes_record_and_rotate_their_pepper() {
+        let first = Peppers::default().with(1, vec![1; 32]).unwrap();
+        let rotated = first.clone().with(2, vec![2; 32]).unwrap();
+        let peppered = light().with_peppers(first);
+        let rotated = light().with_peppers(rotated);
+
+        let unpeppered_hash = light().hash("secret").unwrap();
+        let hash = peppered.hash("secret").unwrap();
+        assert!(hash.contains("keyid="));
+        assert!(peppered.verify("secret", &hash).unwrap());
+        assert!(!peppered.is_outdated(&hash));
+        assert!(peppered.is_outdated(&unpeppered_hash));
+
+        // The old pepper still verifies after a rotation, but its hashes
+        // are due for re-peppering.
+        assert!(rotated.verify("secret", &hash).unwrap());
+        assert!(rotated.is_outdated(&hash));
+        assert!(!rotated.is_outdated(&rotated.hash("secret").unwrap()));
+        // A peppered hash cannot be verified without its pepper.
+        assert!(light().is_outdated(&hash));
+        assert!(light().verify("secret", &hash).is_err());
+    }
+
+    #[test]
+    fn test_peppers_keep_the_schemes_added_before_them() {
+        struct Plain;
+        impl PasswordScheme for Plain {
+            fn recognizes(&self, hash: &str) -> bool {
+                hash.starts_with("plain:")
+            }
+            fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+                Ok(hash == format!("plain:{}", password))
+            }
+        }
+        let peppers = Peppers::default().with(1, vec![1; 32]).unwrap();
+        let hashing = light().with_scheme(Plain).with_peppers(peppers);
+
+        assert!(hashing.verify("secret", "plain:secret").unwrap());
+        assert!(
+            hashing
+                .verify("secret", &bcrypt::hash("secret", 4).unwrap())
+                .unwrap()
+        );
+        let hash = hashing.hash("secret").unwrap();
+        assert!(hash.contains("keyid="));
+        assert!(hashing.verify("secret", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_calibration_keeps_parameters_already_over_target() {
+        let (suggested, took) = light().calibrate(Duration::ZERO);
+        assert_eq!(suggested.to_string(), light().to_string());
+        assert!(took > Duration::ZERO);
+    }
+}
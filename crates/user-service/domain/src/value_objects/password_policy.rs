@@ -0,0 +1,295 @@
+use base::web::error::AppError;
+use base::web::response::ApiError;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+/// Banned words shorter than this are ignored, so a two-letter username
+/// does not rule out every password containing it.
+const MIN_BANNED_WORD_LEN: usize = 3;
+
+/// Rules a tenant sets for its users' passwords, read from the
+/// `password_policy` key of `tbl_tenants.setting`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    /// Minimum length in characters.
+    pub min_length: usize,
+    /// Maximum length in characters, bounding the cost of hashing.
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    /// Anything that is neither a letter nor a digit.
+    pub require_symbol: bool,
+    /// Words a password may not contain, ignoring case.
+    pub banned_words: Vec<String>,
+    /// Also ban the username, the local part of the email address and the
+    /// tenant name.
+    pub ban_account_details: bool,
+    /// Minimum [`strength_score`], from 0 (no check) to 4.
+    pub min_strength: u8,
+    /// How many of the user's previous passwords may not be chosen again.
+    /// The current one never may.
+    pub history_size: usize,
+    /// Days after which a password must be changed at the next login.
+    /// 0 lets passwords live forever.
+    pub max_age_days: u32,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            banned_words: Vec::new(),
+            ban_account_details: true,
+            min_strength: 0,
+            history_size: 0,
+            max_age_days: 0,
+        }
+    }
+}
+
+/// A rule a password broke.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordViolation {
+    TooShort { min: usize },
+    TooLong { max: usize },
+    MissingLowercase,
+    MissingUppercase,
+    MissingDigit,
+    MissingSymbol,
+    ContainsBannedWord,
+    TooWeak { score: u8, min: u8 },
+    Breached,
+    Reused,
+}
+
+impl PasswordViolation {
+    /// Stable identifier clients can map to their own wording.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::TooShort { .. } => "password_too_short",
+            Self::TooLong { .. } => "password_too_long",
+            Self::MissingLowercase => "password_missing_lowercase",
+            Self::MissingUppercase => "password_missing_uppercase",
+            Self::MissingDigit => "password_missing_digit",
+            Self::MissingSymbol => "password_missing_symbol",
+            Self::ContainsBannedWord => "password_contains_banned_word",
+            Self::TooWeak { .. } => "password_too_weak",
+            Self::Breached => "password_breached",
+            Self::Reused => "password_reused",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::TooShort { min } => format!("Password must be at least {} characters", min),
+            Self::TooLong { max } => format!("Password must be at most {} characters", max),
+            Self::MissingLowercase => "Password must contain a lowercase letter".into(),
+            Self::MissingUppercase => "Password must contain an uppercase letter".into(),
+            Self::MissingDigit => "Password must contain a digit".into(),
+            Self::MissingSymbol => "Password must contain a symbol".into(),
+            Self::ContainsBannedWord => {
+                "Password must not contain your account details or banned words".into()
+            }
+            Self::TooWeak { score, min } => {
+                format!(
+                    "Password is too easy to guess (strength {} of {})",
+                    score, min
+                )
+            }
+            Self::Breached => "Password appears in a known data breach".into(),
+            Self::Reused => "Password was used recently, choose another one".into(),
+        }
+    }
+
+    /// The error answering a password that broke `violations`, listing
+    /// each of them.
+    pub fn into_error(violations: Vec<PasswordViolation>) -> AppError {
+        AppError::Validation(
+            violations
+                .iter()
+                .map(|v| ApiError::new(400, v.message()).with_reason(v.reason()))
+                .collect(),
+        )
+    }
+}
+
+impl PasswordPolicy {
+    /// Every rule `password` breaks, `banned` adding words to the policy's
+    /// own, such as the user's account details.
+    pub fn check(&self, password: &str, banned: &[&str]) -> Vec<PasswordViolation> {
+        let mut violations = Vec::new();
+        let length = password.chars().count();
+        if length < self.min_length {
+            violations.push(PasswordViolation::TooShort {
+                min: self.min_length,
+            });
+        }
+        if length > self.max_length {
+            violations.push(PasswordViolation::TooLong {
+                max: self.max_length,
+            });
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(PasswordViolation::MissingLowercase);
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(PasswordViolation::MissingUppercase);
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::MissingDigit);
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            violations.push(PasswordViolation::MissingSymbol);
+        }
+        let lowered = password.to_lowercase();
+        let contains_banned = self
+            .banned_words
+            .iter()
+            .map(String::as_str)
+            .chain(banned.iter().copied())
+            .map(str::to_lowercase)
+            .any(|word| word.chars().count() >= MIN_BANNED_WORD_LEN && lowered.contains(&word));
+        if contains_banned {
+            violations.push(PasswordViolation::ContainsBannedWord);
+        }
+        if self.min_strength > 0 {
+            let score = strength_score(password);
+            if score < self.min_strength {
+                violations.push(PasswordViolation::TooWeak {
+                    score,
+                    min: self.min_strength,
+                });
+            }
+        }
+        violations
+    }
+
+    /// Whether a password set at `set_at` has to be changed at `now`.
+    pub fn is_expired(&self, set_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.max_age_days > 0 && now >= set_at + Duration::days(i64::from(self.max_age_days))
+    }
+}
+
+/// Rough strength of a password from 0 (trivial) to 4 (strong), from the
+/// entropy of its characters. Repeated characters and runs such as `abc`
+/// or `123` count once, since guessers try them first.
+pub fn strength_score(password: &str) -> u8 {
+    let chars: Vec<char> = password.chars().collect();
+    let mut pool = 0u32;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if chars.iter().any(|c| !c.is_ascii_alphanumeric()) {
+        pool += 33;
+    }
+    let effective_len = chars
+        .windows(2)
+        .filter(|pair| {
+            let step = pair[1] as i64 - pair[0] as i64;
+            !(-1..=1).contains(&step)
+        })
+        .count()
+        + usize::from(!chars.is_empty());
+    let bits = effective_len as f64 * f64::from(pool.max(1)).log2();
+    match bits {
+        b if b < 28.0 => 0,
+        b if b < 36.0 => 1,
+        b if b < 60.0 => 2,
+        b if b < 80.0 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_default_policy_only_checks_length_and_account_details() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("correct-horse", &[]).is_empty());
+        assert_eq!(
+            policy.check("short", &[]),
+            vec![PasswordViolation::TooShort { min: 8 }]
+        );
+        assert_eq!(
+            policy.check("Alice-2024!", &["alice"]),
+            vec![PasswordViolation::ContainsBannedWord]
+        );
+        // Too short to ban anything.
+        assert!(policy.check("correct-horse", &["or"]).is_empty());
+    }
+
+    #[test]
+    fn test_every_broken_rule_is_reported() {
+        let policy: PasswordPolicy = serde_json::from_value(json!({
+            "min_length": 12,
+            "require_uppercase": true,
+            "require_digit": true,
+            "require_symbol": true,
+            "banned_words": ["Acme"],
+            "min_strength": 3
+        }))
+        .unwrap();
+        assert_eq!(
+            policy.check("acmeacme", &[]),
+            vec![
+                PasswordViolation::TooShort { min: 12 },
+                PasswordViolation::MissingUppercase,
+                PasswordViolation::MissingDigit,
+                PasswordViolation::MissingSymbol,
+                PasswordViolation::ContainsBannedWord,
+                PasswordViolation::TooWeak { score: 2, min: 3 },
+            ]
+        );
+        assert!(policy.check("Tr0ub4dor&3-horse", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_passwords_expire_after_max_age() {
+        let now = Utc::now();
+        let policy = PasswordPolicy {
+            max_age_days: 90,
+            ..PasswordPolicy::default()
+        };
+        assert!(!policy.is_expired(now - Duration::days(89), now));
+        assert!(policy.is_expired(now - Duration::days(90), now));
+        assert!(!PasswordPolicy::default().is_expired(now - Duration::days(3650), now));
+    }
+
+    #[test]
+    fn test_strength_score_discounts_runs_and_repeats() {
+        assert_eq!(strength_score(""), 0);
+        assert_eq!(strength_score("aaaaaaaaaaaa"), 0);
+        assert_eq!(strength_score("abcdefgh12345678"), 0);
+        assert_eq!(strength_score("Tr0ub4dor&3-horse"), 4);
+    }
+
+    #[test]
+    fn test_violations_become_a_structured_error() {
+        let error = PasswordViolation::into_error(vec![
+            PasswordViolation::TooShort { min: 8 },
+            PasswordViolation::Breached,
+        ]);
+        let AppError::Validation(errors) = error else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].reason.as_deref(), Some("password_too_short"));
+        assert_eq!(errors[1].reason.as_deref(), Some("password_breached"));
+    }
+}
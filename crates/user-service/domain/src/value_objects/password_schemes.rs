@@ -0,0 +1,294 @@
+use argon2::password_hash::{self, PasswordHash, PasswordVerifier};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base::web::error::AppError;
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::value_objects::Peppers;
+use crate::value_objects::password_hashing::pepper_version;
+
+/// A password hash format that stored hashes can be verified against.
+pub trait PasswordScheme: Send + Sync {
+    /// Whether `hash` is in this scheme's format.
+    fn recognizes(&self, hash: &str) -> bool;
+
+    /// Whether `password` matches `hash`. Fails when `hash` is malformed.
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError>;
+}
+
+/// Argon2 in PHC format, `$argon2id$v=19$m=...`. New hashes are always
+/// computed with it. A hash whose `keyid` names a pepper version is
+/// verified with that pepper as the Argon2 secret.
+#[derive(Default)]
+pub struct Argon2Scheme {
+    peppers: Peppers,
+}
+
+impl Argon2Scheme {
+    pub fn new(peppers: Peppers) -> Self {
+        Self { peppers }
+    }
+}
+
+impl PasswordScheme for Argon2Scheme {
+    fn recognizes(&self, hash: &str) -> bool {
+        hash.starts_with("$argon2")
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+        let parsed = PasswordHash::new(hash).map_err(|e| malformed_hash(&e))?;
+        let keyid = Params::try_from(&parsed)
+            .map_err(|e| malformed_hash(&e))?
+            .keyid()
+            .to_vec();
+        // Verification takes the variant and costs from the hash itself.
+        if keyid.is_empty() {
+            return verify_phc(&Argon2::default(), password, hash);
+        }
+        let version = pepper_version(&keyid).ok_or_else(|| malformed_hash(&"invalid keyid"))?;
+        let pepper = self.peppers.get(version).ok_or_else(|| {
+            AppError::InternalServerError(format!("Pepper {} is not configured", version))
+        })?;
+        let argon2 = Argon2::new_with_secret(
+            pepper,
+            Algorithm::default(),
+            Version::default(),
+            Params::default(),
+        )
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+        verify_phc(&argon2, password, hash)
+    }
+}
+
+/// PBKDF2 with SHA-256 or SHA-512 in PHC format,
+/// `$pbkdf2-sha256$i=...,l=...$salt$hash`.
+pub struct Pbkdf2Scheme;
+
+impl PasswordScheme for Pbkdf2Scheme {
+    fn recognizes(&self, hash: &str) -> bool {
+        hash.starts_with("$pbkdf2-")
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+        verify_phc(&Pbkdf2, password, hash)
+    }
+}
+
+/// scrypt in PHC format, `$scrypt$ln=...,r=...,p=...$salt$hash`.
+pub struct ScryptScheme;
+
+impl PasswordScheme for ScryptScheme {
+    fn recognizes(&self, hash: &str) -> bool {
+        hash.starts_with("$scrypt$")
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+        verify_phc(&Scrypt, password, hash)
+    }
+}
+
+/// bcrypt in modular crypt format, `$2b$12$...`, including the older
+/// `$2a$`, `$2x$` and `$2y$` prefixes.
+pub struct BcryptScheme;
+
+impl PasswordScheme for BcryptScheme {
+    fn recognizes(&self, hash: &str) -> bool {
+        ["$2a$", "$2b$", "$2x$", "$2y$"]
+            .iter()
+            .any(|prefix| hash.starts_with(prefix))
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+        bcrypt::verify(password, hash).map_err(|e| malformed_hash(&e))
+    }
+}
+
+/// The schemes a stored hash is matched against, in order. The default
+/// set knows unpeppered Argon2 and the legacy bcrypt, PBKDF2 and scrypt
+/// formats of imported users; [`with`](Self::with) adds more.
+#[derive(Clone)]
+pub struct PasswordSchemes {
+    schemes: Vec<Arc<dyn PasswordScheme>>,
+    /// Position of the Argon2 scheme, so its peppers can be replaced
+    /// without losing the schemes added around it.
+    argon2: Option<usize>,
+}
+
+impl Default for PasswordSchemes {
+    fn default() -> Self {
+        Self::peppered(Peppers::default())
+    }
+}
+
+impl fmt::Debug for PasswordSchemes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PasswordSchemes")
+            .field(&self.schemes.len())
+            .finish()
+    }
+}
+
+impl PasswordSchemes {
+    pub fn empty() -> Self {
+        Self {
+            schemes: Vec::new(),
+            argon2: None,
+        }
+    }
+
+    /// Argon2 verifying with `peppers`, then the legacy formats.
+    pub fn peppered(peppers: Peppers) -> Self {
+        Self::empty()
+            .with_peppers(peppers)
+            .with(Pbkdf2Scheme)
+            .with(ScryptScheme)
+            .with(BcryptScheme)
+    }
+
+    pub fn with(mut self, scheme: impl PasswordScheme + 'static) -> Self {
+        self.schemes.push(Arc::new(scheme));
+        self
+    }
+
+    /// Verifies Argon2 hashes with `peppers`, in place of the Argon2
+    /// scheme there is, or after the other schemes if there is none.
+    pub fn with_peppers(mut self, peppers: Peppers) -> Self {
+        let argon2 = Arc::new(Argon2Scheme::new(peppers));
+        match self.argon2 {
+            Some(position) => self.schemes[position] = argon2,
+            None => {
+                self.argon2 = Some(self.schemes.len());
+                self.schemes.push(argon2);
+            }
+        }
+        self
+    }
+
+    /// Whether `password` matches `hash`, verified by the first scheme
+    /// recognizing it. Fails when none does or the hash is malformed.
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+        let scheme = self
+            .schemes
+            .iter()
+            .find(|scheme| scheme.recognizes(hash))
+            .ok_or_else(|| AppError::InternalServerError("Unknown password hash format".into()))?;
+        scheme.verify(password, hash)
+    }
+}
+
+fn verify_phc(
+    verifier: &impl PasswordVerifier,
+    password: &str,
+    hash: &str,
+) -> Result<bool, AppError> {
+    let parsed = PasswordHash::new(hash).map_err(|e| malformed_hash(&e))?;
+    if parsed.hash.is_none() {
+        return Err(malformed_hash(&"missing hash output"));
+    }
+    match verifier.verify_password(password.as_bytes(), &parsed) {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(malformed_hash(&e)),
+    }
+}
+
+fn malformed_hash(e: &dyn std::fmt::Display) -> AppError {
+    AppError::InternalServerError(format!("Malformed password hash: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_objects::PasswordHashing;
+    use argon2::password_hash::{PasswordHasher, SaltString};
+
+    const SALT: &[u8] = b"0123456789abcdef";
+
+    fn pbkdf2_hash(password: &str) -> String {
+        let salt = SaltString::encode_b64(SALT).unwrap();
+        let params = pbkdf2::Params {
+            rounds: 1_000,
+            output_length: 32,
+        };
+        Pbkdf2
+            .hash_password_customized(password.as_bytes(), None, None, params, &salt)
+            .unwrap()
+            .to_string()
+    }
+
+    fn scrypt_hash(password: &str) -> String {
+        let salt = SaltString::encode_b64(SALT).unwrap();
+        let params = scrypt::Params::new(4, 8, 1, 32).unwrap();
+        Scrypt
+            .hash_password_customized(password.as_bytes(), None, None, params, &salt)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_verifies_legacy_hashes() {
+        let schemes = PasswordSchemes::default();
+        let hashes = [
+            bcrypt::hash("correct-horse", 4).unwrap(),
+            pbkdf2_hash("correct-horse"),
+            scrypt_hash("correct-horse"),
+        ];
+        for hash in hashes {
+            assert!(schemes.verify("correct-horse", &hash).unwrap(), "{}", hash);
+            assert!(!schemes.verify("wrong-horse", &hash).unwrap(), "{}", hash);
+        }
+    }
+
+    #[test]
+    fn test_unknown_or_malformed_hashes_are_errors() {
+        let schemes = PasswordSchemes::default();
+        assert!(schemes.verify("secret", "plaintext").is_err());
+        assert!(schemes.verify("secret", "$argon2id$v=19$broken").is_err());
+        assert!(schemes.verify("secret", "$2b$12$tooshort").is_err());
+        assert!(
+            PasswordSchemes::empty()
+                .verify("secret", &scrypt_hash("secret"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_peppered_argon2_needs_its_pepper() {
+        let peppers = Peppers::default().with(1, vec![7; 32]).unwrap();
+        let hashing = PasswordHashing::new(Algorithm::Argon2id, 8 * 1024, 1, 1)
+            .unwrap()
+            .with_peppers(peppers.clone());
+        let hash = hashing.hash("secret").unwrap();
+
+        let schemes = PasswordSchemes::peppered(peppers);
+        assert!(schemes.verify("secret", &hash).unwrap());
+        assert!(!schemes.verify("other", &hash).unwrap());
+
+        let other_key = Peppers::default().with(1, vec![8; 32]).unwrap();
+        assert!(
+            !PasswordSchemes::peppered(other_key)
+                .verify("secret", &hash)
+                .unwrap()
+        );
+        assert!(PasswordSchemes::default().verify("secret", &hash).is_err());
+    }
+
+    #[test]
+    fn test_added_schemes_are_consulted() {
+        struct Plain;
+        impl PasswordScheme for Plain {
+            fn recognizes(&self, hash: &str) -> bool {
+                hash.starts_with("plain:")
+            }
+            fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+                Ok(hash == format!("plain:{}", password))
+            }
+        }
+
+        let schemes = PasswordSchemes::default().with(Plain);
+        assert!(schemes.verify("secret", "plain:secret").unwrap());
+        assert!(!schemes.verify("other", "plain:secret").unwrap());
+    }
+}
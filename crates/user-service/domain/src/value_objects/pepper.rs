@@ -0,0 +1,115 @@
+use anyhow::{Context, Result, anyhow, bail};
+use data_encoding::BASE64;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Shortest pepper accepted, 128 bits.
+const MIN_PEPPER_LEN: usize = 16;
+
+/// Server-side secrets mixed into password hashes as the Argon2 secret
+/// parameter, kept outside the database so leaked hashes cannot be
+/// cracked on their own. Each has a version recorded in the hashes it
+/// made; the highest version peppers new hashes, the older ones only
+/// verify until their hashes are re-peppered at login.
+#[derive(Clone, Default)]
+pub struct Peppers(BTreeMap<u32, Vec<u8>>);
+
+impl fmt::Debug for Peppers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Peppers")
+            .field("versions", &self.0.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Peppers {
+    /// Reads `version:base64-key` entries separated by commas or newlines,
+    /// as set in config or a secrets file. Blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn parse(encoded: &str) -> Result<Self> {
+        let mut peppers = Self::default();
+        for entry in encoded
+            .split([',', '\n'])
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty() && !entry.starts_with('#'))
+        {
+            let (version, key) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Pepper entries must be version:base64-key"))?;
+            let version = version
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid pepper version {}", version))?;
+            let key = BASE64
+                .decode(key.trim().as_bytes())
+                .with_context(|| format!("Invalid key of pepper {}", version))?;
+            peppers = peppers.with(version, key)?;
+        }
+        Ok(peppers)
+    }
+
+    pub fn with(mut self, version: u32, key: Vec<u8>) -> Result<Self> {
+        if version == 0 {
+            bail!("Pepper versions start at 1");
+        }
+        if key.len() < MIN_PEPPER_LEN {
+            bail!(
+                "Pepper {} must be at least {} bytes",
+                version,
+                MIN_PEPPER_LEN
+            );
+        }
+        if self.0.insert(version, key).is_some() {
+            bail!("Pepper {} is set twice", version);
+        }
+        Ok(self)
+    }
+
+    /// The version and key new hashes are peppered with, if any.
+    pub fn current(&self) -> Option<(u32, &[u8])> {
+        self.0
+            .last_key_value()
+            .map(|(version, key)| (*version, key.as_slice()))
+    }
+
+    pub fn get(&self, version: u32) -> Option<&[u8]> {
+        self.0.get(&version).map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highest_version_is_current() {
+        let peppers = Peppers::parse(&format!(
+            "# rotated 2026-10\n2:{}\n1:{}",
+            BASE64.encode(&[2; 32]),
+            BASE64.encode(&[1; 32])
+        ))
+        .unwrap();
+        assert_eq!(peppers.current(), Some((2, &[2u8; 32][..])));
+        assert_eq!(peppers.get(1), Some(&[1u8; 32][..]));
+        assert_eq!(peppers.get(3), None);
+        assert!(Peppers::default().current().is_none());
+    }
+
+    #[test]
+    fn test_rejects_bad_entries() {
+        let key = BASE64.encode(&[1; 32]);
+        assert!(Peppers::parse(&key).is_err());
+        assert!(Peppers::parse(&format!("0:{}", key)).is_err());
+        assert!(Peppers::parse(&format!("1:{},1:{}", key, key)).is_err());
+        assert!(Peppers::parse(&format!("1:{}", BASE64.encode(&[1; 8]))).is_err());
+        assert!(Peppers::parse("1:not base64").is_err());
+    }
+
+    #[test]
+    fn test_debug_hides_keys() {
+        let peppers = Peppers::default().with(1, vec![0xAB; 32]).unwrap();
+        let debug = format!("{:?}", peppers);
+        assert!(debug.contains("versions: [1]"));
+        assert!(!debug.contains("171"));
+    }
+}
@@ -0,0 +1,87 @@
+use base::web::error::AppError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A user's role within their tenant, as allowed by `users_role_check`.
+///
+/// Roles form a hierarchy: variants are ordered from least to most
+/// privileged, and a role grants everything the roles below it do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Role {
+    Viewer,
+    #[default]
+    User,
+    Manager,
+    Admin,
+    SupperAdmin,
+}
+
+impl Role {
+    /// Name of the role as stored in `tbl_users.role` and carried in tokens.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::User => "user",
+            Role::Manager => "manager",
+            Role::Admin => "admin",
+            Role::SupperAdmin => "supper_admin",
+        }
+    }
+
+    /// Whether this role passes a check for `required`.
+    pub fn satisfies(&self, required: Role) -> bool {
+        *self >= required
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viewer" => Ok(Role::Viewer),
+            "user" => Ok(Role::User),
+            "manager" => Ok(Role::Manager),
+            "admin" => Ok(Role::Admin),
+            "supper_admin" => Ok(Role::SupperAdmin),
+            _ => Err(AppError::BadRequest(format!("Unknown role {}", s))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_round_trips_through_its_name() {
+        for role in [
+            Role::Viewer,
+            Role::User,
+            Role::Manager,
+            Role::Admin,
+            Role::SupperAdmin,
+        ] {
+            assert_eq!(role.as_str().parse::<Role>().unwrap(), role);
+        }
+        assert!(matches!(
+            "api_key".parse::<Role>(),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_higher_roles_satisfy_lower_requirements() {
+        assert!(Role::Admin.satisfies(Role::Manager));
+        assert!(Role::SupperAdmin.satisfies(Role::Admin));
+        assert!(Role::Manager.satisfies(Role::Manager));
+        assert!(!Role::Manager.satisfies(Role::Admin));
+        assert!(!Role::Viewer.satisfies(Role::User));
+    }
+}
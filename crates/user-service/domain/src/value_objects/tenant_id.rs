@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TenantId(Uuid);
+
+impl TenantId {
+    /// Parse a TenantId from a string representation
+    pub fn from_string(s: &str) -> Result<Self, uuid::Error> {
+        Ok(Self(Uuid::parse_str(s)?))
+    }
+
+    /// Get the inner UUID reference
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+
+    /// Get string representation (convenience method)
+    pub fn as_str(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for TenantId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_string(s)
+    }
+}
+
+impl From<Uuid> for TenantId {
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+}
+
+impl From<TenantId> for Uuid {
+    fn from(tenant_id: TenantId) -> Self {
+        tenant_id.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_string_valid() {
+        let uuid_str = "550e8400-e29b-41d4-a716-446655440000";
+        let tenant_id = TenantId::from_string(uuid_str).unwrap();
+        assert_eq!(tenant_id.to_string(), uuid_str);
+    }
+
+    #[test]
+    fn test_from_string_invalid() {
+        assert!(TenantId::from_string("not-a-uuid").is_err());
+    }
+
+    #[test]
+    fn test_serde_serialization() {
+        let tenant_id: TenantId = "550e8400-e29b-41d4-a716-446655440000".parse().unwrap();
+        let json = serde_json::to_string(&tenant_id).unwrap();
+        assert_eq!(json, "\"550e8400-e29b-41d4-a716-446655440000\"");
+    }
+}
@@ -0,0 +1,130 @@
+use crate::value_objects::{PasswordPolicy, Role};
+use serde::Deserialize;
+
+/// User-service options read from `tbl_tenants.setting`.
+///
+/// Unknown keys are ignored and missing keys fall back to their defaults, so
+/// tenants only store what they change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TenantSettings {
+    /// Refuse logins until the user has verified their email address.
+    pub require_email_verification: bool,
+    /// Roles that must sign in with a second factor. Users holding one of them
+    /// are asked to enroll in MFA before they get tokens.
+    pub mfa_required_roles: Vec<String>,
+    /// Corporate OpenID Connect providers the tenant's users may sign in with.
+    pub identity_providers: Vec<IdentityProviderSettings>,
+    /// Access policies added to the service-wide ones for this tenant's
+    /// users, in the format of `auth::policy`.
+    pub access_policies: Vec<serde_json::Value>,
+    /// Accept passwords found in the breached password corpus. The check
+    /// only runs when the service has been given the corpus.
+    pub allow_breached_passwords: bool,
+    /// Rules new passwords of the tenant's users must meet.
+    pub password_policy: PasswordPolicy,
+}
+
+/// An external OpenID Connect provider this service signs users in with as
+/// a relying party.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IdentityProviderSettings {
+    /// Tenant-chosen key the login page refers to the provider by.
+    pub id: String,
+    /// Expected `iss` of the provider's ID tokens.
+    pub issuer: String,
+    pub client_id: String,
+    /// Sent with the code exchange when the provider treats us as a
+    /// confidential client.
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+    /// Create an account on first login when no user matches.
+    #[serde(default = "default_jit_provisioning")]
+    pub jit_provisioning: bool,
+}
+
+fn default_scopes() -> Vec<String> {
+    ["openid", "email", "profile"].map(String::from).to_vec()
+}
+
+fn default_jit_provisioning() -> bool {
+    true
+}
+
+impl TenantSettings {
+    pub fn from_json(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value(value)
+    }
+
+    pub fn requires_mfa(&self, role: Role) -> bool {
+        self.mfa_required_roles
+            .iter()
+            .any(|r| r.eq_ignore_ascii_case(role.as_str()))
+    }
+
+    pub fn identity_provider(&self, id: &str) -> Option<&IdentityProviderSettings> {
+        self.identity_providers.iter().find(|p| p.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_empty_settings_use_defaults() {
+        let settings = TenantSettings::from_json(json!({})).unwrap();
+        assert_eq!(settings, TenantSettings::default());
+        assert!(!settings.require_email_verification);
+        assert!(!settings.allow_breached_passwords);
+        assert_eq!(settings.password_policy.min_length, 8);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_ignored() {
+        let settings = TenantSettings::from_json(json!({
+            "require_email_verification": true,
+            "theme": "dark"
+        }))
+        .unwrap();
+        assert!(settings.require_email_verification);
+    }
+
+    #[test]
+    fn test_requires_mfa_for_listed_roles() {
+        let settings = TenantSettings::from_json(json!({
+            "mfa_required_roles": ["admin", "Manager"]
+        }))
+        .unwrap();
+        assert!(settings.requires_mfa(Role::Admin));
+        assert!(settings.requires_mfa(Role::Manager));
+        assert!(!settings.requires_mfa(Role::User));
+        assert!(!TenantSettings::default().requires_mfa(Role::Admin));
+    }
+
+    #[test]
+    fn test_identity_provider_defaults() {
+        let settings = TenantSettings::from_json(json!({
+            "identity_providers": [{
+                "id": "corp",
+                "issuer": "https://idp.example.com",
+                "client_id": "user-service",
+                "authorization_endpoint": "https://idp.example.com/authorize",
+                "token_endpoint": "https://idp.example.com/token",
+                "jwks_uri": "https://idp.example.com/jwks"
+            }]
+        }))
+        .unwrap();
+        let provider = settings.identity_provider("corp").unwrap();
+        assert_eq!(provider.scopes, vec!["openid", "email", "profile"]);
+        assert!(provider.jit_provisioning);
+        assert_eq!(provider.client_secret, None);
+        assert!(settings.identity_provider("other").is_none());
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha1::Sha1;
+use std::fmt;
+
+/// Length of generated secrets; RFC 4226 recommends 160 bits.
+const SECRET_LEN: usize = 20;
+/// Seconds covered by one TOTP code.
+pub const TOTP_PERIOD: u64 = 30;
+/// Digits in a TOTP code.
+pub const TOTP_DIGITS: u32 = 6;
+/// Steps either side of the current one that are still accepted, to absorb
+/// clock drift between the server and the authenticator app.
+const ALLOWED_SKEW: u64 = 1;
+
+/// Shared secret of a TOTP authenticator (RFC 6238, HMAC-SHA1).
+#[derive(Clone, PartialEq, Eq)]
+pub struct TotpSecret(Vec<u8>);
+
+impl TotpSecret {
+    pub fn generate() -> Self {
+        let mut bytes = vec![0u8; SECRET_LEN];
+        OsRng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// Parse a base32 secret as shown to users and stored in the database.
+    pub fn from_base32(encoded: &str) -> Result<Self, data_encoding::DecodeError> {
+        let normalized = encoded.trim().trim_end_matches('=').to_ascii_uppercase();
+        Ok(Self(BASE32_NOPAD.decode(normalized.as_bytes())?))
+    }
+
+    pub fn to_base32(&self) -> String {
+        BASE32_NOPAD.encode(&self.0)
+    }
+
+    /// `otpauth://` URI that authenticator apps import from a QR code.
+    pub fn provisioning_uri(&self, issuer: &str, account: &str) -> String {
+        let issuer = urlencoding::encode(issuer);
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            issuer,
+            urlencoding::encode(account),
+            self.to_base32(),
+            issuer,
+            TOTP_DIGITS,
+            TOTP_PERIOD
+        )
+    }
+
+    /// Time step that `at` falls into.
+    pub fn step_at(at: DateTime<Utc>) -> u64 {
+        at.timestamp().max(0) as u64 / TOTP_PERIOD
+    }
+
+    /// The code for a given time step.
+    pub fn code_at_step(&self, step: u64) -> String {
+        self.hotp(step, TOTP_DIGITS)
+    }
+
+    /// Checks `code` against the steps around `now` and returns the matching
+    /// step, so callers can refuse to accept the same code twice.
+    pub fn verify(&self, code: &str, now: DateTime<Utc>) -> Option<u64> {
+        let code = code.trim();
+        if code.len() != TOTP_DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let current = Self::step_at(now);
+        (current.saturating_sub(ALLOWED_SKEW)..=current + ALLOWED_SKEW)
+            .find(|step| self.code_at_step(*step) == code)
+    }
+
+    /// HOTP value (RFC 4226) for `counter`, zero-padded to `digits`.
+    fn hotp(&self, counter: u64, digits: u32) -> String {
+        let mut mac = Hmac::<Sha1>::new_from_slice(&self.0).expect("HMAC accepts any key length");
+        mac.update(&counter.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+        format!(
+            "{:0width$}",
+            binary % 10u32.pow(digits),
+            width = digits as usize
+        )
+    }
+}
+
+impl fmt::Debug for TotpSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TotpSecret(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn rfc_secret() -> TotpSecret {
+        TotpSecret(b"12345678901234567890".to_vec())
+    }
+
+    #[test]
+    fn test_rfc6238_sha1_vectors() {
+        let secret = rfc_secret();
+        for (time, expected) in [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ] {
+            let step = TotpSecret::step_at(Utc.timestamp_opt(time, 0).unwrap());
+            assert_eq!(secret.hotp(step, 8), expected, "time {}", time);
+        }
+    }
+
+    #[test]
+    fn test_verify_accepts_adjacent_steps_only() {
+        let secret = rfc_secret();
+        let now = Utc.timestamp_opt(1111111111, 0).unwrap();
+        let step = TotpSecret::step_at(now);
+        assert_eq!(secret.verify(&secret.code_at_step(step), now), Some(step));
+        assert_eq!(
+            secret.verify(&secret.code_at_step(step - 1), now),
+            Some(step - 1)
+        );
+        assert_eq!(secret.verify(&secret.code_at_step(step + 2), now), None);
+        assert_eq!(secret.verify("12345", now), None);
+        assert_eq!(secret.verify("abcdef", now), None);
+    }
+
+    #[test]
+    fn test_base32_roundtrip() {
+        let secret = TotpSecret::generate();
+        let encoded = secret.to_base32();
+        assert_eq!(TotpSecret::from_base32(&encoded).unwrap(), secret);
+        assert_eq!(
+            TotpSecret::from_base32(&encoded.to_lowercase()).unwrap(),
+            secret
+        );
+        assert!(TotpSecret::from_base32("not base32!").is_err());
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        let uri = rfc_secret().provisioning_uri("Acme Corp", "alice@example.com");
+        assert_eq!(
+            uri,
+            "otpauth://totp/Acme%20Corp:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Acme%20Corp&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        assert_eq!(format!("{:?}", rfc_secret()), "TotpSecret(***)");
+    }
+}
@@ -0,0 +1,46 @@
+//! Builds the breached password index the user service screens new
+//! passwords against, from the Have I Been Pwned SHA-1 dump ordered by hash.
+//!
+//! Usage: build_breached_password_index <dump> <index> [--min-count <n>]
+//!
+//! `--min-count` leaves out hashes seen fewer than `n` times, trading
+//! coverage for a smaller index.
+
+use anyhow::{Context, Result, bail};
+use infrastructure::{BreachedPasswordIndexWriter, parse_dump_line};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter};
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(dump_path), Some(index_path)) = (args.next(), args.next()) else {
+        bail!("Usage: build_breached_password_index <dump> <index> [--min-count <n>]");
+    };
+    let min_count = match (args.next().as_deref(), args.next()) {
+        (None, _) => 1,
+        (Some("--min-count"), Some(n)) => n.parse().context("Invalid --min-count")?,
+        _ => bail!("Usage: build_breached_password_index <dump> <index> [--min-count <n>]"),
+    };
+
+    let dump = BufReader::new(
+        File::open(&dump_path).with_context(|| format!("Failed to open {}", dump_path))?,
+    );
+    let index = BufWriter::new(
+        File::create(&index_path).with_context(|| format!("Failed to create {}", index_path))?,
+    );
+    let mut writer = BreachedPasswordIndexWriter::new(index)?;
+    for (number, line) in dump.lines().enumerate() {
+        let line = line?;
+        let parsed = parse_dump_line(&line).with_context(|| format!("Line {}", number + 1))?;
+        if let Some((prefix, count)) = parsed
+            && count >= min_count
+        {
+            writer
+                .push(prefix)
+                .with_context(|| format!("Line {}", number + 1))?;
+        }
+    }
+    let entries = writer.finish()?;
+    println!("Wrote {} entries to {}", entries, index_path);
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use anyhow::{Context, Result, bail, ensure};
+use domain::services::BreachedPasswords;
+use memmap2::Mmap;
+use sha1::{Digest, Sha1};
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"BPWIDX01";
+/// Entries are bucketed by the first two bytes of their hash.
+const BUCKETS: usize = 1 << 16;
+const HEADER_LEN: usize = MAGIC.len() + 8 + (BUCKETS + 1) * 8;
+const ENTRY_LEN: usize = 8;
+
+/// Breached passwords as a sorted index of truncated SHA-1 hashes, read
+/// through a memory map so only the pages a lookup touches are loaded.
+///
+/// The file holds a magic, the entry count, a fan-out table giving for each
+/// 16-bit hash prefix the number of entries below it, then the first 8
+/// bytes of every SHA-1 in ascending order. Keeping 8 of the 20 bytes
+/// shrinks the corpus to a few gigabytes at a false positive rate far
+/// below one in a billion.
+pub struct BreachedPasswordIndex {
+    map: Mmap,
+    count: usize,
+}
+
+impl BreachedPasswordIndex {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| {
+            format!("Failed to open breached password index {}", path.display())
+        })?;
+        // The index is written once by the builder and only ever read here.
+        let map = unsafe { Mmap::map(&file)? };
+        ensure!(
+            map.len() >= HEADER_LEN && &map[..MAGIC.len()] == MAGIC,
+            "{} is not a breached password index",
+            path.display()
+        );
+        let count = read_u64_le(&map, MAGIC.len()) as usize;
+        ensure!(
+            map.len() == HEADER_LEN + count * ENTRY_LEN,
+            "Breached password index {} is truncated",
+            path.display()
+        );
+        let index = Self { map, count };
+        ensure!(
+            index.fanout(BUCKETS) == count,
+            "Breached password index {} has a corrupt fan-out table",
+            path.display()
+        );
+        Ok(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Whether the index holds a SHA-1 starting with `prefix`.
+    pub fn contains_prefix(&self, prefix: u64) -> bool {
+        let bucket = (prefix >> 48) as usize;
+        let (mut low, mut high) = (self.fanout(bucket), self.fanout(bucket + 1));
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let entry = u64::from_be_bytes(
+                self.map[HEADER_LEN + mid * ENTRY_LEN..][..ENTRY_LEN]
+                    .try_into()
+                    .unwrap(),
+            );
+            match entry.cmp(&prefix) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    /// Entries whose hash starts with a 16-bit prefix below `bucket`.
+    fn fanout(&self, bucket: usize) -> usize {
+        read_u64_le(&self.map, MAGIC.len() + 8 + bucket * 8) as usize
+    }
+}
+
+impl BreachedPasswords for BreachedPasswordIndex {
+    fn contains(&self, password: &str) -> bool {
+        self.contains_prefix(sha1_prefix(password))
+    }
+}
+
+/// Writes an index from SHA-1 prefixes fed in ascending order, as in the
+/// Have I Been Pwned dump ordered by hash.
+pub struct BreachedPasswordIndexWriter<W: Write + Seek> {
+    out: W,
+    buckets: Vec<u64>,
+    count: u64,
+    last: Option<u64>,
+}
+
+impl<W: Write + Seek> BreachedPasswordIndexWriter<W> {
+    pub fn new(mut out: W) -> Result<Self> {
+        // The header is only known at the end, so reserve its space.
+        out.write_all(&[0; HEADER_LEN])?;
+        Ok(Self {
+            out,
+            buckets: vec![0; BUCKETS],
+            count: 0,
+            last: None,
+        })
+    }
+
+    /// Adds a prefix. Repeats of the previous one are skipped, since
+    /// distinct hashes can share their first 8 bytes.
+    pub fn push(&mut self, prefix: u64) -> Result<()> {
+        match self.last {
+            Some(last) if prefix == last => return Ok(()),
+            Some(last) if prefix < last => {
+                bail!(
+                    "Hashes must be in ascending order, {:016X} follows {:016X}",
+                    prefix,
+                    last
+                )
+            }
+            _ => {}
+        }
+        self.out.write_all(&prefix.to_be_bytes())?;
+        self.buckets[(prefix >> 48) as usize] += 1;
+        self.count += 1;
+        self.last = Some(prefix);
+        Ok(())
+    }
+
+    /// Writes the header and returns the number of entries.
+    pub fn finish(mut self) -> Result<u64> {
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(MAGIC)?;
+        self.out.write_all(&self.count.to_le_bytes())?;
+        let mut below = 0u64;
+        self.out.write_all(&below.to_le_bytes())?;
+        for bucket in &self.buckets {
+            below += bucket;
+            self.out.write_all(&below.to_le_bytes())?;
+        }
+        self.out.flush()?;
+        Ok(self.count)
+    }
+}
+
+/// Parses a line of the Have I Been Pwned SHA-1 dump, `HASH:COUNT` or a
+/// bare `HASH`, into the hash prefix and the number of times it was seen.
+/// Blank lines yield `None`.
+pub fn parse_dump_line(line: &str) -> Result<Option<(u64, u64)>> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let (hash, count) = match line.split_once(':') {
+        Some((hash, count)) => (
+            hash,
+            count
+                .parse()
+                .with_context(|| format!("Invalid count in {}", line))?,
+        ),
+        None => (line, 1),
+    };
+    ensure!(
+        hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_hexdigit()),
+        "Expected a SHA-1 hash, got {}",
+        hash
+    );
+    let prefix = u64::from_str_radix(&hash[..16], 16)?;
+    Ok(Some((prefix, count)))
+}
+
+pub fn sha1_prefix(password: &str) -> u64 {
+    let digest = Sha1::digest(password.as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+fn read_u64_le(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn build(lines: &[&str]) -> Result<Vec<u8>> {
+        let mut out = Cursor::new(Vec::new());
+        let mut writer = BreachedPasswordIndexWriter::new(&mut out)?;
+        for line in lines {
+            if let Some((prefix, _)) = parse_dump_line(line)? {
+                writer.push(prefix)?;
+            }
+        }
+        writer.finish()?;
+        Ok(out.into_inner())
+    }
+
+    #[test]
+    fn test_index_finds_dumped_passwords_only() {
+        let mut hashes: Vec<String> = ["password123", "letmein", "qwerty"]
+            .iter()
+            .map(|p| format!("{:X}", Sha1::digest(p.as_bytes())))
+            .collect();
+        hashes.sort();
+        let lines: Vec<String> = hashes.iter().map(|h| format!("{}:42", h)).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+
+        let path = std::env::temp_dir().join(format!("bpwidx-{}", uuid::Uuid::now_v7()));
+        std::fs::write(&path, build(&lines).unwrap()).unwrap();
+        let index = BreachedPasswordIndex::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(index.len(), 3);
+        assert!(index.contains("password123"));
+        assert!(index.contains("qwerty"));
+        assert!(!index.contains("correct-horse-battery"));
+    }
+
+    #[test]
+    fn test_writer_requires_ascending_hashes() {
+        let lines = [
+            "7C4A8D09CA3762AF61E59520943DC26494F8941B:1",
+            "0000000A0E3B9F25FF41DE4B5AC238C2D545C7A8:1",
+        ];
+        assert!(build(&lines).is_err());
+        assert!(parse_dump_line("not-a-hash:3").is_err());
+        assert_eq!(parse_dump_line("  ").unwrap(), None);
+    }
+}
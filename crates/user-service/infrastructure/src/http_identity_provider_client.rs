@@ -0,0 +1,110 @@
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use domain::services::IdentityProviderClient;
+use domain::value_objects::IdentityProviderSettings;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A cached key set is fetched again on request only once it is this old, so
+/// tokens naming made-up key ids cannot make us hammer the provider.
+const JWKS_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// Calls external identity providers over HTTPS and caches their key sets.
+pub struct HttpIdentityProviderClient {
+    http: reqwest::Client,
+    jwks_ttl: Duration,
+    jwks_cache: Mutex<HashMap<String, (Instant, serde_json::Value)>>,
+}
+
+impl HttpIdentityProviderClient {
+    pub fn new(timeout: Duration, jwks_ttl: Duration) -> Result<Self> {
+        let http = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self {
+            http,
+            jwks_ttl,
+            jwks_cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn cached_jwks(&self, jwks_uri: &str, max_age: Duration) -> Option<serde_json::Value> {
+        let cache = self.jwks_cache.lock().unwrap();
+        cache
+            .get(jwks_uri)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < max_age)
+            .map(|(_, jwks)| jwks.clone())
+    }
+}
+
+#[async_trait]
+impl IdentityProviderClient for HttpIdentityProviderClient {
+    async fn exchange_code(
+        &self,
+        provider: &IdentityProviderSettings,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+    ) -> Result<String> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", code_verifier),
+            ("client_id", provider.client_id.as_str()),
+        ];
+        if let Some(secret) = provider.client_secret.as_deref() {
+            form.push(("client_secret", secret));
+        }
+        let response = self
+            .http
+            .post(&provider.token_endpoint)
+            .form(&form)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!(
+                "Token endpoint of identity provider {} answered {}: {}",
+                provider.id,
+                status,
+                body
+            );
+        }
+        response
+            .json::<TokenResponse>()
+            .await?
+            .id_token
+            .ok_or_else(|| anyhow!("Identity provider {} returned no ID token", provider.id))
+    }
+
+    async fn fetch_jwks(&self, jwks_uri: &str, refresh: bool) -> Result<serde_json::Value> {
+        let max_age = if refresh {
+            JWKS_MIN_REFRESH_INTERVAL
+        } else {
+            self.jwks_ttl
+        };
+        if let Some(jwks) = self.cached_jwks(jwks_uri, max_age) {
+            return Ok(jwks);
+        }
+        let jwks: serde_json::Value = self
+            .http
+            .get(jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        self.jwks_cache
+            .lock()
+            .unwrap()
+            .insert(jwks_uri.to_string(), (Instant::now(), jwks.clone()));
+        Ok(jwks)
+    }
+}
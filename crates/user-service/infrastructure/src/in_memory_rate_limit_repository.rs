@@ -0,0 +1,43 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::policies::{RateLimit, RateLimitDecision};
+use domain::repository::RateLimitRepository;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Rate limit state of a single instance. Each instance enforces the limits
+/// on its own, so a deployment of several lets proportionally more through.
+#[derive(Default)]
+pub struct InMemoryRateLimitRepository {
+    tats: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl InMemoryRateLimitRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitRepository for InMemoryRateLimitRepository {
+    async fn check(
+        &self,
+        key: &str,
+        limit: &RateLimit,
+        now: DateTime<Utc>,
+    ) -> Result<RateLimitDecision> {
+        let mut tats = self.tats.lock().unwrap();
+        let decision = limit.check(tats.get(key).copied(), now);
+        if decision.allowed {
+            tats.insert(key.to_string(), decision.tat);
+        }
+        Ok(decision)
+    }
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64> {
+        let mut tats = self.tats.lock().unwrap();
+        let before = tats.len();
+        tats.retain(|_, tat| *tat >= now);
+        Ok((before - tats.len()) as u64)
+    }
+}
@@ -1,5 +1,39 @@
+pub mod breached_password_index;
+pub mod http_identity_provider_client;
+pub mod in_memory_rate_limit_repository;
+pub mod log_mailer;
 pub mod model;
+pub mod pg_api_key_repository;
+pub mod pg_audit_event_repository;
+pub mod pg_federation_repository;
+pub mod pg_mfa_repository;
+pub mod pg_oauth_repository;
+pub mod pg_password_history_repository;
+pub mod pg_rate_limit_repository;
+pub mod pg_refresh_token_repository;
 pub mod pg_repository;
+pub mod pg_session_repository;
+pub mod pg_signing_key_repository;
+pub mod pg_tenant_settings_repository;
+pub mod pg_user_token_repository;
+pub mod pg_webauthn_repository;
 
+pub use breached_password_index::*;
+pub use http_identity_provider_client::*;
+pub use in_memory_rate_limit_repository::*;
+pub use log_mailer::*;
 pub use model::*;
+pub use pg_api_key_repository::*;
+pub use pg_audit_event_repository::*;
+pub use pg_federation_repository::*;
+pub use pg_mfa_repository::*;
+pub use pg_oauth_repository::*;
+pub use pg_password_history_repository::*;
+pub use pg_rate_limit_repository::*;
+pub use pg_refresh_token_repository::*;
 pub use pg_repository::*;
+pub use pg_session_repository::*;
+pub use pg_signing_key_repository::*;
+pub use pg_tenant_settings_repository::*;
+pub use pg_user_token_repository::*;
+pub use pg_webauthn_repository::*;
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::services::{EmailMessage, Mailer};
+use tracing::info;
+
+/// Development mailer that writes messages to the log instead of sending them.
+#[derive(Default)]
+pub struct LogMailer;
+
+impl LogMailer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: EmailMessage) -> Result<()> {
+        info!(
+            to = message.to.as_str(),
+            subject = message.subject.as_str(),
+            "Email:\n{}",
+            message.body
+        );
+        Ok(())
+    }
+}
@@ -1,49 +1,125 @@
 use std::fmt;
 
-use domain::User;
+use base::model::Audit;
+use base::model::value_objects::{CreatedAt, CreatedBy, UpdatedAt, UpdatedBy};
+use base::secret::SecretString;
+use chrono::{DateTime, Utc};
 use domain::username::Username;
-use domain::value_objects::{EmailAddress, Password, UserId};
+use domain::value_objects::{EmailAddress, Password, TotpSecret, UserId};
+use domain::{
+    ApiKey, AuditEvent, AuthorizationCode, FederatedLoginState, OAuthClient, RecoveryCode,
+    RefreshToken, Session, SigningKey, TokenPurpose, TotpEnrollment, User, UserIdentity, UserToken,
+    WebAuthnCeremony, WebAuthnChallenge, WebAuthnCredential,
+};
 use sqlx::FromRow;
+use uuid::Uuid;
 
 #[derive(FromRow, Debug, Clone)]
 pub struct UserModel {
-    pub id: String,
+    pub id: Uuid,
+    pub tenant_id: Uuid,
     pub username: String,
-    pub password: String,
+    #[sqlx(try_from = "String")]
+    pub password_hash: SecretString,
     pub email: String,
+    pub email_verified: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub role: String,
+    pub failed_login_attempts: i32,
+    pub locked_util: Option<DateTime<Utc>>,
+    pub password_changed_at: Option<DateTime<Utc>>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Option<Uuid>,
 }
 impl UserModel {
-    pub fn new(id: String, username: String, password: String, email: String) -> Self {
+    pub fn new(
+        id: Uuid,
+        tenant_id: Uuid,
+        username: String,
+        password_hash: SecretString,
+        email: String,
+        role: String,
+    ) -> Self {
         Self {
             id,
+            tenant_id,
             username,
-            password,
+            password_hash,
             email,
+            email_verified: false,
+            email_verified_at: None,
+            role,
+            failed_login_attempts: 0,
+            locked_util: None,
+            password_changed_at: None,
+            last_login_at: None,
+            created_at: Utc::now(),
+            created_by: None,
+            updated_at: Utc::now(),
+            updated_by: None,
         }
     }
 }
 impl From<User> for UserModel {
     fn from(user: User) -> Self {
+        let audit = user
+            .audit
+            .unwrap_or_else(|| Audit::with_created_at(CreatedAt::now()));
+        let audit_id = |id: &str| Uuid::parse_str(id).ok();
         Self {
-            id: user.id.as_str().to_string(),
+            id: user.id.into(),
+            tenant_id: user.tenant_id.into(),
             username: user.username.as_str().to_string(),
-            password: user.password_hash.as_str().to_string(),
+            password_hash: user.password_hash.as_str().into(),
             email: user.email_address.as_str().to_string(),
+            email_verified: user.email_verified,
+            email_verified_at: user.email_verified_at,
+            role: user.role.to_string(),
+            failed_login_attempts: user.failed_login_attempts as i32,
+            locked_util: user.locked_until,
+            password_changed_at: user.password_changed_at,
+            last_login_at: user.last_login_at,
+            created_at: audit.created_at.value(),
+            created_by: audit.created_by.and_then(|by| audit_id(by.as_str())),
+            updated_at: audit
+                .updated_at
+                .map_or(audit.created_at.value(), |at| at.value()),
+            updated_by: audit.updated_by.and_then(|by| audit_id(by.as_str())),
         }
     }
 }
 impl From<UserModel> for User {
     fn from(user_model: UserModel) -> Self {
-        let id = UserId::from_string(&user_model.id).expect("Invalid username");
+        let id = UserId::from(user_model.id);
         let username = Username::new(&user_model.username).expect("Invalid username");
-        let password_hash = Password::from_hash(user_model.password);
+        let password_hash = Password::from_hash(user_model.password_hash.into_exposed());
         let email_address = EmailAddress::new(user_model.email).expect("Invalid email address");
         User {
             id,
+            tenant_id: user_model.tenant_id.into(),
             username,
             password_hash,
             email_address,
-            audit: None,
+            email_verified: user_model.email_verified,
+            email_verified_at: user_model.email_verified_at,
+            role: user_model.role.parse().expect("Invalid role"),
+            failed_login_attempts: user_model.failed_login_attempts.max(0) as u32,
+            locked_until: user_model.locked_util,
+            password_changed_at: user_model.password_changed_at,
+            last_login_at: user_model.last_login_at,
+            audit: Some(Audit {
+                created_at: CreatedAt::from_datetime(user_model.created_at),
+                updated_at: Some(UpdatedAt::from_datetime(user_model.updated_at)),
+                created_by: user_model
+                    .created_by
+                    .map(|by| CreatedBy::new(by.to_string())),
+                updated_by: user_model
+                    .updated_by
+                    .map(|by| UpdatedBy::new(by.to_string())),
+            }),
         }
     }
 }
@@ -51,8 +127,606 @@ impl fmt::Display for UserModel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "UserModel {{ id: {}, username: {}, password: {}, email: {} }}",
-            self.id, self.username, self.password, self.email
+            "UserModel {{ id: {}, tenant_id: {}, username: {}, password: {}, email: {}, role: {} }}",
+            self.id, self.tenant_id, self.username, self.password_hash, self.email, self.role
         )
     }
 }
+
+#[derive(FromRow, Debug, Clone)]
+pub struct RefreshTokenModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<RefreshToken> for RefreshTokenModel {
+    fn from(token: RefreshToken) -> Self {
+        Self {
+            id: token.id,
+            tenant_id: token.tenant_id.into(),
+            user_id: token.user_id.into(),
+            family_id: token.family_id,
+            token_hash: token.token_hash,
+            expires_at: token.expires_at,
+            rotated_at: token.rotated_at,
+            revoked_at: token.revoked_at,
+            created_at: token.created_at,
+        }
+    }
+}
+impl From<RefreshTokenModel> for RefreshToken {
+    fn from(model: RefreshTokenModel) -> Self {
+        RefreshToken {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            user_id: model.user_id.into(),
+            family_id: model.family_id,
+            token_hash: model.token_hash,
+            expires_at: model.expires_at,
+            rotated_at: model.rotated_at,
+            revoked_at: model.revoked_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct SessionModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub mfa_verified: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+impl From<Session> for SessionModel {
+    fn from(session: Session) -> Self {
+        Self {
+            id: session.id,
+            tenant_id: session.tenant_id.into(),
+            user_id: session.user_id.into(),
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            mfa_verified: session.mfa_verified,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            revoked_at: session.revoked_at,
+        }
+    }
+}
+impl From<SessionModel> for Session {
+    fn from(model: SessionModel) -> Self {
+        Session {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            user_id: model.user_id.into(),
+            user_agent: model.user_agent,
+            ip_address: model.ip_address,
+            mfa_verified: model.mfa_verified,
+            created_at: model.created_at,
+            last_seen_at: model.last_seen_at,
+            revoked_at: model.revoked_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct UserTokenModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<UserToken> for UserTokenModel {
+    fn from(token: UserToken) -> Self {
+        Self {
+            id: token.id,
+            tenant_id: token.tenant_id.into(),
+            user_id: token.user_id.into(),
+            purpose: token.purpose.as_str().to_string(),
+            token_hash: token.token_hash,
+            expires_at: token.expires_at,
+            consumed_at: token.consumed_at,
+            created_at: token.created_at,
+        }
+    }
+}
+impl From<UserTokenModel> for UserToken {
+    fn from(model: UserTokenModel) -> Self {
+        UserToken {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            user_id: model.user_id.into(),
+            purpose: model
+                .purpose
+                .parse::<TokenPurpose>()
+                .expect("Invalid token purpose"),
+            token_hash: model.token_hash,
+            expires_at: model.expires_at,
+            consumed_at: model.consumed_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct TotpEnrollmentModel {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub secret: String,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<TotpEnrollment> for TotpEnrollmentModel {
+    fn from(enrollment: TotpEnrollment) -> Self {
+        Self {
+            user_id: enrollment.user_id.into(),
+            tenant_id: enrollment.tenant_id.into(),
+            secret: enrollment.secret.to_base32(),
+            confirmed_at: enrollment.confirmed_at,
+            last_used_step: enrollment.last_used_step.map(|step| step as i64),
+            created_at: enrollment.created_at,
+        }
+    }
+}
+impl From<TotpEnrollmentModel> for TotpEnrollment {
+    fn from(model: TotpEnrollmentModel) -> Self {
+        TotpEnrollment {
+            user_id: model.user_id.into(),
+            tenant_id: model.tenant_id.into(),
+            secret: TotpSecret::from_base32(&model.secret).expect("Invalid TOTP secret"),
+            confirmed_at: model.confirmed_at,
+            last_used_step: model.last_used_step.map(|step| step as u64),
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct RecoveryCodeModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub code_hash: String,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<RecoveryCode> for RecoveryCodeModel {
+    fn from(code: RecoveryCode) -> Self {
+        Self {
+            id: code.id,
+            user_id: code.user_id.into(),
+            code_hash: code.code_hash,
+            used_at: code.used_at,
+            created_at: code.created_at,
+        }
+    }
+}
+impl From<RecoveryCodeModel> for RecoveryCode {
+    fn from(model: RecoveryCodeModel) -> Self {
+        RecoveryCode {
+            id: model.id,
+            user_id: model.user_id.into(),
+            code_hash: model.code_hash,
+            used_at: model.used_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct WebAuthnCredentialModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+impl From<WebAuthnCredential> for WebAuthnCredentialModel {
+    fn from(credential: WebAuthnCredential) -> Self {
+        Self {
+            id: credential.id,
+            tenant_id: credential.tenant_id.into(),
+            user_id: credential.user_id.into(),
+            credential_id: credential.credential_id,
+            public_key: credential.public_key,
+            sign_count: credential.sign_count as i64,
+            name: credential.name,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
+impl From<WebAuthnCredentialModel> for WebAuthnCredential {
+    fn from(model: WebAuthnCredentialModel) -> Self {
+        WebAuthnCredential {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            user_id: model.user_id.into(),
+            credential_id: model.credential_id,
+            public_key: model.public_key,
+            sign_count: model.sign_count as u32,
+            name: model.name,
+            created_at: model.created_at,
+            last_used_at: model.last_used_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct WebAuthnChallengeModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub ceremony: String,
+    pub challenge: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<WebAuthnChallenge> for WebAuthnChallengeModel {
+    fn from(challenge: WebAuthnChallenge) -> Self {
+        Self {
+            id: challenge.id,
+            tenant_id: challenge.tenant_id.into(),
+            user_id: challenge.user_id.map(Uuid::from),
+            ceremony: challenge.ceremony.as_str().to_string(),
+            challenge: challenge.challenge,
+            expires_at: challenge.expires_at,
+            consumed_at: challenge.consumed_at,
+            created_at: challenge.created_at,
+        }
+    }
+}
+impl From<WebAuthnChallengeModel> for WebAuthnChallenge {
+    fn from(model: WebAuthnChallengeModel) -> Self {
+        WebAuthnChallenge {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            user_id: model.user_id.map(UserId::from),
+            ceremony: model
+                .ceremony
+                .parse::<WebAuthnCeremony>()
+                .expect("Invalid WebAuthn ceremony"),
+            challenge: model.challenge,
+            expires_at: model.expires_at,
+            consumed_at: model.consumed_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct OAuthClientModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub client_id: String,
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<OAuthClient> for OAuthClientModel {
+    fn from(client: OAuthClient) -> Self {
+        Self {
+            id: client.id,
+            tenant_id: client.tenant_id.into(),
+            client_id: client.client_id,
+            client_secret_hash: client.client_secret_hash,
+            name: client.name,
+            redirect_uris: client.redirect_uris,
+            created_at: client.created_at,
+        }
+    }
+}
+impl From<OAuthClientModel> for OAuthClient {
+    fn from(model: OAuthClientModel) -> Self {
+        OAuthClient {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            client_id: model.client_id,
+            client_secret_hash: model.client_secret_hash,
+            name: model.name,
+            redirect_uris: model.redirect_uris,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AuthorizationCodeModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub client_id: String,
+    pub code_hash: String,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub nonce: Option<String>,
+    pub code_challenge: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<AuthorizationCode> for AuthorizationCodeModel {
+    fn from(code: AuthorizationCode) -> Self {
+        Self {
+            id: code.id,
+            tenant_id: code.tenant_id.into(),
+            user_id: code.user_id.into(),
+            client_id: code.client_id,
+            code_hash: code.code_hash,
+            redirect_uri: code.redirect_uri,
+            scope: code.scope,
+            nonce: code.nonce,
+            code_challenge: code.code_challenge,
+            expires_at: code.expires_at,
+            consumed_at: code.consumed_at,
+            created_at: code.created_at,
+        }
+    }
+}
+impl From<AuthorizationCodeModel> for AuthorizationCode {
+    fn from(model: AuthorizationCodeModel) -> Self {
+        AuthorizationCode {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            user_id: model.user_id.into(),
+            client_id: model.client_id,
+            code_hash: model.code_hash,
+            redirect_uri: model.redirect_uri,
+            scope: model.scope,
+            nonce: model.nonce,
+            code_challenge: model.code_challenge,
+            expires_at: model.expires_at,
+            consumed_at: model.consumed_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct FederatedLoginStateModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub provider_id: String,
+    pub state_hash: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<FederatedLoginState> for FederatedLoginStateModel {
+    fn from(state: FederatedLoginState) -> Self {
+        Self {
+            id: state.id,
+            tenant_id: state.tenant_id.into(),
+            provider_id: state.provider_id,
+            state_hash: state.state_hash,
+            nonce: state.nonce,
+            code_verifier: state.code_verifier,
+            expires_at: state.expires_at,
+            consumed_at: state.consumed_at,
+            created_at: state.created_at,
+        }
+    }
+}
+impl From<FederatedLoginStateModel> for FederatedLoginState {
+    fn from(model: FederatedLoginStateModel) -> Self {
+        FederatedLoginState {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            provider_id: model.provider_id,
+            state_hash: model.state_hash,
+            nonce: model.nonce,
+            code_verifier: model.code_verifier,
+            expires_at: model.expires_at,
+            consumed_at: model.consumed_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct UserIdentityModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub provider_id: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+impl From<UserIdentity> for UserIdentityModel {
+    fn from(identity: UserIdentity) -> Self {
+        Self {
+            id: identity.id,
+            tenant_id: identity.tenant_id.into(),
+            user_id: identity.user_id.into(),
+            provider_id: identity.provider_id,
+            subject: identity.subject,
+            email: identity.email,
+            created_at: identity.created_at,
+            last_login_at: identity.last_login_at,
+        }
+    }
+}
+impl From<UserIdentityModel> for UserIdentity {
+    fn from(model: UserIdentityModel) -> Self {
+        UserIdentity {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            user_id: model.user_id.into(),
+            provider_id: model.provider_id,
+            subject: model.subject,
+            email: model.email,
+            created_at: model.created_at,
+            last_login_at: model.last_login_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct ApiKeyModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_by: Uuid,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<ApiKey> for ApiKeyModel {
+    fn from(api_key: ApiKey) -> Self {
+        Self {
+            id: api_key.id,
+            tenant_id: api_key.tenant_id.into(),
+            name: api_key.name,
+            prefix: api_key.prefix,
+            key_hash: api_key.key_hash,
+            scopes: api_key.scopes,
+            created_by: api_key.created_by.into(),
+            expires_at: api_key.expires_at,
+            last_used_at: api_key.last_used_at,
+            revoked_at: api_key.revoked_at,
+            created_at: api_key.created_at,
+        }
+    }
+}
+impl From<ApiKeyModel> for ApiKey {
+    fn from(model: ApiKeyModel) -> Self {
+        ApiKey {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            name: model.name,
+            prefix: model.prefix,
+            key_hash: model.key_hash,
+            scopes: model.scopes,
+            created_by: model.created_by.into(),
+            expires_at: model.expires_at,
+            last_used_at: model.last_used_at,
+            revoked_at: model.revoked_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AuditEventModel {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub action: String,
+    pub actor_id: Uuid,
+    pub subject_id: Option<Uuid>,
+    pub details: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+impl From<AuditEvent> for AuditEventModel {
+    fn from(event: AuditEvent) -> Self {
+        Self {
+            id: event.id,
+            tenant_id: event.tenant_id.into(),
+            action: event.action,
+            actor_id: event.actor_id.into(),
+            subject_id: event.subject_id.map(Into::into),
+            details: event.details,
+            occurred_at: event.occurred_at,
+        }
+    }
+}
+impl From<AuditEventModel> for AuditEvent {
+    fn from(model: AuditEventModel) -> Self {
+        AuditEvent {
+            id: model.id,
+            tenant_id: model.tenant_id.into(),
+            action: model.action,
+            actor_id: model.actor_id.into(),
+            subject_id: model.subject_id.map(Into::into),
+            details: model.details,
+            occurred_at: model.occurred_at,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct SigningKeyModel {
+    pub kid: String,
+    pub algorithm: String,
+    pub status: String,
+    pub sealed_private_key: Vec<u8>,
+    pub public_key_pem: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+impl From<SigningKey> for SigningKeyModel {
+    fn from(key: SigningKey) -> Self {
+        Self {
+            kid: key.kid,
+            algorithm: key.algorithm,
+            status: key.status.as_str().to_string(),
+            sealed_private_key: key.sealed_private_key,
+            public_key_pem: key.public_key_pem,
+            created_at: key.created_at,
+            activated_at: key.activated_at,
+            retired_at: key.retired_at,
+        }
+    }
+}
+impl From<SigningKeyModel> for SigningKey {
+    fn from(model: SigningKeyModel) -> Self {
+        SigningKey {
+            kid: model.kid,
+            algorithm: model.algorithm,
+            status: model.status.parse().expect("Invalid signing key status"),
+            sealed_private_key: model.sealed_private_key,
+            public_key_pem: model.public_key_pem,
+            created_at: model.created_at,
+            activated_at: model.activated_at,
+            retired_at: model.retired_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_model_output_hides_the_password_hash() {
+        let model = UserModel::new(
+            Uuid::now_v7(),
+            Uuid::now_v7(),
+            "alice".into(),
+            "$argon2id$v=19$secret-hash".into(),
+            "alice@example.com".into(),
+            "user".into(),
+        );
+        assert!(model.to_string().contains("alice@example.com"));
+        assert!(!model.to_string().contains("secret-hash"));
+        assert!(!format!("{:?}", model).contains("secret-hash"));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::ApiKeyModel;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::value_objects::TenantId;
+use domain::{ApiKey, repository::ApiKeyRepository};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const COLUMNS: &str = "id, tenant_id, name, prefix, key_hash, scopes, created_by, expires_at, \
+     last_used_at, revoked_at, created_at";
+
+pub struct PgApiKeyRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgApiKeyRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for PgApiKeyRepository {
+    async fn create(&self, api_key: ApiKey) -> Result<()> {
+        let model = ApiKeyModel::from(api_key);
+        sqlx::query(&format!(
+            "INSERT INTO tbl_api_keys ({COLUMNS}) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
+        ))
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.name)
+        .bind(model.prefix)
+        .bind(model.key_hash)
+        .bind(model.scopes)
+        .bind(model.created_by)
+        .bind(model.expires_at)
+        .bind(model.last_used_at)
+        .bind(model.revoked_at)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn find_by_prefix(&self, prefix: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query_as::<_, ApiKeyModel>(&format!(
+            "SELECT {COLUMNS} FROM tbl_api_keys WHERE prefix = $1"
+        ))
+        .bind(prefix)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(ApiKey::from))
+    }
+    async fn list(&self, tenant_id: &TenantId) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query_as::<_, ApiKeyModel>(&format!(
+            "SELECT {COLUMNS} FROM tbl_api_keys WHERE tenant_id = $1 ORDER BY created_at"
+        ))
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows.into_iter().map(ApiKey::from).collect())
+    }
+    async fn revoke(&self, tenant_id: &TenantId, id: Uuid, at: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE tbl_api_keys SET revoked_at = $3 \
+             WHERE id = $1 AND tenant_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .bind(tenant_id.as_uuid())
+        .bind(at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+    async fn record_use(&self, id: Uuid, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE tbl_api_keys SET last_used_at = $2 WHERE id = $1")
+            .bind(id)
+            .bind(at)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::AuditEventModel;
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::{AuditEvent, repository::AuditEventRepository};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+pub struct PgAuditEventRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgAuditEventRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditEventRepository for PgAuditEventRepository {
+    async fn record(&self, event: AuditEvent) -> Result<()> {
+        let model = AuditEventModel::from(event);
+        sqlx::query(
+            "INSERT INTO tbl_audit_events \
+             (id, tenant_id, action, actor_id, subject_id, details, occurred_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.action)
+        .bind(model.actor_id)
+        .bind(model.subject_id)
+        .bind(model.details)
+        .bind(model.occurred_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{FederatedLoginStateModel, UserIdentityModel};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::value_objects::TenantId;
+use domain::{FederatedLoginState, UserIdentity, repository::FederationRepository};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const STATE_COLUMNS: &str = "id, tenant_id, provider_id, state_hash, nonce, code_verifier, \
+     expires_at, consumed_at, created_at";
+const IDENTITY_COLUMNS: &str =
+    "id, tenant_id, user_id, provider_id, subject, email, created_at, last_login_at";
+
+pub struct PgFederationRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgFederationRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FederationRepository for PgFederationRepository {
+    async fn create_login_state(&self, state: FederatedLoginState) -> Result<()> {
+        let model = FederatedLoginStateModel::from(state);
+        sqlx::query(&format!(
+            "INSERT INTO tbl_federated_login_states ({STATE_COLUMNS}) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"
+        ))
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.provider_id)
+        .bind(model.state_hash)
+        .bind(model.nonce)
+        .bind(model.code_verifier)
+        .bind(model.expires_at)
+        .bind(model.consumed_at)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn take_login_state(&self, state_hash: &str) -> Result<Option<FederatedLoginState>> {
+        let row = sqlx::query_as::<_, FederatedLoginStateModel>(&format!(
+            "UPDATE tbl_federated_login_states SET consumed_at = now() \
+             WHERE state_hash = $1 AND consumed_at IS NULL AND expires_at > now() \
+             RETURNING {STATE_COLUMNS}"
+        ))
+        .bind(state_hash)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(FederatedLoginState::from))
+    }
+    async fn find_identity(
+        &self,
+        tenant_id: &TenantId,
+        provider_id: &str,
+        subject: &str,
+    ) -> Result<Option<UserIdentity>> {
+        let row = sqlx::query_as::<_, UserIdentityModel>(&format!(
+            "SELECT {IDENTITY_COLUMNS} FROM tbl_user_identities \
+             WHERE tenant_id = $1 AND provider_id = $2 AND subject = $3"
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(provider_id)
+        .bind(subject)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(UserIdentity::from))
+    }
+    async fn create_identity(&self, identity: UserIdentity) -> Result<()> {
+        let model = UserIdentityModel::from(identity);
+        sqlx::query(&format!(
+            "INSERT INTO tbl_user_identities ({IDENTITY_COLUMNS}) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        ))
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.user_id)
+        .bind(model.provider_id)
+        .bind(model.subject)
+        .bind(model.email)
+        .bind(model.created_at)
+        .bind(model.last_login_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn record_identity_login(&self, id: Uuid, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE tbl_user_identities SET last_login_at = $2 WHERE id = $1")
+            .bind(id)
+            .bind(at)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+}
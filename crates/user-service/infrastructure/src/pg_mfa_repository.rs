@@ -0,0 +1,104 @@
+use crate::{RecoveryCodeModel, TotpEnrollmentModel};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{RecoveryCode, TotpEnrollment, repository::MfaRepository, value_objects::UserId};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+const TOTP_COLUMNS: &str = "user_id, tenant_id, secret, confirmed_at, last_used_step, created_at";
+
+pub struct PgMfaRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgMfaRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MfaRepository for PgMfaRepository {
+    async fn find_totp(&self, user_id: &UserId) -> Result<Option<TotpEnrollment>> {
+        let row = sqlx::query_as::<_, TotpEnrollmentModel>(&format!(
+            "SELECT {TOTP_COLUMNS} FROM tbl_user_totp WHERE user_id = $1"
+        ))
+        .bind(user_id.as_uuid())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(TotpEnrollment::from))
+    }
+    async fn save_totp(&self, enrollment: TotpEnrollment) -> Result<()> {
+        let model = TotpEnrollmentModel::from(enrollment);
+        sqlx::query(
+            "INSERT INTO tbl_user_totp (user_id, tenant_id, secret, created_at) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, \
+             confirmed_at = NULL, last_used_step = NULL, created_at = EXCLUDED.created_at",
+        )
+        .bind(model.user_id)
+        .bind(model.tenant_id)
+        .bind(model.secret)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn confirm_totp(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE tbl_user_totp SET confirmed_at = $2 WHERE user_id = $1")
+            .bind(user_id.as_uuid())
+            .bind(at)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+    async fn record_totp_step(&self, user_id: &UserId, step: u64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE tbl_user_totp SET last_used_step = $2 \
+             WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+        )
+        .bind(user_id.as_uuid())
+        .bind(step as i64)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+    async fn replace_recovery_codes(
+        &self,
+        user_id: &UserId,
+        codes: Vec<RecoveryCode>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM tbl_mfa_recovery_codes WHERE user_id = $1")
+            .bind(user_id.as_uuid())
+            .execute(&mut *tx)
+            .await?;
+        for code in codes {
+            let model = RecoveryCodeModel::from(code);
+            sqlx::query(
+                "INSERT INTO tbl_mfa_recovery_codes (id, user_id, code_hash, created_at) \
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(model.id)
+            .bind(model.user_id)
+            .bind(model.code_hash)
+            .bind(model.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+    async fn use_recovery_code(&self, user_id: &UserId, code_hash: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE tbl_mfa_recovery_codes SET used_at = now() \
+             WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL",
+        )
+        .bind(user_id.as_uuid())
+        .bind(code_hash)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+}
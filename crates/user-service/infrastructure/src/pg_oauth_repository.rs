@@ -0,0 +1,84 @@
+use crate::{AuthorizationCodeModel, OAuthClientModel};
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::{AuthorizationCode, OAuthClient, repository::OAuthRepository};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+const CLIENT_COLUMNS: &str =
+    "id, tenant_id, client_id, client_secret_hash, name, redirect_uris, created_at";
+const CODE_COLUMNS: &str = "id, tenant_id, user_id, client_id, code_hash, redirect_uri, scope, \
+     nonce, code_challenge, expires_at, consumed_at, created_at";
+
+pub struct PgOAuthRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgOAuthRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl OAuthRepository for PgOAuthRepository {
+    async fn create_client(&self, client: OAuthClient) -> Result<()> {
+        let model = OAuthClientModel::from(client);
+        sqlx::query(&format!(
+            "INSERT INTO tbl_oauth_clients ({CLIENT_COLUMNS}) VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        ))
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.client_id)
+        .bind(model.client_secret_hash)
+        .bind(model.name)
+        .bind(model.redirect_uris)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn find_client(&self, client_id: &str) -> Result<Option<OAuthClient>> {
+        let row = sqlx::query_as::<_, OAuthClientModel>(&format!(
+            "SELECT {CLIENT_COLUMNS} FROM tbl_oauth_clients WHERE client_id = $1"
+        ))
+        .bind(client_id)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(OAuthClient::from))
+    }
+    async fn create_authorization_code(&self, code: AuthorizationCode) -> Result<()> {
+        let model = AuthorizationCodeModel::from(code);
+        sqlx::query(
+            "INSERT INTO tbl_oauth_authorization_codes \
+             (id, tenant_id, user_id, client_id, code_hash, redirect_uri, scope, nonce, \
+             code_challenge, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        )
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.user_id)
+        .bind(model.client_id)
+        .bind(model.code_hash)
+        .bind(model.redirect_uri)
+        .bind(model.scope)
+        .bind(model.nonce)
+        .bind(model.code_challenge)
+        .bind(model.expires_at)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn take_authorization_code(&self, code_hash: &str) -> Result<Option<AuthorizationCode>> {
+        let row = sqlx::query_as::<_, AuthorizationCodeModel>(&format!(
+            "UPDATE tbl_oauth_authorization_codes SET consumed_at = now() \
+             WHERE code_hash = $1 AND consumed_at IS NULL AND expires_at > now() \
+             RETURNING {CODE_COLUMNS}"
+        ))
+        .bind(code_hash)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(AuthorizationCode::from))
+    }
+}
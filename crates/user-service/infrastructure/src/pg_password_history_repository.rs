@@ -0,0 +1,65 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    repository::PasswordHistoryRepository,
+    value_objects::{Password, TenantId, UserId},
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+pub struct PgPasswordHistoryRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgPasswordHistoryRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PasswordHistoryRepository for PgPasswordHistoryRepository {
+    async fn list_recent(&self, user_id: &UserId, limit: usize) -> Result<Vec<Password>> {
+        let hashes: Vec<String> = sqlx::query_scalar(
+            "SELECT password_hash FROM tbl_password_history WHERE user_id = $1 \
+             ORDER BY replaced_at DESC LIMIT $2",
+        )
+        .bind(user_id.as_uuid())
+        .bind(limit as i64)
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(hashes.into_iter().map(Password::from_hash).collect())
+    }
+    async fn add(
+        &self,
+        tenant_id: &TenantId,
+        user_id: &UserId,
+        password_hash: &Password,
+        replaced_at: DateTime<Utc>,
+        keep: usize,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO tbl_password_history (tenant_id, user_id, password_hash, replaced_at) \
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(user_id.as_uuid())
+        .bind(password_hash.as_str())
+        .bind(replaced_at)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "DELETE FROM tbl_password_history WHERE user_id = $1 AND id NOT IN \
+             (SELECT id FROM tbl_password_history WHERE user_id = $1 \
+             ORDER BY replaced_at DESC LIMIT $2)",
+        )
+        .bind(user_id.as_uuid())
+        .bind(keep as i64)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
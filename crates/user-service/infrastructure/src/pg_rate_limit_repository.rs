@@ -0,0 +1,61 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::policies::{RateLimit, RateLimitDecision};
+use domain::repository::RateLimitRepository;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// Rate limit state shared by every instance of the service.
+pub struct PgRateLimitRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgRateLimitRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RateLimitRepository for PgRateLimitRepository {
+    async fn check(
+        &self,
+        key: &str,
+        limit: &RateLimit,
+        now: DateTime<Utc>,
+    ) -> Result<RateLimitDecision> {
+        let mut tx = self.pool.begin().await?;
+        // A TAT in the past means a full allowance, so a new key starts at
+        // `now`. Inserting first leaves a row to lock for every caller.
+        sqlx::query(
+            "INSERT INTO tbl_rate_limits (key, tat) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(key)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        let tat: DateTime<Utc> =
+            sqlx::query_scalar("SELECT tat FROM tbl_rate_limits WHERE key = $1 FOR UPDATE")
+                .bind(key)
+                .fetch_one(&mut *tx)
+                .await?;
+        let decision = limit.check(Some(tat), now);
+        if decision.allowed {
+            sqlx::query("UPDATE tbl_rate_limits SET tat = $2 WHERE key = $1")
+                .bind(key)
+                .bind(decision.tat)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(decision)
+    }
+    async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM tbl_rate_limits WHERE tat < $1")
+            .bind(now)
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
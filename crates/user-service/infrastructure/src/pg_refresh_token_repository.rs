@@ -0,0 +1,81 @@
+use crate::RefreshTokenModel;
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::{RefreshToken, repository::RefreshTokenRepository, value_objects::UserId};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const REFRESH_TOKEN_COLUMNS: &str = "id, tenant_id, user_id, family_id, token_hash, expires_at, \
+                                     rotated_at, revoked_at, created_at";
+
+pub struct PgRefreshTokenRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgRefreshTokenRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for PgRefreshTokenRepository {
+    async fn create(&self, token: RefreshToken) -> Result<()> {
+        let model = RefreshTokenModel::from(token);
+        sqlx::query(
+            "INSERT INTO tbl_refresh_tokens \
+             (id, tenant_id, user_id, family_id, token_hash, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.user_id)
+        .bind(model.family_id)
+        .bind(model.token_hash)
+        .bind(model.expires_at)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let row = sqlx::query_as::<_, RefreshTokenModel>(&format!(
+            "SELECT {REFRESH_TOKEN_COLUMNS} FROM tbl_refresh_tokens WHERE token_hash = $1"
+        ))
+        .bind(token_hash)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(RefreshToken::from))
+    }
+    async fn mark_rotated(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE tbl_refresh_tokens SET rotated_at = now() \
+             WHERE id = $1 AND rotated_at IS NULL AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+    async fn revoke_family(&self, family_id: Uuid) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE tbl_refresh_tokens SET revoked_at = now() \
+             WHERE family_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(family_id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+    async fn revoke_all_for_user(&self, user_id: &UserId) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE tbl_refresh_tokens SET revoked_at = now() \
+             WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(user_id.as_uuid())
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,13 +1,21 @@
 use crate::UserModel;
 use anyhow::Result;
 use async_trait::async_trait;
+use base::model::value_objects::UpdatedBy;
+use chrono::{DateTime, Utc};
 use domain::{
     User,
     repository::UserRepositories,
-    value_objects::{UserId, Username},
+    value_objects::{EmailAddress, Password, TenantId, UserId, Username},
 };
 use sqlx::PgPool;
 use std::sync::Arc;
+use uuid::Uuid;
+
+const USER_COLUMNS: &str = "id, tenant_id, username, password_hash, email, email_verified, \
+                            email_verified_at, role, failed_login_attempts, locked_util, \
+                            password_changed_at, last_login_at, created_at, created_by, \
+                            updated_at, updated_by";
 
 pub struct PgUserRepository {
     pool: Arc<PgPool>,
@@ -24,21 +32,140 @@ impl UserRepositories for PgUserRepository {
     async fn create(&self, user: User) -> Result<UserId> {
         let user_model = UserModel::from(user.clone());
         sqlx::query(
-            "INSERT INTO tbl_users (id, username, password, email)  VALUES ($1, $2, $3, $4)",
+            "INSERT INTO tbl_users (id, tenant_id, username, password_hash, email, role, created_by) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
         )
         .bind(user_model.id)
+        .bind(user_model.tenant_id)
         .bind(user_model.username)
-        .bind(user_model.password)
+        .bind(user_model.password_hash.expose_secret())
         .bind(user_model.email)
+        .bind(user_model.role)
+        .bind(user_model.created_by)
         .execute(&*self.pool)
         .await?;
         Ok(user.id)
     }
     async fn find_by_id(&self, username: &Username) -> Result<User> {
-        let row = sqlx::query_as::<_, UserModel>("SELECT * FROM tbl_users WHERE username = $1")
-            .bind(username.as_str())
-            .fetch_one(&*self.pool)
-            .await?;
+        let row = sqlx::query_as::<_, UserModel>(&format!(
+            "SELECT {USER_COLUMNS} FROM tbl_users WHERE username = $1 AND deleted_at IS NULL"
+        ))
+        .bind(username.as_str())
+        .fetch_one(&*self.pool)
+        .await?;
         Ok(User::from(row))
     }
+    async fn find_by_user_id(&self, user_id: &UserId) -> Result<Option<User>> {
+        let row = sqlx::query_as::<_, UserModel>(&format!(
+            "SELECT {USER_COLUMNS} FROM tbl_users WHERE id = $1 AND deleted_at IS NULL"
+        ))
+        .bind(user_id.as_uuid())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(User::from))
+    }
+    async fn find_by_email(
+        &self,
+        tenant_id: &TenantId,
+        email: &EmailAddress,
+    ) -> Result<Option<User>> {
+        let row = sqlx::query_as::<_, UserModel>(&format!(
+            "SELECT {USER_COLUMNS} FROM tbl_users \
+             WHERE tenant_id = $1 AND email = $2 AND deleted_at IS NULL"
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(email.as_str())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(User::from))
+    }
+    async fn find_by_username(
+        &self,
+        tenant_id: &TenantId,
+        username: &Username,
+    ) -> Result<Option<User>> {
+        let row = sqlx::query_as::<_, UserModel>(&format!(
+            "SELECT {USER_COLUMNS} FROM tbl_users \
+             WHERE tenant_id = $1 AND username = $2 AND deleted_at IS NULL"
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(username.as_str())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(User::from))
+    }
+    async fn record_failed_login(&self, user_id: &UserId) -> Result<u32> {
+        let attempts: i32 = sqlx::query_scalar(
+            "UPDATE tbl_users SET failed_login_attempts = failed_login_attempts + 1 \
+             WHERE id = $1 RETURNING failed_login_attempts",
+        )
+        .bind(user_id.as_uuid())
+        .fetch_one(&*self.pool)
+        .await?;
+        Ok(attempts.max(0) as u32)
+    }
+    async fn lock_until(&self, user_id: &UserId, until: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE tbl_users SET locked_util = $2 WHERE id = $1")
+            .bind(user_id.as_uuid())
+            .bind(until)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+    async fn clear_lockout(&self, user_id: &UserId, updated_by: Option<&UpdatedBy>) -> Result<()> {
+        let updated_by = updated_by
+            .map(|by| Uuid::parse_str(by.as_str()))
+            .transpose()?;
+        sqlx::query(
+            "UPDATE tbl_users SET failed_login_attempts = 0, locked_util = NULL, \
+             updated_by = COALESCE($2, updated_by) WHERE id = $1",
+        )
+        .bind(user_id.as_uuid())
+        .bind(updated_by)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn mark_email_verified(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            "UPDATE tbl_users SET email_verified = true, email_verified_at = $2 WHERE id = $1",
+        )
+        .bind(user_id.as_uuid())
+        .bind(at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn update_password(
+        &self,
+        user_id: &UserId,
+        password_hash: &Password,
+        changed_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE tbl_users SET password_hash = $2, password_changed_at = $3 WHERE id = $1",
+        )
+        .bind(user_id.as_uuid())
+        .bind(password_hash.as_str())
+        .bind(changed_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn rehash_password(&self, user_id: &UserId, password_hash: &Password) -> Result<()> {
+        sqlx::query("UPDATE tbl_users SET password_hash = $2 WHERE id = $1")
+            .bind(user_id.as_uuid())
+            .bind(password_hash.as_str())
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+    async fn record_login(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE tbl_users SET last_login_at = $2 WHERE id = $1")
+            .bind(user_id.as_uuid())
+            .bind(at)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,108 @@
+use crate::SessionModel;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{Session, repository::SessionRepository, value_objects::UserId};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const SESSION_COLUMNS: &str = "id, tenant_id, user_id, user_agent, ip_address, mfa_verified, \
+                               created_at, last_seen_at, revoked_at";
+
+pub struct PgSessionRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgSessionRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for PgSessionRepository {
+    async fn create(&self, session: Session) -> Result<()> {
+        let model = SessionModel::from(session);
+        sqlx::query(
+            "INSERT INTO tbl_sessions \
+             (id, tenant_id, user_id, user_agent, ip_address, mfa_verified, created_at, \
+             last_seen_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.user_id)
+        .bind(model.user_agent)
+        .bind(model.ip_address)
+        .bind(model.mfa_verified)
+        .bind(model.created_at)
+        .bind(model.last_seen_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn find(&self, id: Uuid) -> Result<Option<Session>> {
+        let row = sqlx::query_as::<_, SessionModel>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM tbl_sessions WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(Session::from))
+    }
+    async fn list_active(
+        &self,
+        user_id: &UserId,
+        seen_after: DateTime<Utc>,
+    ) -> Result<Vec<Session>> {
+        let rows = sqlx::query_as::<_, SessionModel>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM tbl_sessions \
+             WHERE user_id = $1 AND revoked_at IS NULL AND last_seen_at > $2 \
+             ORDER BY last_seen_at DESC"
+        ))
+        .bind(user_id.as_uuid())
+        .bind(seen_after)
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Session::from).collect())
+    }
+    async fn touch(&self, id: Uuid, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE tbl_sessions SET last_seen_at = $2 WHERE id = $1")
+            .bind(id)
+            .bind(at)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+    async fn revoke(&self, id: Uuid, at: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE tbl_sessions SET revoked_at = $2 WHERE id = $1 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .bind(at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+    async fn revoke_all_for_user(&self, user_id: &UserId, at: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            "UPDATE tbl_sessions SET revoked_at = $2 \
+             WHERE user_id = $1 AND revoked_at IS NULL RETURNING id",
+        )
+        .bind(user_id.as_uuid())
+        .bind(at)
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(ids)
+    }
+    async fn list_revoked_since(&self, cutoff: DateTime<Utc>) -> Result<Vec<Session>> {
+        let rows = sqlx::query_as::<_, SessionModel>(&format!(
+            "SELECT {SESSION_COLUMNS} FROM tbl_sessions WHERE revoked_at > $1"
+        ))
+        .bind(cutoff)
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Session::from).collect())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::SigningKeyModel;
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{SigningKey, repository::SigningKeyRepository};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::sync::Arc;
+
+const COLUMNS: &str = "kid, algorithm, status, sealed_private_key, public_key_pem, created_at, \
+     activated_at, retired_at";
+
+pub struct PgSigningKeyRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgSigningKeyRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    async fn insert(tx: &mut Transaction<'_, Postgres>, key: SigningKey) -> Result<()> {
+        let model = SigningKeyModel::from(key);
+        sqlx::query(&format!(
+            "INSERT INTO tbl_signing_keys ({COLUMNS}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        ))
+        .bind(model.kid)
+        .bind(model.algorithm)
+        .bind(model.status)
+        .bind(model.sealed_private_key)
+        .bind(model.public_key_pem)
+        .bind(model.created_at)
+        .bind(model.activated_at)
+        .bind(model.retired_at)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SigningKeyRepository for PgSigningKeyRepository {
+    async fn list(&self) -> Result<Vec<SigningKey>> {
+        let rows = sqlx::query_as::<_, SigningKeyModel>(&format!(
+            "SELECT {COLUMNS} FROM tbl_signing_keys ORDER BY created_at"
+        ))
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows.into_iter().map(SigningKey::from).collect())
+    }
+    async fn initialize(&self, active: SigningKey, next: SigningKey) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        // Instances starting together must not each create keys.
+        sqlx::query("LOCK TABLE tbl_signing_keys IN EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM tbl_signing_keys)")
+            .fetch_one(&mut *tx)
+            .await?;
+        if exists {
+            return Ok(false);
+        }
+        Self::insert(&mut tx, active).await?;
+        Self::insert(&mut tx, next).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+    async fn rotate(&self, active_kid: &str, next: SigningKey, at: DateTime<Utc>) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let retired = sqlx::query(
+            "UPDATE tbl_signing_keys SET status = 'retired', retired_at = $2 \
+             WHERE kid = $1 AND status = 'active'",
+        )
+        .bind(active_kid)
+        .bind(at)
+        .execute(&mut *tx)
+        .await?;
+        if retired.rows_affected() == 0 {
+            return Ok(false);
+        }
+        let activated = sqlx::query(
+            "UPDATE tbl_signing_keys SET status = 'active', activated_at = $1 \
+             WHERE status = 'next'",
+        )
+        .bind(at)
+        .execute(&mut *tx)
+        .await?;
+        if activated.rows_affected() != 1 {
+            bail!("There is no next signing key to activate");
+        }
+        Self::insert(&mut tx, next).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+    async fn delete_retired_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM tbl_signing_keys WHERE status = 'retired' AND retired_at < $1",
+        )
+        .bind(cutoff)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::{
+    repository::TenantSettingsRepository,
+    value_objects::{TenantId, TenantSettings},
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+pub struct PgTenantSettingsRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgTenantSettingsRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TenantSettingsRepository for PgTenantSettingsRepository {
+    async fn find_settings(&self, tenant_id: &TenantId) -> Result<Option<TenantSettings>> {
+        let setting: Option<serde_json::Value> = sqlx::query_scalar(
+            "SELECT setting FROM tbl_tenants WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&*self.pool)
+        .await?;
+        setting
+            .map(TenantSettings::from_json)
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn find_name(&self, tenant_id: &TenantId) -> Result<Option<String>> {
+        let name =
+            sqlx::query_scalar("SELECT name FROM tbl_tenants WHERE id = $1 AND deleted_at IS NULL")
+                .bind(tenant_id.as_uuid())
+                .fetch_optional(&*self.pool)
+                .await?;
+        Ok(name)
+    }
+}
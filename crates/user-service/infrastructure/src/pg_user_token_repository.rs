@@ -0,0 +1,93 @@
+use crate::UserTokenModel;
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::{TokenPurpose, UserToken, repository::UserTokenRepository, value_objects::UserId};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const USER_TOKEN_COLUMNS: &str =
+    "id, tenant_id, user_id, purpose, token_hash, expires_at, consumed_at, created_at";
+
+pub struct PgUserTokenRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgUserTokenRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserTokenRepository for PgUserTokenRepository {
+    async fn create(&self, token: UserToken) -> Result<()> {
+        let model = UserTokenModel::from(token);
+        sqlx::query(
+            "INSERT INTO tbl_user_tokens \
+             (id, tenant_id, user_id, purpose, token_hash, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.user_id)
+        .bind(model.purpose)
+        .bind(model.token_hash)
+        .bind(model.expires_at)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn find_by_hash(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &str,
+    ) -> Result<Option<UserToken>> {
+        let row = sqlx::query_as::<_, UserTokenModel>(&format!(
+            "SELECT {USER_TOKEN_COLUMNS} FROM tbl_user_tokens \
+             WHERE purpose = $1 AND token_hash = $2"
+        ))
+        .bind(purpose.as_str())
+        .bind(token_hash)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(UserToken::from))
+    }
+    async fn consume(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE tbl_user_tokens SET consumed_at = now() \
+             WHERE id = $1 AND consumed_at IS NULL AND expires_at > now()",
+        )
+        .bind(id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+    async fn find_latest(
+        &self,
+        user_id: &UserId,
+        purpose: TokenPurpose,
+    ) -> Result<Option<UserToken>> {
+        let row = sqlx::query_as::<_, UserTokenModel>(&format!(
+            "SELECT {USER_TOKEN_COLUMNS} FROM tbl_user_tokens \
+             WHERE user_id = $1 AND purpose = $2 ORDER BY created_at DESC LIMIT 1"
+        ))
+        .bind(user_id.as_uuid())
+        .bind(purpose.as_str())
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(UserToken::from))
+    }
+    async fn consume_all(&self, user_id: &UserId, purpose: TokenPurpose) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE tbl_user_tokens SET consumed_at = now() \
+             WHERE user_id = $1 AND purpose = $2 AND consumed_at IS NULL",
+        )
+        .bind(user_id.as_uuid())
+        .bind(purpose.as_str())
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
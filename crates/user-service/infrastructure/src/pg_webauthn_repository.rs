@@ -0,0 +1,129 @@
+use crate::{WebAuthnChallengeModel, WebAuthnCredentialModel};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    WebAuthnChallenge, WebAuthnCredential,
+    repository::WebAuthnRepository,
+    value_objects::{TenantId, UserId},
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const CREDENTIAL_COLUMNS: &str = "id, tenant_id, user_id, credential_id, public_key, sign_count, \
+     name, created_at, last_used_at";
+const CHALLENGE_COLUMNS: &str =
+    "id, tenant_id, user_id, ceremony, challenge, expires_at, consumed_at, created_at";
+
+pub struct PgWebAuthnRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgWebAuthnRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebAuthnRepository for PgWebAuthnRepository {
+    async fn create_credential(&self, credential: WebAuthnCredential) -> Result<()> {
+        let model = WebAuthnCredentialModel::from(credential);
+        sqlx::query(
+            "INSERT INTO tbl_webauthn_credentials \
+             (id, tenant_id, user_id, credential_id, public_key, sign_count, name, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.user_id)
+        .bind(model.credential_id)
+        .bind(model.public_key)
+        .bind(model.sign_count)
+        .bind(model.name)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn find_credential(
+        &self,
+        tenant_id: &TenantId,
+        credential_id: &[u8],
+    ) -> Result<Option<WebAuthnCredential>> {
+        let row = sqlx::query_as::<_, WebAuthnCredentialModel>(&format!(
+            "SELECT {CREDENTIAL_COLUMNS} FROM tbl_webauthn_credentials \
+             WHERE tenant_id = $1 AND credential_id = $2"
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(credential_id)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(WebAuthnCredential::from))
+    }
+    async fn list_credentials(&self, user_id: &UserId) -> Result<Vec<WebAuthnCredential>> {
+        let rows = sqlx::query_as::<_, WebAuthnCredentialModel>(&format!(
+            "SELECT {CREDENTIAL_COLUMNS} FROM tbl_webauthn_credentials \
+             WHERE user_id = $1 ORDER BY created_at"
+        ))
+        .bind(user_id.as_uuid())
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows.into_iter().map(WebAuthnCredential::from).collect())
+    }
+    async fn record_credential_use(
+        &self,
+        id: Uuid,
+        sign_count: u32,
+        used_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE tbl_webauthn_credentials SET sign_count = $2, last_used_at = $3 WHERE id = $1",
+        )
+        .bind(id)
+        .bind(sign_count as i64)
+        .bind(used_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn delete_credential(&self, user_id: &UserId, id: Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM tbl_webauthn_credentials WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id.as_uuid())
+                .execute(&*self.pool)
+                .await?;
+        Ok(result.rows_affected() == 1)
+    }
+    async fn create_challenge(&self, challenge: WebAuthnChallenge) -> Result<()> {
+        let model = WebAuthnChallengeModel::from(challenge);
+        sqlx::query(
+            "INSERT INTO tbl_webauthn_challenges \
+             (id, tenant_id, user_id, ceremony, challenge, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(model.id)
+        .bind(model.tenant_id)
+        .bind(model.user_id)
+        .bind(model.ceremony)
+        .bind(model.challenge)
+        .bind(model.expires_at)
+        .bind(model.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+    async fn take_challenge(&self, id: Uuid) -> Result<Option<WebAuthnChallenge>> {
+        let row = sqlx::query_as::<_, WebAuthnChallengeModel>(&format!(
+            "UPDATE tbl_webauthn_challenges SET consumed_at = now() \
+             WHERE id = $1 AND consumed_at IS NULL AND expires_at > now() \
+             RETURNING {CHALLENGE_COLUMNS}"
+        ))
+        .bind(id)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(row.map(WebAuthnChallenge::from))
+    }
+}
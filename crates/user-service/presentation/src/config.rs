@@ -1,11 +1,31 @@
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result, anyhow};
+use application::access_control::AccessControl;
+use application::api_key_service::ApiKeySettings;
+use application::auth_service::AuthSettings;
+use application::federation_service::FederationSettings;
+use application::hashing_pool::HashingPoolSettings;
+use application::impersonation_service::ImpersonationSettings;
+use application::mfa_service::MfaSettings;
+use application::oidc_service::OidcSettings;
+use application::passkey_service::PasskeySettings;
+use application::password_service::PasswordSettings;
+use application::session_service::SessionSettings;
+use application::signing_key_service::SigningKeySettings;
+use application::user_service::UserSettings;
+use argon2::Algorithm;
+use auth::webauthn::RelyingParty;
+use auth::{JwtAlgorithm, JwtConfig, MasterKey, PolicySet};
+use domain::policies::LockoutPolicy;
+use domain::value_objects::{PasswordHashing, Peppers};
 use serde::Deserialize;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use tracing::info;
 
+use crate::rate_limit::{RateLimitRule, RateLimiter};
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct Env {
     pub database_url: String,
@@ -15,6 +35,216 @@ pub struct Env {
 
     #[serde(default = "default_min_connection")]
     pub min_connection: u32,
+
+    /// One of `HS256`, `RS256` or `EdDSA`.
+    #[serde(default = "default_jwt_algorithm")]
+    pub jwt_algorithm: String,
+
+    /// Shared secret used when `jwt_algorithm` is `HS256`.
+    pub jwt_secret: Option<String>,
+
+    /// PEM files used when `jwt_algorithm` is `RS256` or `EdDSA`.
+    pub jwt_private_key_path: Option<String>,
+    pub jwt_public_key_path: Option<String>,
+
+    #[serde(default = "default_jwt_issuer")]
+    pub jwt_issuer: String,
+
+    #[serde(default = "default_jwt_audience")]
+    pub jwt_audience: String,
+
+    /// Base64 encoded 256-bit key the stored signing keys are sealed under.
+    /// When set, signing keys of `jwt_algorithm` are generated, kept in the
+    /// database and rotated, and the configured key is not used.
+    pub signing_key_master_key: Option<String>,
+
+    #[serde(default = "default_signing_key_rotation_interval_secs")]
+    pub signing_key_rotation_interval_secs: u64,
+
+    /// How long a retired key keeps verifying. Must outlast every token it
+    /// signed.
+    #[serde(default = "default_signing_key_retired_ttl_secs")]
+    pub signing_key_retired_ttl_secs: u64,
+
+    /// How often due rotations are run and keys rotated by other instances
+    /// are picked up.
+    #[serde(default = "default_signing_key_check_interval_secs")]
+    pub signing_key_check_interval_secs: u64,
+
+    #[serde(default = "default_access_token_ttl_secs")]
+    pub access_token_ttl_secs: u64,
+
+    #[serde(default = "default_refresh_token_ttl_secs")]
+    pub refresh_token_ttl_secs: u64,
+
+    /// How often sessions revoked through other instances are loaded, and
+    /// so how long their access tokens may still pass here.
+    #[serde(default = "default_session_revocation_sync_secs")]
+    pub session_revocation_sync_secs: u64,
+
+    /// Consecutive failed logins before an account is locked; 0 disables lockout.
+    #[serde(default = "default_lockout_max_attempts")]
+    pub lockout_max_attempts: u32,
+
+    #[serde(default = "default_lockout_duration_secs")]
+    pub lockout_duration_secs: u64,
+
+    /// Doubles the lock for every further failure, up to `lockout_max_duration_secs`.
+    #[serde(default)]
+    pub lockout_exponential_backoff: bool,
+
+    #[serde(default = "default_lockout_max_duration_secs")]
+    pub lockout_max_duration_secs: u64,
+
+    #[serde(default = "default_email_verification_ttl_secs")]
+    pub email_verification_ttl_secs: u64,
+
+    #[serde(default = "default_email_verification_resend_secs")]
+    pub email_verification_resend_secs: u64,
+
+    /// Front-end page that redeems email verification tokens.
+    #[serde(default = "default_email_verification_url")]
+    pub email_verification_url: String,
+
+    #[serde(default = "default_password_reset_ttl_secs")]
+    pub password_reset_ttl_secs: u64,
+
+    #[serde(default = "default_password_reset_interval_secs")]
+    pub password_reset_interval_secs: u64,
+
+    /// Front-end page that redeems password reset tokens.
+    #[serde(default = "default_password_reset_url")]
+    pub password_reset_url: String,
+
+    /// Time allowed between the password and MFA steps of a login.
+    #[serde(default = "default_mfa_challenge_ttl_secs")]
+    pub mfa_challenge_ttl_secs: u64,
+
+    /// Issuer shown by authenticator apps.
+    #[serde(default = "default_totp_issuer")]
+    pub totp_issuer: String,
+
+    /// Domain passkeys are scoped to; must be the origin's host or a parent of it.
+    #[serde(default = "default_webauthn_rp_id")]
+    pub webauthn_rp_id: String,
+
+    #[serde(default = "default_webauthn_rp_name")]
+    pub webauthn_rp_name: String,
+
+    /// Origin of the front-end that runs the passkey ceremonies.
+    #[serde(default = "default_webauthn_origin")]
+    pub webauthn_origin: String,
+
+    /// Require a PIN or biometric rather than a mere touch.
+    #[serde(default)]
+    pub webauthn_require_user_verification: bool,
+
+    #[serde(default = "default_webauthn_timeout_secs")]
+    pub webauthn_timeout_secs: u64,
+
+    /// Public URL relying parties reach this service at; the OIDC issuer.
+    #[serde(default = "default_oidc_issuer")]
+    pub oidc_issuer: String,
+
+    /// Front-end page that signs users in during an OIDC authorization request.
+    #[serde(default = "default_oidc_login_url")]
+    pub oidc_login_url: String,
+
+    #[serde(default = "default_oidc_authorization_code_ttl_secs")]
+    pub oidc_authorization_code_ttl_secs: u64,
+
+    #[serde(default = "default_oidc_id_token_ttl_secs")]
+    pub oidc_id_token_ttl_secs: u64,
+
+    /// Front-end page corporate identity providers send users back to.
+    #[serde(default = "default_federation_redirect_uri")]
+    pub federation_redirect_uri: String,
+
+    #[serde(default = "default_federation_login_state_ttl_secs")]
+    pub federation_login_state_ttl_secs: u64,
+
+    /// Timeout of calls to an identity provider's token and JWKS endpoints.
+    #[serde(default = "default_federation_http_timeout_secs")]
+    pub federation_http_timeout_secs: u64,
+
+    /// How long an identity provider's signing keys are cached.
+    #[serde(default = "default_federation_jwks_cache_secs")]
+    pub federation_jwks_cache_secs: u64,
+
+    /// How often at most an API key's last use is written back.
+    #[serde(default = "default_api_key_usage_record_interval_secs")]
+    pub api_key_usage_record_interval_secs: u64,
+
+    #[serde(default = "default_impersonation_token_ttl_secs")]
+    pub impersonation_token_ttl_secs: u64,
+
+    /// JSON file of service-wide access policies. The built-in defaults
+    /// apply when unset.
+    pub access_policy_path: Option<String>,
+
+    /// Argon2 variant new password hashes use: `argon2id`, `argon2i` or
+    /// `argon2d`.
+    #[serde(default = "default_password_hash_algorithm")]
+    pub password_hash_algorithm: String,
+
+    /// Argon2 memory cost in KiB.
+    #[serde(default = "default_password_hash_memory_kib")]
+    pub password_hash_memory_kib: u32,
+
+    #[serde(default = "default_password_hash_iterations")]
+    pub password_hash_iterations: u32,
+
+    #[serde(default = "default_password_hash_parallelism")]
+    pub password_hash_parallelism: u32,
+
+    /// When set, a benchmark at startup logs the Argon2 parameters that
+    /// hash a password in about this many milliseconds on this machine.
+    pub password_hash_target_ms: Option<u64>,
+
+    /// Password peppers as `version:base64-key` entries separated by
+    /// commas. The highest version peppers new hashes; older ones keep
+    /// verifying until their hashes are re-peppered at login. Hashes are
+    /// not peppered when neither this nor `password_peppers_path` is set.
+    pub password_peppers: Option<String>,
+
+    /// Secrets file with one `version:base64-key` pepper per line, read
+    /// instead of `password_peppers`.
+    pub password_peppers_path: Option<String>,
+
+    /// Password hashes and verifications run at once. Defaults to the
+    /// number of CPUs.
+    pub password_hashing_max_concurrency: Option<usize>,
+
+    /// How long a request waits for a hashing slot before it is answered
+    /// with 503.
+    #[serde(default = "default_password_hashing_queue_timeout_ms")]
+    pub password_hashing_queue_timeout_ms: u64,
+
+    /// Breached password index made by `build_breached_password_index`.
+    /// New passwords are not screened when unset.
+    pub breached_password_index_path: Option<String>,
+
+    /// Where rate limit state lives: `memory`, per instance, or `postgres`,
+    /// shared by every instance.
+    #[serde(default = "default_rate_limit_backend")]
+    pub rate_limit_backend: String,
+
+    /// JSON file of per-route rate limits. The built-in defaults apply when
+    /// unset.
+    pub rate_limit_path: Option<String>,
+
+    /// How often callers back to their full allowance are forgotten.
+    #[serde(default = "default_rate_limit_purge_interval_secs")]
+    pub rate_limit_purge_interval_secs: u64,
+
+    /// Serve Prometheus metrics at `/metrics`.
+    #[serde(default)]
+    pub metrics_enabled: bool,
+
+    /// Address of the internal listener serving `/metrics`. It is kept off
+    /// the public port, so only what can reach this address can scrape.
+    #[serde(default = "default_metrics_addr")]
+    pub metrics_addr: String,
 }
 
 fn default_max_connection() -> u32 {
@@ -25,6 +255,174 @@ fn default_min_connection() -> u32 {
     5
 }
 
+fn default_jwt_algorithm() -> String {
+    "HS256".to_string()
+}
+
+fn default_jwt_issuer() -> String {
+    "user-service".to_string()
+}
+
+fn default_jwt_audience() -> String {
+    "ddd-rust-application".to_string()
+}
+
+fn default_signing_key_rotation_interval_secs() -> u64 {
+    30 * 24 * 60 * 60
+}
+
+fn default_signing_key_retired_ttl_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_signing_key_check_interval_secs() -> u64 {
+    5 * 60
+}
+
+fn default_access_token_ttl_secs() -> u64 {
+    900
+}
+
+fn default_refresh_token_ttl_secs() -> u64 {
+    30 * 24 * 60 * 60
+}
+
+fn default_session_revocation_sync_secs() -> u64 {
+    10
+}
+
+fn default_lockout_max_attempts() -> u32 {
+    5
+}
+
+fn default_lockout_duration_secs() -> u64 {
+    15 * 60
+}
+
+fn default_lockout_max_duration_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_email_verification_ttl_secs() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_email_verification_resend_secs() -> u64 {
+    60
+}
+
+fn default_email_verification_url() -> String {
+    "http://localhost:3000/verify-email".to_string()
+}
+
+fn default_password_reset_ttl_secs() -> u64 {
+    60 * 60
+}
+
+fn default_password_reset_interval_secs() -> u64 {
+    60
+}
+
+fn default_password_reset_url() -> String {
+    "http://localhost:3000/reset-password".to_string()
+}
+
+fn default_mfa_challenge_ttl_secs() -> u64 {
+    5 * 60
+}
+
+fn default_totp_issuer() -> String {
+    "ddd-rust-application".to_string()
+}
+
+fn default_webauthn_rp_id() -> String {
+    "localhost".to_string()
+}
+
+fn default_webauthn_rp_name() -> String {
+    "ddd-rust-application".to_string()
+}
+
+fn default_webauthn_origin() -> String {
+    "http://localhost:3000".to_string()
+}
+
+fn default_webauthn_timeout_secs() -> u64 {
+    5 * 60
+}
+
+fn default_oidc_issuer() -> String {
+    "http://localhost:3000".to_string()
+}
+
+fn default_oidc_login_url() -> String {
+    "http://localhost:3000/login".to_string()
+}
+
+fn default_oidc_authorization_code_ttl_secs() -> u64 {
+    60
+}
+
+fn default_oidc_id_token_ttl_secs() -> u64 {
+    60 * 60
+}
+
+fn default_federation_redirect_uri() -> String {
+    "http://localhost:3000/login/callback".to_string()
+}
+
+fn default_federation_login_state_ttl_secs() -> u64 {
+    10 * 60
+}
+
+fn default_federation_http_timeout_secs() -> u64 {
+    10
+}
+
+fn default_federation_jwks_cache_secs() -> u64 {
+    60 * 60
+}
+
+fn default_api_key_usage_record_interval_secs() -> u64 {
+    60
+}
+
+fn default_impersonation_token_ttl_secs() -> u64 {
+    15 * 60
+}
+
+fn default_password_hash_algorithm() -> String {
+    "argon2id".to_string()
+}
+
+fn default_password_hash_memory_kib() -> u32 {
+    19 * 1024
+}
+
+fn default_password_hash_iterations() -> u32 {
+    2
+}
+
+fn default_password_hash_parallelism() -> u32 {
+    1
+}
+
+fn default_password_hashing_queue_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_rate_limit_backend() -> String {
+    "memory".to_string()
+}
+
+fn default_rate_limit_purge_interval_secs() -> u64 {
+    5 * 60
+}
+
+fn default_metrics_addr() -> String {
+    "127.0.0.1:9100".to_string()
+}
+
 impl Env {
     pub fn from_env() -> Result<Self, config::ConfigError> {
         dotenvy::dotenv().ok();
@@ -35,6 +433,174 @@ impl Env {
 
         config.try_deserialize()
     }
+
+    pub fn user_settings(&self) -> UserSettings {
+        UserSettings {
+            email_verification_ttl: Duration::from_secs(self.email_verification_ttl_secs),
+            verification_resend_interval: Duration::from_secs(self.email_verification_resend_secs),
+            email_verification_url: self.email_verification_url.clone(),
+        }
+    }
+
+    pub fn password_settings(&self) -> PasswordSettings {
+        PasswordSettings {
+            reset_token_ttl: Duration::from_secs(self.password_reset_ttl_secs),
+            reset_request_interval: Duration::from_secs(self.password_reset_interval_secs),
+            password_reset_url: self.password_reset_url.clone(),
+        }
+    }
+
+    pub fn password_hashing(&self) -> Result<PasswordHashing> {
+        let algorithm: Algorithm = self
+            .password_hash_algorithm
+            .parse()
+            .map_err(|e| anyhow!("Invalid password hash algorithm: {}", e))?;
+        let hashing = PasswordHashing::new(
+            algorithm,
+            self.password_hash_memory_kib,
+            self.password_hash_iterations,
+            self.password_hash_parallelism,
+        )
+        .map_err(|e| anyhow!("Invalid password hash parameters: {}", e))?;
+        let peppers = match (&self.password_peppers_path, &self.password_peppers) {
+            (Some(path), _) => Peppers::parse(
+                &std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read peppers {}", path))?,
+            )?,
+            (None, Some(peppers)) => Peppers::parse(peppers)?,
+            (None, None) => Peppers::default(),
+        };
+        Ok(hashing.with_peppers(peppers))
+    }
+
+    pub fn hashing_pool_settings(&self) -> HashingPoolSettings {
+        let defaults = HashingPoolSettings::default();
+        HashingPoolSettings {
+            max_concurrency: self
+                .password_hashing_max_concurrency
+                .unwrap_or(defaults.max_concurrency),
+            queue_timeout: Duration::from_millis(self.password_hashing_queue_timeout_ms),
+        }
+    }
+
+    pub fn auth_settings(&self) -> AuthSettings {
+        AuthSettings {
+            lockout: LockoutPolicy {
+                max_failed_attempts: self.lockout_max_attempts,
+                lock_duration: chrono::Duration::seconds(self.lockout_duration_secs as i64),
+                exponential_backoff: self.lockout_exponential_backoff,
+                max_lock_duration: chrono::Duration::seconds(self.lockout_max_duration_secs as i64),
+            },
+            mfa_challenge_ttl: Duration::from_secs(self.mfa_challenge_ttl_secs),
+            relying_party: self.relying_party(),
+        }
+    }
+
+    pub fn mfa_settings(&self) -> MfaSettings {
+        MfaSettings {
+            totp_issuer: self.totp_issuer.clone(),
+        }
+    }
+
+    pub fn passkey_settings(&self) -> PasskeySettings {
+        PasskeySettings {
+            relying_party: self.relying_party(),
+        }
+    }
+
+    pub fn oidc_settings(&self) -> OidcSettings {
+        OidcSettings {
+            issuer: self.oidc_issuer.clone(),
+            login_url: self.oidc_login_url.clone(),
+            authorization_code_ttl: Duration::from_secs(self.oidc_authorization_code_ttl_secs),
+            id_token_ttl: Duration::from_secs(self.oidc_id_token_ttl_secs),
+        }
+    }
+
+    pub fn federation_settings(&self) -> FederationSettings {
+        FederationSettings {
+            redirect_uri: self.federation_redirect_uri.clone(),
+            login_state_ttl: Duration::from_secs(self.federation_login_state_ttl_secs),
+        }
+    }
+
+    pub fn api_key_settings(&self) -> ApiKeySettings {
+        ApiKeySettings {
+            usage_record_interval: Duration::from_secs(self.api_key_usage_record_interval_secs),
+        }
+    }
+
+    pub fn impersonation_settings(&self) -> ImpersonationSettings {
+        ImpersonationSettings {
+            token_ttl: Duration::from_secs(self.impersonation_token_ttl_secs),
+        }
+    }
+
+    pub fn access_policies(&self) -> Result<PolicySet> {
+        match &self.access_policy_path {
+            Some(path) => Ok(PolicySet::from_file(path)?),
+            None => Ok(AccessControl::default_policies()),
+        }
+    }
+
+    pub fn rate_limit_rules(&self) -> Result<Vec<RateLimitRule>> {
+        match &self.rate_limit_path {
+            Some(path) => RateLimiter::rules_from_file(path),
+            None => Ok(RateLimiter::default_rules()),
+        }
+    }
+
+    fn relying_party(&self) -> RelyingParty {
+        RelyingParty {
+            id: self.webauthn_rp_id.clone(),
+            name: self.webauthn_rp_name.clone(),
+            origin: self.webauthn_origin.clone(),
+            require_user_verification: self.webauthn_require_user_verification,
+            timeout: Duration::from_secs(self.webauthn_timeout_secs),
+        }
+    }
+
+    /// Builds the token signing configuration, reading PEM keys from disk when needed.
+    pub fn jwt_config(&self) -> Result<JwtConfig> {
+        let algorithm: JwtAlgorithm = self.jwt_algorithm.parse()?;
+        let read_key = |path: &Option<String>| -> Result<Option<String>> {
+            path.as_ref()
+                .map(|p| {
+                    std::fs::read_to_string(p).with_context(|| format!("Failed to read key {}", p))
+                })
+                .transpose()
+        };
+        Ok(JwtConfig {
+            algorithm,
+            secret: self.jwt_secret.clone(),
+            private_key_pem: read_key(&self.jwt_private_key_path)?,
+            public_key_pem: read_key(&self.jwt_public_key_path)?,
+            issuer: self.jwt_issuer.clone(),
+            audience: self.jwt_audience.clone(),
+            access_token_ttl: Duration::from_secs(self.access_token_ttl_secs),
+        })
+    }
+
+    pub fn signing_key_master_key(&self) -> Result<Option<MasterKey>> {
+        Ok(self
+            .signing_key_master_key
+            .as_deref()
+            .map(MasterKey::from_base64)
+            .transpose()?)
+    }
+
+    pub fn session_settings(&self) -> SessionSettings {
+        SessionSettings {
+            refresh_token_ttl: Duration::from_secs(self.refresh_token_ttl_secs),
+        }
+    }
+
+    pub fn signing_key_settings(&self) -> SigningKeySettings {
+        SigningKeySettings {
+            rotation_interval: Duration::from_secs(self.signing_key_rotation_interval_secs),
+            retired_key_ttl: Duration::from_secs(self.signing_key_retired_ttl_secs),
+        }
+    }
 }
 
 pub async fn init_connection(db_url: &str, max_conn: u32, min_conn: u32) -> Result<PgPool> {
@@ -1,13 +0,0 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Deserialize)]
-pub struct UserRequest {
-    pub username: String,
-    pub password: String,
-    pub email: String,
-}
-
-#[derive(Serialize)]
-pub struct UserResponse {
-    pub id: String,
-}
@@ -0,0 +1,99 @@
+use application::auth_service::{AuthTokens, LoginOutcome};
+use base::secret::SecretString;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub tenant_id: String,
+    pub email: String,
+    pub password: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub tenant_id: String,
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: SecretString,
+    pub new_password: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: SecretString,
+    pub new_password: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct ChangeExpiredPasswordRequest {
+    pub password_change_token: SecretString,
+    pub new_password: SecretString,
+}
+
+#[derive(Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub refresh_token: String,
+}
+
+impl From<AuthTokens> for TokenResponse {
+    fn from(tokens: AuthTokens) -> Self {
+        Self {
+            access_token: tokens.access_token.into_exposed(),
+            token_type: tokens.token_type,
+            expires_in: tokens.expires_in,
+            refresh_token: tokens.refresh_token.into_exposed(),
+        }
+    }
+}
+
+/// Body of a login response. `status` tells the client whether it got tokens
+/// or has to continue with an MFA step using `mfa_token`, or with a password
+/// change using `password_change_token`.
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LoginResponse {
+    Authenticated(TokenResponse),
+    MfaRequired {
+        mfa_token: String,
+        expires_in: i64,
+    },
+    MfaEnrollmentRequired {
+        mfa_token: String,
+        expires_in: i64,
+    },
+    PasswordChangeRequired {
+        password_change_token: String,
+        expires_in: i64,
+    },
+}
+
+impl From<LoginOutcome> for LoginResponse {
+    fn from(outcome: LoginOutcome) -> Self {
+        match outcome {
+            LoginOutcome::Authenticated(tokens) => Self::Authenticated(tokens.into()),
+            LoginOutcome::MfaRequired(challenge) => Self::MfaRequired {
+                mfa_token: challenge.mfa_token.into_exposed(),
+                expires_in: challenge.expires_in,
+            },
+            LoginOutcome::MfaEnrollmentRequired(challenge) => Self::MfaEnrollmentRequired {
+                mfa_token: challenge.mfa_token.into_exposed(),
+                expires_in: challenge.expires_in,
+            },
+            LoginOutcome::PasswordChangeRequired(challenge) => Self::PasswordChangeRequired {
+                password_change_token: challenge.mfa_token.into_exposed(),
+                expires_in: challenge.expires_in,
+            },
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct StartFederatedLoginRequest {
+    pub tenant_id: String,
+    /// Id of the identity provider in the tenant's settings.
+    pub provider: String,
+}
+
+#[derive(Serialize)]
+pub struct StartFederatedLoginResponse {
+    /// Where to send the browser to sign in at the identity provider.
+    pub authorization_url: String,
+}
+
+/// What the identity provider appended to the redirect URI.
+#[derive(Deserialize)]
+pub struct FinishFederatedLoginRequest {
+    pub state: String,
+    pub code: String,
+}
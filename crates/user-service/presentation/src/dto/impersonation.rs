@@ -0,0 +1,28 @@
+use application::impersonation_service::ImpersonationToken;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct StartImpersonationRequest {
+    pub user_id: String,
+    /// Why the admin needs to act as the user, kept with the audit event.
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct ImpersonationResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub session_id: String,
+}
+
+impl From<ImpersonationToken> for ImpersonationResponse {
+    fn from(token: ImpersonationToken) -> Self {
+        Self {
+            access_token: token.access_token.into_exposed(),
+            token_type: token.token_type,
+            expires_in: token.expires_in,
+            session_id: token.session_id,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use application::mfa_service::TotpSetup;
+use base::secret::SecretString;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct VerifyMfaRequest {
+    pub mfa_token: SecretString,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmTotpRequest {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+impl From<TotpSetup> for TotpSetupResponse {
+    fn from(setup: TotpSetup) -> Self {
+        Self {
+            secret: setup.secret.into_exposed(),
+            otpauth_uri: setup.otpauth_uri.into_exposed(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
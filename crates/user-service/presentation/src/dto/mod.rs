@@ -0,0 +1,21 @@
+pub mod api_key;
+pub mod auth;
+pub mod federation;
+pub mod impersonation;
+pub mod mfa;
+pub mod oidc;
+pub mod passkey;
+pub mod session;
+pub mod signing_key;
+pub mod user;
+
+pub use api_key::*;
+pub use auth::*;
+pub use federation::*;
+pub use impersonation::*;
+pub use mfa::*;
+pub use oidc::*;
+pub use passkey::*;
+pub use session::*;
+pub use signing_key::*;
+pub use user::*;
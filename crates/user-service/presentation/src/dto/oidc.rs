@@ -0,0 +1,140 @@
+use application::commands::{AuthorizeCommand, ExchangeAuthorizationCodeCommand};
+use application::oidc_service::{OAuthError, OAuthErrorCode, OidcTokens, RegisteredClient};
+use axum::{
+    Json,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use base::secret::SecretString;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Authorization request parameters, from the query string of `GET
+/// /authorize` or the body the front-end resubmits after sign-in.
+#[derive(Deserialize)]
+pub struct AuthorizeRequest {
+    pub response_type: Option<String>,
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+}
+
+impl From<AuthorizeRequest> for AuthorizeCommand {
+    fn from(request: AuthorizeRequest) -> Self {
+        Self {
+            response_type: request.response_type,
+            client_id: request.client_id,
+            redirect_uri: request.redirect_uri,
+            scope: request.scope,
+            state: request.state,
+            nonce: request.nonce,
+            code_challenge: request.code_challenge,
+            code_challenge_method: request.code_challenge_method,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AuthorizeResponse {
+    /// Where the front-end should send the browser.
+    pub redirect_to: String,
+}
+
+/// Form body of the token endpoint.
+#[derive(Deserialize)]
+pub struct OidcTokenRequest {
+    pub grant_type: Option<String>,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<SecretString>,
+    pub code_verifier: Option<String>,
+}
+
+impl From<OidcTokenRequest> for ExchangeAuthorizationCodeCommand {
+    fn from(request: OidcTokenRequest) -> Self {
+        Self {
+            grant_type: request.grant_type,
+            code: request.code,
+            redirect_uri: request.redirect_uri,
+            client_id: request.client_id,
+            client_secret: request.client_secret,
+            code_verifier: request.code_verifier,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct OidcTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub id_token: String,
+    pub scope: String,
+}
+
+impl From<OidcTokens> for OidcTokenResponse {
+    fn from(tokens: OidcTokens) -> Self {
+        Self {
+            access_token: tokens.access_token.into_exposed(),
+            token_type: tokens.token_type,
+            expires_in: tokens.expires_in,
+            id_token: tokens.id_token.into_exposed(),
+            scope: tokens.scope,
+        }
+    }
+}
+
+/// Token endpoint errors in the body RFC 6749 §5.2 prescribes, which OAuth
+/// client libraries parse, rather than our usual `ApiResponse`.
+pub struct OAuthErrorResponse(pub OAuthError);
+
+impl IntoResponse for OAuthErrorResponse {
+    fn into_response(self) -> Response {
+        let status = match self.0.code {
+            OAuthErrorCode::InvalidClient => StatusCode::UNAUTHORIZED,
+            OAuthErrorCode::ServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        let body = json!({
+            "error": self.0.code.as_str(),
+            "error_description": self.0.description,
+        });
+        (status, [(header::CACHE_CONTROL, "no-store")], Json(body)).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RegisterOAuthClientRequest {
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    #[serde(default)]
+    pub confidential: bool,
+}
+
+#[derive(Serialize)]
+pub struct RegisteredClientResponse {
+    pub client_id: String,
+    /// Only returned once, at registration.
+    pub client_secret: Option<String>,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RegisteredClient> for RegisteredClientResponse {
+    fn from(registered: RegisteredClient) -> Self {
+        Self {
+            client_id: registered.client.client_id,
+            client_secret: registered.client_secret.map(SecretString::into_exposed),
+            name: registered.client.name,
+            redirect_uris: registered.client.redirect_uris,
+            created_at: registered.client.created_at,
+        }
+    }
+}
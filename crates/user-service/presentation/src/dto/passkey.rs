@@ -0,0 +1,60 @@
+use application::passkey_service::PasskeyCeremony;
+use auth::webauthn::{AuthenticationCredential, RegistrationCredential};
+use chrono::{DateTime, Utc};
+use domain::WebAuthnCredential;
+use serde::{Deserialize, Serialize};
+
+/// Options to pass to `navigator.credentials.create()` or `.get()` as
+/// `publicKey`, and the id to post the browser's answer back with.
+#[derive(Serialize)]
+pub struct PasskeyCeremonyResponse<T> {
+    pub ceremony_id: String,
+    pub public_key: T,
+}
+
+impl<T> From<PasskeyCeremony<T>> for PasskeyCeremonyResponse<T> {
+    fn from(ceremony: PasskeyCeremony<T>) -> Self {
+        Self {
+            ceremony_id: ceremony.ceremony_id,
+            public_key: ceremony.options,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FinishPasskeyRegistrationRequest {
+    pub ceremony_id: String,
+    pub name: Option<String>,
+    pub credential: RegistrationCredential,
+}
+
+#[derive(Serialize)]
+pub struct PasskeyResponse {
+    pub id: String,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<WebAuthnCredential> for PasskeyResponse {
+    fn from(credential: WebAuthnCredential) -> Self {
+        Self {
+            id: credential.id.to_string(),
+            name: credential.name,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StartPasskeyLoginRequest {
+    pub tenant_id: String,
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct FinishPasskeyLoginRequest {
+    pub ceremony_id: String,
+    pub credential: AuthenticationCredential,
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use domain::Session;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct SessionResponse {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub mfa_verified: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Whether this is the session of the request's own access token.
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn new(session: Session, current_session_id: Option<&str>) -> Self {
+        let id = session.id.to_string();
+        Self {
+            current: current_session_id == Some(id.as_str()),
+            id,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            mfa_verified: session.mfa_verified,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct RevokedSessionsResponse {
+    pub revoked: usize,
+}
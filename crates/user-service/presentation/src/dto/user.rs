@@ -0,0 +1,55 @@
+use base::secret::SecretString;
+use chrono::{DateTime, Utc};
+use domain::User;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct UserRequest {
+    pub tenant_id: String,
+    pub username: String,
+    pub password: SecretString,
+    pub email: String,
+}
+
+#[derive(Serialize)]
+pub struct UserResponse {
+    pub id: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct ResendVerificationRequest {
+    pub tenant_id: String,
+    pub email: String,
+}
+
+#[derive(Serialize)]
+pub struct UserDetailsResponse {
+    pub id: String,
+    pub tenant_id: String,
+    pub username: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub role: String,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+impl From<User> for UserDetailsResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id.as_str(),
+            tenant_id: user.tenant_id.as_str(),
+            username: user.username.as_str().to_string(),
+            email: user.email_address.as_str().to_string(),
+            email_verified: user.email_verified,
+            role: user.role.to_string(),
+            locked_until: user.locked_until,
+            last_login_at: user.last_login_at,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::marker::PhantomData;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use application::auth_service::MFA_ENROLLMENT_PURPOSE;
+use auth::{AuthState, Principal, bearer_token};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts},
+};
+use base::web::error::AppError;
+use domain::ClientInfo;
+use domain::value_objects::Role;
+
+use crate::handlers::AppState;
+
+/// The user whose MFA enrollment a request manages: either a signed-in user
+/// or one whose login is waiting for a mandatory enrollment, who only holds
+/// an enrollment challenge token. An admin impersonating the user is
+/// refused.
+pub struct MfaSubject {
+    pub user_id: String,
+}
+
+impl FromRequestParts<Arc<AppState>> for MfaSubject {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Ok(principal) = Principal::from_request_parts(parts, state).await {
+            principal.forbid_impersonation()?;
+            return Ok(Self {
+                user_id: principal.user_id,
+            });
+        }
+        let token = bearer_token(&parts.headers)
+            .ok_or_else(|| AppError::Unauthorized("Missing bearer token".into()))?;
+        let claims = state
+            .jwt_service()
+            .verify_challenge_token(token, MFA_ENROLLMENT_PURPOSE)?;
+        Ok(Self {
+            user_id: claims.sub,
+        })
+    }
+}
+
+/// The device a login request came from, recorded with the session it
+/// starts. The address is the peer of the connection.
+pub struct Client(pub ClientInfo);
+
+impl FromRequestParts<Arc<AppState>> for Client {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let ip_address = peer_ip(parts).map(|ip| ip.to_string());
+        Ok(Self(ClientInfo::new(user_agent, ip_address)))
+    }
+}
+
+/// Address of the connection's peer, when the server records it.
+pub fn peer_ip(parts: &Parts) -> Option<IpAddr> {
+    parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// A role a route can demand through [`RequireRole`].
+pub trait RequiredRole {
+    const ROLE: Role;
+}
+
+/// Marker for routes reserved to tenant administrators.
+pub struct Admin;
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// Marker for routes that act across tenants.
+pub struct SuperAdmin;
+
+impl RequiredRole for SuperAdmin {
+    const ROLE: Role = Role::SupperAdmin;
+}
+
+/// Whether the caller holds `required` or a role above it. API keys hold no
+/// role and never do.
+pub fn has_role(principal: &Principal, required: Role) -> bool {
+    principal
+        .role
+        .parse::<Role>()
+        .is_ok_and(|role| role.satisfies(required))
+}
+
+/// A signed-in caller holding at least the role `R`. Rejects anonymous
+/// requests with 401 and callers below `R` with 403.
+pub struct RequireRole<R> {
+    pub principal: Principal,
+    _role: PhantomData<R>,
+}
+
+impl<R: RequiredRole> FromRequestParts<Arc<AppState>> for RequireRole<R> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let principal = Principal::from_request_parts(parts, state).await?;
+        if !has_role(&principal, R::ROLE) {
+            return Err(AppError::Forbidden(format!(
+                "Requires the {} role",
+                R::ROLE
+            )));
+        }
+        Ok(Self {
+            principal,
+            _role: PhantomData,
+        })
+    }
+}
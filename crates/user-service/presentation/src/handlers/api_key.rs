@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use application::commands::{CreateApiKeyCommand, RevokeApiKeyCommand};
+use application::queries::ListApiKeysQuery;
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{ApiKeyResponse, CreateApiKeyRequest, CreatedApiKeyResponse};
+use crate::extractors::{Admin, RequireRole};
+use crate::handlers::AppState;
+
+pub async fn create_api_key_handler(
+    RequireRole { principal, .. }: RequireRole<Admin>,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    principal.forbid_impersonation()?;
+    let command = CreateApiKeyCommand {
+        tenant_id: principal.tenant_id,
+        created_by: principal.user_id,
+        name: request.name,
+        scopes: request.scopes,
+        expires_at: request.expires_at,
+    };
+    let created = app_state.api_key_service.create(command).await?;
+    Ok(ApiResponse::created(CreatedApiKeyResponse::from(created)))
+}
+
+pub async fn list_api_keys_handler(
+    RequireRole { principal, .. }: RequireRole<Admin>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = ListApiKeysQuery {
+        tenant_id: principal.tenant_id,
+    };
+    let api_keys = app_state.api_key_service.list(query).await?;
+    Ok(ApiResponse::ok(
+        api_keys
+            .into_iter()
+            .map(ApiKeyResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+pub async fn revoke_api_key_handler(
+    RequireRole { principal, .. }: RequireRole<Admin>,
+    State(app_state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = RevokeApiKeyCommand {
+        tenant_id: principal.tenant_id,
+        api_key_id: id,
+    };
+    app_state.api_key_service.revoke(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
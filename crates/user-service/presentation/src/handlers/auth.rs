@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use application::commands::{
+    ChangeExpiredPasswordCommand, ChangePasswordCommand, ForgotPasswordCommand, LoginCommand,
+    RefreshTokenCommand, ResetPasswordCommand,
+};
+use auth::Principal;
+use axum::{Json, extract::State, response::IntoResponse};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{
+    ChangeExpiredPasswordRequest, ChangePasswordRequest, ForgotPasswordRequest, LoginRequest,
+    LoginResponse, RefreshRequest, ResetPasswordRequest, TokenResponse,
+};
+use crate::extractors::Client;
+use crate::handlers::AppState;
+
+pub async fn login_handler(
+    Client(client): Client,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<LoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = LoginCommand {
+        tenant_id: request.tenant_id,
+        email: request.email,
+        password: request.password,
+        client,
+    };
+    let outcome = app_state.auth_service.login(command).await?;
+    Ok(ApiResponse::ok(LoginResponse::from(outcome)))
+}
+
+pub async fn refresh_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = RefreshTokenCommand {
+        refresh_token: request.refresh_token,
+    };
+    let tokens = app_state.auth_service.refresh(command).await?;
+    Ok(ApiResponse::ok(TokenResponse::from(tokens)))
+}
+
+pub async fn forgot_password_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<ForgotPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = ForgotPasswordCommand {
+        tenant_id: request.tenant_id,
+        email: request.email,
+    };
+    app_state.password_service.forgot_password(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
+
+pub async fn reset_password_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = ResetPasswordCommand {
+        token: request.token,
+        new_password: request.new_password,
+    };
+    app_state.password_service.reset_password(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
+
+pub async fn change_password_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    principal.forbid_impersonation()?;
+    let command = ChangePasswordCommand {
+        user_id: principal.user_id,
+        current_password: request.current_password,
+        new_password: request.new_password,
+    };
+    app_state.password_service.change_password(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
+
+/// Second step of a login whose password expired. Answers like a login,
+/// since an MFA step may still follow.
+pub async fn change_expired_password_handler(
+    Client(client): Client,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<ChangeExpiredPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = ChangeExpiredPasswordCommand {
+        password_change_token: request.password_change_token,
+        new_password: request.new_password,
+        client,
+    };
+    let outcome = app_state
+        .auth_service
+        .change_expired_password(command)
+        .await?;
+    Ok(ApiResponse::ok(LoginResponse::from(outcome)))
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use application::commands::{FinishFederatedLoginCommand, StartFederatedLoginCommand};
+use axum::{Json, extract::State, response::IntoResponse};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{
+    FinishFederatedLoginRequest, StartFederatedLoginRequest, StartFederatedLoginResponse,
+    TokenResponse,
+};
+use crate::extractors::Client;
+use crate::handlers::AppState;
+
+pub async fn start_federated_login_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<StartFederatedLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = StartFederatedLoginCommand {
+        tenant_id: request.tenant_id,
+        provider: request.provider,
+    };
+    let authorization_url = app_state.federation_service.start(command).await?;
+    Ok(ApiResponse::ok(StartFederatedLoginResponse {
+        authorization_url,
+    }))
+}
+
+pub async fn finish_federated_login_handler(
+    Client(client): Client,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<FinishFederatedLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = FinishFederatedLoginCommand {
+        state: request.state,
+        code: request.code,
+        client,
+    };
+    let tokens = app_state.federation_service.finish(command).await?;
+    Ok(ApiResponse::ok(TokenResponse::from(tokens)))
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use application::commands::{EndImpersonationCommand, StartImpersonationCommand};
+use auth::Principal;
+use axum::{Json, extract::State, response::IntoResponse};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{ImpersonationResponse, StartImpersonationRequest};
+use crate::handlers::AppState;
+
+/// Which users a caller may act as is decided by the access policies.
+pub async fn start_impersonation_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<StartImpersonationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = StartImpersonationCommand {
+        actor: principal,
+        user_id: request.user_id,
+        reason: request.reason,
+    };
+    let token = app_state.impersonation_service.start(command).await?;
+    Ok(ApiResponse::created(ImpersonationResponse::from(token)))
+}
+
+/// Called with the impersonation token itself.
+pub async fn end_impersonation_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = EndImpersonationCommand { actor: principal };
+    app_state.impersonation_service.end(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
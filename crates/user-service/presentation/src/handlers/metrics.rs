@@ -0,0 +1,8 @@
+use axum::extract::State;
+use metrics_exporter_prometheus::PrometheusHandle;
+
+/// Prometheus scrape endpoint, served on the internal metrics listener
+/// only.
+pub async fn metrics_handler(State(handle): State<PrometheusHandle>) -> String {
+    handle.render()
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use application::commands::{ConfirmTotpCommand, EnrollTotpCommand, VerifyMfaCommand};
+use axum::{Json, extract::State, response::IntoResponse};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{
+    ConfirmTotpRequest, RecoveryCodesResponse, TokenResponse, TotpSetupResponse, VerifyMfaRequest,
+};
+use crate::extractors::{Client, MfaSubject};
+use crate::handlers::AppState;
+
+pub async fn verify_mfa_handler(
+    Client(client): Client,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<VerifyMfaRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = VerifyMfaCommand {
+        mfa_token: request.mfa_token,
+        code: request.code,
+        recovery_code: request.recovery_code,
+        client,
+    };
+    let tokens = app_state.auth_service.verify_mfa(command).await?;
+    Ok(ApiResponse::ok(TokenResponse::from(tokens)))
+}
+
+pub async fn enroll_totp_handler(
+    subject: MfaSubject,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = EnrollTotpCommand {
+        user_id: subject.user_id,
+    };
+    let setup = app_state.mfa_service.enroll_totp(command).await?;
+    Ok(ApiResponse::ok(TotpSetupResponse::from(setup)))
+}
+
+pub async fn confirm_totp_handler(
+    subject: MfaSubject,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<ConfirmTotpRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = ConfirmTotpCommand {
+        user_id: subject.user_id,
+        code: request.code,
+    };
+    let recovery_codes = app_state.mfa_service.confirm_totp(command).await?;
+    Ok(ApiResponse::ok(RecoveryCodesResponse { recovery_codes }))
+}
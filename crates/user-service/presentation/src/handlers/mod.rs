@@ -0,0 +1,80 @@
+pub mod api_key;
+pub mod auth;
+pub mod federation;
+pub mod impersonation;
+pub mod metrics;
+pub mod mfa;
+pub mod oidc;
+pub mod passkey;
+pub mod session;
+pub mod signing_key;
+pub mod user;
+
+use std::sync::Arc;
+
+use ::auth::{ApiKeyVerifier, AuthState, JwtService, KeyRefresher, RevocationList};
+use application::api_key_service::ApiKeyApplicationService;
+use application::auth_service::AuthApplicationService;
+use application::federation_service::FederationApplicationService;
+use application::impersonation_service::ImpersonationApplicationService;
+use application::mfa_service::MfaApplicationService;
+use application::oidc_service::OidcApplicationService;
+use application::passkey_service::PasskeyApplicationService;
+use application::password_service::PasswordApplicationService;
+use application::session_service::SessionApplicationService;
+use application::signing_key_service::SigningKeyApplicationService;
+use application::user_service::UserApplicationService;
+use infrastructure::PgUserRepository;
+
+use crate::rate_limit::RateLimiter;
+
+pub use api_key::*;
+pub use auth::*;
+pub use federation::*;
+pub use impersonation::*;
+pub use metrics::*;
+pub use mfa::*;
+pub use oidc::*;
+pub use passkey::*;
+pub use session::*;
+pub use signing_key::*;
+pub use user::*;
+
+pub struct AppState {
+    pub user_service: Arc<UserApplicationService<PgUserRepository>>,
+    pub auth_service: Arc<AuthApplicationService<PgUserRepository>>,
+    pub password_service: Arc<PasswordApplicationService<PgUserRepository>>,
+    pub mfa_service: Arc<MfaApplicationService<PgUserRepository>>,
+    pub passkey_service: Arc<PasskeyApplicationService<PgUserRepository>>,
+    pub oidc_service: Arc<OidcApplicationService<PgUserRepository>>,
+    pub federation_service: Arc<FederationApplicationService<PgUserRepository>>,
+    pub api_key_service: Arc<ApiKeyApplicationService>,
+    pub session_service: Arc<SessionApplicationService<PgUserRepository>>,
+    pub impersonation_service: Arc<ImpersonationApplicationService<PgUserRepository>>,
+    /// `None` when the keys come from the configuration rather than the
+    /// key store.
+    pub signing_key_service: Option<Arc<SigningKeyApplicationService>>,
+    pub jwt: Arc<JwtService>,
+    pub revocations: Arc<RevocationList>,
+    pub rate_limiter: Arc<RateLimiter>,
+}
+
+impl AuthState for AppState {
+    fn jwt_service(&self) -> &JwtService {
+        &self.jwt
+    }
+
+    fn api_key_verifier(&self) -> Option<&dyn ApiKeyVerifier> {
+        Some(self.api_key_service.as_ref())
+    }
+
+    fn key_refresher(&self) -> Option<&dyn KeyRefresher> {
+        self.signing_key_service
+            .as_deref()
+            .map(|service| service as &dyn KeyRefresher)
+    }
+
+    fn revocation_list(&self) -> Option<&RevocationList> {
+        Some(&self.revocations)
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use application::commands::{
+    AuthorizeCommand, ExchangeAuthorizationCodeCommand, RegisterOAuthClientCommand,
+};
+use application::queries::GetUserInfoQuery;
+use auth::oidc::ProviderMetadata;
+use auth::{Principal, bearer_token};
+use axum::{
+    Form, Json,
+    extract::{Query, State},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Redirect},
+};
+use base::{web::error::AppError, web::response::ApiResponse};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::dto::{
+    AuthorizeRequest, AuthorizeResponse, OAuthErrorResponse, OidcTokenRequest, OidcTokenResponse,
+    RegisterOAuthClientRequest, RegisteredClientResponse,
+};
+use crate::extractors::{Admin, RequireRole};
+use crate::handlers::AppState;
+
+pub async fn openid_configuration_handler(
+    State(app_state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(ProviderMetadata::new(
+        app_state.oidc_service.issuer(),
+        app_state.jwt.algorithm(),
+    ))
+}
+
+pub async fn jwks_handler(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(app_state.jwt.jwks())
+}
+
+/// Entry point relying parties send the browser to. The user is not known
+/// here, so valid requests continue at the login page.
+pub async fn authorize_handler(
+    State(app_state): State<Arc<AppState>>,
+    Query(request): Query<AuthorizeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let location = app_state
+        .oidc_service
+        .authorize(AuthorizeCommand::from(request), None)
+        .await?;
+    Ok(Redirect::to(&location))
+}
+
+/// Completes an authorization request for the signed-in user. The front-end
+/// sends the browser to `redirect_to`.
+pub async fn authorize_signed_in_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<AuthorizeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    // Signing in to other applications would carry the impersonation beyond
+    // this service, where the admin behind it is no longer visible.
+    principal.forbid_impersonation()?;
+    let redirect_to = app_state
+        .oidc_service
+        .authorize(AuthorizeCommand::from(request), Some(principal.user_id))
+        .await?;
+    Ok(ApiResponse::ok(AuthorizeResponse { redirect_to }))
+}
+
+pub async fn oidc_token_handler(
+    State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Form(request): Form<OidcTokenRequest>,
+) -> Result<impl IntoResponse, OAuthErrorResponse> {
+    let mut command = ExchangeAuthorizationCodeCommand::from(request);
+    if let Some((client_id, client_secret)) = basic_credentials(&headers) {
+        command.client_id = Some(client_id);
+        command.client_secret = Some(client_secret.into());
+    }
+    let tokens = app_state
+        .oidc_service
+        .exchange_code(command)
+        .await
+        .map_err(OAuthErrorResponse)?;
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(OidcTokenResponse::from(tokens)),
+    ))
+}
+
+pub async fn userinfo_handler(
+    State(app_state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let access_token = bearer_token(&headers)
+        .ok_or_else(|| AppError::Unauthorized("Missing bearer token".into()))?;
+    let userinfo = app_state
+        .oidc_service
+        .userinfo(GetUserInfoQuery {
+            access_token: access_token.to_string(),
+        })
+        .await?;
+    Ok(Json(userinfo))
+}
+
+pub async fn register_oauth_client_handler(
+    RequireRole { principal, .. }: RequireRole<Admin>,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<RegisterOAuthClientRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = RegisterOAuthClientCommand {
+        tenant_id: principal.tenant_id,
+        name: request.name,
+        redirect_uris: request.redirect_uris,
+        confidential: request.confidential,
+    };
+    let registered = app_state.oidc_service.register_client(command).await?;
+    Ok(ApiResponse::created(RegisteredClientResponse::from(
+        registered,
+    )))
+}
+
+/// Client credentials sent with HTTP Basic authentication. RFC 6749 §2.3.1
+/// has both parts form-urlencoded before they are joined.
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, encoded) = value.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (client_id, client_secret) = decoded.split_once(':')?;
+    let decode = |part: &str| {
+        urlencoding::decode(&part.replace('+', " "))
+            .ok()
+            .map(|p| p.into_owned())
+    };
+    Some((decode(client_id)?, decode(client_secret)?))
+}
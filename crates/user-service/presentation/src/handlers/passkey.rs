@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use application::commands::{
+    DeletePasskeyCommand, FinishPasskeyLoginCommand, FinishPasskeyRegistrationCommand,
+    StartPasskeyLoginCommand, StartPasskeyRegistrationCommand,
+};
+use application::queries::ListPasskeysQuery;
+use auth::Principal;
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{
+    FinishPasskeyLoginRequest, FinishPasskeyRegistrationRequest, PasskeyCeremonyResponse,
+    PasskeyResponse, StartPasskeyLoginRequest, TokenResponse,
+};
+use crate::extractors::Client;
+use crate::handlers::AppState;
+
+pub async fn start_passkey_registration_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    principal.forbid_impersonation()?;
+    let command = StartPasskeyRegistrationCommand {
+        user_id: principal.user_id,
+    };
+    let ceremony = app_state
+        .passkey_service
+        .start_registration(command)
+        .await?;
+    Ok(ApiResponse::ok(PasskeyCeremonyResponse::from(ceremony)))
+}
+
+pub async fn finish_passkey_registration_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<FinishPasskeyRegistrationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    principal.forbid_impersonation()?;
+    let command = FinishPasskeyRegistrationCommand {
+        user_id: principal.user_id,
+        ceremony_id: request.ceremony_id,
+        name: request.name,
+        credential: request.credential,
+    };
+    let passkey = app_state
+        .passkey_service
+        .finish_registration(command)
+        .await?;
+    Ok(ApiResponse::created(PasskeyResponse::from(passkey)))
+}
+
+pub async fn list_passkeys_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = ListPasskeysQuery {
+        user_id: principal.user_id,
+    };
+    let passkeys = app_state.passkey_service.list(query).await?;
+    Ok(ApiResponse::ok(
+        passkeys
+            .into_iter()
+            .map(PasskeyResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+pub async fn delete_passkey_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Path(passkey_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    principal.forbid_impersonation()?;
+    let command = DeletePasskeyCommand {
+        user_id: principal.user_id,
+        passkey_id,
+    };
+    app_state.passkey_service.delete(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
+
+pub async fn start_passkey_login_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<StartPasskeyLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = StartPasskeyLoginCommand {
+        tenant_id: request.tenant_id,
+        email: request.email,
+    };
+    let ceremony = app_state.auth_service.start_passkey_login(command).await?;
+    Ok(ApiResponse::ok(PasskeyCeremonyResponse::from(ceremony)))
+}
+
+pub async fn finish_passkey_login_handler(
+    Client(client): Client,
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<FinishPasskeyLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = FinishPasskeyLoginCommand {
+        ceremony_id: request.ceremony_id,
+        credential: request.credential,
+        client,
+    };
+    let tokens = app_state.auth_service.finish_passkey_login(command).await?;
+    Ok(ApiResponse::ok(TokenResponse::from(tokens)))
+}
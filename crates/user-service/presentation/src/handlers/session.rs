@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use application::commands::{RevokeAllSessionsCommand, RevokeSessionCommand};
+use application::queries::ListSessionsQuery;
+use auth::Principal;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{RevokedSessionsResponse, SessionResponse};
+use crate::handlers::AppState;
+
+/// Whose sessions a caller may see and end is decided by the access
+/// policies.
+pub async fn list_sessions_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let current_session_id = principal.session_id().map(str::to_string);
+    let query = ListSessionsQuery {
+        actor: principal,
+        user_id,
+    };
+    let sessions = app_state.session_service.list(query).await?;
+    Ok(ApiResponse::ok(
+        sessions
+            .into_iter()
+            .map(|session| SessionResponse::new(session, current_session_id.as_deref()))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+pub async fn revoke_session_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Path((user_id, session_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = RevokeSessionCommand {
+        actor: principal,
+        user_id,
+        session_id,
+    };
+    app_state.session_service.revoke(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
+
+/// Logs the user out everywhere, the caller's own session included.
+pub async fn revoke_all_sessions_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = RevokeAllSessionsCommand {
+        actor: principal,
+        user_id,
+    };
+    let revoked = app_state.session_service.revoke_all(command).await?;
+    Ok(ApiResponse::ok(RevokedSessionsResponse { revoked }))
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use application::signing_key_service::SigningKeyApplicationService;
+use axum::{extract::State, response::IntoResponse};
+use base::{web::error::AppError, web::response::ApiResponse};
+use domain::SigningKey;
+
+use crate::dto::SigningKeyResponse;
+use crate::extractors::{RequireRole, SuperAdmin};
+use crate::handlers::AppState;
+
+fn signing_key_service(app_state: &AppState) -> Result<&SigningKeyApplicationService, AppError> {
+    app_state
+        .signing_key_service
+        .as_deref()
+        .ok_or_else(|| AppError::NotFound("Signing keys are not kept in a key store".into()))
+}
+
+fn responses(keys: Vec<SigningKey>) -> Vec<SigningKeyResponse> {
+    keys.into_iter().map(SigningKeyResponse::from).collect()
+}
+
+/// Signing keys are shared by every tenant, so only super admins see them.
+pub async fn list_signing_keys_handler(
+    _: RequireRole<SuperAdmin>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let keys = signing_key_service(&app_state)?.list().await?;
+    Ok(ApiResponse::ok(responses(keys)))
+}
+
+pub async fn rotate_signing_keys_handler(
+    RequireRole { principal, .. }: RequireRole<SuperAdmin>,
+    State(app_state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    principal.forbid_impersonation()?;
+    let keys = signing_key_service(&app_state)?.rotate().await?;
+    Ok(ApiResponse::ok(responses(keys)))
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use application::commands::{
+    AddUserCommand, ResendVerificationEmailCommand, UnlockUserCommand, VerifyEmailCommand,
+};
+use application::queries::GetUserByIdQuery;
+use auth::Principal;
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use base::{web::error::AppError, web::response::ApiResponse};
+
+use crate::dto::{
+    ResendVerificationRequest, UserDetailsResponse, UserRequest, UserResponse, VerifyEmailRequest,
+};
+use crate::handlers::AppState;
+
+pub async fn create_user_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<UserRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = AddUserCommand {
+        tenant_id: request.tenant_id,
+        username: request.username,
+        password: request.password,
+        email: request.email,
+    };
+    let user_id = app_state
+        .user_service
+        .create(command)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let response = UserResponse {
+        id: user_id.as_str().to_string(),
+    };
+    Ok(ApiResponse::created(response))
+}
+
+pub async fn verify_email_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<VerifyEmailRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = VerifyEmailCommand {
+        token: request.token,
+    };
+    app_state.user_service.verify_email(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
+
+pub async fn resend_verification_handler(
+    State(app_state): State<Arc<AppState>>,
+    Json(request): Json<ResendVerificationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = ResendVerificationEmailCommand {
+        tenant_id: request.tenant_id,
+        email: request.email,
+    };
+    app_state
+        .user_service
+        .resend_verification_email(command)
+        .await?;
+    Ok(ApiResponse::<()>::no_content())
+}
+
+/// Which users a caller may see is decided by the access policies.
+pub async fn get_user_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = GetUserByIdQuery {
+        actor: principal,
+        user_id,
+    };
+    let user = app_state.user_service.get_by_id(query).await?;
+    Ok(ApiResponse::ok(UserDetailsResponse::from(user)))
+}
+
+pub async fn unlock_user_handler(
+    principal: Principal,
+    State(app_state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let command = UnlockUserCommand {
+        actor: principal,
+        user_id,
+    };
+    app_state.user_service.unlock(command).await?;
+    Ok(ApiResponse::<()>::no_content())
+}
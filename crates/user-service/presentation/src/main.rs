@@ -1,15 +1,61 @@
 mod config;
 mod dto;
+mod extractors;
 mod handlers;
+mod rate_limit;
 
+use application::access_control::AccessControl;
+use application::api_key_service::ApiKeyApplicationService;
+use application::auth_service::AuthApplicationService;
+use application::federation_service::FederationApplicationService;
+use application::hashing_pool::{self, HashingPool};
+use application::impersonation_service::ImpersonationApplicationService;
+use application::mfa_service::MfaApplicationService;
+use application::oidc_service::OidcApplicationService;
+use application::passkey_service::PasskeyApplicationService;
+use application::password_screen::PasswordScreen;
+use application::password_service::PasswordApplicationService;
+use application::session_service::SessionApplicationService;
+use application::signing_key_service::SigningKeyApplicationService;
 use application::user_service::UserApplicationService;
-use axum::{Router, routing::post};
+use auth::{JwtService, RevocationList};
+use axum::{
+    Router, middleware,
+    routing::{delete, get, post},
+};
 use config::Env;
+use domain::repository::RateLimitRepository;
+use domain::services::BreachedPasswords;
+use domain::value_objects::PasswordHashing;
 use handlers::AppState;
-use handlers::create_user_handler;
-use infrastructure::PgUserRepository;
+use handlers::{
+    authorize_handler, authorize_signed_in_handler, change_expired_password_handler,
+    change_password_handler, confirm_totp_handler, create_api_key_handler, create_user_handler,
+    delete_passkey_handler, end_impersonation_handler, enroll_totp_handler,
+    finish_federated_login_handler, finish_passkey_login_handler,
+    finish_passkey_registration_handler, forgot_password_handler, get_user_handler, jwks_handler,
+    list_api_keys_handler, list_passkeys_handler, list_sessions_handler, list_signing_keys_handler,
+    login_handler, metrics_handler, oidc_token_handler, openid_configuration_handler,
+    refresh_handler, register_oauth_client_handler, resend_verification_handler,
+    reset_password_handler, revoke_all_sessions_handler, revoke_api_key_handler,
+    revoke_session_handler, rotate_signing_keys_handler, start_federated_login_handler,
+    start_impersonation_handler, start_passkey_login_handler, start_passkey_registration_handler,
+    unlock_user_handler, userinfo_handler, verify_email_handler, verify_mfa_handler,
+};
+use infrastructure::{
+    BreachedPasswordIndex, HttpIdentityProviderClient, InMemoryRateLimitRepository, LogMailer,
+    PgApiKeyRepository, PgAuditEventRepository, PgFederationRepository, PgMfaRepository,
+    PgOAuthRepository, PgPasswordHistoryRepository, PgRateLimitRepository,
+    PgRefreshTokenRepository, PgSessionRepository, PgSigningKeyRepository,
+    PgTenantSettingsRepository, PgUserRepository, PgUserTokenRepository, PgWebAuthnRepository,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use rate_limit::{RateLimiter, enforce_rate_limit};
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tracing::info;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -20,8 +66,21 @@ async fn main() -> anyhow::Result<()> {
         })
         .unwrap();
     info!("Load configuration successfully.");
+    let metrics = if cfg.metrics_enabled {
+        Some(install_metrics_recorder()?)
+    } else {
+        None
+    };
 
-    let db_url = cfg.database_url;
+    let jwt_config = cfg.jwt_config()?;
+    let jwt = Arc::new(if cfg.signing_key_master_key.is_some() {
+        JwtService::without_keys(jwt_config)
+    } else {
+        JwtService::new(jwt_config)?
+    });
+    info!("Token signing configured with {}", jwt.algorithm());
+
+    let db_url = cfg.database_url.clone();
     let max_conn = cfg.max_connection;
     let min_conn = cfg.min_connection;
     let pool = config::init_connection(&db_url, max_conn, min_conn).await?;
@@ -29,18 +88,389 @@ async fn main() -> anyhow::Result<()> {
 
     let conn = Arc::new(pool);
     let user_repo = Arc::new(PgUserRepository::new(Arc::clone(&conn)));
-    let user_service = Arc::new(UserApplicationService::new(Arc::clone(&user_repo)));
+    let user_token_repo = Arc::new(PgUserTokenRepository::new(Arc::clone(&conn)));
+    let tenant_settings_repo = Arc::new(PgTenantSettingsRepository::new(Arc::clone(&conn)));
+    let mailer = Arc::new(LogMailer::new());
+    let refresh_token_repo = Arc::new(PgRefreshTokenRepository::new(Arc::clone(&conn)));
+    let mfa_repo = Arc::new(PgMfaRepository::new(Arc::clone(&conn)));
+    let webauthn_repo = Arc::new(PgWebAuthnRepository::new(Arc::clone(&conn)));
+    let oauth_repo = Arc::new(PgOAuthRepository::new(Arc::clone(&conn)));
+    let federation_repo = Arc::new(PgFederationRepository::new(Arc::clone(&conn)));
+    let api_key_repo = Arc::new(PgApiKeyRepository::new(Arc::clone(&conn)));
+    let audit_event_repo = Arc::new(PgAuditEventRepository::new(Arc::clone(&conn)));
+    let idp_client = Arc::new(HttpIdentityProviderClient::new(
+        Duration::from_secs(cfg.federation_http_timeout_secs),
+        Duration::from_secs(cfg.federation_jwks_cache_secs),
+    )?);
+    let access_control = Arc::new(AccessControl::new(
+        cfg.access_policies()?,
+        tenant_settings_repo.clone(),
+    ));
+    let breached_passwords = match &cfg.breached_password_index_path {
+        Some(path) => {
+            let index = BreachedPasswordIndex::open(path)?;
+            info!(
+                "Screening new passwords against {} breached hashes",
+                index.len()
+            );
+            Some(Arc::new(index) as Arc<dyn BreachedPasswords>)
+        }
+        None => {
+            warn!("No breached password index configured, new passwords are not screened");
+            None
+        }
+    };
+    let password_hashing = cfg.password_hashing()?;
+    info!("Hashing new passwords with {}", password_hashing);
+    if let Some(target_ms) = cfg.password_hash_target_ms {
+        spawn_password_hash_calibration(password_hashing.clone(), Duration::from_millis(target_ms));
+    }
+    let hashing_pool = Arc::new(HashingPool::new(
+        password_hashing,
+        cfg.hashing_pool_settings(),
+    ));
+    let password_screen = Arc::new(PasswordScreen::new(
+        breached_passwords,
+        tenant_settings_repo.clone(),
+        Arc::new(PgPasswordHistoryRepository::new(Arc::clone(&conn))),
+        hashing_pool,
+    ));
+    let user_service = Arc::new(UserApplicationService::new(
+        Arc::clone(&user_repo),
+        user_token_repo.clone(),
+        mailer.clone(),
+        Arc::clone(&access_control),
+        Arc::clone(&password_screen),
+        cfg.user_settings(),
+    ));
+    let revocations = Arc::new(RevocationList::new());
+    let impersonation_settings = cfg.impersonation_settings();
+    let session_service = Arc::new(SessionApplicationService::new(
+        Arc::clone(&user_repo),
+        Arc::new(PgSessionRepository::new(Arc::clone(&conn))),
+        refresh_token_repo,
+        Arc::clone(&access_control),
+        Arc::clone(&revocations),
+        // Impersonation tokens are bound to sessions too, and may outlive
+        // access tokens.
+        jwt.access_token_ttl().max(impersonation_settings.token_ttl),
+        cfg.session_settings(),
+    ));
+    session_service
+        .sync_revocations()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load revoked sessions: {}", e))?;
+    spawn_revocation_sync(
+        Arc::clone(&session_service),
+        Duration::from_secs(cfg.session_revocation_sync_secs),
+    );
+    let password_service = Arc::new(PasswordApplicationService::new(
+        Arc::clone(&user_repo),
+        user_token_repo,
+        Arc::clone(&session_service),
+        mailer,
+        password_screen,
+        cfg.password_settings(),
+    ));
+    let auth_service = Arc::new(AuthApplicationService::new(
+        Arc::clone(&user_repo),
+        Arc::clone(&session_service),
+        Arc::clone(&password_service),
+        tenant_settings_repo.clone(),
+        mfa_repo.clone(),
+        webauthn_repo.clone(),
+        Arc::clone(&jwt),
+        cfg.auth_settings(),
+    ));
+    let mfa_service = Arc::new(MfaApplicationService::new(
+        Arc::clone(&user_repo),
+        mfa_repo,
+        cfg.mfa_settings(),
+    ));
+    let passkey_service = Arc::new(PasskeyApplicationService::new(
+        Arc::clone(&user_repo),
+        webauthn_repo,
+        cfg.passkey_settings(),
+    ));
+    let oidc_service = Arc::new(OidcApplicationService::new(
+        Arc::clone(&user_repo),
+        oauth_repo,
+        Arc::clone(&jwt),
+        cfg.oidc_settings(),
+    ));
+    let impersonation_service = Arc::new(ImpersonationApplicationService::new(
+        Arc::clone(&user_repo),
+        Arc::clone(&session_service),
+        audit_event_repo,
+        access_control,
+        Arc::clone(&jwt),
+        impersonation_settings,
+    ));
+    let federation_service = Arc::new(FederationApplicationService::new(
+        Arc::clone(&user_repo),
+        tenant_settings_repo,
+        federation_repo,
+        idp_client,
+        Arc::clone(&user_service),
+        Arc::clone(&auth_service),
+        cfg.federation_settings(),
+    ));
+    let signing_key_service = match cfg.signing_key_master_key()? {
+        Some(master_key) => {
+            let service = Arc::new(SigningKeyApplicationService::new(
+                Arc::new(PgSigningKeyRepository::new(Arc::clone(&conn))),
+                master_key,
+                Arc::clone(&jwt),
+                cfg.signing_key_settings(),
+            ));
+            service
+                .maintain()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load signing keys: {}", e))?;
+            info!("Signing keys loaded from the key store");
+            spawn_signing_key_maintenance(
+                Arc::clone(&service),
+                Duration::from_secs(cfg.signing_key_check_interval_secs),
+            );
+            Some(service)
+        }
+        None => {
+            warn!("No signing key master key configured, the configured key never rotates");
+            None
+        }
+    };
+    let api_key_service = Arc::new(ApiKeyApplicationService::new(
+        api_key_repo,
+        cfg.api_key_settings(),
+    ));
+
+    let rate_limit_repo: Arc<dyn RateLimitRepository> = match cfg.rate_limit_backend.as_str() {
+        "memory" => Arc::new(InMemoryRateLimitRepository::new()),
+        "postgres" => Arc::new(PgRateLimitRepository::new(Arc::clone(&conn))),
+        other => anyhow::bail!("Unknown rate limit backend {}", other),
+    };
+    let rate_limiter = Arc::new(RateLimiter::new(rate_limit_repo, cfg.rate_limit_rules()?)?);
+    spawn_rate_limit_purge(
+        Arc::clone(&rate_limiter),
+        Duration::from_secs(cfg.rate_limit_purge_interval_secs),
+    );
 
-    let share_state = Arc::new(AppState { user_service });
+    let share_state = Arc::new(AppState {
+        user_service,
+        auth_service,
+        password_service,
+        mfa_service,
+        passkey_service,
+        oidc_service,
+        federation_service,
+        api_key_service,
+        session_service,
+        impersonation_service,
+        signing_key_service,
+        jwt,
+        revocations,
+        rate_limiter,
+    });
     let app = Router::new()
         .route("/users", post(create_user_handler))
+        .route("/users/verify-email", post(verify_email_handler))
+        .route(
+            "/users/verify-email/resend",
+            post(resend_verification_handler),
+        )
+        .route("/users/{id}", get(get_user_handler))
+        .route("/users/{id}/unlock", post(unlock_user_handler))
+        .route(
+            "/users/{id}/sessions",
+            get(list_sessions_handler).delete(revoke_all_sessions_handler),
+        )
+        .route(
+            "/users/{id}/sessions/{session_id}",
+            delete(revoke_session_handler),
+        )
+        .route("/auth/login", post(login_handler))
+        .route("/auth/refresh", post(refresh_handler))
+        .route("/auth/password/forgot", post(forgot_password_handler))
+        .route("/auth/password/reset", post(reset_password_handler))
+        .route("/auth/password/change", post(change_password_handler))
+        .route(
+            "/auth/password/expired",
+            post(change_expired_password_handler),
+        )
+        .route(
+            "/auth/federation/start",
+            post(start_federated_login_handler),
+        )
+        .route(
+            "/auth/federation/callback",
+            post(finish_federated_login_handler),
+        )
+        .route(
+            "/auth/impersonation",
+            post(start_impersonation_handler).delete(end_impersonation_handler),
+        )
+        .route("/auth/mfa/verify", post(verify_mfa_handler))
+        .route("/auth/mfa/totp", post(enroll_totp_handler))
+        .route("/auth/mfa/totp/confirm", post(confirm_totp_handler))
+        .route("/auth/passkeys", get(list_passkeys_handler))
+        .route("/auth/passkeys/{id}", delete(delete_passkey_handler))
+        .route(
+            "/auth/passkeys/register/start",
+            post(start_passkey_registration_handler),
+        )
+        .route(
+            "/auth/passkeys/register/finish",
+            post(finish_passkey_registration_handler),
+        )
+        .route(
+            "/auth/passkeys/login/start",
+            post(start_passkey_login_handler),
+        )
+        .route(
+            "/auth/passkeys/login/finish",
+            post(finish_passkey_login_handler),
+        )
+        .route(
+            "/.well-known/openid-configuration",
+            get(openid_configuration_handler),
+        )
+        .route("/jwks.json", get(jwks_handler))
+        .route(
+            "/authorize",
+            get(authorize_handler).post(authorize_signed_in_handler),
+        )
+        .route("/token", post(oidc_token_handler))
+        .route("/userinfo", get(userinfo_handler).post(userinfo_handler))
+        .route("/oauth/clients", post(register_oauth_client_handler))
+        .route(
+            "/api-keys",
+            get(list_api_keys_handler).post(create_api_key_handler),
+        )
+        .route("/api-keys/{id}", delete(revoke_api_key_handler))
+        .route("/signing-keys", get(list_signing_keys_handler))
+        .route("/signing-keys/rotate", post(rotate_signing_keys_handler))
+        .route_layer(middleware::from_fn_with_state(
+            Arc::clone(&share_state),
+            enforce_rate_limit::<AppState>,
+        ))
         .with_state(share_state);
 
+    if let Some(handle) = metrics {
+        spawn_metrics_server(handle, &cfg.metrics_addr).await?;
+    }
+
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
     info!(
         "User service is running on https://{}",
         listener.local_addr()?
     );
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Serves `/metrics` on its own listener, apart from the public routes.
+async fn spawn_metrics_server(handle: PrometheusHandle, addr: &str) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(handle);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(
+        "Metrics are served on http://{}/metrics",
+        listener.local_addr()?
+    );
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            warn!("Metrics server failed: {}", e);
+        }
+    });
     Ok(())
 }
+
+/// Runs due rotations and picks up keys rotated by other instances.
+fn spawn_signing_key_maintenance(service: Arc<SigningKeyApplicationService>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick is immediate, and startup has just done the work.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = service.maintain().await {
+                warn!("Signing key maintenance failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Picks up sessions revoked through other instances.
+fn spawn_revocation_sync(
+    service: Arc<SessionApplicationService<PgUserRepository>>,
+    every: Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick is immediate, and startup has just synced.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = service.sync_revocations().await {
+                warn!("Revoked session sync failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Benchmarks Argon2 on this machine and logs parameters hitting `target`,
+/// leaving it to the operator to adopt them.
+fn spawn_password_hash_calibration(current: PasswordHashing, target: Duration) {
+    tokio::spawn(async move {
+        match tokio::task::spawn_blocking(move || current.calibrate(target)).await {
+            Ok((suggested, took)) => info!(
+                "Suggested password hashing for {} ms: {} ({} ms here)",
+                target.as_millis(),
+                suggested,
+                took.as_millis()
+            ),
+            Err(e) => warn!("Password hash calibration failed: {}", e),
+        }
+    });
+}
+
+/// Records metrics for `/metrics` and keeps their histograms trimmed.
+fn install_metrics_recorder() -> anyhow::Result<PrometheusHandle> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(hashing_pool::DURATION_METRIC.to_string()),
+            &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0],
+        )?
+        .install_recorder()?;
+    let upkeep = handle.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(5));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            upkeep.run_upkeep();
+        }
+    });
+    Ok(handle)
+}
+
+/// Keeps the rate limit store from growing with every caller ever seen.
+fn spawn_rate_limit_purge(limiter: Arc<RateLimiter>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = limiter.purge_expired().await {
+                warn!("Rate limit purge failed: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use auth::{AuthState, Principal};
+use axum::{
+    extract::{FromRequestParts, MatchedPath, Request, State},
+    http::{HeaderMap, HeaderValue, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base::web::error::AppError;
+use chrono::{Duration, Utc};
+use domain::policies::{RateLimit, RateLimitDecision};
+use domain::repository::RateLimitRepository;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::extractors::peer_ip;
+use crate::handlers::AppState;
+
+/// Limits used when the deployment does not configure its own.
+const DEFAULT_RULES: &str = include_str!("default_rate_limits.json");
+
+/// Whose requests a rule counts.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKey {
+    Ip,
+    /// The signed-in user, or the client IP for anonymous requests.
+    User,
+    /// The caller's tenant, or the client IP for anonymous requests.
+    Tenant,
+}
+
+/// A limit on one route, as configured.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RateLimitRule {
+    /// Method and path pattern of the route, as in `POST /users/{id}/unlock`.
+    pub route: String,
+    pub key: RateLimitKey,
+    pub requests: u32,
+    pub period_secs: u64,
+}
+
+/// Throttles the routes that have rules, each by its own keys. A route may
+/// carry several rules, e.g. one per IP and one per tenant; a request must
+/// pass all of them.
+pub struct RateLimiter {
+    repo: Arc<dyn RateLimitRepository>,
+    rules: HashMap<String, Vec<(RateLimitKey, RateLimit)>>,
+}
+
+impl RateLimiter {
+    pub fn new(repo: Arc<dyn RateLimitRepository>, rules: Vec<RateLimitRule>) -> Result<Self> {
+        let mut by_route: HashMap<String, Vec<(RateLimitKey, RateLimit)>> = HashMap::new();
+        for rule in rules {
+            if rule.requests == 0 || rule.period_secs == 0 {
+                bail!(
+                    "Rate limit of {} must allow requests over a period",
+                    rule.route
+                );
+            }
+            let limit = RateLimit::new(rule.requests, Duration::seconds(rule.period_secs as i64));
+            by_route
+                .entry(rule.route)
+                .or_default()
+                .push((rule.key, limit));
+        }
+        Ok(Self {
+            repo,
+            rules: by_route,
+        })
+    }
+
+    pub fn default_rules() -> Vec<RateLimitRule> {
+        serde_json::from_str(DEFAULT_RULES).expect("Invalid default rate limits")
+    }
+
+    pub fn rules_from_file(path: &str) -> Result<Vec<RateLimitRule>> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rate limits {}", path))?;
+        serde_json::from_str(&json).with_context(|| format!("Invalid rate limits in {}", path))
+    }
+
+    /// Forgets callers that are back to their full allowance.
+    pub async fn purge_expired(&self) -> Result<u64> {
+        self.repo.purge_expired(Utc::now()).await
+    }
+
+    fn rules_for(&self, route: &str) -> Option<&[(RateLimitKey, RateLimit)]> {
+        self.rules.get(route).map(Vec::as_slice)
+    }
+
+    /// Checks the request against every rule of the route and returns the
+    /// most restrictive decision. A failing store lets the request through
+    /// rather than take the route down with it.
+    async fn check(
+        &self,
+        route: &str,
+        rules: &[(RateLimitKey, RateLimit)],
+        caller: &Caller,
+    ) -> Option<RateLimitDecision> {
+        let now = Utc::now();
+        let mut tightest: Option<RateLimitDecision> = None;
+        for (key, limit) in rules {
+            let key = format!("{}|{}", route, caller.key(*key));
+            let decision = match self.repo.check(&key, limit, now).await {
+                Ok(decision) => decision,
+                Err(e) => {
+                    warn!(key = %key, "Rate limit check failed, letting the request through: {}", e);
+                    continue;
+                }
+            };
+            if !decision.allowed {
+                return Some(decision);
+            }
+            if tightest.is_none_or(|t| decision.remaining < t.remaining) {
+                tightest = Some(decision);
+            }
+        }
+        tightest
+    }
+}
+
+/// What [`enforce_rate_limit`] needs from the application state: the
+/// limiter, and what it takes to tell who is calling.
+pub trait RateLimitState: AuthState {
+    fn rate_limiter(&self) -> &RateLimiter;
+}
+
+impl RateLimitState for AppState {
+    fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+}
+
+/// Who is making a request, as far as the rules need to know.
+struct Caller {
+    ip: String,
+    principal: Option<Principal>,
+}
+
+impl Caller {
+    async fn identify<S: RateLimitState>(
+        parts: &mut Parts,
+        state: &Arc<S>,
+        rules: &[(RateLimitKey, RateLimit)],
+    ) -> Self {
+        let ip = peer_ip(parts).map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+        // Only verify the token when a rule counts by user or tenant; an
+        // invalid one is left for the handler to reject.
+        let principal = if rules.iter().any(|(key, _)| *key != RateLimitKey::Ip) {
+            Principal::from_request_parts(parts, state).await.ok()
+        } else {
+            None
+        };
+        Self { ip, principal }
+    }
+
+    fn key(&self, key: RateLimitKey) -> String {
+        match (key, &self.principal) {
+            (RateLimitKey::User, Some(principal)) => format!("user:{}", principal.user_id),
+            (RateLimitKey::Tenant, Some(principal)) => format!("tenant:{}", principal.tenant_id),
+            _ => format!("ip:{}", self.ip),
+        }
+    }
+}
+
+/// Middleware enforcing [`RateLimiter`] on the matched route. Responses
+/// carry the `RateLimit-*` headers, and refusals a 429 with `Retry-After`.
+pub async fn enforce_rate_limit<S: RateLimitState>(
+    State(state): State<Arc<S>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(path) = request.extensions().get::<MatchedPath>() else {
+        return next.run(request).await;
+    };
+    let route = format!("{} {}", request.method(), path.as_str());
+    let limiter = state.rate_limiter();
+    let Some(rules) = limiter.rules_for(&route) else {
+        return next.run(request).await;
+    };
+
+    let (mut parts, body) = request.into_parts();
+    let caller = Caller::identify(&mut parts, &state, rules).await;
+    let Some(decision) = limiter.check(&route, rules, &caller).await else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+
+    let mut response = if decision.allowed {
+        next.run(Request::from_parts(parts, body)).await
+    } else {
+        let retry_after = ceil_secs(decision.retry_after);
+        AppError::TooManyRequests(format!(
+            "Too many requests, retry in {} seconds",
+            retry_after
+        ))
+        .into_response()
+    };
+    set_headers(response.headers_mut(), &decision);
+    response
+}
+
+fn set_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert("ratelimit-limit", HeaderValue::from(decision.limit));
+    headers.insert("ratelimit-remaining", HeaderValue::from(decision.remaining));
+    headers.insert(
+        "ratelimit-reset",
+        HeaderValue::from(ceil_secs(decision.reset_after)),
+    );
+    if !decision.allowed {
+        headers.insert(
+            "retry-after",
+            HeaderValue::from(ceil_secs(decision.retry_after)),
+        );
+    }
+}
+
+/// Whole seconds, rounded up so a client waiting that long is let in.
+fn ceil_secs(duration: Duration) -> i64 {
+    let millis = duration.num_milliseconds().max(0);
+    (millis + 999) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auth::{JwtAlgorithm, JwtConfig, JwtService};
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use axum::http::StatusCode;
+    use axum::http::header::AUTHORIZATION;
+    use axum::{Router, middleware, routing::get};
+    use infrastructure::InMemoryRateLimitRepository;
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    struct TestState {
+        jwt: JwtService,
+        rate_limiter: RateLimiter,
+    }
+
+    impl AuthState for TestState {
+        fn jwt_service(&self) -> &JwtService {
+            &self.jwt
+        }
+    }
+
+    impl RateLimitState for TestState {
+        fn rate_limiter(&self) -> &RateLimiter {
+            &self.rate_limiter
+        }
+    }
+
+    struct Fixture {
+        app: Router,
+        state: Arc<TestState>,
+    }
+
+    /// `/limited` under a rule counting `requests` a minute by `key`, next
+    /// to `/open` without rules.
+    fn fixture(key: RateLimitKey, requests: u32) -> Fixture {
+        let jwt = JwtService::new(JwtConfig {
+            algorithm: JwtAlgorithm::HS256,
+            secret: Some("test-secret".to_string()),
+            private_key_pem: None,
+            public_key_pem: None,
+            issuer: "user-service".to_string(),
+            audience: "ddd-rust-application".to_string(),
+            access_token_ttl: std::time::Duration::from_secs(900),
+        })
+        .unwrap();
+        let rules = vec![RateLimitRule {
+            route: "GET /limited".to_string(),
+            key,
+            requests,
+            period_secs: 60,
+        }];
+        let rate_limiter =
+            RateLimiter::new(Arc::new(InMemoryRateLimitRepository::new()), rules).unwrap();
+        let state = Arc::new(TestState { jwt, rate_limiter });
+        let app = Router::new()
+            .route("/limited", get(|| async { "ok" }))
+            .route("/open", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                Arc::clone(&state),
+                enforce_rate_limit::<TestState>,
+            ));
+        Fixture { app, state }
+    }
+
+    impl Fixture {
+        fn token(&self, user_id: &str, tenant_id: &str) -> String {
+            self.state
+                .jwt
+                .issue_access_token(user_id, tenant_id, "user")
+                .unwrap()
+                .token
+        }
+
+        async fn call(&self, path: &str, ip: [u8; 4], token: Option<&str>) -> Response {
+            let mut request = Request::get(path);
+            if let Some(token) = token {
+                request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+            }
+            let mut request = request.body(Body::empty()).unwrap();
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from((ip, 40000))));
+            self.app.clone().oneshot(request).await.unwrap()
+        }
+    }
+
+    fn header(response: &Response, name: &str) -> Option<i64> {
+        response
+            .headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap().parse().unwrap())
+    }
+
+    const IP: [u8; 4] = [203, 0, 113, 7];
+    const OTHER_IP: [u8; 4] = [203, 0, 113, 8];
+
+    #[tokio::test]
+    async fn test_refuses_requests_over_the_limit() {
+        let fixture = fixture(RateLimitKey::Ip, 2);
+
+        let first = fixture.call("/limited", IP, None).await;
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(header(&first, "ratelimit-limit"), Some(2));
+        assert_eq!(header(&first, "ratelimit-remaining"), Some(1));
+        assert_eq!(header(&first, "ratelimit-reset"), Some(30));
+        assert_eq!(header(&first, "retry-after"), None);
+
+        let second = fixture.call("/limited", IP, None).await;
+        assert_eq!(second.status(), StatusCode::OK);
+        assert_eq!(header(&second, "ratelimit-remaining"), Some(0));
+
+        let refused = fixture.call("/limited", IP, None).await;
+        assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(header(&refused, "ratelimit-limit"), Some(2));
+        assert_eq!(header(&refused, "ratelimit-remaining"), Some(0));
+        assert_eq!(header(&refused, "ratelimit-reset"), Some(60));
+        assert_eq!(header(&refused, "retry-after"), Some(30));
+    }
+
+    #[tokio::test]
+    async fn test_routes_without_rules_are_not_limited() {
+        let fixture = fixture(RateLimitKey::Ip, 1);
+        for _ in 0..3 {
+            let response = fixture.call("/open", IP, None).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(header(&response, "ratelimit-limit"), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ip_rules_count_each_address_apart() {
+        let fixture = fixture(RateLimitKey::Ip, 1);
+        let alice = fixture.token("alice", "tenant-1");
+        let bob = fixture.token("bob", "tenant-1");
+
+        let allowed = fixture.call("/limited", IP, Some(&alice)).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+        let refused = fixture.call("/limited", IP, Some(&bob)).await;
+        assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+        let allowed = fixture.call("/limited", OTHER_IP, Some(&alice)).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_user_rules_count_each_user_apart() {
+        let fixture = fixture(RateLimitKey::User, 1);
+        let alice = fixture.token("alice", "tenant-1");
+        let bob = fixture.token("bob", "tenant-1");
+
+        let allowed = fixture.call("/limited", IP, Some(&alice)).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+        let refused = fixture.call("/limited", OTHER_IP, Some(&alice)).await;
+        assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+        let allowed = fixture.call("/limited", IP, Some(&bob)).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+
+        // Anonymous callers, and ones with an invalid token, count by IP.
+        let allowed = fixture.call("/limited", IP, None).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+        let refused = fixture.call("/limited", IP, Some("not-a-token")).await;
+        assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_rules_count_each_tenant_apart() {
+        let fixture = fixture(RateLimitKey::Tenant, 1);
+        let alice = fixture.token("alice", "tenant-1");
+        let bob = fixture.token("bob", "tenant-1");
+        let carol = fixture.token("carol", "tenant-2");
+
+        let allowed = fixture.call("/limited", IP, Some(&alice)).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+        let refused = fixture.call("/limited", OTHER_IP, Some(&bob)).await;
+        assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+        let allowed = fixture.call("/limited", IP, Some(&carol)).await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,52 @@
+-- Add migration script here
+alter table tbl_users
+    add column username varchar(50);
+
+-- Existing users get the local part of their email, reduced to the
+-- characters usernames allow and kept within 3 to 20 characters. Users of a
+-- tenant sharing a local part are told apart by a numeric suffix, skipping
+-- any suffix that would spell the local part of another user of the tenant.
+with local_parts as (select id,
+                            tenant_id,
+                            left(regexp_replace(lower(split_part(email, '@', 1)),
+                                                '[^a-z0-9_-]', '_', 'g'), 14) as local_part
+                     from tbl_users),
+     candidates as (select id,
+                           tenant_id,
+                           rpad(local_part, greatest(length(local_part), 3), '_') as base
+                    from local_parts),
+     numbered as (select id,
+                         tenant_id,
+                         base,
+                         row_number() over (partition by tenant_id, base order by id) as n
+                  from candidates),
+     shared as (select tenant_id, base, count(*) as users
+                from numbered
+                group by tenant_id, base
+                having count(*) > 1),
+     suffixes as (select shared.tenant_id,
+                         shared.base,
+                         suffix,
+                         row_number() over (partition by shared.tenant_id, shared.base order by suffix) + 1 as n
+                  from shared
+                           cross join lateral generate_series(2, shared.users + (select count(*)
+                                                                                 from numbered
+                                                                                 where numbered.tenant_id = shared.tenant_id)) as suffix
+                  where not exists (select 1
+                                    from numbered
+                                    where numbered.tenant_id = shared.tenant_id
+                                      and numbered.n = 1
+                                      and numbered.base = shared.base || '-' || suffix))
+update tbl_users u
+set username = case when numbered.n = 1 then numbered.base else numbered.base || '-' || suffixes.suffix end
+from numbered
+         left join suffixes on suffixes.tenant_id = numbered.tenant_id
+    and suffixes.base = numbered.base
+    and suffixes.n = numbered.n
+where u.id = numbered.id;
+
+alter table tbl_users
+    alter column username set not null;
+
+alter table tbl_users
+    add constraint users_username_tenant_unique unique (tenant_id, username);
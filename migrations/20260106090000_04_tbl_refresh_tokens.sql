@@ -0,0 +1,17 @@
+-- Add migration script here
+create table tbl_refresh_tokens
+(
+    id          uuid primary key     default uuid_generate_v7(),
+    tenant_id   uuid        not null references tbl_tenants (id) on delete cascade,
+    user_id     uuid        not null references tbl_users (id) on delete cascade,
+    -- Every token rotated from the same login shares a family
+    family_id   uuid        not null,
+    token_hash  varchar(64) not null unique,
+    expires_at  timestamptz not null,
+    rotated_at  timestamptz,
+    revoked_at  timestamptz,
+    created_at  timestamptz not null default now()
+);
+
+create index idx_refresh_tokens_user on tbl_refresh_tokens (tenant_id, user_id) where revoked_at is null;
+create index idx_refresh_tokens_family on tbl_refresh_tokens (family_id);
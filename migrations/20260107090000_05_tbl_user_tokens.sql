@@ -0,0 +1,16 @@
+-- Add migration script here
+create table tbl_user_tokens
+(
+    id          uuid primary key     default uuid_generate_v7(),
+    tenant_id   uuid        not null references tbl_tenants (id) on delete cascade,
+    user_id     uuid        not null references tbl_users (id) on delete cascade,
+    purpose     varchar(50) not null,
+    token_hash  varchar(64) not null unique,
+    expires_at  timestamptz not null,
+    consumed_at timestamptz,
+    created_at  timestamptz not null default now(),
+    -- Constraints
+    constraint user_tokens_purpose_check check (purpose in ('email_verification'))
+);
+
+create index idx_user_tokens_user on tbl_user_tokens (user_id, purpose, created_at desc);
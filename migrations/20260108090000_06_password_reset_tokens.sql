@@ -0,0 +1,6 @@
+-- Add migration script here
+alter table tbl_user_tokens
+    drop constraint user_tokens_purpose_check;
+
+alter table tbl_user_tokens
+    add constraint user_tokens_purpose_check check (purpose in ('email_verification', 'password_reset'));
@@ -0,0 +1,21 @@
+-- Add migration script here
+create table tbl_user_totp
+(
+    user_id        uuid primary key references tbl_users (id) on delete cascade,
+    tenant_id      uuid        not null references tbl_tenants (id) on delete cascade,
+    secret         varchar(64) not null,
+    confirmed_at   timestamptz,
+    last_used_step bigint,
+    created_at     timestamptz not null default now()
+);
+
+create table tbl_mfa_recovery_codes
+(
+    id         uuid primary key     default uuid_generate_v7(),
+    user_id    uuid        not null references tbl_users (id) on delete cascade,
+    code_hash  varchar(64) not null,
+    used_at    timestamptz,
+    created_at timestamptz not null default now(),
+    -- Constraints
+    constraint mfa_recovery_codes_user_code_unique unique (user_id, code_hash)
+);
@@ -0,0 +1,31 @@
+-- Add migration script here
+create table tbl_webauthn_credentials
+(
+    id            uuid primary key      default uuid_generate_v7(),
+    tenant_id     uuid         not null references tbl_tenants (id) on delete cascade,
+    user_id       uuid         not null references tbl_users (id) on delete cascade,
+    credential_id bytea        not null,
+    public_key    bytea        not null,
+    sign_count    bigint       not null default 0,
+    name          varchar(100),
+    created_at    timestamptz  not null default now(),
+    last_used_at  timestamptz,
+    -- Constraints
+    constraint webauthn_credentials_tenant_credential_unique unique (tenant_id, credential_id)
+);
+
+create index idx_webauthn_credentials_user on tbl_webauthn_credentials (user_id);
+
+create table tbl_webauthn_challenges
+(
+    id          uuid primary key     default uuid_generate_v7(),
+    tenant_id   uuid        not null references tbl_tenants (id) on delete cascade,
+    user_id     uuid references tbl_users (id) on delete cascade,
+    ceremony    varchar(20) not null,
+    challenge   varchar(128) not null,
+    expires_at  timestamptz not null,
+    consumed_at timestamptz,
+    created_at  timestamptz not null default now(),
+    -- Constraints
+    constraint webauthn_challenges_ceremony_check check (ceremony in ('registration', 'authentication'))
+);
@@ -0,0 +1,31 @@
+-- Add migration script here
+create table tbl_oauth_clients
+(
+    id                 uuid primary key      default uuid_generate_v7(),
+    tenant_id          uuid         not null references tbl_tenants (id) on delete cascade,
+    client_id          varchar(64)  not null unique,
+    client_secret_hash text,
+    name               varchar(255) not null,
+    redirect_uris      text[]       not null,
+    created_at         timestamptz  not null default now()
+);
+
+create index idx_oauth_clients_tenant on tbl_oauth_clients (tenant_id);
+
+create table tbl_oauth_authorization_codes
+(
+    id             uuid primary key     default uuid_generate_v7(),
+    tenant_id      uuid        not null references tbl_tenants (id) on delete cascade,
+    user_id        uuid        not null references tbl_users (id) on delete cascade,
+    client_id      varchar(64) not null references tbl_oauth_clients (client_id) on delete cascade,
+    code_hash      varchar(64) not null unique,
+    redirect_uri   text        not null,
+    scope          text        not null,
+    nonce          text,
+    code_challenge varchar(128) not null,
+    expires_at     timestamptz not null,
+    consumed_at    timestamptz,
+    created_at     timestamptz not null default now()
+);
+
+create index idx_oauth_authorization_codes_expires on tbl_oauth_authorization_codes (expires_at);
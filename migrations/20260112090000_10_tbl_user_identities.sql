@@ -0,0 +1,30 @@
+-- Add migration script here
+create table tbl_federated_login_states
+(
+    id            uuid primary key      default uuid_generate_v7(),
+    tenant_id     uuid         not null references tbl_tenants (id) on delete cascade,
+    provider_id   varchar(64)  not null,
+    state_hash    varchar(64)  not null unique,
+    nonce         text         not null,
+    code_verifier varchar(128) not null,
+    expires_at    timestamptz  not null,
+    consumed_at   timestamptz,
+    created_at    timestamptz  not null default now()
+);
+
+create index idx_federated_login_states_expires on tbl_federated_login_states (expires_at);
+
+create table tbl_user_identities
+(
+    id            uuid primary key      default uuid_generate_v7(),
+    tenant_id     uuid         not null references tbl_tenants (id) on delete cascade,
+    user_id       uuid         not null references tbl_users (id) on delete cascade,
+    provider_id   varchar(64)  not null,
+    subject       varchar(255) not null,
+    email         varchar(255),
+    created_at    timestamptz  not null default now(),
+    last_login_at timestamptz,
+    constraint user_identities_subject_unique unique (tenant_id, provider_id, subject)
+);
+
+create index idx_user_identities_user on tbl_user_identities (user_id);
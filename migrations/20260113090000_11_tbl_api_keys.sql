@@ -0,0 +1,17 @@
+-- Add migration script here
+create table tbl_api_keys
+(
+    id           uuid primary key      default uuid_generate_v7(),
+    tenant_id    uuid         not null references tbl_tenants (id) on delete cascade,
+    name         varchar(255) not null,
+    prefix       varchar(16)  not null unique,
+    key_hash     varchar(64)  not null,
+    scopes       text[]       not null,
+    created_by   uuid         not null references tbl_users (id),
+    expires_at   timestamptz,
+    last_used_at timestamptz,
+    revoked_at   timestamptz,
+    created_at   timestamptz  not null default now()
+);
+
+create index idx_api_keys_tenant on tbl_api_keys (tenant_id);
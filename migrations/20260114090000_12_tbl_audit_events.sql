@@ -0,0 +1,15 @@
+-- Add migration script here
+create table tbl_audit_events
+(
+    id          uuid primary key      default uuid_generate_v7(),
+    tenant_id   uuid         not null references tbl_tenants (id) on delete cascade,
+    action      varchar(100) not null,
+    -- No foreign keys to tbl_users: the trail outlives the accounts it names.
+    actor_id    uuid         not null,
+    subject_id  uuid,
+    details     jsonb        not null default '{}',
+    occurred_at timestamptz  not null default now()
+);
+
+create index idx_audit_events_tenant on tbl_audit_events (tenant_id, occurred_at);
+create index idx_audit_events_subject on tbl_audit_events (subject_id) where subject_id is not null;
@@ -0,0 +1,17 @@
+-- Add migration script here
+create table tbl_signing_keys
+(
+    kid                varchar(64) primary key,
+    algorithm          varchar(16) not null,
+    status             varchar(16) not null,
+    -- Sealed under the master key from the service configuration.
+    sealed_private_key bytea       not null,
+    public_key_pem     text,
+    created_at         timestamptz not null default now(),
+    activated_at       timestamptz,
+    retired_at         timestamptz
+);
+
+-- At most one key signs at a time and one waits to take over.
+create unique index idx_signing_keys_current on tbl_signing_keys (status)
+    where status in ('active', 'next');
@@ -0,0 +1,16 @@
+-- Add migration script here
+create table tbl_sessions
+(
+    id           uuid primary key      default uuid_generate_v7(),
+    tenant_id    uuid         not null references tbl_tenants (id) on delete cascade,
+    user_id      uuid         not null references tbl_users (id) on delete cascade,
+    user_agent   varchar(512),
+    ip_address   varchar(45),
+    mfa_verified boolean      not null default false,
+    created_at   timestamptz  not null default now(),
+    last_seen_at timestamptz  not null default now(),
+    revoked_at   timestamptz
+);
+
+create index idx_sessions_user on tbl_sessions (user_id, last_seen_at desc) where revoked_at is null;
+create index idx_sessions_revoked on tbl_sessions (revoked_at) where revoked_at is not null;
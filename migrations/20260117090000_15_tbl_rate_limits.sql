@@ -0,0 +1,9 @@
+-- Add migration script here
+create table tbl_rate_limits
+(
+    key varchar(255) primary key,
+    -- Theoretical arrival time: when the key is back to its full allowance.
+    tat timestamptz  not null
+);
+
+create index idx_rate_limits_tat on tbl_rate_limits (tat);
@@ -0,0 +1,11 @@
+-- Add migration script here
+create table tbl_password_history
+(
+    id            uuid primary key      default uuid_generate_v7(),
+    tenant_id     uuid         not null references tbl_tenants (id) on delete cascade,
+    user_id       uuid         not null references tbl_users (id) on delete cascade,
+    password_hash text         not null,
+    replaced_at   timestamptz  not null default now()
+);
+
+create index idx_password_history_user on tbl_password_history (user_id, replaced_at desc);
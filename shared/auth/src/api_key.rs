@@ -0,0 +1,68 @@
+//! Tenant API keys look like `ak_<prefix>_<secret>`. The prefix is public,
+//! identifies the key in listings and is what keys are looked up by; only
+//! the [`hash_token`](crate::hash_token) of the whole key is stored.
+
+use rand_core::{OsRng, RngCore};
+
+use crate::opaque::generate_opaque_token;
+
+const API_KEY_MARKER: &str = "ak";
+/// Random bytes in the hex encoded prefix.
+const PREFIX_BYTES: usize = 6;
+
+/// A freshly minted key. `key` is handed to the caller once.
+#[derive(Debug, Clone)]
+pub struct GeneratedApiKey {
+    pub key: String,
+    pub prefix: String,
+}
+
+pub fn generate_api_key() -> GeneratedApiKey {
+    let mut bytes = [0u8; PREFIX_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    let prefix = hex::encode(bytes);
+    GeneratedApiKey {
+        key: format!("{}_{}_{}", API_KEY_MARKER, prefix, generate_opaque_token()),
+        prefix,
+    }
+}
+
+/// The lookup prefix of a presented key, or `None` if it is not shaped like
+/// one of ours.
+pub fn api_key_prefix(key: &str) -> Option<&str> {
+    let mut parts = key.splitn(3, '_');
+    let (marker, prefix, secret) = (parts.next()?, parts.next()?, parts.next()?);
+    (marker == API_KEY_MARKER
+        && prefix.len() == PREFIX_BYTES * 2
+        && prefix.bytes().all(|b| b.is_ascii_hexdigit())
+        && !secret.is_empty())
+    .then_some(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_key_carries_its_prefix() {
+        let generated = generate_api_key();
+        assert!(generated.key.starts_with("ak_"));
+        assert_eq!(
+            api_key_prefix(&generated.key),
+            Some(generated.prefix.as_str())
+        );
+        assert_ne!(generate_api_key().prefix, generated.prefix);
+    }
+
+    #[test]
+    fn test_malformed_keys_have_no_prefix() {
+        assert_eq!(api_key_prefix("ak_0123456789ab_"), None);
+        assert_eq!(api_key_prefix("ak_0123456789ab"), None);
+        assert_eq!(api_key_prefix("ak_xyz_secret"), None);
+        assert_eq!(api_key_prefix("pk_0123456789ab_secret"), None);
+        assert_eq!(
+            api_key_prefix("ak_0123456789ab_sec_ret"),
+            Some("0123456789ab")
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use base::web::error::AppError;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("Invalid key configuration: {0}")]
+    InvalidKey(String),
+    #[error("Token expired")]
+    TokenExpired,
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+    #[error("Failed to sign token: {0}")]
+    Signing(String),
+    #[error("Token signed by unknown key {0:?}")]
+    UnknownKey(String),
+    #[error("Invalid access policy: {0}")]
+    InvalidPolicy(String),
+    #[error("Verification keys unavailable: {0}")]
+    KeysUnavailable(String),
+    #[error("Session has been revoked")]
+    SessionRevoked,
+}
+
+impl From<AuthError> for AppError {
+    fn from(err: AuthError) -> Self {
+        match err {
+            AuthError::TokenExpired
+            | AuthError::InvalidToken(_)
+            | AuthError::UnknownKey(_)
+            | AuthError::SessionRevoked => AppError::Unauthorized(err.to_string()),
+            AuthError::InvalidKey(_)
+            | AuthError::Signing(_)
+            | AuthError::InvalidPolicy(_)
+            | AuthError::KeysUnavailable(_) => AppError::InternalServerError(err.to_string()),
+        }
+    }
+}
+
+/// Reasons a WebAuthn ceremony response is rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WebAuthnError {
+    #[error("Malformed WebAuthn response: {0}")]
+    Malformed(String),
+    #[error("Unexpected client data type {0}")]
+    TypeMismatch(String),
+    #[error("Challenge does not match")]
+    ChallengeMismatch,
+    #[error("Origin {0} is not allowed")]
+    OriginMismatch(String),
+    #[error("Relying party id does not match")]
+    RpIdMismatch,
+    #[error("User presence was not asserted")]
+    UserNotPresent,
+    #[error("User verification is required")]
+    UserNotVerified,
+    #[error("Unsupported credential public key")]
+    UnsupportedKey,
+    #[error("Invalid signature")]
+    InvalidSignature,
+    #[error("Signature counter did not increase, the authenticator may be cloned")]
+    SignCountRegression,
+}
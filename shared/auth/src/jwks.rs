@@ -0,0 +1,133 @@
+//! Publishing token verification keys as a JSON Web Key Set.
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::traits::PublicKeyParts;
+use rsa::RsaPublicKey;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::error::AuthError;
+use crate::jwt::JwtAlgorithm;
+
+/// DER prefix of an Ed25519 `SubjectPublicKeyInfo`; the raw key follows it.
+pub(crate) const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Builds the public JWK for a PEM encoded verification key. The key id is
+/// the RFC 7638 thumbprint, so it is stable for a given key.
+///
+/// Returns `None` for HS256: a shared secret must never be published.
+pub(crate) fn public_jwk(
+    algorithm: JwtAlgorithm,
+    public_key_pem: &str,
+) -> Result<Option<Jwk>, AuthError> {
+    let (key_algorithm, parameters, thumbprint_input) = match algorithm {
+        JwtAlgorithm::HS256 => return Ok(None),
+        JwtAlgorithm::RS256 => {
+            let key = RsaPublicKey::from_public_key_pem(public_key_pem)
+                .or_else(|_| RsaPublicKey::from_pkcs1_pem(public_key_pem))
+                .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+            let n = URL_SAFE_NO_PAD.encode(key.n().to_bytes_be());
+            let e = URL_SAFE_NO_PAD.encode(key.e().to_bytes_be());
+            let thumbprint_input = json!({ "e": e, "kty": "RSA", "n": n });
+            let parameters = AlgorithmParameters::RSA(RSAKeyParameters {
+                key_type: RSAKeyType::RSA,
+                n,
+                e,
+            });
+            (KeyAlgorithm::RS256, parameters, thumbprint_input)
+        }
+        JwtAlgorithm::EdDSA => {
+            let x = URL_SAFE_NO_PAD.encode(ed25519_public_key(public_key_pem)?);
+            let thumbprint_input = json!({ "crv": "Ed25519", "kty": "OKP", "x": x });
+            let parameters = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x,
+            });
+            (KeyAlgorithm::EdDSA, parameters, thumbprint_input)
+        }
+    };
+    // serde_json sorts object keys, which is the canonical form RFC 7638 asks for.
+    let thumbprint = Sha256::digest(thumbprint_input.to_string().as_bytes());
+    Ok(Some(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(URL_SAFE_NO_PAD.encode(thumbprint)),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    }))
+}
+
+fn ed25519_public_key(pem: &str) -> Result<[u8; 32], AuthError> {
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("-----"))
+        .collect();
+    let der = STANDARD
+        .decode(body)
+        .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+    der.strip_prefix(ED25519_SPKI_PREFIX.as_slice())
+        .and_then(|key| <[u8; 32]>::try_from(key).ok())
+        .ok_or_else(|| AuthError::InvalidKey("Not an Ed25519 public key".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ED_PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MCowBQYDK2VwAyEAfRL0XzHJQzy8hpCxcUZZxNMPOnOl5VdYUqvAY4Y94ss=
+-----END PUBLIC KEY-----";
+    const RSA_PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEArVPHwljbmkf50hUR+5JN
+h2S4IuEiKcs8gnPPUVcMcKNv5Rxd4dwn6kouPGBtfDcUQPvxGcuPE8vRaDe8wMYE
+cmwksta8pE6f0zlsYveM2vI3OEABfUgv1Kb3Lm5TKwV+V6nOyuHb6guYkcbLu0Pq
+4ko9O6A3fU4DlkZk+C3xhaj/LvTfdLtHnv3mTpkV4aq7mjkV1emCgkz41BTM4AGS
+CMbciapnO9UpwYxj4DFsi+GfyKSatw8mlgkfVnzoYCP/coJWa7gou69JLkR2g6bb
+rDC3sWs51RTcfO7htRi3d9Uu9EbDuiUXQqjeBcknt+LOuGHn9RckZag6fuPcm6B5
+9wIDAQAB
+-----END PUBLIC KEY-----";
+
+    #[test]
+    fn test_ed25519_jwk() {
+        let jwk = public_jwk(JwtAlgorithm::EdDSA, ED_PUBLIC_KEY)
+            .unwrap()
+            .unwrap();
+        let AlgorithmParameters::OctetKeyPair(params) = &jwk.algorithm else {
+            panic!("expected an OKP key");
+        };
+        assert_eq!(params.x, "fRL0XzHJQzy8hpCxcUZZxNMPOnOl5VdYUqvAY4Y94ss");
+        assert_eq!(jwk.common.key_algorithm, Some(KeyAlgorithm::EdDSA));
+        assert!(jwk.common.key_id.is_some());
+    }
+
+    #[test]
+    fn test_rsa_jwk() {
+        let jwk = public_jwk(JwtAlgorithm::RS256, RSA_PUBLIC_KEY)
+            .unwrap()
+            .unwrap();
+        let AlgorithmParameters::RSA(params) = &jwk.algorithm else {
+            panic!("expected an RSA key");
+        };
+        assert_eq!(params.e, "AQAB");
+        assert_eq!(URL_SAFE_NO_PAD.decode(&params.n).unwrap().len(), 256);
+    }
+
+    #[test]
+    fn test_secret_is_never_published() {
+        assert_eq!(public_jwk(JwtAlgorithm::HS256, "secret"), Ok(None));
+        assert!(public_jwk(JwtAlgorithm::EdDSA, RSA_PUBLIC_KEY).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Keeping the keys of a service that verifies another service's tokens in
+//! step with the JWKS that service publishes.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use jsonwebtoken::jwk::JwkSet;
+
+use crate::error::AuthError;
+use crate::jwt::JwtService;
+use crate::principal::KeyRefresher;
+
+/// Keys are fetched again for a token naming an unknown key only once they
+/// are this old, so made-up key ids cannot make us hammer the issuer.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Where a token issuer's key set comes from.
+#[async_trait]
+pub trait JwksSource: Send + Sync {
+    async fn fetch_jwks(&self) -> Result<JwkSet, AuthError>;
+}
+
+/// Fetches the key set from the issuer's JWKS endpoint.
+pub struct HttpJwksSource {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl HttpJwksSource {
+    pub fn new(url: impl Into<String>, timeout: Duration) -> Result<Self, AuthError> {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| AuthError::KeysUnavailable(e.to_string()))?;
+        Ok(Self {
+            http,
+            url: url.into(),
+        })
+    }
+}
+
+#[async_trait]
+impl JwksSource for HttpJwksSource {
+    async fn fetch_jwks(&self) -> Result<JwkSet, AuthError> {
+        let unavailable = |e: reqwest::Error| AuthError::KeysUnavailable(e.to_string());
+        self.http
+            .get(&self.url)
+            .send()
+            .await
+            .map_err(unavailable)?
+            .error_for_status()
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)
+    }
+}
+
+/// Installs an issuer's published keys into a [`JwtService`] and fetches
+/// them again once they get stale or a token names a key not seen yet, as
+/// happens after the issuer rotated its keys.
+pub struct JwksCache {
+    jwt: Arc<JwtService>,
+    source: Arc<dyn JwksSource>,
+    max_age: Duration,
+    fetched_at: Mutex<Option<Instant>>,
+}
+
+impl JwksCache {
+    pub fn new(jwt: Arc<JwtService>, source: Arc<dyn JwksSource>, max_age: Duration) -> Self {
+        Self {
+            jwt,
+            source,
+            max_age,
+            fetched_at: Mutex::new(None),
+        }
+    }
+
+    /// Fetches the keys unless those installed are younger than the maximum
+    /// age. Call it at startup and then periodically.
+    pub async fn refresh_if_stale(&self) -> Result<(), AuthError> {
+        self.refresh_older_than(self.max_age).await
+    }
+
+    async fn refresh_older_than(&self, age: Duration) -> Result<(), AuthError> {
+        {
+            let mut fetched_at = self.fetched_at.lock().unwrap();
+            if fetched_at.is_some_and(|at| at.elapsed() < age) {
+                return Ok(());
+            }
+            // Claimed before fetching so concurrent callers do not all fetch.
+            *fetched_at = Some(Instant::now());
+        }
+        let jwks = self.source.fetch_jwks().await?;
+        self.jwt.set_jwks(&jwks)
+    }
+}
+
+#[async_trait]
+impl KeyRefresher for JwksCache {
+    async fn refresh_keys(&self) -> Result<(), AuthError> {
+        self.refresh_older_than(MIN_REFRESH_INTERVAL).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::{JwtAlgorithm, JwtConfig};
+    use crate::keys::generate_signing_key;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves the keys of an issuer and counts how often it was asked.
+    struct IssuerJwks {
+        issuer: Arc<JwtService>,
+        fetches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl JwksSource for IssuerJwks {
+        async fn fetch_jwks(&self) -> Result<JwkSet, AuthError> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(self.issuer.jwks())
+        }
+    }
+
+    fn config() -> JwtConfig {
+        JwtConfig {
+            algorithm: JwtAlgorithm::EdDSA,
+            secret: None,
+            private_key_pem: None,
+            public_key_pem: None,
+            issuer: "user-service".to_string(),
+            audience: "ddd-rust-application".to_string(),
+            access_token_ttl: Duration::from_secs(900),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rotated_keys_are_picked_up_at_most_once_per_interval() {
+        let issuer = Arc::new(JwtService::without_keys(config()));
+        let first = generate_signing_key(JwtAlgorithm::EdDSA).unwrap().key;
+        issuer
+            .set_keys(vec![first.clone()], Some(first.kid()))
+            .unwrap();
+        let source = Arc::new(IssuerJwks {
+            issuer: issuer.clone(),
+            fetches: AtomicUsize::new(0),
+        });
+        let verifier = Arc::new(JwtService::without_keys(config()));
+        let cache = JwksCache::new(verifier.clone(), source.clone(), Duration::from_secs(3600));
+        cache.refresh_if_stale().await.unwrap();
+        cache.refresh_if_stale().await.unwrap();
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+
+        let second = generate_signing_key(JwtAlgorithm::EdDSA).unwrap().key;
+        issuer
+            .set_keys(vec![first, second.clone()], Some(second.kid()))
+            .unwrap();
+        let token = issuer
+            .issue_access_token("user-1", "tenant-1", "user")
+            .unwrap()
+            .token;
+        assert!(matches!(
+            verifier.verify_access_token(&token),
+            Err(AuthError::UnknownKey(_))
+        ));
+        // Fetched moments ago, so a token naming a new key does not trigger
+        // another fetch yet.
+        cache.refresh_keys().await.unwrap();
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+
+        *cache.fetched_at.lock().unwrap() = Some(Instant::now() - MIN_REFRESH_INTERVAL);
+        cache.refresh_keys().await.unwrap();
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 2);
+        assert!(verifier.verify_access_token(&token).is_ok());
+    }
+}
@@ -0,0 +1,320 @@
+//! Token signing keys: generating them, sealing them for storage and loading
+//! them into a [`JwtService`](crate::JwtService).
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use jsonwebtoken::jwk::{Jwk, KeyAlgorithm};
+use jsonwebtoken::{DecodingKey, EncodingKey};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::RsaPrivateKey;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::error::AuthError;
+use crate::jwks::{public_jwk, ED25519_SPKI_PREFIX};
+use crate::jwt::JwtAlgorithm;
+
+const RSA_KEY_BITS: usize = 2048;
+const HS256_SECRET_LEN: usize = 32;
+const MASTER_KEY_LEN: usize = 32;
+
+/// A key tokens are signed and verified with, named by its key id.
+#[derive(Clone)]
+pub struct JwtKey {
+    pub(crate) kid: String,
+    pub(crate) algorithm: JwtAlgorithm,
+    pub(crate) encoding_key: Option<EncodingKey>,
+    pub(crate) decoding_key: DecodingKey,
+    /// Public half of an asymmetric key, published in the JWKS.
+    pub(crate) jwk: Option<Jwk>,
+}
+
+impl JwtKey {
+    /// Builds a key from its material. HS256 needs the secret as
+    /// `private_key`; RS256 and EdDSA need `public_key_pem` and, to sign, the
+    /// PEM encoded `private_key`.
+    ///
+    /// The key id is the RFC 7638 thumbprint of the key, so it is stable for
+    /// a given key wherever it is loaded.
+    pub fn new(
+        algorithm: JwtAlgorithm,
+        private_key: Option<&[u8]>,
+        public_key_pem: Option<&str>,
+    ) -> Result<Self, AuthError> {
+        match algorithm {
+            JwtAlgorithm::HS256 => {
+                let secret = private_key
+                    .filter(|s| !s.is_empty())
+                    .ok_or_else(|| AuthError::InvalidKey("HS256 requires a secret".into()))?;
+                Ok(Self {
+                    kid: secret_key_id(secret),
+                    algorithm,
+                    encoding_key: Some(EncodingKey::from_secret(secret)),
+                    decoding_key: DecodingKey::from_secret(secret),
+                    jwk: None,
+                })
+            }
+            JwtAlgorithm::RS256 | JwtAlgorithm::EdDSA => {
+                let public_key = public_key_pem.ok_or_else(|| {
+                    AuthError::InvalidKey(format!("{} requires a public key", algorithm))
+                })?;
+                let decoding_key = match algorithm {
+                    JwtAlgorithm::RS256 => DecodingKey::from_rsa_pem(public_key.as_bytes()),
+                    _ => DecodingKey::from_ed_pem(public_key.as_bytes()),
+                }
+                .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+                let encoding_key = private_key
+                    .map(|private_key| match algorithm {
+                        JwtAlgorithm::RS256 => EncodingKey::from_rsa_pem(private_key),
+                        _ => EncodingKey::from_ed_pem(private_key),
+                    })
+                    .transpose()
+                    .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+                let jwk = public_jwk(algorithm, public_key)?
+                    .ok_or_else(|| AuthError::InvalidKey("No public key to publish".into()))?;
+                Ok(Self {
+                    kid: jwk.common.key_id.clone().unwrap_or_default(),
+                    algorithm,
+                    encoding_key,
+                    decoding_key,
+                    jwk: Some(jwk),
+                })
+            }
+        }
+    }
+
+    /// A verification-only key published by a token issuer.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, AuthError> {
+        let kid = jwk
+            .common
+            .key_id
+            .clone()
+            .ok_or_else(|| AuthError::InvalidKey("JWK has no key id".into()))?;
+        let algorithm = match jwk.common.key_algorithm {
+            Some(KeyAlgorithm::RS256) => JwtAlgorithm::RS256,
+            Some(KeyAlgorithm::EdDSA) => JwtAlgorithm::EdDSA,
+            other => {
+                return Err(AuthError::InvalidKey(format!(
+                    "Unsupported JWK algorithm {:?}",
+                    other
+                )))
+            }
+        };
+        let decoding_key =
+            DecodingKey::from_jwk(jwk).map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+        Ok(Self {
+            kid,
+            algorithm,
+            encoding_key: None,
+            decoding_key,
+            jwk: Some(jwk.clone()),
+        })
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        self.algorithm
+    }
+
+    pub fn can_sign(&self) -> bool {
+        self.encoding_key.is_some()
+    }
+}
+
+/// RFC 7638 thumbprint of a symmetric key. It only ever appears in token
+/// headers, and reveals no more about the secret than the signatures next
+/// to it do.
+fn secret_key_id(secret: &[u8]) -> String {
+    let thumbprint_input = json!({ "k": URL_SAFE_NO_PAD.encode(secret), "kty": "oct" });
+    URL_SAFE_NO_PAD.encode(Sha256::digest(thumbprint_input.to_string().as_bytes()))
+}
+
+/// A newly generated signing key together with the material a key store
+/// keeps to load it again.
+pub struct GeneratedKey {
+    pub key: JwtKey,
+    /// PEM encoded private key, or the secret for HS256.
+    pub private_key: Vec<u8>,
+    /// `None` for HS256.
+    pub public_key_pem: Option<String>,
+}
+
+/// Generates a random signing key for `algorithm`.
+pub fn generate_signing_key(algorithm: JwtAlgorithm) -> Result<GeneratedKey, AuthError> {
+    let rng = SystemRandom::new();
+    let (private_key, public_key_pem) = match algorithm {
+        JwtAlgorithm::HS256 => {
+            let mut secret = vec![0u8; HS256_SECRET_LEN];
+            rng.fill(&mut secret)
+                .map_err(|_| AuthError::InvalidKey("Failed to generate a secret".into()))?;
+            (secret, None)
+        }
+        JwtAlgorithm::RS256 => {
+            let private_key = RsaPrivateKey::new(&mut rand_core::OsRng, RSA_KEY_BITS)
+                .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+            let private_pem = private_key
+                .to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+            let public_pem = private_key
+                .to_public_key()
+                .to_public_key_pem(LineEnding::LF)
+                .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+            (private_pem.as_bytes().to_vec(), Some(public_pem))
+        }
+        JwtAlgorithm::EdDSA => {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng)
+                .map_err(|_| AuthError::InvalidKey("Failed to generate an Ed25519 key".into()))?;
+            let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+                .map_err(|e| AuthError::InvalidKey(e.to_string()))?;
+            let spki = [
+                ED25519_SPKI_PREFIX.as_slice(),
+                key_pair.public_key().as_ref(),
+            ]
+            .concat();
+            (
+                pem("PRIVATE KEY", pkcs8.as_ref()).into_bytes(),
+                Some(pem("PUBLIC KEY", &spki)),
+            )
+        }
+    };
+    let key = JwtKey::new(algorithm, Some(&private_key), public_key_pem.as_deref())?;
+    Ok(GeneratedKey {
+        key,
+        private_key,
+        public_key_pem,
+    })
+}
+
+fn pem(label: &str, der: &[u8]) -> String {
+    let body = STANDARD.encode(der);
+    let lines: Vec<&str> = body
+        .as_bytes()
+        .chunks(64)
+        .map(|line| std::str::from_utf8(line).unwrap_or_default())
+        .collect();
+    format!(
+        "-----BEGIN {label}-----\n{}\n-----END {label}-----\n",
+        lines.join("\n")
+    )
+}
+
+/// The key signing keys are sealed under before they are stored, using
+/// AES-256-GCM.
+pub struct MasterKey {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl MasterKey {
+    /// Reads a base64 encoded 256-bit key.
+    pub fn from_base64(encoded: &str) -> Result<Self, AuthError> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| AuthError::InvalidKey(format!("Invalid master key: {}", e)))?;
+        if bytes.len() != MASTER_KEY_LEN {
+            return Err(AuthError::InvalidKey(format!(
+                "The master key must be {} bytes",
+                MASTER_KEY_LEN
+            )));
+        }
+        let key = UnboundKey::new(&AES_256_GCM, &bytes)
+            .map_err(|_| AuthError::InvalidKey("Invalid master key".into()))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Encrypts `plaintext` for storage. The result only opens with the same
+    /// `context`, such as the id of the key it protects, so sealed values
+    /// cannot be swapped between rows.
+    pub fn seal(&self, plaintext: &[u8], context: &str) -> Result<Vec<u8>, AuthError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| AuthError::InvalidKey("Failed to generate a nonce".into()))?;
+        let mut sealed = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(context.as_bytes()),
+                &mut sealed,
+            )
+            .map_err(|_| AuthError::InvalidKey("Failed to seal key".into()))?;
+        Ok([nonce.as_slice(), &sealed].concat())
+    }
+
+    pub fn open(&self, sealed: &[u8], context: &str) -> Result<Vec<u8>, AuthError> {
+        let invalid =
+            || AuthError::InvalidKey("Sealed key does not open with the master key".into());
+        if sealed.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| invalid())?;
+        let mut plaintext = ciphertext.to_vec();
+        let len = self
+            .key
+            .open_in_place(nonce, Aad::from(context.as_bytes()), &mut plaintext)
+            .map_err(|_| invalid())?
+            .len();
+        plaintext.truncate(len);
+        Ok(plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn master_key() -> MasterKey {
+        MasterKey::from_base64(&STANDARD.encode([7u8; MASTER_KEY_LEN])).unwrap()
+    }
+
+    #[test]
+    fn test_generated_keys_load_from_their_material() {
+        for algorithm in [JwtAlgorithm::HS256, JwtAlgorithm::EdDSA] {
+            let generated = generate_signing_key(algorithm).unwrap();
+            assert!(generated.key.can_sign());
+            let loaded = JwtKey::new(
+                algorithm,
+                Some(&generated.private_key),
+                generated.public_key_pem.as_deref(),
+            )
+            .unwrap();
+            assert_eq!(loaded.kid(), generated.key.kid());
+            assert_eq!(loaded.jwk.is_some(), algorithm != JwtAlgorithm::HS256);
+        }
+        let first = generate_signing_key(JwtAlgorithm::EdDSA).unwrap();
+        let second = generate_signing_key(JwtAlgorithm::EdDSA).unwrap();
+        assert_ne!(first.key.kid(), second.key.kid());
+    }
+
+    #[test]
+    fn test_published_key_verifies_only() {
+        let generated = generate_signing_key(JwtAlgorithm::EdDSA).unwrap();
+        let published = JwtKey::from_jwk(generated.key.jwk.as_ref().unwrap()).unwrap();
+        assert_eq!(published.kid(), generated.key.kid());
+        assert_eq!(published.algorithm(), JwtAlgorithm::EdDSA);
+        assert!(!published.can_sign());
+    }
+
+    #[test]
+    fn test_sealed_key_opens_in_its_context_only() {
+        let master_key = master_key();
+        let sealed = master_key.seal(b"private key", "kid-1").unwrap();
+        assert_ne!(&sealed[NONCE_LEN..], b"private key".as_slice());
+        assert_eq!(master_key.open(&sealed, "kid-1").unwrap(), b"private key");
+        assert!(master_key.open(&sealed, "kid-2").is_err());
+
+        let other = MasterKey::from_base64(&STANDARD.encode([8u8; MASTER_KEY_LEN])).unwrap();
+        assert!(other.open(&sealed, "kid-1").is_err());
+        assert!(MasterKey::from_base64(&STANDARD.encode([7u8; 16])).is_err());
+    }
+}
@@ -1,14 +1,28 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+pub mod api_key;
+pub mod error;
+pub mod federation;
+mod jwks;
+pub mod jwks_cache;
+pub mod jwt;
+pub mod keys;
+pub mod oidc;
+pub mod opaque;
+pub mod policy;
+pub mod principal;
+pub mod revocation;
+pub mod webauthn;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use api_key::{api_key_prefix, generate_api_key, GeneratedApiKey};
+pub use error::{AuthError, WebAuthnError};
+pub use jwks_cache::{HttpJwksSource, JwksCache, JwksSource};
+pub use jwt::{
+    AccessClaims, ActorClaim, ChallengeClaims, IssuedToken, JwtAlgorithm, JwtConfig, JwtService,
+};
+pub use keys::{generate_signing_key, GeneratedKey, JwtKey, MasterKey};
+pub use opaque::{generate_opaque_token, hash_token};
+pub use policy::{AccessRequest, Attributes, Decision, PolicySet};
+pub use principal::{
+    api_key, bearer_token, ApiKeyVerifier, AuthState, Credential, KeyRefresher, Principal,
+    API_KEY_ROLE,
+};
+pub use revocation::RevocationList;
@@ -0,0 +1,185 @@
+//! Building blocks of the OpenID Connect provider: ID token and userinfo
+//! claims, PKCE and the discovery document.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::jwt::JwtAlgorithm;
+
+pub const SCOPE_OPENID: &str = "openid";
+pub const SCOPE_PROFILE: &str = "profile";
+pub const SCOPE_EMAIL: &str = "email";
+pub const SUPPORTED_SCOPES: [&str; 3] = [SCOPE_OPENID, SCOPE_PROFILE, SCOPE_EMAIL];
+
+/// The only PKCE method accepted; `plain` offers no protection.
+pub const PKCE_METHOD_S256: &str = "S256";
+
+/// Standard claims describing the user, released according to the granted
+/// scopes: `email` and `email_verified` for `email`, `name` for `profile`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandardClaims {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Claims of an ID token issued to a relying party.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    /// User id.
+    pub sub: String,
+    /// Client id of the relying party.
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    /// When the user authenticated.
+    pub auth_time: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// Tenant id.
+    pub tid: String,
+    #[serde(flatten)]
+    pub profile: StandardClaims,
+}
+
+/// Claims of an access token handed to a relying party. It is only good for
+/// the userinfo endpoint, never for the rest of the API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OidcAccessClaims {
+    /// User id.
+    pub sub: String,
+    /// Tenant id.
+    pub tid: String,
+    /// Client id the token was issued to.
+    pub client_id: String,
+    /// Space separated granted scopes.
+    pub scope: String,
+    pub iss: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: String,
+}
+
+/// Body of the userinfo response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub sub: String,
+    #[serde(flatten)]
+    pub profile: StandardClaims,
+}
+
+/// The S256 code challenge for a PKCE code verifier.
+pub fn pkce_s256_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Whether `code_verifier` has the shape RFC 7636 §4.1 requires: 43 to 128
+/// characters from the unreserved set.
+pub fn is_valid_code_verifier(code_verifier: &str) -> bool {
+    (43..=128).contains(&code_verifier.len())
+        && code_verifier
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'))
+}
+
+/// Checks a code verifier against the S256 challenge sent to `/authorize`.
+pub fn verify_pkce_s256(code_verifier: &str, code_challenge: &str) -> bool {
+    is_valid_code_verifier(code_verifier) && pkce_s256_challenge(code_verifier) == code_challenge
+}
+
+/// The document served at `/.well-known/openid-configuration`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub jwks_uri: String,
+    pub response_types_supported: Vec<String>,
+    pub grant_types_supported: Vec<String>,
+    pub subject_types_supported: Vec<String>,
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    pub scopes_supported: Vec<String>,
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    pub claims_supported: Vec<String>,
+    pub code_challenge_methods_supported: Vec<String>,
+}
+
+impl ProviderMetadata {
+    /// Metadata for a provider whose endpoints live directly under `issuer`.
+    pub fn new(issuer: &str, algorithm: JwtAlgorithm) -> Self {
+        let base = issuer.trim_end_matches('/');
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        Self {
+            issuer: issuer.to_string(),
+            authorization_endpoint: format!("{}/authorize", base),
+            token_endpoint: format!("{}/token", base),
+            userinfo_endpoint: format!("{}/userinfo", base),
+            jwks_uri: format!("{}/jwks.json", base),
+            response_types_supported: strings(&["code"]),
+            grant_types_supported: strings(&["authorization_code"]),
+            subject_types_supported: strings(&["public"]),
+            id_token_signing_alg_values_supported: strings(&[algorithm.as_str()]),
+            scopes_supported: strings(&SUPPORTED_SCOPES),
+            token_endpoint_auth_methods_supported: strings(&[
+                "client_secret_basic",
+                "client_secret_post",
+                "none",
+            ]),
+            claims_supported: strings(&[
+                "iss",
+                "sub",
+                "aud",
+                "iat",
+                "exp",
+                "auth_time",
+                "nonce",
+                "email",
+                "email_verified",
+                "name",
+            ]),
+            code_challenge_methods_supported: strings(&[PKCE_METHOD_S256]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_s256_rfc7636_vector() {
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        let challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+        assert_eq!(pkce_s256_challenge(verifier), challenge);
+        assert!(verify_pkce_s256(verifier, challenge));
+        assert!(!verify_pkce_s256(
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXx",
+            challenge
+        ));
+    }
+
+    #[test]
+    fn test_code_verifier_shape() {
+        assert!(!is_valid_code_verifier("too-short"));
+        assert!(!is_valid_code_verifier(&"a".repeat(129)));
+        assert!(!is_valid_code_verifier(&format!("{}+", "a".repeat(42))));
+        assert!(is_valid_code_verifier(&"a~b.c-d_".repeat(6)));
+    }
+
+    #[test]
+    fn test_metadata_endpoints_hang_off_issuer() {
+        let metadata = ProviderMetadata::new("https://id.example.com/", JwtAlgorithm::EdDSA);
+        assert_eq!(metadata.issuer, "https://id.example.com/");
+        assert_eq!(metadata.token_endpoint, "https://id.example.com/token");
+        assert_eq!(metadata.jwks_uri, "https://id.example.com/jwks.json");
+        assert_eq!(metadata.id_token_signing_alg_values_supported, ["EdDSA"]);
+    }
+}
@@ -0,0 +1,42 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Number of random bytes in an opaque token.
+const TOKEN_BYTES: usize = 32;
+
+/// Generates a random, URL safe opaque token.
+///
+/// Only the value returned by [`hash_token`] should be persisted; the token
+/// itself is handed to the client once.
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hex encoded SHA-256 digest of a token, used as its lookup key.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_tokens_are_unique() {
+        let first = generate_opaque_token();
+        let second = generate_opaque_token();
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 43);
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        assert_eq!(hash_token("token"), hash_token("token"));
+        assert_ne!(hash_token("token"), hash_token("other"));
+        assert_eq!(hash_token("token").len(), 64);
+    }
+}
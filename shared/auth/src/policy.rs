@@ -0,0 +1,532 @@
+//! Attribute-based access control.
+//!
+//! A [`PolicySet`] decides whether a subject may perform an action on a
+//! resource by matching the attributes of an [`AccessRequest`] against
+//! declarative policies, typically loaded from JSON:
+//!
+//! ```json
+//! [
+//!   {
+//!     "id": "managers-edit-non-admins",
+//!     "effect": "allow",
+//!     "actions": ["users:unlock"],
+//!     "condition": {
+//!       "all": [
+//!         { "eq": [{ "attr": "subject.role" }, "manager"] },
+//!         { "eq": [{ "attr": "resource.tenant_id" }, { "attr": "subject.tenant_id" }] },
+//!         { "not_in": [{ "attr": "resource.role" }, ["admin", "supper_admin"]] }
+//!       ]
+//!     }
+//!   }
+//! ]
+//! ```
+//!
+//! Any matching `deny` policy wins over `allow` policies, and requests no
+//! policy allows are denied.
+
+use std::cmp::Ordering;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::error::AuthError;
+use crate::principal::{Credential, Principal};
+
+/// Named attributes of one side of an access request.
+pub type Attributes = Map<String, Value>;
+
+/// Roots an attribute path may start with.
+const ATTRIBUTE_ROOTS: [&str; 4] = ["subject", "resource", "context", "action"];
+
+/// What is being asked: may `subject` perform `action` on `resource`?
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccessRequest {
+    pub subject: Attributes,
+    pub action: String,
+    pub resource: Attributes,
+    /// Facts about the request itself, such as the time it is made at.
+    pub context: Attributes,
+}
+
+impl AccessRequest {
+    /// Resolves a dotted attribute path such as `subject.role`. The bare
+    /// path `action` names the requested action.
+    fn attribute(&self, path: &str) -> Option<Value> {
+        let mut segments = path.split('.');
+        let attributes = match segments.next()? {
+            "subject" => &self.subject,
+            "resource" => &self.resource,
+            "context" => &self.context,
+            "action" if path == "action" => return Some(Value::String(self.action.clone())),
+            _ => return None,
+        };
+        let mut value = attributes.get(segments.next()?)?;
+        for segment in segments {
+            value = value.get(segment)?;
+        }
+        Some(value.clone())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// One side of a comparison: an attribute of the request or a literal.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Operand {
+    Attribute { attr: String },
+    Literal(Value),
+}
+
+/// A predicate over the attributes of a request.
+///
+/// Comparisons involving an attribute the request does not carry are
+/// false, so a missing attribute never satisfies an `allow` condition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+    Eq(Operand, Operand),
+    Ne(Operand, Operand),
+    /// The left operand is an element of the array on the right.
+    In(Operand, Operand),
+    NotIn(Operand, Operand),
+    Gt(Operand, Operand),
+    Gte(Operand, Operand),
+    Lt(Operand, Operand),
+    Lte(Operand, Operand),
+}
+
+impl Condition {
+    fn evaluate(&self, request: &AccessRequest) -> bool {
+        match self {
+            Condition::All(conditions) => conditions.iter().all(|c| c.evaluate(request)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.evaluate(request)),
+            Condition::Not(condition) => !condition.evaluate(request),
+            Condition::Eq(l, r) => compare(request, l, r, |l, r| l == r),
+            Condition::Ne(l, r) => compare(request, l, r, |l, r| l != r),
+            Condition::In(l, r) => compare(request, l, r, contains),
+            Condition::NotIn(l, r) => {
+                compare(request, l, r, |l, r| r.is_array() && !contains(l, r))
+            }
+            Condition::Gt(l, r) => compare(request, l, r, |l, r| {
+                order(l, r).is_some_and(Ordering::is_gt)
+            }),
+            Condition::Gte(l, r) => compare(request, l, r, |l, r| {
+                order(l, r).is_some_and(Ordering::is_ge)
+            }),
+            Condition::Lt(l, r) => compare(request, l, r, |l, r| {
+                order(l, r).is_some_and(Ordering::is_lt)
+            }),
+            Condition::Lte(l, r) => compare(request, l, r, |l, r| {
+                order(l, r).is_some_and(Ordering::is_le)
+            }),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().try_for_each(Condition::validate)
+            }
+            Condition::Not(condition) => condition.validate(),
+            Condition::Eq(l, r)
+            | Condition::Ne(l, r)
+            | Condition::In(l, r)
+            | Condition::NotIn(l, r)
+            | Condition::Gt(l, r)
+            | Condition::Gte(l, r)
+            | Condition::Lt(l, r)
+            | Condition::Lte(l, r) => [l, r].into_iter().try_for_each(|operand| match operand {
+                Operand::Attribute { attr } => validate_path(attr),
+                Operand::Literal(_) => Ok(()),
+            }),
+        }
+    }
+}
+
+fn compare(
+    request: &AccessRequest,
+    left: &Operand,
+    right: &Operand,
+    op: impl Fn(&Value, &Value) -> bool,
+) -> bool {
+    let resolve = |operand: &Operand| match operand {
+        Operand::Attribute { attr } => request.attribute(attr),
+        Operand::Literal(value) => Some(value.clone()),
+    };
+    match (resolve(left), resolve(right)) {
+        (Some(left), Some(right)) => op(&left, &right),
+        _ => false,
+    }
+}
+
+fn contains(item: &Value, list: &Value) -> bool {
+    list.as_array().is_some_and(|list| list.contains(item))
+}
+
+/// Orders numbers numerically and strings lexically; other values are
+/// not ordered.
+fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64()?.partial_cmp(&r.as_f64()?),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+fn validate_path(path: &str) -> Result<(), String> {
+    let root = path.split('.').next().unwrap_or_default();
+    let valid = ATTRIBUTE_ROOTS.contains(&root) && (root == "action") == (path == "action");
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Unknown attribute {}", path))
+    }
+}
+
+/// A rule granting or refusing `actions` when its condition holds.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Policy {
+    pub id: String,
+    /// Why the policy exists; repeated in the decisions it makes.
+    #[serde(default)]
+    pub description: String,
+    pub effect: Effect,
+    /// Actions the policy applies to. `*` matches every action.
+    pub actions: Vec<String>,
+    /// Policies without a condition apply to every request for their actions.
+    #[serde(default)]
+    pub condition: Option<Condition>,
+}
+
+impl Policy {
+    fn applies_to(&self, request: &AccessRequest) -> bool {
+        self.actions
+            .iter()
+            .any(|action| action == "*" || *action == request.action)
+            && self
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition.evaluate(request))
+    }
+
+    fn explain(&self, verb: &str) -> String {
+        if self.description.is_empty() {
+            format!("{} by policy {}", verb, self.id)
+        } else {
+            format!("{} by policy {}: {}", verb, self.id, self.description)
+        }
+    }
+}
+
+/// The outcome of evaluating an [`AccessRequest`], with the reason for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    /// The policy that decided, or `None` when no policy applied.
+    pub policy_id: Option<String>,
+    pub reason: String,
+}
+
+/// An ordered collection of policies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolicySet {
+    policies: Vec<Policy>,
+}
+
+impl PolicySet {
+    pub fn new(policies: Vec<Policy>) -> Result<Self, AuthError> {
+        for policy in &policies {
+            if let Some(condition) = &policy.condition {
+                condition
+                    .validate()
+                    .map_err(|e| AuthError::InvalidPolicy(format!("{}: {}", policy.id, e)))?;
+            }
+        }
+        Ok(Self { policies })
+    }
+
+    /// Parses a JSON array of policies.
+    pub fn from_value(value: Value) -> Result<Self, AuthError> {
+        let policies =
+            serde_json::from_value(value).map_err(|e| AuthError::InvalidPolicy(e.to_string()))?;
+        Self::new(policies)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, AuthError> {
+        let policies =
+            serde_json::from_str(json).map_err(|e| AuthError::InvalidPolicy(e.to_string()))?;
+        Self::new(policies)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, AuthError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| AuthError::InvalidPolicy(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    pub fn evaluate(&self, request: &AccessRequest) -> Decision {
+        self.evaluate_with(&PolicySet::default(), request)
+    }
+
+    /// Evaluates this set together with `extra`, e.g. a tenant's own
+    /// policies on top of the service-wide ones.
+    pub fn evaluate_with(&self, extra: &PolicySet, request: &AccessRequest) -> Decision {
+        let mut allowed_by = None;
+        for policy in self.policies.iter().chain(&extra.policies) {
+            if !policy.applies_to(request) {
+                continue;
+            }
+            match policy.effect {
+                Effect::Deny => {
+                    return Decision {
+                        allowed: false,
+                        policy_id: Some(policy.id.clone()),
+                        reason: policy.explain("Denied"),
+                    };
+                }
+                Effect::Allow => {
+                    allowed_by.get_or_insert(policy);
+                }
+            }
+        }
+        match allowed_by {
+            Some(policy) => Decision {
+                allowed: true,
+                policy_id: Some(policy.id.clone()),
+                reason: policy.explain("Allowed"),
+            },
+            None => Decision {
+                allowed: false,
+                policy_id: None,
+                reason: format!("No policy allows {}", request.action),
+            },
+        }
+    }
+}
+
+impl Principal {
+    /// The caller as the subject of an [`AccessRequest`]: `id`,
+    /// `tenant_id`, `role`, `credential` and, for API keys, `scopes` or,
+    /// for impersonations, `actor_id`.
+    pub fn attributes(&self) -> Attributes {
+        let mut attributes = Attributes::new();
+        attributes.insert("id".into(), self.user_id.clone().into());
+        attributes.insert("tenant_id".into(), self.tenant_id.clone().into());
+        attributes.insert("role".into(), self.role.clone().into());
+        match &self.credential {
+            Credential::AccessToken { .. } => {
+                attributes.insert("credential".into(), "access_token".into());
+            }
+            Credential::ApiKey { scopes } => {
+                attributes.insert("credential".into(), "api_key".into());
+                attributes.insert("scopes".into(), scopes.clone().into());
+            }
+            Credential::Impersonation { actor_id, .. } => {
+                attributes.insert("credential".into(), "impersonation".into());
+                attributes.insert("actor_id".into(), actor_id.clone().into());
+            }
+        }
+        attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const POLICIES: &str = r#"[
+        {
+            "id": "admins",
+            "effect": "allow",
+            "actions": ["*"],
+            "condition": { "in": [{ "attr": "subject.role" }, ["admin", "supper_admin"]] }
+        },
+        {
+            "id": "managers-edit-non-admins",
+            "description": "Managers may edit users of their tenant who are not admins",
+            "effect": "allow",
+            "actions": ["users:update"],
+            "condition": {
+                "all": [
+                    { "eq": [{ "attr": "subject.role" }, "manager"] },
+                    { "not_in": [{ "attr": "resource.role" }, ["admin", "supper_admin"]] }
+                ]
+            }
+        },
+        {
+            "id": "viewers-see-active-users",
+            "effect": "allow",
+            "actions": ["users:read"],
+            "condition": {
+                "all": [
+                    { "eq": [{ "attr": "subject.role" }, "viewer"] },
+                    { "eq": [{ "attr": "resource.status" }, "active"] }
+                ]
+            }
+        },
+        {
+            "id": "tenant-isolation",
+            "description": "Resources of other tenants are off limits",
+            "effect": "deny",
+            "actions": ["*"],
+            "condition": { "ne": [{ "attr": "resource.tenant_id" }, { "attr": "subject.tenant_id" }] }
+        }
+    ]"#;
+
+    fn attributes(value: Value) -> Attributes {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    fn request(role: &str, action: &str, resource: Value) -> AccessRequest {
+        AccessRequest {
+            subject: attributes(json!({ "id": "u1", "tenant_id": "t1", "role": role })),
+            action: action.to_string(),
+            resource: attributes(resource),
+            context: Attributes::new(),
+        }
+    }
+
+    #[test]
+    fn test_manager_may_edit_non_admins_only() {
+        let policies = PolicySet::from_json(POLICIES).unwrap();
+        let user = json!({ "tenant_id": "t1", "role": "user" });
+        let admin = json!({ "tenant_id": "t1", "role": "admin" });
+
+        let decision = policies.evaluate(&request("manager", "users:update", user.clone()));
+        assert!(decision.allowed);
+        assert_eq!(
+            decision.policy_id.as_deref(),
+            Some("managers-edit-non-admins")
+        );
+        assert!(decision.reason.contains("Managers may edit users"));
+
+        let decision = policies.evaluate(&request("manager", "users:update", admin));
+        assert!(!decision.allowed);
+        assert_eq!(decision.policy_id, None);
+        assert_eq!(decision.reason, "No policy allows users:update");
+
+        assert!(
+            !policies
+                .evaluate(&request("manager", "users:delete", user))
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_deny_overrides_allow() {
+        let policies = PolicySet::from_json(POLICIES).unwrap();
+        let decision = policies.evaluate(&request(
+            "admin",
+            "users:update",
+            json!({ "tenant_id": "t2", "role": "user" }),
+        ));
+        assert!(!decision.allowed);
+        assert_eq!(decision.policy_id.as_deref(), Some("tenant-isolation"));
+    }
+
+    #[test]
+    fn test_missing_attributes_never_allow() {
+        let policies = PolicySet::from_json(POLICIES).unwrap();
+        let active = json!({ "tenant_id": "t1", "status": "active" });
+        let suspended = json!({ "tenant_id": "t1", "status": "suspended" });
+        let unknown = json!({ "tenant_id": "t1" });
+        assert!(
+            policies
+                .evaluate(&request("viewer", "users:read", active))
+                .allowed
+        );
+        assert!(
+            !policies
+                .evaluate(&request("viewer", "users:read", suspended))
+                .allowed
+        );
+        assert!(
+            !policies
+                .evaluate(&request("viewer", "users:read", unknown))
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_extra_policies_are_evaluated_too() {
+        let policies = PolicySet::from_json(POLICIES).unwrap();
+        let tenant = PolicySet::from_value(json!([{
+            "id": "no-night-edits",
+            "effect": "deny",
+            "actions": ["users:update"],
+            "condition": { "gte": [{ "attr": "context.hour" }, 22] }
+        }]))
+        .unwrap();
+        let mut late = request("admin", "users:update", json!({ "tenant_id": "t1" }));
+        late.context.insert("hour".into(), 23.into());
+        let decision = policies.evaluate_with(&tenant, &late);
+        assert!(!decision.allowed);
+        assert_eq!(decision.policy_id.as_deref(), Some("no-night-edits"));
+        assert!(policies.evaluate(&late).allowed);
+    }
+
+    #[test]
+    fn test_api_key_scopes_are_attributes() {
+        let policies = PolicySet::from_value(json!([{
+            "id": "api-keys-read",
+            "effect": "allow",
+            "actions": ["users:read"],
+            "condition": { "in": ["users:read", { "attr": "subject.scopes" }] }
+        }]))
+        .unwrap();
+        let key = Principal {
+            user_id: "k1".into(),
+            tenant_id: "t1".into(),
+            role: crate::API_KEY_ROLE.into(),
+            credential: Credential::ApiKey {
+                scopes: vec!["users:read".into()],
+            },
+        };
+        let request = AccessRequest {
+            subject: key.attributes(),
+            action: "users:read".into(),
+            ..Default::default()
+        };
+        assert!(policies.evaluate(&request).allowed);
+        let user = Principal {
+            credential: Credential::AccessToken { session_id: None },
+            ..key
+        };
+        let request = AccessRequest {
+            subject: user.attributes(),
+            ..request
+        };
+        assert!(!policies.evaluate(&request).allowed);
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        for policies in [
+            json!([{ "id": "p", "effect": "allow", "actions": ["*"],
+                     "condition": { "eq": [{ "attr": "user.role" }, "admin"] } }]),
+            json!([{ "id": "p", "effect": "maybe", "actions": ["*"] }]),
+            json!([{ "id": "p", "effect": "allow", "actions": ["*"],
+                     "condition": { "like": [{ "attr": "subject.role" }, "adm%"] } }]),
+        ] {
+            assert!(matches!(
+                PolicySet::from_value(policies),
+                Err(AuthError::InvalidPolicy(_))
+            ));
+        }
+    }
+}
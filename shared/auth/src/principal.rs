@@ -0,0 +1,305 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use base::web::error::AppError;
+
+use crate::error::AuthError;
+use crate::jwt::{AccessClaims, JwtService};
+use crate::revocation::RevocationList;
+
+/// Role of principals authenticated with an API key. It matches no user
+/// role, so role checks never let a key through.
+pub const API_KEY_ROLE: &str = "api_key";
+
+/// How a [`Principal`] authenticated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credential {
+    /// A user's access token, issued for the login session `session_id`
+    /// unless it predates sessions.
+    AccessToken { session_id: Option<String> },
+    /// A tenant API key, limited to the scopes it was minted with.
+    ApiKey { scopes: Vec<String> },
+    /// An access token `actor_id` was issued to act as the user.
+    /// `session_id` is the impersonation's session, which tells
+    /// impersonations apart in the audit trail and revokes the token.
+    Impersonation {
+        actor_id: String,
+        session_id: String,
+    },
+}
+
+/// The authenticated caller of a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// User id, or the key id for API keys.
+    pub user_id: String,
+    pub tenant_id: String,
+    pub role: String,
+    pub credential: Credential,
+}
+
+impl Principal {
+    pub fn is_api_key(&self) -> bool {
+        matches!(self.credential, Credential::ApiKey { .. })
+    }
+
+    /// The admin behind an impersonation, if this is one.
+    pub fn impersonator(&self) -> Option<&str> {
+        match &self.credential {
+            Credential::Impersonation { actor_id, .. } => Some(actor_id),
+            _ => None,
+        }
+    }
+
+    /// Who changes made by this caller are attributed to: the impersonating
+    /// admin rather than the user they act as.
+    pub fn audit_id(&self) -> &str {
+        self.impersonator().unwrap_or(&self.user_id)
+    }
+
+    /// Guards actions only the account holder may take, such as managing
+    /// their credentials.
+    pub fn forbid_impersonation(&self) -> Result<(), AppError> {
+        match self.impersonator() {
+            Some(_) => Err(AppError::Forbidden(
+                "Not allowed while impersonating a user".into(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Login session the caller's access token was issued for.
+    pub fn session_id(&self) -> Option<&str> {
+        match &self.credential {
+            Credential::AccessToken { session_id } => session_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the caller may act within `scope`. Users are only bounded by
+    /// their role, API keys by the scopes they carry.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        match &self.credential {
+            Credential::AccessToken { .. } | Credential::Impersonation { .. } => true,
+            Credential::ApiKey { scopes } => scopes.iter().any(|s| s == scope),
+        }
+    }
+
+    pub fn require_scope(&self, scope: &str) -> Result<(), AppError> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!("Missing scope {}", scope)))
+        }
+    }
+}
+
+impl From<AccessClaims> for Principal {
+    fn from(claims: AccessClaims) -> Self {
+        let credential = match claims.act {
+            Some(actor) => Credential::Impersonation {
+                actor_id: actor.sub,
+                // Tokens issued before impersonations had sessions only
+                // have their own id to go by.
+                session_id: claims.sid.unwrap_or(claims.jti),
+            },
+            None => Credential::AccessToken {
+                session_id: claims.sid,
+            },
+        };
+        Self {
+            user_id: claims.sub,
+            tenant_id: claims.tid,
+            role: claims.role,
+            credential,
+        }
+    }
+}
+
+/// Resolves the keys of `Authorization: ApiKey ...` headers.
+#[async_trait]
+pub trait ApiKeyVerifier: Send + Sync {
+    async fn verify_api_key(&self, key: &str) -> Result<Principal, AppError>;
+}
+
+/// Reloads token verification keys, so tokens signed by a key that was
+/// rotated in after the keys were loaded verify without a restart.
+#[async_trait]
+pub trait KeyRefresher: Send + Sync {
+    /// Implementations limit how often the keys are actually reloaded: any
+    /// caller can present a token naming a made-up key.
+    async fn refresh_keys(&self) -> Result<(), AuthError>;
+}
+
+/// Application state that can authenticate requests.
+///
+/// Implement this for the router state to use [`Principal`] as an extractor.
+pub trait AuthState: Send + Sync {
+    fn jwt_service(&self) -> &JwtService;
+
+    /// Verifier for API keys. States without one reject them.
+    fn api_key_verifier(&self) -> Option<&dyn ApiKeyVerifier> {
+        None
+    }
+
+    /// Consulted when a token names a key the [`JwtService`] does not hold.
+    /// States without one reject such tokens.
+    fn key_refresher(&self) -> Option<&dyn KeyRefresher> {
+        None
+    }
+
+    /// Revoked login sessions, whose access tokens are refused. States
+    /// without one accept tokens until they expire.
+    fn revocation_list(&self) -> Option<&RevocationList> {
+        None
+    }
+}
+
+impl<T: AuthState> AuthState for Arc<T> {
+    fn jwt_service(&self) -> &JwtService {
+        (**self).jwt_service()
+    }
+
+    fn api_key_verifier(&self) -> Option<&dyn ApiKeyVerifier> {
+        (**self).api_key_verifier()
+    }
+
+    fn key_refresher(&self) -> Option<&dyn KeyRefresher> {
+        (**self).key_refresher()
+    }
+
+    fn revocation_list(&self) -> Option<&RevocationList> {
+        (**self).revocation_list()
+    }
+}
+
+/// Returns the credentials of an `Authorization: Bearer ...` header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    authorization(headers, "bearer")
+}
+
+/// Returns the key of an `Authorization: ApiKey ...` header.
+pub fn api_key(headers: &HeaderMap) -> Option<&str> {
+    authorization(headers, "apikey")
+}
+
+fn authorization<'a>(headers: &'a HeaderMap, expected_scheme: &str) -> Option<&'a str> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, credentials) = value.split_once(' ')?;
+    let credentials = credentials.trim();
+    (scheme.eq_ignore_ascii_case(expected_scheme) && !credentials.is_empty()).then_some(credentials)
+}
+
+impl<S: AuthState> FromRequestParts<S> for Principal {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(key) = api_key(&parts.headers) {
+            let verifier = state
+                .api_key_verifier()
+                .ok_or_else(|| AppError::Unauthorized("API keys are not accepted".into()))?;
+            return verifier.verify_api_key(key).await;
+        }
+        let token = bearer_token(&parts.headers)
+            .ok_or_else(|| AppError::Unauthorized("Missing bearer token".into()))?;
+        let mut result = state.jwt_service().verify_access_token(token);
+        if let (Err(AuthError::UnknownKey(_)), Some(refresher)) = (&result, state.key_refresher()) {
+            refresher.refresh_keys().await?;
+            result = state.jwt_service().verify_access_token(token);
+        }
+        let claims = result?;
+        if let (Some(session_id), Some(revocations)) = (&claims.sid, state.revocation_list()) {
+            if revocations.is_revoked(session_id) {
+                return Err(AuthError::SessionRevoked.into());
+            }
+        }
+        Ok(Principal::from(claims))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token(&headers("Bearer abc")), Some("abc"));
+        assert_eq!(bearer_token(&headers("bearer  abc ")), Some("abc"));
+        assert_eq!(bearer_token(&headers("Basic abc")), None);
+        assert_eq!(bearer_token(&headers("Bearer ")), None);
+        assert_eq!(bearer_token(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_api_key() {
+        assert_eq!(api_key(&headers("ApiKey ak_1_2")), Some("ak_1_2"));
+        assert_eq!(api_key(&headers("apikey ak_1_2")), Some("ak_1_2"));
+        assert_eq!(api_key(&headers("Bearer ak_1_2")), None);
+        assert_eq!(bearer_token(&headers("ApiKey ak_1_2")), None);
+    }
+
+    #[test]
+    fn test_api_keys_are_limited_to_their_scopes() {
+        let key = Principal {
+            user_id: "key-1".into(),
+            tenant_id: "tenant-1".into(),
+            role: API_KEY_ROLE.into(),
+            credential: Credential::ApiKey {
+                scopes: vec!["users:read".into()],
+            },
+        };
+        assert!(key.is_api_key());
+        assert!(key.has_scope("users:read"));
+        assert!(matches!(
+            key.require_scope("users:write"),
+            Err(AppError::Forbidden(_))
+        ));
+
+        let user = Principal {
+            credential: Credential::AccessToken { session_id: None },
+            ..key
+        };
+        assert!(user.has_scope("users:write"));
+    }
+
+    #[test]
+    fn test_impersonation_is_attributed_to_the_actor() {
+        let user = Principal {
+            user_id: "user-1".into(),
+            tenant_id: "tenant-1".into(),
+            role: "user".into(),
+            credential: Credential::AccessToken {
+                session_id: Some("session-1".into()),
+            },
+        };
+        assert_eq!(user.session_id(), Some("session-1"));
+        assert_eq!(user.audit_id(), "user-1");
+        assert!(user.forbid_impersonation().is_ok());
+
+        let impersonated = Principal {
+            credential: Credential::Impersonation {
+                actor_id: "admin-1".into(),
+                session_id: "session-1".into(),
+            },
+            ..user
+        };
+        assert_eq!(impersonated.impersonator(), Some("admin-1"));
+        assert_eq!(impersonated.session_id(), None);
+        assert_eq!(impersonated.audit_id(), "admin-1");
+        assert!(matches!(
+            impersonated.forbid_impersonation(),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+}
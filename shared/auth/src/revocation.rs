@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+
+/// Login sessions revoked while access tokens issued for them may still be
+/// unexpired.
+///
+/// Access tokens are checked against it on every request, so it lives in
+/// memory. An entry only needs to outlast the access tokens of its session,
+/// after which it is dropped and the list stays small.
+#[derive(Debug, Default)]
+pub struct RevocationList {
+    /// Session id to the time its last access token expires.
+    sessions: RwLock<HashMap<String, DateTime<Utc>>>,
+}
+
+impl RevocationList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuses the access tokens of `session_id` until `until`.
+    pub fn revoke(&self, session_id: &str, until: DateTime<Utc>) {
+        self.extend([(session_id.to_string(), until)]);
+    }
+
+    /// Adds revocations learned elsewhere, such as from other instances,
+    /// and drops the entries no longer needed.
+    pub fn extend(&self, revocations: impl IntoIterator<Item = (String, DateTime<Utc>)>) {
+        let now = Utc::now();
+        let mut sessions = self.sessions.write().unwrap();
+        sessions.retain(|_, until| *until > now);
+        for (session_id, until) in revocations {
+            if until > now {
+                let entry = sessions.entry(session_id).or_insert(until);
+                *entry = (*entry).max(until);
+            }
+        }
+    }
+
+    pub fn is_revoked(&self, session_id: &str) -> bool {
+        self.sessions
+            .read()
+            .unwrap()
+            .get(session_id)
+            .is_some_and(|until| *until > Utc::now())
+    }
+
+    /// When the access tokens of `session_id` stop being refused.
+    pub fn revoked_until(&self, session_id: &str) -> Option<DateTime<Utc>> {
+        self.sessions.read().unwrap().get(session_id).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_revocations_expire_with_the_tokens() {
+        let list = RevocationList::new();
+        let now = Utc::now();
+        list.revoke("session-1", now + Duration::minutes(15));
+        list.extend([
+            ("session-2".to_string(), now - Duration::seconds(1)),
+            ("session-3".to_string(), now + Duration::minutes(5)),
+        ]);
+        assert!(list.is_revoked("session-1"));
+        assert!(!list.is_revoked("session-2"));
+        assert!(list.is_revoked("session-3"));
+        assert!(!list.is_revoked("session-4"));
+        assert_eq!(list.len(), 2);
+
+        // A later revocation of the same session never shortens it.
+        list.revoke("session-1", now + Duration::minutes(1));
+        list.revoke("session-3", now - Duration::minutes(1));
+        assert!(list.is_revoked("session-1"));
+        assert!(list.is_revoked("session-3"));
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::Cursor;
+
+use ciborium::Value;
+use p256::ecdsa::VerifyingKey;
+use p256::{EncodedPoint, FieldBytes};
+
+use crate::error::WebAuthnError;
+
+/// User present.
+pub(crate) const FLAG_UP: u8 = 0x01;
+/// User verified.
+pub(crate) const FLAG_UV: u8 = 0x04;
+/// Attested credential data included.
+pub(crate) const FLAG_AT: u8 = 0x40;
+
+/// COSE key type, algorithm and curve identifiers for ES256 keys.
+const COSE_KTY_EC2: i64 = 2;
+pub(crate) const COSE_ALG_ES256: i64 = -7;
+const COSE_CRV_P256: i64 = 1;
+
+/// The authenticator data structure of WebAuthn §6.1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: u8,
+    pub sign_count: u32,
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AttestedCredential {
+    pub aaguid: [u8; 16],
+    pub credential_id: Vec<u8>,
+    /// COSE encoded public key.
+    pub public_key: Vec<u8>,
+}
+
+impl AuthenticatorData {
+    pub fn parse(bytes: &[u8]) -> Result<Self, WebAuthnError> {
+        if bytes.len() < 37 {
+            return Err(malformed("authenticator data is too short"));
+        }
+        let mut rp_id_hash = [0u8; 32];
+        rp_id_hash.copy_from_slice(&bytes[..32]);
+        let flags = bytes[32];
+        let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+
+        let attested_credential = if flags & FLAG_AT != 0 {
+            let rest = &bytes[37..];
+            if rest.len() < 18 {
+                return Err(malformed("attested credential data is too short"));
+            }
+            let mut aaguid = [0u8; 16];
+            aaguid.copy_from_slice(&rest[..16]);
+            let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+            let rest = &rest[18..];
+            if rest.len() < id_len {
+                return Err(malformed("credential id is truncated"));
+            }
+            let credential_id = rest[..id_len].to_vec();
+            // The public key is a CBOR item of unknown length; decode it to
+            // find out where it ends.
+            let mut cursor = Cursor::new(&rest[id_len..]);
+            ciborium::from_reader::<Value, _>(&mut cursor)
+                .map_err(|e| malformed(format!("credential public key: {}", e)))?;
+            let key_len = cursor.position() as usize;
+            let public_key = rest[id_len..id_len + key_len].to_vec();
+            Some(AttestedCredential {
+                aaguid,
+                credential_id,
+                public_key,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rp_id_hash,
+            flags,
+            sign_count,
+            attested_credential,
+        })
+    }
+
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.rp_id_hash.to_vec();
+        bytes.push(self.flags);
+        bytes.extend_from_slice(&self.sign_count.to_be_bytes());
+        if let Some(credential) = &self.attested_credential {
+            bytes.extend_from_slice(&credential.aaguid);
+            bytes.extend_from_slice(&(credential.credential_id.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&credential.credential_id);
+            bytes.extend_from_slice(&credential.public_key);
+        }
+        bytes
+    }
+
+    pub fn user_present(&self) -> bool {
+        self.flags & FLAG_UP != 0
+    }
+
+    pub fn user_verified(&self) -> bool {
+        self.flags & FLAG_UV != 0
+    }
+}
+
+/// Reads an ES256 public key from its COSE encoding.
+pub(crate) fn es256_verifying_key(cose_key: &[u8]) -> Result<VerifyingKey, WebAuthnError> {
+    let value: Value = ciborium::from_reader(cose_key)
+        .map_err(|e| malformed(format!("credential public key: {}", e)))?;
+    let map = value
+        .as_map()
+        .ok_or_else(|| malformed("credential public key is not a map"))?;
+    let int = |key: i64| {
+        lookup(map, key)
+            .and_then(Value::as_integer)
+            .and_then(|i| i64::try_from(i).ok())
+    };
+    let coordinate = |key: i64| {
+        lookup(map, key)
+            .and_then(Value::as_bytes)
+            .filter(|b| b.len() == 32)
+    };
+    if int(1) != Some(COSE_KTY_EC2)
+        || int(3) != Some(COSE_ALG_ES256)
+        || int(-1) != Some(COSE_CRV_P256)
+    {
+        return Err(WebAuthnError::UnsupportedKey);
+    }
+    let (Some(x), Some(y)) = (coordinate(-2), coordinate(-3)) else {
+        return Err(WebAuthnError::UnsupportedKey);
+    };
+    let point = EncodedPoint::from_affine_coordinates(
+        FieldBytes::from_slice(x),
+        FieldBytes::from_slice(y),
+        false,
+    );
+    VerifyingKey::from_encoded_point(&point).map_err(|_| WebAuthnError::UnsupportedKey)
+}
+
+/// COSE encoding of an ES256 public key.
+#[cfg(any(test, feature = "test-util"))]
+pub(crate) fn es256_cose_key(key: &VerifyingKey) -> Vec<u8> {
+    let point = key.to_encoded_point(false);
+    let entry = |k: i64, v: Value| (Value::Integer(k.into()), v);
+    let value = Value::Map(vec![
+        entry(1, Value::Integer(COSE_KTY_EC2.into())),
+        entry(3, Value::Integer(COSE_ALG_ES256.into())),
+        entry(-1, Value::Integer(COSE_CRV_P256.into())),
+        entry(
+            -2,
+            Value::Bytes(point.x().expect("uncompressed point").to_vec()),
+        ),
+        entry(
+            -3,
+            Value::Bytes(point.y().expect("uncompressed point").to_vec()),
+        ),
+    ]);
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&value, &mut bytes).expect("writing to a Vec cannot fail");
+    bytes
+}
+
+fn lookup(map: &[(Value, Value)], key: i64) -> Option<&Value> {
+    map.iter()
+        .find(|(k, _)| {
+            k.as_integer()
+                .and_then(|i| i64::try_from(i).ok())
+                .is_some_and(|k| k == key)
+        })
+        .map(|(_, v)| v)
+}
+
+pub(crate) fn malformed(reason: impl Into<String>) -> WebAuthnError {
+    WebAuthnError::Malformed(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::SigningKey;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_authenticator_data_roundtrip_with_credential() {
+        let key = SigningKey::random(&mut OsRng);
+        let data = AuthenticatorData {
+            rp_id_hash: [7u8; 32],
+            flags: FLAG_UP | FLAG_AT,
+            sign_count: 42,
+            attested_credential: Some(AttestedCredential {
+                aaguid: [0u8; 16],
+                credential_id: vec![1, 2, 3],
+                public_key: es256_cose_key(key.verifying_key()),
+            }),
+        };
+        let parsed = AuthenticatorData::parse(&data.to_bytes()).unwrap();
+        assert_eq!(parsed, data);
+        assert!(parsed.user_present());
+        assert!(!parsed.user_verified());
+    }
+
+    #[test]
+    fn test_truncated_data_is_rejected() {
+        assert!(AuthenticatorData::parse(&[0u8; 36]).is_err());
+        let mut bytes = vec![0u8; 37];
+        bytes[32] = FLAG_AT;
+        assert!(AuthenticatorData::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_cose_key_roundtrip() {
+        let key = SigningKey::random(&mut OsRng);
+        let cose = es256_cose_key(key.verifying_key());
+        assert_eq!(&es256_verifying_key(&cose).unwrap(), key.verifying_key());
+        assert_eq!(
+            es256_verifying_key(&[0xa0]),
+            Err(WebAuthnError::UnsupportedKey)
+        );
+    }
+}
@@ -0,0 +1,550 @@
+//! Server side of the WebAuthn registration and authentication ceremonies.
+//!
+//! Only ES256 credentials are supported, which every platform authenticator
+//! and security key implements. Attestation statements are not verified,
+//! matching the `none` conveyance preference sent in the creation options.
+
+mod authenticator_data;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+
+use std::time::Duration;
+
+use base64::alphabet::URL_SAFE;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use ciborium::Value;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::Signature;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::WebAuthnError;
+use authenticator_data::{es256_verifying_key, malformed, AuthenticatorData, COSE_ALG_ES256};
+
+const PUBLIC_KEY_TYPE: &str = "public-key";
+const CREATE_TYPE: &str = "webauthn.create";
+const GET_TYPE: &str = "webauthn.get";
+
+/// base64url as used by the WebAuthn JSON encoding. Padding is tolerated on
+/// input because some client libraries add it.
+const BASE64URL: GeneralPurpose = GeneralPurpose::new(
+    &URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub fn encode_base64url(bytes: &[u8]) -> String {
+    BASE64URL.encode(bytes)
+}
+
+pub fn decode_base64url(value: &str) -> Result<Vec<u8>, WebAuthnError> {
+    BASE64URL
+        .decode(value)
+        .map_err(|e| malformed(format!("invalid base64url: {}", e)))
+}
+
+/// The relying party a deployment acts as.
+#[derive(Debug, Clone)]
+pub struct RelyingParty {
+    /// Domain the credentials are scoped to, e.g. `example.com`.
+    pub id: String,
+    /// Name authenticators may show to the user.
+    pub name: String,
+    /// Origin the browser reports, e.g. `https://app.example.com`.
+    pub origin: String,
+    /// Reject ceremonies in which the authenticator did not verify the user
+    /// with a PIN or biometric.
+    pub require_user_verification: bool,
+    /// How long the browser and the server wait for a ceremony to finish.
+    pub timeout: Duration,
+}
+
+impl Default for RelyingParty {
+    fn default() -> Self {
+        Self {
+            id: "localhost".to_string(),
+            name: "ddd-rust-application".to_string(),
+            origin: "http://localhost:3000".to_string(),
+            require_user_verification: false,
+            timeout: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// `PublicKeyCredentialCreationOptions` in their JSON form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptions {
+    pub rp: RelyingPartyEntity,
+    pub user: UserEntity,
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<CredentialParameters>,
+    pub timeout: u64,
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    pub authenticator_selection: AuthenticatorSelection,
+    pub attestation: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelyingPartyEntity {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    /// base64url user handle.
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialParameters {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub alg: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// base64url credential id.
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: String,
+    pub user_verification: String,
+}
+
+/// `PublicKeyCredentialRequestOptions` in their JSON form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    pub challenge: String,
+    pub timeout: u64,
+    pub rp_id: String,
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    pub user_verification: String,
+}
+
+/// The `PublicKeyCredential` a browser returns from `navigator.credentials.create()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationCredential {
+    pub id: String,
+    pub raw_id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub response: AttestationResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+/// The `PublicKeyCredential` a browser returns from `navigator.credentials.get()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationCredential {
+    pub id: String,
+    pub raw_id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub response: AssertionResponse,
+}
+
+impl AuthenticationCredential {
+    /// The credential id the assertion claims to be made with.
+    pub fn credential_id(&self) -> Result<Vec<u8>, WebAuthnError> {
+        decode_base64url(&self.raw_id)
+    }
+
+    /// The user handle reported by a discoverable credential, if any.
+    pub fn user_handle(&self) -> Result<Option<Vec<u8>>, WebAuthnError> {
+        self.response
+            .user_handle
+            .as_deref()
+            .filter(|h| !h.is_empty())
+            .map(decode_base64url)
+            .transpose()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_handle: Option<String>,
+}
+
+/// A credential that passed registration, ready to be stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedCredential {
+    pub credential_id: Vec<u8>,
+    /// COSE encoded public key.
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+    /// Model identifier of the authenticator; all zeros when not disclosed.
+    pub aaguid: [u8; 16],
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+impl RelyingParty {
+    /// Options for `navigator.credentials.create()`. `exclude` lists the
+    /// credentials the user already has so the same authenticator is not
+    /// registered twice.
+    pub fn creation_options(
+        &self,
+        challenge: &str,
+        user_handle: &[u8],
+        user_name: &str,
+        exclude: &[Vec<u8>],
+    ) -> CreationOptions {
+        CreationOptions {
+            rp: RelyingPartyEntity {
+                id: self.id.clone(),
+                name: self.name.clone(),
+            },
+            user: UserEntity {
+                id: encode_base64url(user_handle),
+                name: user_name.to_string(),
+                display_name: user_name.to_string(),
+            },
+            challenge: challenge.to_string(),
+            pub_key_cred_params: vec![CredentialParameters {
+                kind: PUBLIC_KEY_TYPE.to_string(),
+                alg: COSE_ALG_ES256,
+            }],
+            timeout: self.timeout.as_millis() as u64,
+            exclude_credentials: descriptors(exclude),
+            authenticator_selection: AuthenticatorSelection {
+                resident_key: "preferred".to_string(),
+                user_verification: self.user_verification().to_string(),
+            },
+            attestation: "none".to_string(),
+        }
+    }
+
+    /// Options for `navigator.credentials.get()`. An empty `allow` list lets
+    /// the authenticator offer any discoverable credential for this party.
+    pub fn request_options(&self, challenge: &str, allow: &[Vec<u8>]) -> RequestOptions {
+        RequestOptions {
+            challenge: challenge.to_string(),
+            timeout: self.timeout.as_millis() as u64,
+            rp_id: self.id.clone(),
+            allow_credentials: descriptors(allow),
+            user_verification: self.user_verification().to_string(),
+        }
+    }
+
+    /// Checks a registration response against the challenge that was issued
+    /// for it and extracts the new credential.
+    pub fn verify_registration(
+        &self,
+        credential: &RegistrationCredential,
+        challenge: &str,
+    ) -> Result<VerifiedCredential, WebAuthnError> {
+        self.verify_client_data(
+            &credential.response.client_data_json,
+            CREATE_TYPE,
+            challenge,
+        )?;
+
+        let attestation = decode_base64url(&credential.response.attestation_object)?;
+        let attestation: Value = ciborium::from_reader(attestation.as_slice())
+            .map_err(|e| malformed(format!("attestation object: {}", e)))?;
+        let auth_data = attestation
+            .as_map()
+            .and_then(|map| {
+                map.iter()
+                    .find(|(k, _)| k.as_text() == Some("authData"))
+                    .and_then(|(_, v)| v.as_bytes())
+            })
+            .ok_or_else(|| malformed("attestation object has no authData"))?;
+
+        let auth_data = AuthenticatorData::parse(auth_data)?;
+        self.verify_authenticator_data(&auth_data)?;
+        let attested = auth_data
+            .attested_credential
+            .ok_or_else(|| malformed("no attested credential data"))?;
+        if decode_base64url(&credential.raw_id)? != attested.credential_id {
+            return Err(malformed("credential id does not match authenticator data"));
+        }
+        // Reject keys we could never verify assertions for.
+        es256_verifying_key(&attested.public_key)?;
+
+        Ok(VerifiedCredential {
+            credential_id: attested.credential_id,
+            public_key: attested.public_key,
+            sign_count: auth_data.sign_count,
+            aaguid: attested.aaguid,
+        })
+    }
+
+    /// Checks an assertion made with a stored credential and returns the
+    /// authenticator's new signature counter.
+    ///
+    /// A counter that does not move forward means two authenticators share
+    /// the key, so the assertion is refused. Authenticators that do not keep
+    /// a counter always report zero, which is accepted.
+    pub fn verify_authentication(
+        &self,
+        credential: &AuthenticationCredential,
+        challenge: &str,
+        public_key: &[u8],
+        stored_sign_count: u32,
+    ) -> Result<u32, WebAuthnError> {
+        let client_data =
+            self.verify_client_data(&credential.response.client_data_json, GET_TYPE, challenge)?;
+        let raw_auth_data = decode_base64url(&credential.response.authenticator_data)?;
+        let auth_data = AuthenticatorData::parse(&raw_auth_data)?;
+        self.verify_authenticator_data(&auth_data)?;
+
+        let key = es256_verifying_key(public_key)?;
+        let signature = Signature::from_der(&decode_base64url(&credential.response.signature)?)
+            .map_err(|_| WebAuthnError::InvalidSignature)?;
+        let mut signed = raw_auth_data;
+        signed.extend_from_slice(&Sha256::digest(&client_data));
+        key.verify(&signed, &signature)
+            .map_err(|_| WebAuthnError::InvalidSignature)?;
+
+        let new_count = auth_data.sign_count;
+        if (new_count != 0 || stored_sign_count != 0) && new_count <= stored_sign_count {
+            return Err(WebAuthnError::SignCountRegression);
+        }
+        Ok(new_count)
+    }
+
+    /// Validates the client data and returns its raw bytes.
+    fn verify_client_data(
+        &self,
+        encoded: &str,
+        expected_type: &str,
+        challenge: &str,
+    ) -> Result<Vec<u8>, WebAuthnError> {
+        let raw = decode_base64url(encoded)?;
+        let client_data: ClientData =
+            serde_json::from_slice(&raw).map_err(|e| malformed(format!("client data: {}", e)))?;
+        if client_data.kind != expected_type {
+            return Err(WebAuthnError::TypeMismatch(client_data.kind));
+        }
+        if decode_base64url(&client_data.challenge)? != decode_base64url(challenge)? {
+            return Err(WebAuthnError::ChallengeMismatch);
+        }
+        if client_data.origin != self.origin {
+            return Err(WebAuthnError::OriginMismatch(client_data.origin));
+        }
+        Ok(raw)
+    }
+
+    fn verify_authenticator_data(
+        &self,
+        auth_data: &AuthenticatorData,
+    ) -> Result<(), WebAuthnError> {
+        if auth_data.rp_id_hash[..] != Sha256::digest(self.id.as_bytes())[..] {
+            return Err(WebAuthnError::RpIdMismatch);
+        }
+        if !auth_data.user_present() {
+            return Err(WebAuthnError::UserNotPresent);
+        }
+        if self.require_user_verification && !auth_data.user_verified() {
+            return Err(WebAuthnError::UserNotVerified);
+        }
+        Ok(())
+    }
+
+    fn user_verification(&self) -> &'static str {
+        if self.require_user_verification {
+            "required"
+        } else {
+            "preferred"
+        }
+    }
+}
+
+fn descriptors(ids: &[Vec<u8>]) -> Vec<CredentialDescriptor> {
+    ids.iter()
+        .map(|id| CredentialDescriptor {
+            kind: PUBLIC_KEY_TYPE.to_string(),
+            id: encode_base64url(id),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::SoftwareAuthenticator;
+    use super::*;
+    use crate::generate_opaque_token;
+
+    fn relying_party() -> RelyingParty {
+        RelyingParty::default()
+    }
+
+    fn register(
+        rp: &RelyingParty,
+        authenticator: &mut SoftwareAuthenticator,
+    ) -> VerifiedCredential {
+        let challenge = generate_opaque_token();
+        let options = rp.creation_options(&challenge, b"user-1", "alice@example.com", &[]);
+        let response = authenticator.register(&options);
+        rp.verify_registration(&response, &challenge).unwrap()
+    }
+
+    #[test]
+    fn test_registration_and_authentication() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::new(&rp.origin);
+        let credential = register(&rp, &mut authenticator);
+        assert_eq!(credential.credential_id, authenticator.credential_id());
+        assert_eq!(credential.sign_count, 0);
+
+        let challenge = generate_opaque_token();
+        let options =
+            rp.request_options(&challenge, std::slice::from_ref(&credential.credential_id));
+        let assertion = authenticator.authenticate(&options);
+        assert_eq!(assertion.credential_id().unwrap(), credential.credential_id);
+        assert_eq!(assertion.user_handle().unwrap(), Some(b"user-1".to_vec()));
+        let count = rp
+            .verify_authentication(&assertion, &challenge, &credential.public_key, 0)
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_wrong_challenge_or_origin_is_rejected() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::new(&rp.origin);
+        let options = rp.creation_options(&generate_opaque_token(), b"user-1", "alice", &[]);
+        let response = authenticator.register(&options);
+        assert_eq!(
+            rp.verify_registration(&response, &generate_opaque_token()),
+            Err(WebAuthnError::ChallengeMismatch)
+        );
+
+        let mut phished = SoftwareAuthenticator::new("https://evil.example");
+        let challenge = generate_opaque_token();
+        let options = rp.creation_options(&challenge, b"user-1", "alice", &[]);
+        let response = phished.register(&options);
+        assert!(matches!(
+            rp.verify_registration(&response, &challenge),
+            Err(WebAuthnError::OriginMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_rp_id_must_match() {
+        let rp = relying_party();
+        let other = RelyingParty {
+            id: "other.example".to_string(),
+            ..relying_party()
+        };
+        let mut authenticator = SoftwareAuthenticator::new(&rp.origin);
+        let challenge = generate_opaque_token();
+        let response = authenticator.register(&other.creation_options(&challenge, b"u", "a", &[]));
+        assert_eq!(
+            rp.verify_registration(&response, &challenge),
+            Err(WebAuthnError::RpIdMismatch)
+        );
+    }
+
+    #[test]
+    fn test_assertion_type_cannot_register() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::new(&rp.origin);
+        let credential = register(&rp, &mut authenticator);
+        let challenge = generate_opaque_token();
+        let assertion = authenticator.authenticate(&rp.request_options(&challenge, &[]));
+        let forged = RegistrationCredential {
+            id: assertion.id.clone(),
+            raw_id: assertion.raw_id.clone(),
+            kind: assertion.kind.clone(),
+            response: AttestationResponse {
+                client_data_json: assertion.response.client_data_json.clone(),
+                attestation_object: String::new(),
+            },
+        };
+        assert!(matches!(
+            rp.verify_registration(&forged, &challenge),
+            Err(WebAuthnError::TypeMismatch(_))
+        ));
+        assert!(rp
+            .verify_authentication(&assertion, &challenge, &credential.public_key, 0)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_signature_from_another_key_is_rejected() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::new(&rp.origin);
+        register(&rp, &mut authenticator);
+        let mut impostor = SoftwareAuthenticator::new(&rp.origin);
+        let impostor_key = register(&rp, &mut impostor).public_key;
+
+        let challenge = generate_opaque_token();
+        let assertion = authenticator.authenticate(&rp.request_options(&challenge, &[]));
+        assert_eq!(
+            rp.verify_authentication(&assertion, &challenge, &impostor_key, 0),
+            Err(WebAuthnError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_sign_count_must_increase() {
+        let rp = relying_party();
+        let mut authenticator = SoftwareAuthenticator::new(&rp.origin);
+        let credential = register(&rp, &mut authenticator);
+        authenticator.set_sign_count(4);
+
+        let challenge = generate_opaque_token();
+        let assertion = authenticator.authenticate(&rp.request_options(&challenge, &[]));
+        assert_eq!(
+            rp.verify_authentication(&assertion, &challenge, &credential.public_key, 5),
+            Err(WebAuthnError::SignCountRegression)
+        );
+        assert_eq!(
+            rp.verify_authentication(&assertion, &challenge, &credential.public_key, 4),
+            Ok(5)
+        );
+    }
+
+    #[test]
+    fn test_user_verification_can_be_required() {
+        let rp = RelyingParty {
+            require_user_verification: true,
+            ..relying_party()
+        };
+        let mut authenticator = SoftwareAuthenticator::new(&rp.origin);
+        authenticator.set_user_verification(false);
+        let challenge = generate_opaque_token();
+        let response = authenticator.register(&rp.creation_options(&challenge, b"u", "a", &[]));
+        assert_eq!(
+            rp.verify_registration(&response, &challenge),
+            Err(WebAuthnError::UserNotVerified)
+        );
+    }
+}
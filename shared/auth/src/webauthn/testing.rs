@@ -0,0 +1,138 @@
+//! A software authenticator for exercising the ceremonies in tests.
+
+use ciborium::Value;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use rand_core::{OsRng, RngCore};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use super::authenticator_data::{
+    es256_cose_key, AttestedCredential, AuthenticatorData, FLAG_AT, FLAG_UP, FLAG_UV,
+};
+use super::{
+    decode_base64url, encode_base64url, AssertionResponse, AttestationResponse,
+    AuthenticationCredential, CreationOptions, RegistrationCredential, RequestOptions,
+    PUBLIC_KEY_TYPE,
+};
+
+/// Holds one ES256 credential and answers ceremonies the way a browser and
+/// a security key would together, using `origin` as the calling page.
+pub struct SoftwareAuthenticator {
+    origin: String,
+    signing_key: SigningKey,
+    credential_id: Vec<u8>,
+    user_handle: Option<Vec<u8>>,
+    sign_count: u32,
+    user_verification: bool,
+}
+
+impl SoftwareAuthenticator {
+    pub fn new(origin: &str) -> Self {
+        let mut credential_id = vec![0u8; 16];
+        OsRng.fill_bytes(&mut credential_id);
+        Self {
+            origin: origin.to_string(),
+            signing_key: SigningKey::random(&mut OsRng),
+            credential_id,
+            user_handle: None,
+            sign_count: 0,
+            user_verification: true,
+        }
+    }
+
+    pub fn credential_id(&self) -> &[u8] {
+        &self.credential_id
+    }
+
+    /// Sets the counter the next assertion increments, e.g. to mimic a clone.
+    pub fn set_sign_count(&mut self, sign_count: u32) {
+        self.sign_count = sign_count;
+    }
+
+    /// Whether the authenticator claims to have verified the user.
+    pub fn set_user_verification(&mut self, verified: bool) {
+        self.user_verification = verified;
+    }
+
+    pub fn register(&mut self, options: &CreationOptions) -> RegistrationCredential {
+        self.user_handle = Some(decode_base64url(&options.user.id).expect("valid user handle"));
+        let client_data = self.client_data("webauthn.create", &options.challenge);
+        let auth_data = AuthenticatorData {
+            rp_id_hash: Sha256::digest(options.rp.id.as_bytes()).into(),
+            flags: self.flags() | FLAG_AT,
+            sign_count: self.sign_count,
+            attested_credential: Some(AttestedCredential {
+                aaguid: [0u8; 16],
+                credential_id: self.credential_id.clone(),
+                public_key: es256_cose_key(self.signing_key.verifying_key()),
+            }),
+        };
+        let attestation = Value::Map(vec![
+            (Value::Text("fmt".into()), Value::Text("none".into())),
+            (Value::Text("attStmt".into()), Value::Map(vec![])),
+            (
+                Value::Text("authData".into()),
+                Value::Bytes(auth_data.to_bytes()),
+            ),
+        ]);
+        let mut attestation_object = Vec::new();
+        ciborium::into_writer(&attestation, &mut attestation_object)
+            .expect("writing to a Vec cannot fail");
+
+        RegistrationCredential {
+            id: encode_base64url(&self.credential_id),
+            raw_id: encode_base64url(&self.credential_id),
+            kind: PUBLIC_KEY_TYPE.to_string(),
+            response: AttestationResponse {
+                client_data_json: encode_base64url(&client_data),
+                attestation_object: encode_base64url(&attestation_object),
+            },
+        }
+    }
+
+    pub fn authenticate(&mut self, options: &RequestOptions) -> AuthenticationCredential {
+        self.sign_count += 1;
+        let client_data = self.client_data("webauthn.get", &options.challenge);
+        let auth_data = AuthenticatorData {
+            rp_id_hash: Sha256::digest(options.rp_id.as_bytes()).into(),
+            flags: self.flags(),
+            sign_count: self.sign_count,
+            attested_credential: None,
+        }
+        .to_bytes();
+        let mut signed = auth_data.clone();
+        signed.extend_from_slice(&Sha256::digest(&client_data));
+        let signature: Signature = self.signing_key.sign(&signed);
+
+        AuthenticationCredential {
+            id: encode_base64url(&self.credential_id),
+            raw_id: encode_base64url(&self.credential_id),
+            kind: PUBLIC_KEY_TYPE.to_string(),
+            response: AssertionResponse {
+                client_data_json: encode_base64url(&client_data),
+                authenticator_data: encode_base64url(&auth_data),
+                signature: encode_base64url(signature.to_der().as_bytes()),
+                user_handle: self.user_handle.as_deref().map(encode_base64url),
+            },
+        }
+    }
+
+    fn flags(&self) -> u8 {
+        if self.user_verification {
+            FLAG_UP | FLAG_UV
+        } else {
+            FLAG_UP
+        }
+    }
+
+    fn client_data(&self, kind: &str, challenge: &str) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "type": kind,
+            "challenge": challenge,
+            "origin": self.origin,
+            "crossOrigin": false,
+        }))
+        .expect("serializing JSON cannot fail")
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+/// What a secret prints and serializes as.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A value that must not end up in logs, error messages or responses by
+/// accident, such as a plaintext password or a token. `Debug`, `Display`
+/// and serialization print [`REDACTED`]; the value is only reachable
+/// through [`expose_secret`](Self::expose_secret) and is wiped from
+/// memory when dropped. Deserialization reads the plain value, so request
+/// bodies can carry secrets straight into it.
+pub struct Secret<T: Zeroize>(T);
+
+pub type SecretString = Secret<String>;
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+
+    /// Takes the value out, e.g. to hand it over in a response body. The
+    /// caller becomes responsible for not leaking it.
+    pub fn into_exposed(mut self) -> T
+    where
+        T: Default,
+    {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Not constant time; compare secrets an attacker can probe through their
+/// hashes instead.
+impl<T: Zeroize + PartialEq> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Zeroize + Eq> Eq for Secret<T> {}
+
+impl<T: Zeroize + Hash> Hash for Secret<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de, T: Zeroize + Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct LoginBody {
+        email: String,
+        password: SecretString,
+    }
+
+    #[test]
+    fn secret_is_redacted_in_debug_display_and_serde() {
+        let body: LoginBody =
+            serde_json::from_str(r#"{"email":"alice@example.com","password":"hunter2"}"#).unwrap();
+        assert_eq!(body.password.expose_secret(), "hunter2");
+
+        let debug = format!("{:?}", body);
+        assert!(debug.contains("alice@example.com"));
+        assert!(!debug.contains("hunter2"));
+        assert_eq!(body.password.to_string(), REDACTED);
+        let json = serde_json::to_string(&body).unwrap();
+        assert!(!json.contains("hunter2"));
+        assert!(json.contains(REDACTED));
+    }
+
+    #[test]
+    fn secret_hands_its_value_over() {
+        let secret = SecretString::from("hunter2");
+        assert_eq!(secret.clone(), secret);
+        assert_eq!(secret.into_exposed(), "hunter2");
+    }
+}
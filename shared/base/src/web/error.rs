@@ -3,25 +3,52 @@ use axum::{
     response::{IntoResponse, Response},
 };
 
-use crate::web::response::ApiResponse;
+use crate::web::response::{ApiError, ApiResponse};
 
 #[derive(Debug)]
 pub enum AppError {
     BadRequest(String),
+    /// A request refused for several reasons at once, e.g. every rule a
+    /// new password breaks. Answered with 400 and one error per reason.
+    Validation(Vec<ApiError>),
+    Unauthorized(String),
+    Forbidden(String),
     NotFound(String),
+    Locked(String),
+    TooManyRequests(String),
     InternalServerError(String),
+    /// The service is too busy to take the request now; clients may retry.
+    ServiceUnavailable(String),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message, code) = match self {
+            AppError::Validation(errors) => {
+                return ApiResponse::<()>::new(
+                    Some(StatusCode::BAD_REQUEST.as_u16()),
+                    Some("VALIDATION_FAILED".to_string()),
+                    None,
+                    Some(errors),
+                )
+                .into_response();
+            }
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg, "BAD_REQUEST"),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg, "UNAUTHORIZED"),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, "FORBIDDEN"),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, "NOT_FOUND"),
+            AppError::Locked(msg) => (StatusCode::LOCKED, msg, "LOCKED"),
+            AppError::TooManyRequests(msg) => {
+                (StatusCode::TOO_MANY_REQUESTS, msg, "TOO_MANY_REQUESTS")
+            }
             AppError::InternalServerError(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 msg,
                 "INTERNAL_SERVER_ERROR",
             ),
+            AppError::ServiceUnavailable(msg) => {
+                (StatusCode::SERVICE_UNAVAILABLE, msg, "SERVICE_UNAVAILABLE")
+            }
         };
         ApiResponse::<()>::error(status.as_u16(), message, code.to_string()).into_response()
     }
@@ -31,8 +58,17 @@ impl std::fmt::Display for AppError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AppError::BadRequest(msg) => write!(f, "{}", msg),
+            AppError::Validation(errors) => {
+                let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+                write!(f, "{}", messages.join("; "))
+            }
+            AppError::Unauthorized(msg) => write!(f, "{}", msg),
+            AppError::Forbidden(msg) => write!(f, "{}", msg),
             AppError::NotFound(msg) => write!(f, "{}", msg),
+            AppError::Locked(msg) => write!(f, "{}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "{}", msg),
             AppError::InternalServerError(msg) => write!(f, "{}", msg),
+            AppError::ServiceUnavailable(msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 pub struct ApiError {
     pub code: u16,
     pub message: String,
+    /// Machine-readable cause, such as the validation rule that failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 impl ApiError {
@@ -12,8 +15,14 @@ impl ApiError {
         Self {
             code,
             message: message.into(),
+            reason: None,
         }
     }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -41,7 +50,7 @@ where
         if status == StatusCode::NO_CONTENT {
             return status.into_response();
         }
-        
+
         (status, Json(self)).into_response()
     }
 }
@@ -82,7 +91,7 @@ impl<T> ApiResponse<T> {
     pub fn no_content() -> Self {
         Self {
             status: Some(204),
-            message: None,
+            message: Some("No Content".to_string()),
             data: None,
             errors: None,
         }